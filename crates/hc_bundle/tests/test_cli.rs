@@ -188,6 +188,7 @@ async fn test_multi_integrity() {
                 .into(),
             ),
         ],
+        rate_limits: Default::default(),
     };
     assert_eq!(
         dna.dna_def().integrity_zomes[0]
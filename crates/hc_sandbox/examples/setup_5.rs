@@ -55,6 +55,8 @@ async fn main() -> anyhow::Result<()> {
             source: AppBundleSource::Bundle(bundle),
             membrane_proofs: Default::default(),
             uid: None,
+            network_overrides: Default::default(),
+            existing_cells: Default::default(),
         };
 
         let r = AdminRequest::InstallAppBundle(Box::new(payload));
@@ -21,6 +21,7 @@ use holochain_types::prelude::InstallAppDnaPayload;
 use holochain_types::prelude::InstallAppPayload;
 use holochain_types::prelude::RegisterDnaPayload;
 use holochain_types::prelude::YamlProperties;
+use holochain_types::prelude::ZomeFunctions;
 use holochain_types::prelude::{AgentPubKey, AppBundleSource};
 use holochain_types::prelude::{CellId, InstallAppBundlePayload};
 use holochain_types::prelude::{DnaSource, Uid};
@@ -64,6 +65,8 @@ pub enum AdminRequestCli {
     ListAppWs,
     /// Calls AdminRequest::ListDnas.
     ListDnas,
+    /// Calls AdminRequest::ListZomeFunctions.
+    ListZomeFns(ListZomeFns),
     /// Calls AdminRequest::GenerateAgentPubKey.
     NewAgent,
     /// Calls AdminRequest::ListCellIds.
@@ -201,6 +204,16 @@ pub struct DumpState {
     pub agent_key: AgentPubKey,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::ListZomeFunctions
+/// and prints the callbacks and zome functions
+/// exported by each zome of the dna.
+pub struct ListZomeFns {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The dna hash to list the functions of.
+    pub dna: DnaHash,
+}
+
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::RequestAgentInfo
 /// and pretty prints the agent info on
@@ -309,6 +322,17 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             let dnas = list_dnas(cmd).await?;
             msg!("Dnas: {:?}", dnas);
         }
+        AdminRequestCli::ListZomeFns(args) => {
+            let zomes = list_zome_functions(cmd, args).await?;
+            for zome in zomes {
+                msg!(
+                    "Zome {}:\n  Callbacks: {:?}\n  Zome functions: {:?}",
+                    zome.zome_name,
+                    zome.callbacks,
+                    zome.zome_fns
+                );
+            }
+        }
         AdminRequestCli::NewAgent => {
             let agent = generate_agent_pub_key(cmd).await?;
             msg!("Added agent {}", agent);
@@ -400,6 +424,7 @@ pub async fn add_admin_interface(cmd: &mut CmdRunner, args: AddAdminWs) -> anyho
         .command(AdminRequest::AddAdminInterfaces(vec![
             AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port },
+                auth: None,
             },
         ]))
         .await?;
@@ -510,6 +535,8 @@ pub async fn install_app_bundle(
         source: AppBundleSource::Path(path),
         membrane_proofs: Default::default(),
         uid,
+        network_overrides: Default::default(),
+        existing_cells: Default::default(),
     };
 
     let r = AdminRequest::InstallAppBundle(Box::new(payload));
@@ -553,6 +580,17 @@ pub async fn list_dnas(cmd: &mut CmdRunner) -> anyhow::Result<Vec<DnaHash>> {
     Ok(expect_match!(resp => AdminResponse::DnasListed, "Failed to list dnas"))
 }
 
+/// Calls [`AdminRequest::ListZomeFunctions`].
+pub async fn list_zome_functions(
+    cmd: &mut CmdRunner,
+    args: ListZomeFns,
+) -> anyhow::Result<Vec<ZomeFunctions>> {
+    let resp = cmd
+        .command(AdminRequest::ListZomeFunctions { dna_hash: args.dna })
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::ZomeFunctionsListed, "Failed to list zome functions"))
+}
+
 /// Calls [`AdminRequest::GenerateAgentPubKey`].
 pub async fn generate_agent_pub_key(cmd: &mut CmdRunner) -> anyhow::Result<AgentPubKey> {
     let resp = cmd.command(AdminRequest::GenerateAgentPubKey).await?;
@@ -615,7 +653,12 @@ pub async fn disable_app(cmd: &mut CmdRunner, args: DisableApp) -> anyhow::Resul
 /// Calls [`AdminRequest::AttachAppInterface`] and adds another app interface.
 pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow::Result<u16> {
     let resp = cmd
-        .command(AdminRequest::AttachAppInterface { port: args.port })
+        .command(AdminRequest::AttachAppInterface {
+            port: args.port,
+            rate_limit: None,
+            tls: None,
+            require_auth: false,
+        })
         .await?;
     tracing::debug!(?resp);
     match resp {
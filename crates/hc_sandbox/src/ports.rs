@@ -33,6 +33,7 @@ pub async fn get_admin_ports(paths: Vec<PathBuf>) -> anyhow::Result<Vec<u16>> {
             if let Some(ai) = config.admin_interfaces {
                 if let Some(AdminInterfaceConfig {
                     driver: InterfaceDriver::Websocket { port },
+                    ..
                 }) = ai.get(0)
                 {
                     ports.push(*port)
@@ -62,15 +63,19 @@ pub(crate) fn random_admin_port(config: &mut ConductorConfig) {
     match config.admin_interfaces.as_mut().and_then(|i| i.first_mut()) {
         Some(AdminInterfaceConfig {
             driver: InterfaceDriver::Websocket { port },
+            ..
         }) => {
             if *port != 0 {
                 *port = 0;
             }
         }
+        // Interfaces other than plain websockets have no port to randomize.
+        Some(_) => (),
         None => {
             let port = 0;
             config.admin_interfaces = Some(vec![AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port },
+                auth: None,
             }]);
         }
     }
@@ -80,6 +85,7 @@ pub(crate) fn set_admin_port(config: &mut ConductorConfig, port: u16) {
     let p = port;
     let port = AdminInterfaceConfig {
         driver: InterfaceDriver::Websocket { port },
+        auth: None,
     };
     match config
         .admin_interfaces
@@ -198,3 +198,39 @@ pub fn update_cap_grant(
         chain_top_ordering: ChainTopOrdering::default(),
     })
 }
+
+/// List the capability grants on the local source chain.
+///
+/// Only live grants are returned, i.e. grants that have been updated or deleted as per
+/// [`update_cap_grant`] and [`delete_cap_grant`] are filtered out.
+///
+/// The [`CapGrantsInput`] can narrow the results to grants with a given tag and/or grants
+/// that include a given zome function. An empty input returns every live grant.
+///
+/// Each [`CapGrantInfo`] includes the action hash of the grant, which can be passed to
+/// [`update_cap_grant`] and [`delete_cap_grant`] to roll or revoke it.
+pub fn capability_grants(input: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>> {
+    HDK.with(|h| h.borrow().capability_grants(input))
+}
+
+/// List the capability claims on the local source chain.
+///
+/// Only live claims are returned, i.e. claims that have been updated or deleted are filtered out.
+///
+/// The [`CapClaimsInput`] can narrow the results to claims on grants from a given grantor
+/// and/or claims with a given tag. An empty input returns every live claim.
+///
+/// A returned claim is no guarantee that the grantor still honours it, see [`create_cap_claim`].
+pub fn capability_claims(input: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>> {
+    HDK.with(|h| h.borrow().capability_claims(input))
+}
+
+/// Get the capability that authorized the current zome call.
+///
+/// The [`CapabilityInfo`] includes the calling agent, the [`CapGrant`] that allowed the call
+/// and the action hash of that grant on the local source chain.
+/// Calls made by the chain author are authorized by the implicit [`CapGrant::ChainAuthor`]
+/// grant, which has no action hash.
+pub fn capability_info() -> ExternResult<CapabilityInfo> {
+    HDK.with(|h| h.borrow().capability_info(()))
+}
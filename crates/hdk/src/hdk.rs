@@ -27,6 +27,10 @@ pub trait HdkT: HdiT {
         get_agent_activity_input: GetAgentActivityInput,
     ) -> ExternResult<AgentActivity>;
     fn query(&self, filter: ChainQueryFilter) -> ExternResult<Vec<Record>>;
+    // Capability
+    fn capability_claims(&self, input: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>>;
+    fn capability_grants(&self, input: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>>;
+    fn capability_info(&self, capability_info_input: ()) -> ExternResult<CapabilityInfo>;
     // Ed25519
     fn sign(&self, sign: Sign) -> ExternResult<Signature>;
     fn sign_ephemeral(&self, sign_ephemeral: SignEphemeral) -> ExternResult<EphemeralSignatures>;
@@ -97,6 +101,10 @@ mockall::mock! {
             get_agent_activity_input: GetAgentActivityInput,
         ) -> ExternResult<AgentActivity>;
         fn query(&self, filter: ChainQueryFilter) -> ExternResult<Vec<Record>>;
+        // Capability
+        fn capability_claims(&self, input: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>>;
+        fn capability_grants(&self, input: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>>;
+        fn capability_info(&self, capability_info_input: ()) -> ExternResult<CapabilityInfo>;
         // Ed25519
         fn sign(&self, sign: Sign) -> ExternResult<Signature>;
         fn sign_ephemeral(&self, sign_ephemeral: SignEphemeral) -> ExternResult<EphemeralSignatures>;
@@ -265,6 +273,16 @@ impl HdkT for ErrHdk {
     fn query(&self, _: ChainQueryFilter) -> ExternResult<Vec<Record>> {
         Self::err()
     }
+    // Capability
+    fn capability_claims(&self, _: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>> {
+        Self::err()
+    }
+    fn capability_grants(&self, _: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>> {
+        Self::err()
+    }
+    fn capability_info(&self, _: ()) -> ExternResult<CapabilityInfo> {
+        Self::err()
+    }
     fn sign(&self, _: Sign) -> ExternResult<Signature> {
         Self::err()
     }
@@ -450,6 +468,15 @@ impl HdkT for HostHdk {
     fn query(&self, filter: ChainQueryFilter) -> ExternResult<Vec<Record>> {
         host_call::<ChainQueryFilter, Vec<Record>>(__query, filter)
     }
+    fn capability_claims(&self, input: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>> {
+        host_call::<CapClaimsInput, Vec<CapClaimInfo>>(__capability_claims, input)
+    }
+    fn capability_grants(&self, input: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>> {
+        host_call::<CapGrantsInput, Vec<CapGrantInfo>>(__capability_grants, input)
+    }
+    fn capability_info(&self, _: ()) -> ExternResult<CapabilityInfo> {
+        host_call::<(), CapabilityInfo>(__capability_info, ())
+    }
     fn sign(&self, sign: Sign) -> ExternResult<Signature> {
         host_call::<Sign, Signature>(__sign, sign)
     }
@@ -1,3 +1,6 @@
+pub use crate::capability::capability_claims;
+pub use crate::capability::capability_grants;
+pub use crate::capability::capability_info;
 pub use crate::capability::create_cap_claim;
 pub use crate::capability::create_cap_grant;
 pub use crate::capability::delete_cap_grant;
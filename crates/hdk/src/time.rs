@@ -65,12 +65,27 @@ pub fn sys_time() -> ExternResult<Timestamp> {
     HDK.with(|h| h.borrow().sys_time(()))
 }
 
-/// @todo Not implemented
+/// Schedule a function in the current zome to be run by the conductor as soon
+/// as possible, once the current zome call has committed.
+///
+/// The scheduled function is called with the [`Schedule`] it was run for,
+/// `None` the first time, and returns the schedule to run it on next, or `None`
+/// to stop running it. A [`Schedule::Persisted`] cron expression keeps being
+/// run each time it fires, even across conductor restarts, while a
+/// [`Schedule::Ephemeral`] duration runs the function once after that long.
+///
+/// Runs of persisted schedules missed while the conductor wasn't running are
+/// skipped or run once, depending on the conductor's `schedule_catch_up` config.
 pub fn schedule(scheduled_fn: &str) -> ExternResult<()> {
     HDK.with(|h| h.borrow().schedule(String::from(scheduled_fn)))
 }
 
-/// @todo Not implemented
+/// Suspend the current call for a while, e.g. to back off before retrying a
+/// [`call_remote`].
+///
+/// The sleep is capped by the conductor's `max_sleep_ms` config, and counts
+/// toward the zome call timeout. Sleeping past the timeout returns an error.
+/// Only coordinator zomes can sleep.
 pub fn sleep(wake_after: std::time::Duration) -> ExternResult<()> {
     HDK.with(|h| h.borrow().sleep(wake_after))
 }
@@ -185,6 +185,7 @@ async fn setup() -> (Producer, Consumer, Others) {
             network: Some(network),
             admin_interfaces: Some(vec![AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port: 0 },
+                auth: None,
             }]),
             ..Default::default()
         }
@@ -18,7 +18,6 @@ use holochain_state::host_fn_workspace::SourceChainWorkspace;
 use holochain_types::prelude::*;
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::mpsc::OwnedPermit;
-use tracing::*;
 
 /// The concrete implementation of [`CellConductorApiT`], which is used to give
 /// Cells an API for calling back to their [`Conductor`](crate::conductor::Conductor).
@@ -67,9 +66,8 @@ impl CellConductorApiT for CellConductorApi {
         }
     }
 
-    async fn dpki_request(&self, _method: String, _args: String) -> ConductorApiResult<String> {
-        warn!("Using placeholder dpki");
-        Ok("TODO".to_string())
+    async fn dpki_key_state(&self, agent_key: AgentPubKey) -> ConductorApiResult<Option<KeyState>> {
+        Ok(self.conductor_handle.dpki_key_state(agent_key).await?)
     }
 
     fn keystore(&self) -> &MetaLairClient {
@@ -132,9 +130,9 @@ pub trait CellConductorApiT: Send + Sync + Sized {
         call: ZomeCall,
     ) -> ConductorApiResult<ZomeCallResult>;
 
-    /// Make a request to the DPKI service running for this Conductor.
-    /// TODO: decide on actual signature
-    async fn dpki_request(&self, method: String, args: String) -> ConductorApiResult<String>;
+    /// Get the state of an agent key from the DPKI app running for this Conductor,
+    /// or `None` if the Conductor is not using DPKI.
+    async fn dpki_key_state(&self, agent_key: AgentPubKey) -> ConductorApiResult<Option<KeyState>>;
 
     /// Request access to this conductor's keystore
     fn keystore(&self) -> &MetaLairClient;
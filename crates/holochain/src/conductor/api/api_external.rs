@@ -18,4 +18,18 @@ pub trait InterfaceApi: 'static + Send + Sync + Clone {
         &self,
         request: Result<Self::ApiRequest, SerializedBytesError>,
     ) -> InterfaceResult<Self::ApiResponse>;
+
+    /// Whether each connection must authenticate with its first request
+    /// before this API will handle any others
+    fn requires_authentication(&self) -> bool {
+        false
+    }
+
+    /// Handle the first request on a connection which must authenticate.
+    /// Along with the response, returns the API to handle the rest of the
+    /// connection's requests with, or `None` if it failed to authenticate.
+    async fn authenticate(
+        &self,
+        request: Result<Self::ApiRequest, SerializedBytesError>,
+    ) -> InterfaceResult<(Self::ApiResponse, Option<Self>)>;
 }
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use super::InterfaceApi;
 use crate::conductor::api::error::ConductorApiError;
@@ -9,6 +10,7 @@ use crate::conductor::error::ConductorError;
 use crate::conductor::interface::error::InterfaceError;
 use crate::conductor::interface::error::InterfaceResult;
 use crate::conductor::ConductorHandle;
+use crate::core::ribosome::RibosomeT;
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::dna::DnaBundle;
 use holochain_types::prelude::*;
@@ -52,11 +54,38 @@ pub trait AdminInterfaceApi: 'static + Send + Sync + Clone {
 pub struct RealAdminInterfaceApi {
     /// Mutable access to the Conductor
     conductor_handle: ConductorHandle,
+    /// How connections authenticate, if they must
+    auth: Option<Arc<AdminInterfaceAuth>>,
 }
 
 impl RealAdminInterfaceApi {
     pub(crate) fn new(conductor_handle: ConductorHandle) -> Self {
-        RealAdminInterfaceApi { conductor_handle }
+        RealAdminInterfaceApi {
+            conductor_handle,
+            auth: None,
+        }
+    }
+
+    /// Require connections to authenticate
+    pub(crate) fn with_auth(mut self, auth: Option<AdminInterfaceAuth>) -> Self {
+        self.auth = auth.map(Arc::new);
+        self
+    }
+
+    fn is_valid_token(&self, token: &[u8]) -> bool {
+        use subtle::ConstantTimeEq;
+        // Compared in constant time so the secret can't be guessed byte by
+        // byte from how long failed attempts take.
+        let secret_matches = self
+            .auth
+            .as_ref()
+            .and_then(|auth| auth.secret.as_ref())
+            .map_or(false, |secret| bool::from(secret.as_bytes().ct_eq(token)));
+        secret_matches
+            || self
+                .conductor_handle
+                .interface_auth_tokens()
+                .authenticate_admin(token)
     }
 }
 
@@ -137,6 +166,35 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::CloneCellCreated(cell_id))
             }
+            ArchiveCloneCell(payload) => {
+                self.conductor_handle
+                    .clone()
+                    .archive_clone_cell(*payload)
+                    .await?;
+                Ok(AdminResponse::CloneCellArchived)
+            }
+            RestoreCloneCell(payload) => {
+                self.conductor_handle
+                    .clone()
+                    .restore_clone_cell(*payload)
+                    .await?;
+                Ok(AdminResponse::CloneCellRestored)
+            }
+            DeleteArchivedCloneCell(payload) => {
+                self.conductor_handle
+                    .clone()
+                    .delete_archived_clone_cell(*payload)
+                    .await?;
+                Ok(AdminResponse::ArchivedCloneCellDeleted)
+            }
+            ProvisionAppRole(payload) => {
+                let cell_id = self
+                    .conductor_handle
+                    .clone()
+                    .provision_app_role(*payload)
+                    .await?;
+                Ok(AdminResponse::AppRoleProvisioned(cell_id))
+            }
             InstallApp(payload) => {
                 trace!(?payload.dnas);
                 let InstallAppPayload {
@@ -210,15 +268,45 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let dna_list = self.conductor_handle.list_dnas();
                 Ok(AdminResponse::DnasListed(dna_list))
             }
-            GenerateAgentPubKey => {
-                let agent_pub_key = self
+            ListZomeFunctions { dna_hash } => {
+                let mut zome_functions = self
                     .conductor_handle
-                    .keystore()
-                    .clone()
-                    .new_sign_keypair_random()
-                    .await?;
+                    .get_ribosome(&dna_hash)?
+                    .list_zome_functions()
+                    .map_err(ConductorError::from)?
+                    .as_ref()
+                    .clone();
+                // Which functions are scheduled is only known from the
+                // schedules of the cells running the DNA.
+                let scheduled = self
+                    .conductor_handle
+                    .list_scheduled_functions(None)
+                    .await?
+                    .into_iter()
+                    .filter(|info| *info.cell_id.dna_hash() == dna_hash)
+                    .map(|info| info.scheduled_fn);
+                for scheduled_fn in scheduled {
+                    if let Some(zome) = zome_functions
+                        .iter_mut()
+                        .find(|z| &z.zome_name == scheduled_fn.zome_name())
+                    {
+                        zome.mark_scheduled(scheduled_fn.fn_name());
+                    }
+                }
+                Ok(AdminResponse::ZomeFunctionsListed(zome_functions))
+            }
+            GenerateAgentPubKey => {
+                let agent_pub_key = self.conductor_handle.generate_agent_pub_key().await?;
                 Ok(AdminResponse::AgentPubKeyGenerated(agent_pub_key))
             }
+            RevokeAgentPubKey { agent_key } => {
+                self.conductor_handle.dpki_revoke_key(agent_key).await?;
+                Ok(AdminResponse::AgentPubKeyRevoked)
+            }
+            RotateAgentPubKey { agent_key } => {
+                let new_key = self.conductor_handle.dpki_rotate_key(agent_key).await?;
+                Ok(AdminResponse::AgentPubKeyRotated(new_key))
+            }
             ListCellIds => {
                 let cell_ids = self
                     .conductor_handle
@@ -281,12 +369,17 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::AppStarted(app.status().is_running()))
             }
-            AttachAppInterface { port } => {
+            AttachAppInterface {
+                port,
+                rate_limit,
+                tls,
+                require_auth,
+            } => {
                 let port = port.unwrap_or(0);
                 let port = self
                     .conductor_handle
                     .clone()
-                    .add_app_interface(port)
+                    .add_app_interface(port, rate_limit, tls, require_auth)
                     .await?;
                 Ok(AdminResponse::AppInterfaceAttached { port })
             }
@@ -294,6 +387,43 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let interfaces = self.conductor_handle.list_app_interfaces().await?;
                 Ok(AdminResponse::AppInterfacesListed(interfaces))
             }
+            Authenticate { .. } => Ok(AdminResponse::Error(
+                ExternalApiWireError::AuthenticationFailed(
+                    "This connection is already authenticated, or its interface doesn't require authentication".into(),
+                ),
+            )),
+            IssueAdminAuthenticationToken(payload) => {
+                let issued = self
+                    .conductor_handle
+                    .interface_auth_tokens()
+                    .issue_admin(payload.expiry_seconds, payload.single_use);
+                Ok(AdminResponse::AdminAuthenticationTokenIssued(issued))
+            }
+            IssueAppAuthenticationToken(payload) => {
+                let IssueAppAuthenticationTokenPayload {
+                    installed_app_id,
+                    expiry_seconds,
+                    single_use,
+                } = payload;
+                if self
+                    .conductor_handle
+                    .get_app_info(&installed_app_id)
+                    .await?
+                    .is_none()
+                {
+                    return Err(ConductorError::AppNotInstalled(installed_app_id).into());
+                }
+                let issued = self.conductor_handle.interface_auth_tokens().issue_app(
+                    installed_app_id,
+                    expiry_seconds,
+                    single_use,
+                );
+                Ok(AdminResponse::AppAuthenticationTokenIssued(issued))
+            }
+            RevokeAuthenticationToken { token } => {
+                self.conductor_handle.interface_auth_tokens().revoke(&token);
+                Ok(AdminResponse::AuthenticationTokenRevoked)
+            }
             DumpState { cell_id } => {
                 let state = self.conductor_handle.dump_cell_state(&cell_id).await?;
                 Ok(AdminResponse::StateDumped(state))
@@ -348,6 +478,46 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::RecordsAdded)
             }
+            ExportCellChain { cell_id } => {
+                let archive = self.conductor_handle.export_cell_chain(&cell_id).await?;
+                Ok(AdminResponse::CellChainExported(archive))
+            }
+            ImportCellChain {
+                archive,
+                truncate,
+                validate,
+            } => {
+                self.conductor_handle
+                    .clone()
+                    .import_cell_chain(archive, truncate, validate)
+                    .await?;
+                Ok(AdminResponse::CellChainImported)
+            }
+            ListScheduledFunctions { cell_id } => {
+                let scheduled = self
+                    .conductor_handle
+                    .list_scheduled_functions(cell_id)
+                    .await?;
+                Ok(AdminResponse::ScheduledFunctionsListed(scheduled))
+            }
+            InspectCountersigningSession { cell_id } => {
+                let state = self
+                    .conductor_handle
+                    .countersigning_session_state(&cell_id)
+                    .await?;
+                Ok(AdminResponse::CountersigningSessionInspected(state))
+            }
+            AbandonCountersigningSession { cell_id } => {
+                let abandoned = self
+                    .conductor_handle
+                    .abandon_countersigning_session(&cell_id)
+                    .await?;
+                Ok(AdminResponse::CountersigningSessionAbandoned(abandoned))
+            }
+            RotateDatabaseKey => {
+                self.conductor_handle.rotate_db_key().await?;
+                Ok(AdminResponse::DatabaseKeyRotationStaged)
+            }
         }
     }
 }
@@ -395,6 +565,27 @@ impl InterfaceApi for RealAdminInterfaceApi {
             Err(e) => Ok(AdminResponse::Error(SerializationError::from(e).into())),
         }
     }
+
+    fn requires_authentication(&self) -> bool {
+        self.auth.is_some()
+    }
+
+    async fn authenticate(
+        &self,
+        request: Result<Self::ApiRequest, SerializedBytesError>,
+    ) -> InterfaceResult<(Self::ApiResponse, Option<Self>)> {
+        let reason = match request {
+            Ok(AdminRequest::Authenticate { token }) if self.is_valid_token(&token) => {
+                return Ok((AdminResponse::Authenticated, Some(self.clone())));
+            }
+            Ok(AdminRequest::Authenticate { .. }) => "The token is not valid for this interface",
+            _ => "The connection must authenticate before making any other request",
+        };
+        Ok((
+            AdminResponse::Error(ExternalApiWireError::AuthenticationFailed(reason.into())),
+            None,
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -2,15 +2,19 @@ use super::InterfaceApi;
 use crate::conductor::api::error::ConductorApiResult;
 use crate::conductor::api::error::ExternalApiWireError;
 use crate::conductor::api::error::SerializationError;
+use crate::conductor::error::ConductorError;
 use crate::conductor::interface::error::InterfaceError;
 use crate::conductor::interface::error::InterfaceResult;
+use crate::conductor::interface::SignalSubscriptions;
 use crate::conductor::ConductorHandle;
+use holochain_conductor_api::signal_subscription::SignalFilterSet;
 
 use holochain_serialized_bytes::prelude::*;
 
 use holochain_types::prelude::*;
 
 pub use holochain_conductor_api::*;
+use std::sync::Arc;
 
 /// The interface that a Conductor exposes to the outside world.
 #[async_trait::async_trait]
@@ -41,12 +45,104 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
 #[derive(Clone)]
 pub struct RealAppInterfaceApi {
     conductor_handle: ConductorHandle,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    signal_subscriptions: SignalSubscriptions,
+    require_auth: bool,
+    /// The app an authenticated connection is bound to
+    installed_app_id: Option<InstalledAppId>,
 }
 
 impl RealAppInterfaceApi {
     /// Create a new instance from a shared Conductor reference
     pub fn new(conductor_handle: ConductorHandle) -> Self {
-        Self { conductor_handle }
+        Self {
+            signal_subscriptions: SignalSubscriptions::all(conductor_handle.app_cells().clone()),
+            conductor_handle,
+            rate_limiter: None,
+            require_auth: false,
+            installed_app_id: None,
+        }
+    }
+
+    /// A copy of this API for a new connection to the interface,
+    /// with its own signal subscriptions.
+    /// A connection which must authenticate receives no App signals until it
+    /// subscribes to an app or authenticates for one.
+    pub fn for_connection(&self) -> Self {
+        let app_cells = self.conductor_handle.app_cells().clone();
+        let signal_subscriptions = if self.require_auth {
+            SignalSubscriptions::none(app_cells)
+        } else {
+            SignalSubscriptions::all(app_cells)
+        };
+        Self {
+            signal_subscriptions,
+            ..self.clone()
+        }
+    }
+
+    /// The signal subscriptions of the connection this API is serving
+    pub fn signal_subscriptions(&self) -> &SignalSubscriptions {
+        &self.signal_subscriptions
+    }
+
+    /// Limit the zome calls made through this interface
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimitConfig>) -> Self {
+        self.rate_limiter = rate_limit.map(|config| Arc::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// Require connections to authenticate with an app token
+    pub fn with_require_auth(mut self, require_auth: bool) -> Self {
+        self.require_auth = require_auth;
+        self
+    }
+
+    /// Requests which change an app are only served on connections
+    /// authenticated for that app, which [`Self::is_for_app`] has checked.
+    /// Any other connection could otherwise change any app.
+    fn require_app_connection(&self, request: &str) -> Option<AppResponse> {
+        if self.installed_app_id.is_some() {
+            return None;
+        }
+        Some(AppResponse::Error(ExternalApiWireError::AppNotAuthorized(
+            format!(
+                "{} requires a connection authenticated for the app, use the admin interface instead",
+                request
+            ),
+        )))
+    }
+
+    /// Whether the request only concerns this app
+    async fn is_for_app(
+        &self,
+        installed_app_id: &InstalledAppId,
+        request: &AppRequest,
+    ) -> ConductorApiResult<bool> {
+        let cell_id = match request {
+            AppRequest::Authenticate { .. } => return Ok(true),
+            AppRequest::AppInfo {
+                installed_app_id: id,
+            } => return Ok(id == installed_app_id),
+            AppRequest::SignalSubscription(subscription) => {
+                return Ok(subscription.installed_app_id() == installed_app_id)
+            }
+            AppRequest::ArchiveCloneCell(payload) | AppRequest::RestoreCloneCell(payload) => {
+                return Ok(&payload.installed_app_id == installed_app_id)
+            }
+            AppRequest::ProvisionAppRole(payload) => {
+                return Ok(&payload.installed_app_id == installed_app_id)
+            }
+            AppRequest::ZomeCall(call) | AppRequest::ZomeCallInvocation(call) => &call.cell_id,
+            AppRequest::Crypto(request) => &request.cell_id,
+        };
+        Ok(self
+            .conductor_handle
+            .get_app_info(installed_app_id)
+            .await?
+            .map_or(false, |app| {
+                app.cell_data.iter().any(|cell| cell.as_id() == cell_id)
+            }))
     }
 }
 
@@ -57,7 +153,22 @@ impl AppInterfaceApi for RealAppInterfaceApi {
         &self,
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse> {
+        if let Some(installed_app_id) = &self.installed_app_id {
+            if !self.is_for_app(installed_app_id, &request).await? {
+                return Ok(AppResponse::Error(ExternalApiWireError::AppNotAuthorized(
+                    format!(
+                        "This connection is authenticated for the app {}",
+                        installed_app_id
+                    ),
+                )));
+            }
+        }
         match request {
+            AppRequest::Authenticate { .. } => Ok(AppResponse::Error(
+                ExternalApiWireError::AuthenticationFailed(
+                    "This connection is already authenticated, or its interface doesn't require authentication".into(),
+                ),
+            )),
             AppRequest::AppInfo { installed_app_id } => Ok(AppResponse::AppInfo(
                 self.conductor_handle
                     .get_app_info(&installed_app_id)
@@ -79,6 +190,13 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     })
             }
             AppRequest::ZomeCall(call) => {
+                if let Some(rate_limiter) = &self.rate_limiter {
+                    if let Err(e) = rate_limiter.check_zome_call() {
+                        return Ok(AppResponse::Error(ExternalApiWireError::RateLimited(
+                            e.to_string(),
+                        )));
+                    }
+                }
                 match self.conductor_handle.call_zome(*call.clone()).await? {
                     Ok(ZomeCallResponse::Ok(output)) => Ok(AppResponse::ZomeCall(Box::new(output))),
                     Ok(ZomeCallResponse::Unauthorized(_, _, _, _)) => Ok(AppResponse::Error(
@@ -100,8 +218,50 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     Err(e) => Ok(AppResponse::Error(e.into())),
                 }
             }
-            AppRequest::SignalSubscription(_) => Ok(AppResponse::Unimplemented(request)),
-            AppRequest::Crypto(_) => Ok(AppResponse::Unimplemented(request)),
+            AppRequest::ArchiveCloneCell(payload) => {
+                if let Some(response) = self.require_app_connection("ArchiveCloneCell") {
+                    return Ok(response);
+                }
+                self.conductor_handle
+                    .clone()
+                    .archive_clone_cell(*payload)
+                    .await?;
+                Ok(AppResponse::CloneCellArchived)
+            }
+            AppRequest::RestoreCloneCell(payload) => {
+                if let Some(response) = self.require_app_connection("RestoreCloneCell") {
+                    return Ok(response);
+                }
+                self.conductor_handle
+                    .clone()
+                    .restore_clone_cell(*payload)
+                    .await?;
+                Ok(AppResponse::CloneCellRestored)
+            }
+            AppRequest::ProvisionAppRole(payload) => {
+                if let Some(response) = self.require_app_connection("ProvisionAppRole") {
+                    return Ok(response);
+                }
+                let cell_id = self
+                    .conductor_handle
+                    .clone()
+                    .provision_app_role(*payload)
+                    .await?;
+                Ok(AppResponse::AppRoleProvisioned(cell_id))
+            }
+            AppRequest::SignalSubscription(subscription) => {
+                let installed_app_id = subscription.installed_app_id().clone();
+                self.conductor_handle
+                    .get_app_info(&installed_app_id)
+                    .await?
+                    .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
+                self.signal_subscriptions
+                    .subscribe(installed_app_id, subscription.filters().clone());
+                Ok(AppResponse::SignalSubscriptionUpdated)
+            }
+            AppRequest::Crypto(request) => Ok(AppResponse::Crypto(Box::new(
+                self.conductor_handle.crypto_request(*request).await?,
+            ))),
         }
     }
 }
@@ -125,4 +285,50 @@ impl InterfaceApi for RealAppInterfaceApi {
             Err(e) => Ok(AppResponse::Error(SerializationError::from(e).into())),
         }
     }
+
+    fn requires_authentication(&self) -> bool {
+        self.require_auth
+    }
+
+    async fn authenticate(
+        &self,
+        request: Result<Self::ApiRequest, SerializedBytesError>,
+    ) -> InterfaceResult<(Self::ApiResponse, Option<Self>)> {
+        let reason = match request {
+            Ok(AppRequest::Authenticate { token }) => {
+                match self
+                    .conductor_handle
+                    .interface_auth_tokens()
+                    .authenticate_app(&token)
+                {
+                    Some(installed_app_id) => {
+                        // The connection receives the signals of all the
+                        // app's cells, until it subscribes to fewer.
+                        let app_info = self
+                            .conductor_handle
+                            .get_app_info(&installed_app_id)
+                            .await
+                            .map_err(Box::new)
+                            .map_err(InterfaceError::RequestHandler)?;
+                        if app_info.is_some() {
+                            self.signal_subscriptions
+                                .subscribe(installed_app_id.clone(), SignalFilterSet::allow_all());
+                            let api = Self {
+                                installed_app_id: Some(installed_app_id),
+                                ..self.clone()
+                            };
+                            return Ok((AppResponse::Authenticated, Some(api)));
+                        }
+                        "The app this token was issued for is no longer installed"
+                    }
+                    None => "The token is not valid for this interface",
+                }
+            }
+            _ => "The connection must authenticate before making any other request",
+        };
+        Ok((
+            AppResponse::Error(ExternalApiWireError::AuthenticationFailed(reason.into())),
+            None,
+        ))
+    }
 }
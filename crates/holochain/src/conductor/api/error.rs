@@ -106,6 +106,23 @@ pub enum ConductorApiError {
     #[error(transparent)]
     RusqliteError(#[from] rusqlite::Error),
 
+    /// A cell chain archive could not be imported.
+    #[error("Invalid cell chain archive: {0}")]
+    InvalidCellChainArchive(String),
+
+    /// No capability grant allows the crypto request.
+    #[error(
+        "No capability grant for {fn_name} allows {provenance} to make the crypto request for cell {cell_id:?}"
+    )]
+    CryptoRequestUnauthorized {
+        /// The cell whose keys were to be used
+        cell_id: CellId,
+        /// The function a grant must include to allow the operation
+        fn_name: FunctionName,
+        /// The provenance of the request
+        provenance: AgentPubKey,
+    },
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -151,6 +168,14 @@ impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
         match err {
             ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
+            e @ ConductorApiError::CryptoRequestUnauthorized { .. } => {
+                ExternalApiWireError::CryptoRequestUnauthorized(e.to_string())
+            }
+            ConductorApiError::CellError(CellError::WorkflowError(e))
+                if matches!(*e, WorkflowError::RateLimited(_)) =>
+            {
+                ExternalApiWireError::RateLimited(e.to_string())
+            }
             e => ExternalApiWireError::internal(e),
         }
     }
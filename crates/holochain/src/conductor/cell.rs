@@ -35,13 +35,16 @@ use hash_type::AnyDht;
 use holo_hash::*;
 use holochain_cascade::authority;
 use holochain_cascade::Cascade;
+use holochain_conductor_api::CountersigningSessionState;
 use holochain_p2p::event::CountersigningSessionNegotiationMessage;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_sqlite::prelude::*;
+use holochain_state::chain_lock::chain_lock_expires_at;
 use holochain_state::host_fn_workspace::HostFnWorkspace;
 use holochain_state::host_fn_workspace::SourceChainWorkspace;
 use holochain_state::prelude::*;
-use holochain_state::schedule::live_scheduled_fns;
+use holochain_state::schedule::next_scheduled_run;
+use holochain_state::schedule::scheduled_fns_with_next_run;
 use holochain_types::db_cache::DhtDbQueryCache;
 use holochain_types::prelude::*;
 use rusqlite::OptionalExtension;
@@ -107,6 +110,7 @@ where
     holochain_p2p_cell: P2pCell,
     queue_triggers: QueueTriggers,
     init_mutex: tokio::sync::Mutex<()>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl Cell {
@@ -127,6 +131,11 @@ impl Cell {
         managed_task_stop_broadcaster: sync::broadcast::Sender<()>,
     ) -> CellResult<(Self, InitialQueueTriggers)> {
         let conductor_api = CellConductorApi::new(conductor_handle.clone(), id.clone());
+        let rate_limiter = conductor_handle
+            .get_config()
+            .cell_rate_limit
+            .clone()
+            .map(|config| Arc::new(RateLimiter::new(config)));
 
         // check if genesis has been run
         let has_genesis = {
@@ -156,6 +165,7 @@ impl Cell {
                     holochain_p2p_cell,
                     queue_triggers,
                     init_mutex: Default::default(),
+                    rate_limiter,
                 },
                 initial_queue_triggers,
             ))
@@ -248,23 +258,101 @@ impl Cell {
             .await?)
     }
 
+    /// When the next of this cell's scheduled functions is due to run.
+    pub(super) async fn next_scheduled_run(self: Arc<Self>) -> CellResult<Option<Timestamp>> {
+        let author = self.id.agent_pubkey().clone();
+        Ok(self
+            .space
+            .authored_db
+            .async_reader(move |txn| next_scheduled_run(&txn, &author))
+            .await?)
+    }
+
+    /// The functions this cell has scheduled, with when each is next due to run.
+    pub(super) async fn scheduled_fns(
+        self: Arc<Self>,
+    ) -> CellResult<Vec<(ScheduledFn, Option<Schedule>, Timestamp)>> {
+        let author = self.id.agent_pubkey().clone();
+        Ok(self
+            .space
+            .authored_db
+            .async_reader(move |txn| scheduled_fns_with_next_run(&txn, &author))
+            .await?)
+    }
+
+    /// The countersigning session this cell's chain is locked for, if any.
+    pub(super) async fn countersigning_session_state(
+        &self,
+    ) -> CellResult<Option<CountersigningSessionState>> {
+        let author = Arc::new(self.id.agent_pubkey().clone());
+        self.space
+            .authored_db
+            .async_reader(move |txn| {
+                let locked_until = match chain_lock_expires_at(&txn, &author)? {
+                    Some(locked_until) => locked_until,
+                    None => return CellResult::Ok(None),
+                };
+                let session = current_countersigning_session(&txn, author)?;
+                let (entry_hash, session_data) = session.unzip();
+                Ok(Some(CountersigningSessionState {
+                    locked_until,
+                    entry_hash,
+                    session_data,
+                }))
+            })
+            .await
+    }
+
+    /// Abandon the countersigning session this cell's chain is locked for,
+    /// unlocking the chain and dropping the ops collected for the session.
+    /// Returns false if the chain wasn't locked.
+    pub(super) async fn abandon_countersigning_session(&self) -> CellResult<bool> {
+        let author = Arc::new(self.id.agent_pubkey().clone());
+        let abandoned = self
+            .space
+            .authored_db
+            .async_commit(move |txn: &mut Transaction| {
+                if chain_lock_expires_at(txn, &author)?.is_none() {
+                    return CellResult::Ok(None);
+                }
+                let entry_hash =
+                    current_countersigning_session(txn, author.clone())?.map(|(hash, _)| hash);
+                unlock_chain(txn, &author)?;
+                if let Some(entry_hash) = &entry_hash {
+                    delete_countersigning_session(txn, entry_hash)?;
+                }
+                Ok(Some(entry_hash))
+            })
+            .await?;
+        let entry_hash = match abandoned {
+            Some(entry_hash) => entry_hash,
+            None => return Ok(false),
+        };
+        if let Some(entry_hash) = &entry_hash {
+            self.space.countersigning_workspace.remove(entry_hash);
+        }
+        let signal = SystemSignal::AbandonedCountersigning {
+            cell_id: self.id.clone(),
+            entry_hash,
+        };
+        // Nothing may be listening for signals.
+        if let Err(e) = self.signal_broadcaster().await.send(signal.into()) {
+            debug!(?e, "Failed to send the abandoned countersigning signal");
+        }
+        Ok(true)
+    }
+
     pub(super) async fn dispatch_scheduled_fns(self: Arc<Self>) {
         let now = Timestamp::now();
         let author = self.id.agent_pubkey().clone();
+        let catch_up = self.conductor_handle.get_config().schedule_catch_up;
         let lives = self
             .space
             .authored_db
             .async_commit(move |txn: &mut Transaction| {
-                // Rescheduling should not fail as the data in the database
-                // should be valid schedules only.
-                reschedule_expired(txn, now, &author)?;
-                let lives = live_scheduled_fns(txn, now, &author);
-                // We know what to run so we can delete the ephemerals.
-                if lives.is_ok() {
-                    // Failing to delete should rollback this attempt.
-                    delete_live_ephemeral_scheduled_fns(txn, now, &author)?;
-                }
-                lives
+                // Taking the due functions advances their schedules, so each
+                // only runs once for each time it's due.
+                take_due_scheduled_fns(txn, now, &author, catch_up)
             })
             .await;
 
@@ -304,12 +392,24 @@ impl Cell {
                     .space
                     .authored_db
                     .async_commit(move |txn: &mut Transaction| {
-                        for ((scheduled_fn, _), result) in lives.iter().zip(results.iter()) {
+                        for ((scheduled_fn, schedule), result) in lives.iter().zip(results.iter()) {
                             match result {
                                 Ok(Ok(ZomeCallResponse::Ok(extern_io))) => {
                                     let next_schedule: Schedule = match extern_io.decode() {
                                         Ok(Some(v)) => v,
+                                        // The function doesn't want to run again.
+                                        // Ephemeral schedules were already
+                                        // removed when they were taken.
                                         Ok(None) => {
+                                            if matches!(schedule, Some(Schedule::Persisted(_))) {
+                                                if let Err(e) = unschedule_fn(
+                                                    txn,
+                                                    &author,
+                                                    scheduled_fn.clone(),
+                                                ) {
+                                                    error!("{}", e.to_string());
+                                                }
+                                            }
                                             continue;
                                         }
                                         Err(e) => {
@@ -356,6 +456,7 @@ impl Cell {
             | QueryAgentInfoSignedNearBasis { .. }
             | QueryPeerDensity { .. }
             | Publish { .. }
+            | PublishWarrant { .. }
             | FetchOpData { .. } => {
                 // These events are aggregated over a set of cells, so need to be handled at the conductor level.
                 unreachable!()
@@ -534,8 +635,10 @@ impl Cell {
                 incoming_countersigning(
                     ops,
                     &self.space.countersigning_workspace,
+                    &self.space.authored_db,
                     self.queue_triggers.countersigning.clone(),
                 )
+                .await
                 .map_err(Box::new)?;
                 Ok(())
             }
@@ -645,14 +748,18 @@ impl Cell {
             .map_err(Into::into)
     }
 
-    #[instrument(skip(self, _dht_hash, _options))]
+    #[instrument(skip(self, options))]
     /// a remote node is asking us for metadata
     async fn handle_get_meta(
         &self,
-        _dht_hash: holo_hash::AnyDhtHash,
-        _options: holochain_p2p::event::GetMetaOptions,
+        dht_hash: holo_hash::AnyDhtHash,
+        options: holochain_p2p::event::GetMetaOptions,
     ) -> CellResult<MetadataSet> {
-        unimplemented!()
+        debug!(id = ?self.id());
+        let db = self.space.dht_db.clone();
+        authority::handle_get_meta(db.into(), dht_hash, options)
+            .await
+            .map_err(Into::into)
     }
 
     #[instrument(skip(self, options))]
@@ -793,6 +900,36 @@ impl Cell {
         Ok(self.call_zome(invocation, None).await??.try_into()?)
     }
 
+    /// Check the source chain for a capability grant which allows the
+    /// provenance to use this function, the same way as for zome calls.
+    /// Unless `committed_only` is set, the cell's own agent is always allowed.
+    pub async fn is_authorized(
+        &self,
+        function: GrantedFunction,
+        provenance: AgentPubKey,
+        cap_secret: Option<CapSecret>,
+        committed_only: bool,
+    ) -> CellResult<bool> {
+        let source_chain = SourceChain::new(
+            self.authored_db().clone(),
+            self.dht_db().clone(),
+            self.space.dht_query_cache.clone(),
+            self.conductor_api.keystore().clone(),
+            self.id.agent_pubkey().clone(),
+        )
+        .await?;
+        let grant = if committed_only {
+            source_chain
+                .committed_cap_grant(function, provenance, cap_secret)
+                .await?
+        } else {
+            source_chain
+                .valid_cap_grant(function, provenance, cap_secret)
+                .await?
+        };
+        Ok(grant.is_some())
+    }
+
     /// Function called by the Conductor
     #[instrument(skip(self, call, workspace_lock))]
     pub async fn call_zome(
@@ -845,6 +982,7 @@ impl Cell {
             signal_tx,
             conductor_handle,
             is_root_zome_call,
+            rate_limiter: self.rate_limiter.clone(),
         };
         Ok(call_zome_workflow(
             workspace_lock,
@@ -42,6 +42,9 @@ async fn test_cell_handle_publish() {
         .expect_get_queue_consumer_workflows()
         .return_const(spaces.queue_consumer_map.clone());
     mock_handle.expect_keystore().return_const(keystore.clone());
+    mock_handle
+        .expect_get_config()
+        .return_const(holochain_conductor_api::conductor::ConductorConfig::default());
 
     let mock_handle: crate::conductor::handle::ConductorHandle = Arc::new(mock_handle);
     let mut mock_ribosome = MockRibosomeT::new();
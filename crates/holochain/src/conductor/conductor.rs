@@ -13,14 +13,17 @@ pub use self::share::RwShare;
 use super::api::RealAppInterfaceApi;
 use super::config::AdminInterfaceConfig;
 use super::config::InterfaceDriver;
+use super::config::TlsConfig;
 use super::entry_def_store::get_entry_defs;
 use super::error::ConductorError;
 use super::handle::ConductorHandleImpl;
+use super::interface::auth::InterfaceAuthTokens;
 use super::interface::error::InterfaceResult;
 use super::interface::websocket::spawn_admin_interface_task;
 use super::interface::websocket::spawn_app_interface_task;
 use super::interface::websocket::spawn_websocket_listener;
 use super::interface::websocket::SIGNAL_BUFFER_SIZE;
+use super::interface::AppCells;
 use super::interface::SignalBroadcaster;
 use super::manager::keep_alive_task;
 use super::manager::ManagedTaskAdd;
@@ -59,6 +62,7 @@ use holochain_conductor_api::AppStatusFilter;
 use holochain_conductor_api::FullIntegrationStateDump;
 use holochain_conductor_api::InstalledAppInfo;
 use holochain_conductor_api::IntegrationStateDump;
+use holochain_keystore::file_keystore::spawn_file_keystore;
 use holochain_keystore::lair_keystore::spawn_lair_keystore;
 use holochain_keystore::lair_keystore::spawn_new_lair_keystore;
 use holochain_keystore::test_keystore::spawn_legacy_test_keystore;
@@ -153,6 +157,12 @@ where
     /// Collection app interface data, keyed by id
     app_interfaces: RwShare<HashMap<AppInterfaceId, AppInterfaceRuntime>>,
 
+    /// The tokens issued for authenticating connections to interfaces
+    pub(super) interface_auth_tokens: InterfaceAuthTokens,
+
+    /// The cells of each installed app, for resolving signal subscriptions
+    pub(super) app_cells: AppCells,
+
     /// The channels and handles needed to interact with the task_manager task.
     /// If this is None, then the task manager has not yet been initialized.
     pub(super) task_manager: RwShare<Option<TaskManagerClient>>,
@@ -298,23 +308,25 @@ impl Conductor {
         });
 
         // Closure to process each admin config item
-        let spawn_from_config = |AdminInterfaceConfig { driver, .. }| {
-            let admin_api = admin_api.clone();
+        let spawn_from_config = |AdminInterfaceConfig { driver, auth }| {
+            let admin_api = admin_api.clone().with_auth(auth);
             let stop_tx = stop_tx.clone();
             async move {
-                match driver {
-                    InterfaceDriver::Websocket { port } => {
-                        let (listener_handle, listener) = spawn_websocket_listener(port).await?;
-                        let port = listener_handle.local_addr().port().unwrap_or(port);
-                        let handle: ManagedTaskHandle = spawn_admin_interface_task(
-                            listener_handle,
-                            listener,
-                            admin_api.clone(),
-                            stop_tx.subscribe(),
-                        )?;
-                        InterfaceResult::Ok((port, handle))
-                    }
-                }
+                let (listener_handle, listener) = spawn_websocket_listener(&driver).await?;
+                let local_addr = listener_handle.local_addr().clone();
+                // Only plain websocket ports are handed out to clients
+                // asking for an admin port.
+                let port = match driver {
+                    InterfaceDriver::Websocket { port } => Some(local_addr.port().unwrap_or(port)),
+                    _ => None,
+                };
+                let handle: ManagedTaskHandle = spawn_admin_interface_task(
+                    listener_handle,
+                    listener,
+                    admin_api.clone(),
+                    stop_tx.subscribe(),
+                )?;
+                InterfaceResult::Ok((local_addr, port, handle))
             }
         };
 
@@ -340,11 +352,11 @@ impl Conductor {
             .await?;
 
             // Now that tasks are spawned, register them with the TaskManager
-            for (port, handle) in handles {
-                ports.push(port);
+            for (local_addr, port, handle) in handles {
+                ports.extend(port);
                 self.manage_task(ManagedTaskAdd::ignore(
                     handle,
-                    &format!("admin interface, port {}", port),
+                    &format!("admin interface, {}", local_addr),
                 ))
                 .await?
             }
@@ -358,6 +370,9 @@ impl Conductor {
     pub(super) async fn add_app_interface_via_handle(
         &self,
         port: either::Either<u16, AppInterfaceId>,
+        rate_limit: Option<RateLimitConfig>,
+        tls: Option<TlsConfig>,
+        require_auth: bool,
         handle: ConductorHandle,
     ) -> ConductorResult<u16> {
         let interface_id = match port {
@@ -366,7 +381,9 @@ impl Conductor {
         };
         let port = interface_id.port();
         tracing::debug!("Attaching interface {}", port);
-        let app_api = RealAppInterfaceApi::new(handle);
+        let app_api = RealAppInterfaceApi::new(handle)
+            .with_rate_limit(rate_limit.clone())
+            .with_require_auth(require_auth);
         // This receiver is thrown away because we can produce infinite new
         // receivers from the Sender
         let (signal_tx, _r) = tokio::sync::broadcast::channel(SIGNAL_BUFFER_SIZE);
@@ -376,9 +393,10 @@ impl Conductor {
                 .task_stop_broadcaster()
                 .subscribe()
         });
-        let (port, task) = spawn_app_interface_task(port, app_api, signal_tx.clone(), stop_rx)
-            .await
-            .map_err(Box::new)?;
+        let (port, task) =
+            spawn_app_interface_task(port, tls.clone(), app_api, signal_tx.clone(), stop_rx)
+                .await
+                .map_err(Box::new)?;
         // TODO: RELIABILITY: Handle this task by restarting it if it fails and log the error
         self.manage_task(ManagedTaskAdd::ignore(
             task,
@@ -397,7 +415,12 @@ impl Conductor {
             app_interfaces.insert(interface_id.clone(), interface);
             Ok(())
         })?;
-        let config = AppInterfaceConfig::websocket(port);
+        let config = match tls {
+            Some(tls) => AppInterfaceConfig::tls_websocket(port, tls),
+            None => AppInterfaceConfig::websocket(port),
+        }
+        .with_rate_limit(rate_limit)
+        .with_require_auth(require_auth);
         self.update_state(|mut state| {
             state.app_interfaces.insert(interface_id, config);
             Ok(state)
@@ -413,7 +436,7 @@ impl Conductor {
             .await?
             .app_interfaces
             .values()
-            .map(|config| config.driver.port())
+            .filter_map(|config| config.driver.port())
             .collect())
     }
 
@@ -443,6 +466,18 @@ impl Conductor {
         self.ribosome_store.share_mut(|d| d.add_ribosome(ribosome));
     }
 
+    /// Forget a DNA which no cell uses any more, so it is not loaded again
+    /// at startup. Its wasm is kept, since other DNAs may share it.
+    pub(super) async fn unregister_dna(&self, dna_hash: &DnaHash) -> ConductorResult<()> {
+        self.ribosome_store.share_mut(|d| d.remove_dna(dna_hash));
+        let dna_hash = dna_hash.clone();
+        self.spaces
+            .wasm_db
+            .async_commit(move |txn| holochain_state::dna_def::delete(txn, &dna_hash))
+            .await?;
+        Ok(())
+    }
+
     pub(super) fn get_queue_consumer_workflows(&self) -> QueueConsumerMap {
         self.spaces.queue_consumer_map.clone()
     }
@@ -454,10 +489,22 @@ impl Conductor {
         &self,
         handle: ConductorHandle,
     ) -> ConductorResult<()> {
-        for id in self.get_state().await?.app_interfaces.keys().cloned() {
+        let state = self.get_state().await?;
+        self.app_cells.update(state.installed_apps().iter());
+        for (id, config) in state.app_interfaces {
             tracing::debug!("Starting up app interface: {:?}", id);
+            let tls = match config.driver {
+                InterfaceDriver::TlsWebsocket { tls, .. } => Some(tls),
+                _ => None,
+            };
             let _ = self
-                .add_app_interface_via_handle(either::Right(id), handle.clone())
+                .add_app_interface_via_handle(
+                    either::Right(id),
+                    config.rate_limit,
+                    tls,
+                    config.require_auth,
+                    handle.clone(),
+                )
                 .await?;
         }
         Ok(())
@@ -794,6 +841,150 @@ impl Conductor {
         Ok(cell_id)
     }
 
+    /// Mark the cell of an app role which was deferred at install time as
+    /// being provisioned, so that it is only provisioned once
+    pub(super) async fn start_provisioning_app_role(
+        &self,
+        app_id: InstalledAppId,
+        role_id: AppRoleId,
+    ) -> ConductorResult<CellId> {
+        let (_, cell_id) = self
+            .update_state_prime(move |mut state| {
+                let cell_id = state
+                    .installed_apps_mut()
+                    .get_mut(&app_id)
+                    .ok_or_else(|| ConductorError::AppNotInstalled(app_id.clone()))?
+                    .start_provisioning_role(&role_id)?;
+                Ok((state, cell_id))
+            })
+            .await?;
+        Ok(cell_id)
+    }
+
+    /// Stop provisioning the cell of a role, e.g. because its genesis failed
+    pub(super) async fn abort_provisioning_app_role(
+        &self,
+        app_id: InstalledAppId,
+        role_id: AppRoleId,
+    ) -> ConductorResult<()> {
+        self.update_state_prime(move |mut state| {
+            state
+                .installed_apps_mut()
+                .get_mut(&app_id)
+                .ok_or_else(|| ConductorError::AppNotInstalled(app_id.clone()))?
+                .abort_provisioning_role(&role_id)?;
+            Ok((state, ()))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Stop provisioning the cells of all roles, which is only left
+    /// unfinished if the conductor stopped during their genesis
+    pub(super) async fn abort_all_provisioning(&self) -> ConductorResult<()> {
+        self.update_state_prime(|mut state| {
+            for app in state.installed_apps_mut().values_mut() {
+                let role_ids: Vec<_> = app.roles().keys().cloned().collect();
+                for role_id in role_ids {
+                    app.abort_provisioning_role(&role_id)?;
+                }
+            }
+            Ok((state, ()))
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Mark the cell of an app role which was deferred at install time as
+    /// provisioned
+    pub(super) async fn provision_app_role(
+        &self,
+        app_id: InstalledAppId,
+        role_id: AppRoleId,
+    ) -> ConductorResult<CellId> {
+        let (_, cell_id) = self
+            .update_state_prime(move |mut state| {
+                let cell_id = state
+                    .installed_apps_mut()
+                    .get_mut(&app_id)
+                    .ok_or_else(|| ConductorError::AppNotInstalled(app_id.clone()))?
+                    .provision_role(&role_id)?;
+                Ok((state, cell_id))
+            })
+            .await?;
+        Ok(cell_id)
+    }
+
+    /// Move a clone cell of an app into the app's archived clones
+    pub(super) async fn archive_clone_cell(
+        &self,
+        payload: CloneCellPayload,
+    ) -> ConductorResult<()> {
+        self.update_clone_cell(payload, |app, role_id, cell_id| {
+            app.archive_clone(role_id, cell_id)
+        })
+        .await
+    }
+
+    /// Move an archived clone cell of an app back into the app's clones
+    pub(super) async fn restore_clone_cell(
+        &self,
+        payload: CloneCellPayload,
+    ) -> ConductorResult<()> {
+        self.update_clone_cell(payload, |app, role_id, cell_id| {
+            app.restore_clone(role_id, cell_id)
+        })
+        .await
+    }
+
+    /// Remove an archived clone cell from its app.
+    ///
+    /// Returns true if no other cell of any installed app uses the same DNA,
+    /// i.e. if the data of the clone's DNA space can be safely deleted.
+    pub(super) async fn remove_archived_clone_cell(
+        &self,
+        payload: CloneCellPayload,
+    ) -> ConductorResult<bool> {
+        let CloneCellPayload {
+            installed_app_id,
+            role_id,
+            cell_id,
+        } = payload;
+        let (_, space_unused) = self
+            .update_state_prime(move |mut state| {
+                state
+                    .installed_apps_mut()
+                    .get_mut(&installed_app_id)
+                    .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?
+                    .delete_archived_clone(&role_id, &cell_id)?;
+                let space_unused = !state.dna_in_use(cell_id.dna_hash());
+                Ok((state, space_unused))
+            })
+            .await?;
+        Ok(space_unused)
+    }
+
+    async fn update_clone_cell<F>(&self, payload: CloneCellPayload, f: F) -> ConductorResult<()>
+    where
+        F: FnOnce(&mut InstalledApp, &AppRoleId, &CellId) -> AppResult<()> + Send + 'static,
+    {
+        let CloneCellPayload {
+            installed_app_id,
+            role_id,
+            cell_id,
+        } = payload;
+        self.update_state(move |mut state| {
+            let app = state
+                .installed_apps_mut()
+                .get_mut(&installed_app_id)
+                .ok_or_else(|| ConductorError::AppNotInstalled(installed_app_id.clone()))?;
+            f(app, &role_id, &cell_id)?;
+            Ok(state)
+        })
+        .await?;
+        Ok(())
+    }
+
     pub(super) async fn load_wasms_into_dna_files(
         &self,
     ) -> ConductorResult<(
@@ -1291,6 +1482,8 @@ impl Conductor {
             config,
             shutting_down: Arc::new(AtomicBool::new(false)),
             app_interfaces: RwShare::new(HashMap::new()),
+            interface_auth_tokens: InterfaceAuthTokens::default(),
+            app_cells: AppCells::default(),
             task_manager: RwShare::new(None),
             admin_websocket_ports: RwShare::new(Vec::new()),
             ribosome_store,
@@ -1358,6 +1551,7 @@ impl Conductor {
         O: Send + 'static,
     {
         self.check_running()?;
+        let app_cells = self.app_cells.clone();
         let output = self
             .spaces
             .conductor_db
@@ -1373,6 +1567,9 @@ impl Conductor {
                 };
                 let (new_state, output) = f(state)?;
                 mutations::insert_conductor_state(txn, (&new_state).try_into()?)?;
+                // Updated while the write lock is held, so that concurrent
+                // updates can't leave the index behind the stored state.
+                app_cells.update(new_state.installed_apps().iter());
                 Result::<_, ConductorError>::Ok((new_state, output))
             })
             .await?;
@@ -1492,7 +1689,25 @@ mod builder {
                         };
                         spawn_new_lair_keystore(connection_url.clone(), passphrase).await?
                     }
-                    oth => unimplemented!("unimplemented keystore config: {:?}", oth),
+                    KeystoreConfig::FileKeystore { keystore_path } => {
+                        let passphrase = match self.passphrase {
+                            None => {
+                                return Err(one_err::OneErr::new(
+                                    "passphrase required for file keystore",
+                                )
+                                .into())
+                            }
+                            Some(p) => p,
+                        };
+                        spawn_file_keystore(keystore_path.clone(), passphrase).await?
+                    }
+                    oth => {
+                        return Err(one_err::OneErr::new(format!(
+                            "unsupported keystore config: {:?}",
+                            oth
+                        ))
+                        .into())
+                    }
                 }
             };
 
@@ -1516,13 +1731,16 @@ mod builder {
             let strat =
                 ArqStrat::from_params(network_config.tuning_params.gossip_redundancy_target);
 
-            let spaces = Spaces::new(&config)?;
-            let host = KitsuneHostImpl::new(
-                spaces.clone(),
-                ribosome_store.clone(),
-                network_config.tuning_params.clone(),
-                strat,
+            #[cfg(feature = "db-encryption")]
+            let db_key = Some(
+                crate::conductor::db_key::load_db_key(config.environment_path.as_ref(), &keystore)
+                    .await?,
             );
+            #[cfg(not(feature = "db-encryption"))]
+            let db_key = None;
+
+            let spaces = Spaces::with_db_key(&config, db_key)?;
+            let host = KitsuneHostImpl::new(spaces.clone(), ribosome_store.clone(), strat);
 
             let (holochain_p2p, p2p_evt) =
                 holochain_p2p::spawn_holochain_p2p(network_config, tls_config, host).await?;
@@ -1682,12 +1900,11 @@ mod builder {
             let spaces = Spaces::new(&self.config)?;
 
             let network_config = self.config.network.clone().unwrap_or_default();
-            let tuning_params = network_config.tuning_params.clone();
-            let strat = ArqStrat::from_params(tuning_params.gossip_redundancy_target);
+            let strat =
+                ArqStrat::from_params(network_config.tuning_params.gossip_redundancy_target);
 
             let ribosome_store = RwShare::new(self.ribosome_store);
-            let host =
-                KitsuneHostImpl::new(spaces.clone(), ribosome_store.clone(), tuning_params, strat);
+            let host = KitsuneHostImpl::new(spaces.clone(), ribosome_store.clone(), strat);
 
             let (holochain_p2p, p2p_evt) =
                 holochain_p2p::spawn_holochain_p2p(network_config, holochain_p2p::kitsune_p2p::dependencies::kitsune_p2p_types::tls::TlsConfig::new_ephemeral().await.unwrap(), host)
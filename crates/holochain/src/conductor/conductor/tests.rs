@@ -14,14 +14,19 @@ use crate::{
 use ::fixt::prelude::*;
 use holochain_conductor_api::InstalledAppInfoStatus;
 use holochain_conductor_api::{AdminRequest, AdminResponse, AppRequest, AppResponse, ZomeCall};
+use holochain_conductor_api::{CryptoOperation, CryptoRequest, CryptoResponse};
 use holochain_keystore::crude_mock_keystore::spawn_crude_mock_keystore;
 use holochain_keystore::crude_mock_keystore::spawn_real_or_mock_keystore;
+use holochain_keystore::AgentPubKeyExt;
 use holochain_state::prelude::{test_keystore, *};
 use holochain_types::inline_zome::InlineZomeSet;
 use holochain_types::test_utils::fake_cell_id;
 use holochain_wasm_test_utils::TestWasm;
 use holochain_websocket::WebsocketSender;
+use holochain_zome_types::bytes::Bytes;
 use holochain_zome_types::op::Op;
+use holochain_zome_types::test_utils::fake_agent_pubkey_2;
+use kitsune_p2p::KitsuneP2pConfigOverride;
 use kitsune_p2p_types::dependencies::lair_keystore_api_0_0::LairError;
 use maplit::hashset;
 use matches::assert_matches;
@@ -156,6 +161,127 @@ async fn can_add_clone_cell_to_app() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_archive_restore_and_delete_clone_cell() {
+    let db_dir = test_db_dir();
+    let keystore = test_keystore();
+    let holochain_p2p = holochain_p2p::stub_network().await;
+
+    let agent = fixt!(AgentPubKey);
+    let dna = fake_valid_dna_file("");
+    let cell_id = CellId::new(dna.dna_hash().to_owned(), agent.clone());
+
+    let ribosome_store = RibosomeStore::new();
+    let (post_commit_sender, _post_commit_receiver) =
+        tokio::sync::mpsc::channel(POST_COMMIT_CHANNEL_BOUND);
+    let spaces = Spaces::new(&ConductorConfig {
+        environment_path: db_dir.path().to_path_buf().into(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let conductor = Conductor::new(
+        Default::default(),
+        ribosome_store,
+        keystore,
+        holochain_p2p,
+        spaces,
+        post_commit_sender,
+    )
+    .await
+    .unwrap();
+
+    let role = AppRoleAssignment::new(cell_id.clone(), true, 1);
+    let app = InstalledAppCommon::new("app", agent, vec![("role_id".into(), role)]);
+
+    conductor.register_phenotype(RealRibosome::empty(dna));
+    conductor
+        .update_state(move |mut state| {
+            state
+                .installed_apps_mut()
+                .insert(RunningApp::from(app).into());
+            Ok(state)
+        })
+        .await
+        .unwrap();
+
+    let clone_id = conductor
+        .add_clone_cell_to_app("app".to_string(), "role_id".to_string(), ().into())
+        .await
+        .unwrap();
+    let payload = CloneCellPayload {
+        installed_app_id: "app".to_string(),
+        role_id: "role_id".to_string(),
+        cell_id: clone_id.clone(),
+    };
+
+    // An active clone can't be deleted
+    assert_matches!(
+        conductor.remove_archived_clone_cell(payload.clone()).await,
+        Err(ConductorError::AppError(
+            AppError::ArchivedCloneCellMissing(_, _)
+        ))
+    );
+
+    conductor.archive_clone_cell(payload.clone()).await.unwrap();
+    let state = conductor.get_state().await.unwrap();
+    let app = state.get_app(&"app".to_string()).unwrap();
+    assert_eq!(app.cloned_cells().count(), 0);
+    assert_eq!(
+        app.archived_cloned_cells().collect::<Vec<_>>(),
+        vec![&clone_id]
+    );
+    let info = state.get_app_info(&"app".to_string()).unwrap();
+    assert_eq!(info.cell_data.len(), 1);
+    assert_eq!(info.archived_clones.len(), 1);
+
+    // The archived clone still occupies its slot in the clone limit
+    assert_matches!(
+        conductor
+            .add_clone_cell_to_app("app".to_string(), "role_id".to_string(), ().into())
+            .await,
+        Err(ConductorError::AppError(AppError::CloneLimitExceeded(1, _)))
+    );
+
+    conductor.restore_clone_cell(payload.clone()).await.unwrap();
+    let state = conductor.get_state().await.unwrap();
+    let app = state.get_app(&"app".to_string()).unwrap();
+    assert_eq!(app.cloned_cells().collect::<Vec<_>>(), vec![&clone_id]);
+    assert_eq!(app.archived_cloned_cells().count(), 0);
+
+    // Deleting the archived clone frees its slot, and the space of its
+    // unique DNA is no longer used by anything.
+    conductor.archive_clone_cell(payload.clone()).await.unwrap();
+    let authored_path = conductor
+        .spaces
+        .authored_db(clone_id.dna_hash())
+        .unwrap()
+        .path()
+        .clone();
+    assert!(authored_path.exists());
+    assert!(conductor.remove_archived_clone_cell(payload).await.unwrap());
+    conductor
+        .spaces
+        .remove_space(clone_id.dna_hash())
+        .await
+        .unwrap();
+    assert!(!authored_path.exists());
+    conductor.unregister_dna(clone_id.dna_hash()).await.unwrap();
+    assert_matches!(
+        conductor.get_ribosome(clone_id.dna_hash()),
+        Err(ConductorError::DnaError(DnaError::DnaMissing(_)))
+    );
+
+    let state = conductor.get_state().await.unwrap();
+    let app = state.get_app(&"app".to_string()).unwrap();
+    assert_eq!(app.all_cells().collect::<Vec<_>>(), vec![&cell_id]);
+    assert_eq!(app.archived_cloned_cells().count(), 0);
+    conductor
+        .add_clone_cell_to_app("app".to_string(), "role_id".to_string(), ().into())
+        .await
+        .unwrap();
+}
+
 /// App can't be installed if another app is already installed under the
 /// same InstalledAppId
 #[tokio::test(flavor = "multi_thread")]
@@ -486,7 +612,11 @@ async fn test_signing_error_during_genesis_doesnt_bork_interfaces() {
     let (cell1,) = app1.into_tuple();
     let (cell2,) = app2.into_tuple();
 
-    let app_port = conductor.inner_handle().add_app_interface(0).await.unwrap();
+    let app_port = conductor
+        .inner_handle()
+        .add_app_interface(0, None, None, false)
+        .await
+        .unwrap();
     let (mut app_client, _) = websocket_client_by_port(app_port).await.unwrap();
     let (mut admin_client, _) = conductor.admin_ws_client().await;
 
@@ -1009,3 +1139,469 @@ async fn test_init_concurrency() {
     assert_eq!(num_calls_clone.fetch_add(0, Ordering::SeqCst), 100);
     assert_eq!(num_inits_clone.fetch_add(0, Ordering::SeqCst), 1);
 }
+
+async fn app_bundle_with_network(
+    dna: &DnaFile,
+    network: Option<KitsuneP2pConfigOverride>,
+) -> AppBundle {
+    app_bundle(dna, CellProvisioning::Create { deferred: false }, network).await
+}
+
+async fn app_bundle(
+    dna: &DnaFile,
+    provisioning: CellProvisioning,
+    network: Option<KitsuneP2pConfigOverride>,
+) -> AppBundle {
+    let path = std::path::PathBuf::from(format!("{}", dna.dna_hash()));
+    let manifest = AppManifestCurrentBuilder::default()
+        .name("app".into())
+        .description(None)
+        .roles(vec![AppRoleManifest {
+            id: "role".into(),
+            provisioning: Some(provisioning),
+            dna: AppRoleDnaManifest {
+                location: Some(DnaLocation::Bundled(path.clone())),
+                properties: None,
+                uid: None,
+                version: None,
+                clone_limit: 0,
+            },
+            network,
+        }])
+        .build()
+        .unwrap();
+    let resources = vec![(path, DnaBundle::from_dna_file(dna.clone()).await.unwrap())];
+    AppBundle::new(manifest.into(), resources, std::path::PathBuf::from("."))
+        .await
+        .unwrap()
+}
+
+fn install_payload(
+    installed_app_id: &str,
+    agent_key: AgentPubKey,
+    bundle: AppBundle,
+    network_overrides: HashMap<AppRoleId, KitsuneP2pConfigOverride>,
+) -> InstallAppBundlePayload {
+    InstallAppBundlePayload {
+        source: AppBundleSource::Bundle(bundle),
+        agent_key,
+        installed_app_id: Some(installed_app_id.to_string()),
+        membrane_proofs: HashMap::new(),
+        uid: None,
+        network_overrides,
+        existing_cells: HashMap::new(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn network_overrides_apply_per_space() {
+    observability::test_run().ok();
+    let conductor = SweetConductor::from_standard_config().await;
+    let handle = conductor.handle();
+    let agent = SweetAgents::one(handle.keystore().clone()).await;
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let (other_dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let tuning = |delay: &str| KitsuneP2pConfigOverride {
+        tuning_params: maplit::btreemap! {
+            "gossip_loop_iteration_delay_ms".to_string() => delay.to_string(),
+        },
+        ..Default::default()
+    };
+
+    // The override in the manifest is replaced by the one given at install.
+    let bundle = app_bundle_with_network(&dna, Some(tuning("100"))).await;
+    handle
+        .clone()
+        .install_app_bundle(install_payload(
+            "app",
+            agent.clone(),
+            bundle,
+            maplit::hashmap! { "role".to_string() => tuning("200") },
+        ))
+        .await
+        .unwrap();
+    let bundle = app_bundle_with_network(&other_dna, None).await;
+    handle
+        .clone()
+        .install_app_bundle(install_payload(
+            "other",
+            agent.clone(),
+            bundle,
+            HashMap::new(),
+        ))
+        .await
+        .unwrap();
+    conductor.enable_app("app".to_string()).await.unwrap();
+    conductor.enable_app("other".to_string()).await.unwrap();
+
+    let spaces = handle.get_spaces();
+    let default_delay = handle
+        .get_config()
+        .network
+        .clone()
+        .unwrap_or_default()
+        .tuning_params
+        .gossip_loop_iteration_delay_ms;
+    assert_eq!(
+        spaces
+            .network_config(dna.dna_hash())
+            .tuning_params
+            .gossip_loop_iteration_delay_ms,
+        200
+    );
+    assert_eq!(
+        spaces
+            .network_config(other_dna.dna_hash())
+            .tuning_params
+            .gossip_loop_iteration_delay_ms,
+        default_delay
+    );
+
+    // Another app can't use the same DNA with different overrides.
+    let bundle = app_bundle_with_network(&dna, None).await;
+    assert_matches!(
+        handle
+            .clone()
+            .install_app_bundle(install_payload(
+                "conflict",
+                agent.clone(),
+                bundle,
+                HashMap::new()
+            ))
+            .await,
+        Err(ConductorError::InvalidNetworkOverride(..))
+    );
+
+    // The transport is shared, so its proxy config can't be overridden.
+    let proxy = KitsuneP2pConfigOverride {
+        proxy_config: Some(kitsune_p2p::ProxyConfig::LocalProxyServer {
+            proxy_accept_config: None,
+        }),
+        ..Default::default()
+    };
+    let (proxied_dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let bundle = app_bundle_with_network(&proxied_dna, Some(proxy)).await;
+    assert_matches!(
+        handle
+            .clone()
+            .install_app_bundle(install_payload(
+                "proxied",
+                agent.clone(),
+                bundle,
+                HashMap::new()
+            ))
+            .await,
+        Err(ConductorError::InvalidNetworkOverride(..))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deferred_roles_are_provisioned_once_and_spun_up() {
+    observability::test_run().ok();
+    let conductor = SweetConductor::from_standard_config().await;
+    let handle = conductor.handle();
+    let agent = SweetAgents::one(handle.keystore().clone()).await;
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+
+    let bundle = app_bundle(&dna, CellProvisioning::Create { deferred: true }, None).await;
+    handle
+        .clone()
+        .install_app_bundle(install_payload(
+            "app",
+            agent.clone(),
+            bundle,
+            HashMap::new(),
+        ))
+        .await
+        .unwrap();
+    conductor.enable_app("app".to_string()).await.unwrap();
+    assert!(!handle.list_cell_ids(None).contains(&cell_id));
+
+    // Only one of concurrent requests provisions the role.
+    let provision = || {
+        handle.clone().provision_app_role(ProvisionAppRolePayload {
+            installed_app_id: "app".into(),
+            role_id: "role".into(),
+            membrane_proof: None,
+        })
+    };
+    let (first, second) = futures::future::join(provision(), provision()).await;
+    let provisioned: Vec<_> = vec![first, second]
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect();
+    assert_eq!(provisioned, vec![cell_id.clone()]);
+
+    // The cell of the running app is spun up.
+    assert!(handle.list_cell_ids(None).contains(&cell_id));
+    let state = handle.get_state_from_handle().await.unwrap();
+    assert!(state
+        .get_app(&"app".to_string())
+        .unwrap()
+        .all_cells()
+        .any(|c| *c == cell_id));
+
+    assert_matches!(
+        provision().await,
+        Err(ConductorError::AppError(
+            AppError::AppRoleAlreadyProvisioned(_)
+        ))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shared_cells_outlive_the_app_which_created_them() {
+    observability::test_run().ok();
+    let conductor = SweetConductor::from_standard_config().await;
+    let handle = conductor.handle();
+    let agent = SweetAgents::one(handle.keystore().clone()).await;
+    let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent.clone());
+
+    let bundle = app_bundle_with_network(&dna, None).await;
+    handle
+        .clone()
+        .install_app_bundle(install_payload(
+            "app",
+            agent.clone(),
+            bundle,
+            HashMap::new(),
+        ))
+        .await
+        .unwrap();
+    conductor.enable_app("app".to_string()).await.unwrap();
+
+    let sharing_bundle = || async {
+        let manifest = AppManifestCurrentBuilder::default()
+            .name("sharer".into())
+            .description(None)
+            .roles(vec![AppRoleManifest {
+                id: "role".into(),
+                provisioning: Some(CellProvisioning::UseExisting { deferred: false }),
+                dna: AppRoleDnaManifest {
+                    location: None,
+                    properties: None,
+                    uid: None,
+                    version: Some(DnaVersionSpec::from(vec![dna.dna_hash().clone().into()]).into()),
+                    clone_limit: 0,
+                },
+                network: None,
+            }])
+            .build()
+            .unwrap();
+        AppBundle::new(manifest.into(), vec![], std::path::PathBuf::from("."))
+            .await
+            .unwrap()
+    };
+
+    // A cell which no app uses can't be shared.
+    let other_agent = SweetAgents::one(handle.keystore().clone()).await;
+    let mut payload = install_payload(
+        "missing",
+        other_agent.clone(),
+        sharing_bundle().await,
+        HashMap::new(),
+    );
+    let missing_cell_id = CellId::new(dna.dna_hash().clone(), other_agent.clone());
+    payload.existing_cells = maplit::hashmap! { "role".to_string() => missing_cell_id };
+    assert_matches!(
+        handle.clone().install_app_bundle(payload).await,
+        Err(ConductorError::CellMissing(_))
+    );
+
+    // Nor can another agent's cell.
+    let mut payload = install_payload(
+        "other agent",
+        other_agent,
+        sharing_bundle().await,
+        HashMap::new(),
+    );
+    payload.existing_cells = maplit::hashmap! { "role".to_string() => cell_id.clone() };
+    assert_matches!(
+        handle.clone().install_app_bundle(payload).await,
+        Err(ConductorError::AppBundleError(
+            AppBundleError::ExistingCellAgentMismatch(_, _)
+        ))
+    );
+
+    let mut payload = install_payload(
+        "sharer",
+        agent.clone(),
+        sharing_bundle().await,
+        HashMap::new(),
+    );
+    payload.existing_cells = maplit::hashmap! { "role".to_string() => cell_id.clone() };
+    handle.clone().install_app_bundle(payload).await.unwrap();
+    conductor.enable_app("sharer".to_string()).await.unwrap();
+
+    // Uninstalling the app which created the cell leaves it to the other app.
+    handle
+        .clone()
+        .uninstall_app(&"app".to_string())
+        .await
+        .unwrap();
+    assert!(handle.list_cell_ids(None).contains(&cell_id));
+    let state = handle.get_state_from_handle().await.unwrap();
+    assert_eq!(
+        state.apps_using_cell(&cell_id).collect::<Vec<_>>(),
+        vec!["sharer"]
+    );
+
+    handle
+        .clone()
+        .uninstall_app(&"sharer".to_string())
+        .await
+        .unwrap();
+    assert!(!handle.list_cell_ids(None).contains(&cell_id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cell_rate_limit() {
+    observability::test_run().ok();
+    let mut config = standard_config();
+    // Too few bytes for any commit to fit.
+    config.cell_rate_limit = Some(RateLimitConfig {
+        zome_calls_per_second: None,
+        bytes_committed_per_minute: Some(1),
+    });
+    let mut conductor = SweetConductor::from_config(config).await;
+    let (dna, _, _) = SweetDnaFile::unique_from_inline_zomes(simple_create_entry_zome())
+        .await
+        .unwrap();
+    let app = conductor.setup_app("app", &[dna]).await.unwrap();
+    let (cell,) = app.into_tuple();
+
+    let err = conductor
+        .call_fallible::<_, ActionHash, _>(&cell.zome("create_entry"), "create_entry", ())
+        .await
+        .unwrap_err();
+    assert_matches!(
+        holochain_conductor_api::ExternalApiWireError::from(err),
+        holochain_conductor_api::ExternalApiWireError::RateLimited(_)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_crypto_request() {
+    observability::test_run().ok();
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let zomes = simple_create_entry_zome().callback(
+        "create_entry",
+        "grant",
+        |api, grant: CapGrantEntry| {
+            let hash = api.create(CreateInput::new(
+                EntryDefLocation::CapGrant,
+                EntryVisibility::Private,
+                Entry::CapGrant(grant),
+                ChainTopOrdering::default(),
+            ))?;
+            Ok(hash)
+        },
+    );
+    let (dna, _, _) = SweetDnaFile::unique_from_inline_zomes(zomes).await.unwrap();
+    let app = conductor.setup_app("app", &[dna]).await.unwrap();
+    let (cell,) = app.into_tuple();
+    let agent = cell.agent_pubkey().clone();
+    let handle = conductor.inner_handle();
+    let secret = fixt!(CapSecret);
+    let request = |provenance: &AgentPubKey, operation| CryptoRequest {
+        cell_id: cell.cell_id().clone(),
+        cap_secret: Some(secret),
+        provenance: provenance.clone(),
+        operation,
+    };
+
+    // The cell's own agent can sign without a grant.
+    let data = vec![1, 2, 3];
+    let response = handle
+        .crypto_request(request(
+            &agent,
+            CryptoOperation::Sign(Bytes::from(data.clone())),
+        ))
+        .await
+        .unwrap();
+    let signature = match response {
+        CryptoResponse::Signed(signature) => signature,
+        other => panic!("unexpected response {:?}", other),
+    };
+    assert!(agent.verify_signature_raw(&signature, data.into()).await);
+
+    // Anyone else needs a grant.
+    let stranger = fake_agent_pubkey_2();
+    assert_matches!(
+        handle
+            .crypto_request(request(&stranger, CryptoOperation::Sign(Bytes::new())))
+            .await,
+        Err(ConductorApiError::CryptoRequestUnauthorized { .. })
+    );
+
+    let keystore = conductor.keystore();
+    let sender: X25519PubKey = (*keystore.new_x25519_keypair_random().await.unwrap()).into();
+    let recipient: X25519PubKey = (*keystore.new_x25519_keypair_random().await.unwrap()).into();
+    let message = XSalsa20Poly1305Data::from(vec![4, 5, 6]);
+    let encrypt = CryptoOperation::Encrypt(X25519XSalsa20Poly1305Encrypt::new(
+        sender,
+        recipient,
+        message.clone(),
+    ));
+
+    // Even the cell's own agent needs a grant for the x25519 key,
+    // which isn't tied to the agent.
+    assert_matches!(
+        handle
+            .crypto_request(request(&agent, encrypt.clone()))
+            .await,
+        Err(ConductorApiError::CryptoRequestUnauthorized { .. })
+    );
+    let grant = ZomeCallCapGrant::new(
+        "crypto".into(),
+        CapAccess::from(secret),
+        [encrypt.granted_function()].into_iter().collect(),
+    );
+    let _: ActionHash = conductor
+        .call(&cell.zome("create_entry"), "grant", grant)
+        .await;
+
+    let response = handle
+        .crypto_request(request(&agent, encrypt))
+        .await
+        .unwrap();
+    let encrypted = match response {
+        CryptoResponse::Encrypted(encrypted) => encrypted,
+        other => panic!("unexpected response {:?}", other),
+    };
+    // The grant names the sender's key for boxing,
+    // not the recipient's key for unboxing.
+    let decrypt = CryptoOperation::Decrypt(X25519XSalsa20Poly1305Decrypt::new(
+        recipient, sender, encrypted,
+    ));
+    assert_matches!(
+        handle.crypto_request(request(&agent, decrypt.clone())).await,
+        Err(ConductorApiError::CryptoRequestUnauthorized { fn_name, .. })
+            if fn_name == decrypt.granted_function().1
+    );
+    let grant = ZomeCallCapGrant::new(
+        "crypto".into(),
+        CapAccess::from(secret),
+        [decrypt.granted_function()].into_iter().collect(),
+    );
+    let _: ActionHash = conductor
+        .call(&cell.zome("create_entry"), "grant", grant)
+        .await;
+    let response = handle
+        .crypto_request(request(&agent, decrypt))
+        .await
+        .unwrap();
+    assert_matches!(response, CryptoResponse::Decrypted(decrypted) if decrypted == message);
+}
@@ -0,0 +1,192 @@
+//! The key the conductor's databases are encrypted with.
+//!
+//! The key is generated by the conductor and kept in a file next to the
+//! databases, sealed with a secret which never leaves the keystore. Rotating
+//! the key seals a new one, and every database is re-encrypted with it the
+//! next time the conductor starts, before any database is opened.
+
+use super::error::ConductorError;
+use super::error::ConductorResult;
+use holochain_keystore::MetaLairClient;
+use holochain_sqlite::key::rekey_database_file;
+use holochain_sqlite::key::DbKey;
+use holochain_sqlite::key::DB_KEY_LEN;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The file in the environment directory which holds the sealed keys.
+const DB_KEY_FILENAME: &str = "database_key.json";
+
+/// The keys kept in the key file.
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedDbKeys {
+    /// The key the databases are encrypted with.
+    /// This is `None` if they were encrypted by an earlier version of
+    /// Holochain with the legacy key.
+    current: Option<SealedDbKey>,
+    /// The key the databases will be re-encrypted with on the next start.
+    next: Option<SealedDbKey>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedDbKey {
+    /// The tag of the keystore secret the key is sealed with.
+    tag: String,
+    nonce: [u8; 24],
+    cipher: Vec<u8>,
+}
+
+/// Get the key to open the conductor's databases with.
+///
+/// A new key is generated the first time, and a staged rotation is finished by
+/// re-encrypting every database in the environment directory with the new key.
+/// This must happen before any of the databases are opened.
+pub async fn load_db_key(env_path: &Path, keystore: &MetaLairClient) -> ConductorResult<DbKey> {
+    let mut keys = match read_keys(env_path)? {
+        Some(keys) => keys,
+        None => {
+            // Existing databases were encrypted with the legacy key,
+            // and are re-encrypted with the new one below.
+            let keys = SealedDbKeys {
+                current: None,
+                next: Some(seal(keystore, &DbKey::generate()).await?),
+            };
+            write_keys(env_path, &keys)?;
+            keys
+        }
+    };
+
+    if let Some(next) = keys.next.take() {
+        let next_key = unseal(keystore, &next).await?;
+        let current_key = match &keys.current {
+            Some(current) => unseal(keystore, current).await?,
+            None => DbKey::legacy(),
+        };
+        let db_files = database_files(env_path)?;
+        for db_file in &db_files {
+            rekey_database_file(db_file, &current_key, &next_key)?;
+        }
+        keys.current = Some(next);
+        write_keys(env_path, &keys)?;
+        tracing::info!(
+            "Re-encrypted {} database files with a new key",
+            db_files.len()
+        );
+        return Ok(next_key);
+    }
+
+    match &keys.current {
+        Some(current) => unseal(keystore, current).await,
+        None => Err(ConductorError::other("The database key file holds no key")),
+    }
+}
+
+/// Seal a new database key, which the databases are re-encrypted with the
+/// next time the conductor starts. This replaces any rotation which is
+/// already staged.
+pub async fn stage_db_key_rotation(
+    env_path: &Path,
+    keystore: &MetaLairClient,
+) -> ConductorResult<()> {
+    let mut keys = read_keys(env_path)?
+        .ok_or_else(|| ConductorError::other("There is no database key to rotate"))?;
+    keys.next = Some(seal(keystore, &DbKey::generate()).await?);
+    write_keys(env_path, &keys)
+}
+
+async fn seal(keystore: &MetaLairClient, key: &DbKey) -> ConductorResult<SealedDbKey> {
+    let bytes = key
+        .to_bytes()
+        .ok_or_else(|| ConductorError::other("The legacy database key can't be sealed"))?;
+    let tag: Arc<str> = format!("holochain-db-key-{}", nanoid::nanoid!()).into();
+    keystore.new_shared_secret(tag.clone()).await?;
+    let (nonce, cipher) = keystore
+        .shared_secret_encrypt(tag.clone(), bytes.to_vec().into())
+        .await?;
+    Ok(SealedDbKey {
+        tag: tag.to_string(),
+        nonce,
+        cipher: cipher.to_vec(),
+    })
+}
+
+async fn unseal(keystore: &MetaLairClient, sealed: &SealedDbKey) -> ConductorResult<DbKey> {
+    let bytes = keystore
+        .shared_secret_decrypt(
+            sealed.tag.as_str().into(),
+            sealed.nonce,
+            sealed.cipher.clone().into(),
+        )
+        .await?;
+    let bytes: [u8; DB_KEY_LEN] = bytes
+        .as_ref()
+        .try_into()
+        .map_err(|_| ConductorError::other("The sealed database key has the wrong length"))?;
+    Ok(DbKey::from_bytes(bytes))
+}
+
+fn read_keys(env_path: &Path) -> ConductorResult<Option<SealedDbKeys>> {
+    match std::fs::read(env_path.join(DB_KEY_FILENAME)) {
+        Ok(bytes) => Ok(Some(
+            serde_json::from_slice(&bytes).map_err(ConductorError::other)?,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write the key file, replacing the old one only once the new one is
+/// complete so a crash can't lose the keys.
+fn write_keys(env_path: &Path, keys: &SealedDbKeys) -> ConductorResult<()> {
+    std::fs::create_dir_all(env_path)?;
+    let path = env_path.join(DB_KEY_FILENAME);
+    let tmp_path = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec(keys).map_err(ConductorError::other)?;
+    std::fs::write(&tmp_path, bytes)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// The database files in the environment directory,
+/// including the backups taken before migrations.
+fn database_files(dir: &Path) -> ConductorResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(database_files(&path)?);
+        } else if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| {
+                name.ends_with(".sqlite3") || name.ends_with(".bak")
+            })
+        {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_keystore::test_keystore::spawn_test_keystore;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn db_key_survives_restarts_and_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = spawn_test_keystore().await.unwrap();
+
+        let key = load_db_key(dir.path(), &keystore).await.unwrap();
+        assert_eq!(load_db_key(dir.path(), &keystore).await.unwrap(), key);
+
+        stage_db_key_rotation(dir.path(), &keystore).await.unwrap();
+        let rotated = load_db_key(dir.path(), &keystore).await.unwrap();
+        assert_ne!(rotated, key);
+        assert_eq!(load_db_key(dir.path(), &keystore).await.unwrap(), rotated);
+    }
+}
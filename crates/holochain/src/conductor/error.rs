@@ -119,6 +119,21 @@ pub enum ConductorError {
     #[error(transparent)]
     RibosomeError(#[from] crate::core::ribosome::error::RibosomeError),
 
+    #[error("Invalid network config overrides for the space of DNA {0}: {1}")]
+    InvalidNetworkOverride(DnaHash, String),
+
+    #[error("The conductor is not configured with DPKI")]
+    DpkiNotConfigured,
+
+    #[error("The DPKI app {0} is not running")]
+    DpkiNotRunning(InstalledAppId),
+
+    #[error("The DPKI call to {0} failed: {1}")]
+    DpkiCallFailed(String, String),
+
+    #[error("This conductor was built without database encryption")]
+    DbEncryptionNotEnabled,
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -35,11 +35,16 @@
 //! types for testing. If we did not have a way of hiding this type genericity,
 //! code which interacted with the Conductor would also have to be highly generic.
 
+use super::api::error::ConductorApiError;
 use super::api::error::ConductorApiResult;
 use super::api::ZomeCall;
 use super::conductor::CellStatus;
 use super::config::AdminInterfaceConfig;
+use super::config::TlsConfig;
+use super::error::ConductorError;
 use super::error::ConductorResult;
+use super::interface::auth::InterfaceAuthTokens;
+use super::interface::AppCells;
 use super::interface::SignalBroadcaster;
 use super::manager::spawn_task_manager;
 use super::manager::TaskManagerClient;
@@ -58,18 +63,32 @@ use crate::conductor::p2p_agent_store::get_single_agent_info;
 use crate::conductor::p2p_agent_store::query_peer_density;
 use crate::conductor::p2p_agent_store::P2pBatch;
 use crate::core::queue_consumer::QueueConsumerMap;
+use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckHostAccess;
+use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckInvocation;
+use crate::core::ribosome::guest_callback::genesis_self_check::GenesisSelfCheckResult;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitArgs;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::RibosomeT;
+use crate::core::warrant::restore_blocks;
+use crate::core::warrant::verify_warrant;
 use crate::core::workflow::ZomeCallResult;
 use derive_more::From;
 use futures::future::FutureExt;
 use futures::StreamExt;
 use holochain_conductor_api::conductor::ConductorConfig;
 use holochain_conductor_api::AppStatusFilter;
+use holochain_conductor_api::CellChainArchive;
+use holochain_conductor_api::CellChainArchiveContent;
+use holochain_conductor_api::CountersigningSessionState;
+use holochain_conductor_api::CryptoOperation;
+use holochain_conductor_api::CryptoRequest;
+use holochain_conductor_api::CryptoResponse;
 use holochain_conductor_api::FullStateDump;
 use holochain_conductor_api::InstalledAppInfo;
 use holochain_conductor_api::JsonDump;
+use holochain_conductor_api::ScheduledFunctionInfo;
+use holochain_conductor_api::CELL_CHAIN_ARCHIVE_VERSION;
+use holochain_keystore::AgentPubKeyExt;
 use holochain_keystore::MetaLairClient;
 use holochain_p2p::actor::HolochainP2pRefToDna;
 use holochain_p2p::event::HolochainP2pEvent;
@@ -84,6 +103,7 @@ use holochain_state::prelude::StateMutationResult;
 use holochain_state::source_chain;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use kitsune_p2p::KitsuneP2pConfigOverride;
 use kitsune_p2p_types::config::JOIN_NETWORK_TIMEOUT;
 use std::collections::HashMap;
 use std::{collections::HashSet, sync::Arc};
@@ -128,8 +148,15 @@ pub trait ConductorHandleT: Send + Sync {
         configs: Vec<AdminInterfaceConfig>,
     ) -> ConductorResult<()>;
 
-    /// Add an app interface
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+    /// Add an app interface, optionally limiting the zome calls made through it,
+    /// securing it with TLS and requiring connections to authenticate
+    async fn add_app_interface(
+        self: Arc<Self>,
+        port: u16,
+        rate_limit: Option<RateLimitConfig>,
+        tls: Option<TlsConfig>,
+        require_auth: bool,
+    ) -> ConductorResult<u16>;
 
     /// List the app interfaces currently installed.
     async fn list_app_interfaces(&self) -> ConductorResult<Vec<u16>>;
@@ -180,6 +207,10 @@ pub trait ConductorHandleT: Send + Sync {
         workspace_lock: SourceChainWorkspace,
     ) -> ConductorApiResult<ZomeCallResult>;
 
+    /// Sign, encrypt or decrypt data with the keys of a cell's agent,
+    /// if a capability grant allows it
+    async fn crypto_request(&self, request: CryptoRequest) -> ConductorApiResult<CryptoResponse>;
+
     /// Get a Websocket port which will
     fn get_arbitrary_admin_websocket_port(&self) -> Option<u16>;
 
@@ -189,6 +220,12 @@ pub trait ConductorHandleT: Send + Sync {
     /// Get the conductor config
     fn get_config(&self) -> &ConductorConfig;
 
+    /// The tokens issued for authenticating connections to interfaces
+    fn interface_auth_tokens(&self) -> &InterfaceAuthTokens;
+
+    /// The cells of each installed app, for resolving signal subscriptions
+    fn app_cells(&self) -> &AppCells;
+
     /// Return the JoinHandle for all managed tasks, which when resolved will
     /// signal that the Conductor has completely shut down.
     ///
@@ -202,6 +239,24 @@ pub trait ConductorHandleT: Send + Sync {
     /// Request access to this conductor's keystore
     fn keystore(&self) -> &MetaLairClient;
 
+    /// Generate a new agent key in the keystore, registering it with DPKI
+    /// if the conductor is configured with DPKI.
+    async fn generate_agent_pub_key(&self) -> ConductorResult<AgentPubKey>;
+
+    /// Revoke an agent key in DPKI.
+    async fn dpki_revoke_key(&self, agent_key: AgentPubKey) -> ConductorResult<()>;
+
+    /// Replace an agent key with a newly generated key in DPKI,
+    /// returning the new key.
+    async fn dpki_rotate_key(&self, agent_key: AgentPubKey) -> ConductorResult<AgentPubKey>;
+
+    /// Get the state of an agent key from DPKI, or `None` if the conductor
+    /// is not configured with DPKI or the DPKI app is not running yet.
+    async fn dpki_key_state(&self, agent_key: AgentPubKey) -> ConductorResult<Option<KeyState>>;
+
+    /// The DNA of the DPKI app's cell, if the DPKI app is running.
+    async fn dpki_dna_hash(&self) -> Option<DnaHash>;
+
     /// Request access to this conductor's networking handle
     fn holochain_p2p(&self) -> &holochain_p2p::HolochainP2pRef;
 
@@ -214,8 +269,25 @@ pub trait ConductorHandleT: Send + Sync {
         payload: CreateCloneCellPayload,
     ) -> ConductorResult<CellId>;
 
-    /// Destroy a cloned Cell
-    async fn destroy_clone_cell(self: Arc<Self>, cell_id: CellId) -> ConductorResult<()>;
+    /// Archive a cloned Cell. The Cell is stopped, but its data is kept,
+    /// so that it can be restored later.
+    async fn archive_clone_cell(self: Arc<Self>, payload: CloneCellPayload) -> ConductorResult<()>;
+
+    /// Restore an archived cloned Cell, starting it if its app is running
+    async fn restore_clone_cell(self: Arc<Self>, payload: CloneCellPayload) -> ConductorResult<()>;
+
+    /// Permanently delete an archived cloned Cell, along with its databases
+    async fn delete_archived_clone_cell(
+        self: Arc<Self>,
+        payload: CloneCellPayload,
+    ) -> ConductorResult<()>;
+
+    /// Run genesis on the cell of an app role which was deferred at install
+    /// time, starting it if its app is running
+    async fn provision_app_role(
+        self: Arc<Self>,
+        payload: ProvisionAppRolePayload,
+    ) -> ConductorResult<CellId>;
 
     /// Install Cells into ConductorState based on installation info, and run
     /// genesis on all new source chains
@@ -271,6 +343,27 @@ pub trait ConductorHandleT: Send + Sync {
     /// Dispatch all due scheduled functions.
     async fn dispatch_scheduled_fns(self: Arc<Self>);
 
+    /// List the functions scheduled by a cell, or by every cell if no cell
+    /// is given, with when each is next due to run.
+    async fn list_scheduled_functions(
+        &self,
+        cell_id: Option<CellId>,
+    ) -> ConductorApiResult<Vec<ScheduledFunctionInfo>>;
+
+    /// The countersigning session a cell's chain is locked for, if any.
+    async fn countersigning_session_state(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<Option<CountersigningSessionState>>;
+
+    /// Abandon the countersigning session a cell's chain is locked for,
+    /// unlocking the chain. Returns false if there was no session.
+    async fn abandon_countersigning_session(&self, cell_id: &CellId) -> ConductorApiResult<bool>;
+
+    /// Seal a new key for the conductor's databases, which they are
+    /// re-encrypted with the next time the conductor starts.
+    async fn rotate_db_key(&self) -> ConductorResult<()>;
+
     /// Get an OwnedPermit to the post commit task.
     async fn post_commit_permit(&self) -> Result<OwnedPermit<PostCommitArgs>, SendError<()>>;
 
@@ -361,6 +454,18 @@ pub trait ConductorHandleT: Send + Sync {
         records: Vec<Record>,
     ) -> ConductorApiResult<()>;
 
+    /// Export a cell's source chain as a signed archive.
+    async fn export_cell_chain(&self, cell_id: &CellId) -> ConductorApiResult<CellChainArchive>;
+
+    /// Restore a cell's source chain from an archive made by
+    /// [`ConductorHandleT::export_cell_chain`].
+    async fn import_cell_chain(
+        self: Arc<Self>,
+        archive: CellChainArchive,
+        truncate: bool,
+        validate: bool,
+    ) -> ConductorApiResult<()>;
+
     /// Retrieve the authored environment for this dna. FOR TESTING ONLY.
     #[cfg(any(test, feature = "test_utils"))]
     fn get_authored_db(&self, cell_id: &DnaHash) -> ConductorApiResult<DbWrite<DbKindAuthored>>;
@@ -553,16 +658,39 @@ impl ConductorHandleT for ConductorHandleImpl {
             .startup_app_interfaces_via_handle(self.clone())
             .await?;
 
+        // Roles are only left being provisioned if the conductor stopped
+        // during their genesis, so they can be provisioned again.
+        self.conductor.abort_all_provisioning().await?;
+
         // We don't care what fx are returned here, since all cells need to
         // be spun up
         let _ = self.conductor.start_paused_apps().await?;
 
-        self.process_app_status_fx(AppStatusFx::SpinUp, None).await
+        let errors = self
+            .clone()
+            .process_app_status_fx(AppStatusFx::SpinUp, None)
+            .await?;
+
+        self.initialize_dpki().await?;
+
+        Ok(errors)
     }
 
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+    async fn add_app_interface(
+        self: Arc<Self>,
+        port: u16,
+        rate_limit: Option<RateLimitConfig>,
+        tls: Option<TlsConfig>,
+        require_auth: bool,
+    ) -> ConductorResult<u16> {
         self.conductor
-            .add_app_interface_via_handle(either::Left(port), self.clone())
+            .add_app_interface_via_handle(
+                either::Left(port),
+                rate_limit,
+                tls,
+                require_auth,
+                self.clone(),
+            )
             .await
     }
 
@@ -655,6 +783,14 @@ impl ConductorHandleT for ConductorHandleImpl {
         &self.conductor.config
     }
 
+    fn interface_auth_tokens(&self) -> &InterfaceAuthTokens {
+        &self.conductor.interface_auth_tokens
+    }
+
+    fn app_cells(&self) -> &AppCells {
+        &self.conductor.app_cells
+    }
+
     #[instrument(skip(self))]
     async fn dispatch_holochain_p2p_event(
         &self,
@@ -743,13 +879,7 @@ impl ConductorHandleT for ConductorHandleImpl {
                 respond,
                 ..
             } => {
-                let cutoff = self
-                    .get_config()
-                    .network
-                    .clone()
-                    .unwrap_or_default()
-                    .tuning_params
-                    .danger_gossip_recent_threshold();
+                let cutoff = self.conductor.spaces.recent_threshold(&dna_hash);
                 let topo = self
                     .get_dna_def(&dna_hash)
                     .ok_or_else(|| DnaError::DnaMissing(dna_hash.clone()))?
@@ -806,6 +936,29 @@ impl ConductorHandleT for ConductorHandleImpl {
                 .instrument(debug_span!("handle_publish"))
                 .await;
             }
+            PublishWarrant {
+                dna_hash,
+                respond,
+                warrant,
+                ..
+            } => {
+                async {
+                    let network = self.holochain_p2p().to_dna(dna_hash.clone());
+                    let res = match self
+                        .conductor
+                        .spaces
+                        .handle_publish_warrant(&dna_hash, warrant, self, network.clone())
+                        .await
+                    {
+                        Ok(Some(warrantee)) => network.block_agent(warrantee).await,
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(holochain_p2p::HolochainP2pError::other(e)),
+                    };
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("handle_publish_warrant"))
+                .await;
+            }
             FetchOpData {
                 respond,
                 query,
@@ -862,6 +1015,70 @@ impl ConductorHandleT for ConductorHandleImpl {
         Ok(cell.call_zome(call, Some(workspace_lock)).await?)
     }
 
+    async fn crypto_request(&self, request: CryptoRequest) -> ConductorApiResult<CryptoResponse> {
+        let CryptoRequest {
+            cell_id,
+            cap_secret,
+            provenance,
+            operation,
+        } = request;
+        let cell = self.cell_by_id(&cell_id)?;
+        if !cell
+            .is_authorized(
+                operation.granted_function(),
+                provenance.clone(),
+                cap_secret,
+                !operation.uses_agent_key(),
+            )
+            .await?
+        {
+            return Err(ConductorApiError::CryptoRequestUnauthorized {
+                fn_name: operation.granted_function().1,
+                cell_id,
+                provenance,
+            });
+        }
+        let keystore = self.keystore();
+        let response = match operation {
+            CryptoOperation::Sign(data) => CryptoResponse::Signed(
+                keystore
+                    .sign(cell_id.agent_pubkey().clone(), data.into_vec().into())
+                    .await
+                    .map_err(ConductorApiError::other)?,
+            ),
+            CryptoOperation::Encrypt(input) => {
+                let (nonce, cipher) = keystore
+                    .crypto_box_xsalsa(
+                        x25519_bytes(input.as_sender_ref()).into(),
+                        x25519_bytes(input.as_recipient_ref()).into(),
+                        input.as_data_ref().as_ref().into(),
+                    )
+                    .await
+                    .map_err(ConductorApiError::other)?;
+                CryptoResponse::Encrypted(XSalsa20Poly1305EncryptedData::new(
+                    nonce.into(),
+                    cipher.to_vec(),
+                ))
+            }
+            CryptoOperation::Decrypt(input) => {
+                let encrypted = input.as_encrypted_data_ref();
+                let mut nonce = [0; 24];
+                nonce.copy_from_slice(encrypted.as_nonce_ref().as_ref());
+                let data = keystore
+                    .crypto_box_xsalsa_open(
+                        x25519_bytes(input.as_sender_ref()).into(),
+                        x25519_bytes(input.as_recipient_ref()).into(),
+                        nonce,
+                        encrypted.as_encrypted_data_ref().into(),
+                    )
+                    .await
+                    .map_err(ConductorApiError::other)?;
+                CryptoResponse::Decrypted(data.to_vec().into())
+            }
+        };
+        Ok(response)
+    }
+
     fn take_shutdown_handle(&self) -> Option<TaskManagerRunHandle> {
         self.conductor.take_shutdown_handle()
     }
@@ -882,6 +1099,47 @@ impl ConductorHandleT for ConductorHandleImpl {
         self.conductor.keystore()
     }
 
+    async fn generate_agent_pub_key(&self) -> ConductorResult<AgentPubKey> {
+        let agent_key = self.keystore().new_sign_keypair_random().await?;
+        if self.get_config().dpki.is_some() {
+            self.call_dpki::<_, ()>(DPKI_REGISTER_KEY_FN, &agent_key)
+                .await?;
+        }
+        Ok(agent_key)
+    }
+
+    async fn dpki_revoke_key(&self, agent_key: AgentPubKey) -> ConductorResult<()> {
+        self.call_dpki(DPKI_REVOKE_KEY_FN, &agent_key).await
+    }
+
+    async fn dpki_rotate_key(&self, agent_key: AgentPubKey) -> ConductorResult<AgentPubKey> {
+        let new_key = self.keystore().new_sign_keypair_random().await?;
+        self.call_dpki::<_, ()>(
+            DPKI_ROTATE_KEY_FN,
+            &RotateKeyInput {
+                old_key: agent_key,
+                new_key: new_key.clone(),
+            },
+        )
+        .await?;
+        Ok(new_key)
+    }
+
+    async fn dpki_key_state(&self, agent_key: AgentPubKey) -> ConductorResult<Option<KeyState>> {
+        match self.call_dpki(DPKI_KEY_STATE_FN, &agent_key).await {
+            Ok(key_state) => Ok(Some(key_state)),
+            Err(ConductorError::DpkiNotConfigured | ConductorError::DpkiNotRunning(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn dpki_dna_hash(&self) -> Option<DnaHash> {
+        self.dpki_cell_id()
+            .await
+            .ok()
+            .map(|cell_id| cell_id.dna_hash().clone())
+    }
+
     fn holochain_p2p(&self) -> &holochain_p2p::HolochainP2pRef {
         self.conductor.holochain_p2p()
     }
@@ -916,8 +1174,90 @@ impl ConductorHandleT for ConductorHandleImpl {
         Ok(cell_id)
     }
 
-    async fn destroy_clone_cell(self: Arc<Self>, _cell_id: CellId) -> ConductorResult<()> {
-        todo!()
+    async fn archive_clone_cell(self: Arc<Self>, payload: CloneCellPayload) -> ConductorResult<()> {
+        self.conductor.archive_clone_cell(payload).await?;
+
+        // Remove the now dangling clone cell
+        self.process_app_status_fx(AppStatusFx::SpinDown, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_clone_cell(self: Arc<Self>, payload: CloneCellPayload) -> ConductorResult<()> {
+        let app_id = payload.installed_app_id.clone();
+        self.conductor.restore_clone_cell(payload).await?;
+
+        // Create and join the restored clone cell if the app is running
+        self.process_app_status_fx(
+            AppStatusFx::SpinUp,
+            Some(vec![app_id].into_iter().collect()),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_archived_clone_cell(
+        self: Arc<Self>,
+        payload: CloneCellPayload,
+    ) -> ConductorResult<()> {
+        let dna_hash = payload.cell_id.dna_hash().clone();
+        let space_unused = self.conductor.remove_archived_clone_cell(payload).await?;
+
+        // Clone cells have their own unique DNA, so unless some other app
+        // uses the same DNA, all the data of the space belongs to this clone.
+        if space_unused {
+            self.conductor.spaces.remove_space(&dna_hash).await?;
+            self.conductor.unregister_dna(&dna_hash).await?;
+        } else {
+            tracing::warn!(
+                ?dna_hash,
+                "Deleted an archived clone cell whose DNA is still in use, so its data was kept"
+            );
+        }
+        Ok(())
+    }
+
+    async fn provision_app_role(
+        self: Arc<Self>,
+        payload: ProvisionAppRolePayload,
+    ) -> ConductorResult<CellId> {
+        let ProvisionAppRolePayload {
+            installed_app_id,
+            role_id,
+            membrane_proof,
+        } = payload;
+
+        // Claim the role before running genesis, so that it can only be
+        // provisioned once even if it is requested concurrently.
+        let cell_id = self
+            .conductor
+            .start_provisioning_app_role(installed_app_id.clone(), role_id.clone())
+            .await?;
+
+        if let Err(e) = crate::conductor::conductor::genesis_cells(
+            &self.conductor,
+            vec![(cell_id.clone(), membrane_proof)],
+            self.clone(),
+        )
+        .await
+        {
+            self.conductor
+                .abort_provisioning_app_role(installed_app_id, role_id)
+                .await?;
+            return Err(e);
+        }
+
+        self.conductor
+            .provision_app_role(installed_app_id.clone(), role_id)
+            .await?;
+
+        // Create and join the new cell if the app is running
+        self.process_app_status_fx(
+            AppStatusFx::SpinUp,
+            Some(vec![installed_app_id].into_iter().collect()),
+        )
+        .await?;
+        Ok(cell_id)
     }
 
     async fn install_app(
@@ -937,10 +1277,15 @@ impl ConductorHandleT for ConductorHandleImpl {
 
         let cell_data = cell_data.into_iter().map(|(c, _)| c);
         let app = InstalledAppCommon::new_legacy(installed_app_id, cell_data)?;
+        let agent_keys: HashSet<_> = app.all_cells().map(|c| c.agent_pubkey().clone()).collect();
 
         // Update the db
         let _ = self.conductor.add_disabled_app_to_db(app).await?;
 
+        for agent_key in agent_keys {
+            self.dpki_register_app_key(agent_key).await?;
+        }
+
         Ok(())
     }
 
@@ -954,13 +1299,20 @@ impl ConductorHandleT for ConductorHandleImpl {
             installed_app_id,
             membrane_proofs,
             uid,
+            network_overrides,
+            existing_cells,
         } = payload;
 
         let bundle: AppBundle = {
             let original_bundle = source.resolve().await?;
-            if let Some(uid) = uid {
+            if uid.is_some() || !network_overrides.is_empty() {
                 let mut manifest = original_bundle.manifest().to_owned();
-                manifest.set_uid(uid);
+                if let Some(uid) = uid {
+                    manifest.set_uid(uid);
+                }
+                manifest
+                    .set_network_overrides(network_overrides)
+                    .map_err(AppBundleError::from)?;
                 AppBundle::from(original_bundle.into_inner().update_manifest(manifest)?)
             } else {
                 original_bundle
@@ -970,9 +1322,25 @@ impl ConductorHandleT for ConductorHandleImpl {
         let installed_app_id =
             installed_app_id.unwrap_or_else(|| bundle.manifest().app_name().to_owned());
         let ops = bundle
-            .resolve_cells(agent_key.clone(), DnaGamut::placeholder(), membrane_proofs)
+            .resolve_cells(
+                agent_key.clone(),
+                DnaGamut::placeholder(),
+                membrane_proofs,
+                existing_cells,
+            )
             .await?;
 
+        // Existing cells are shared with the apps which already use them,
+        // so there must be such an app.
+        let state = self.conductor.get_state().await?;
+        for cell_id in ops.existing_cells() {
+            if state.apps_using_cell(cell_id).next().is_none() {
+                return Err(ConductorError::CellMissing(cell_id.clone()));
+            }
+        }
+
+        self.check_network_overrides(&ops.role_assignments).await?;
+
         let cells_to_create = ops.cells_to_create();
 
         for (dna, _) in ops.dnas_to_register {
@@ -983,11 +1351,13 @@ impl ConductorHandleT for ConductorHandleImpl {
             .await?;
 
         let roles = ops.role_assignments;
-        let app = InstalledAppCommon::new(installed_app_id, agent_key, roles);
+        let app = InstalledAppCommon::new(installed_app_id, agent_key.clone(), roles);
 
         // Update the db
         let stopped_app = self.conductor.add_disabled_app_to_db(app).await?;
 
+        self.dpki_register_app_key(agent_key).await?;
+
         Ok(stopped_app)
     }
 
@@ -1014,10 +1384,18 @@ impl ConductorHandleT for ConductorHandleImpl {
 
         let scheduler_handle = self.clone();
         tokio::task::spawn(async move {
-            let mut interval = tokio::time::interval(interval_period);
             loop {
-                interval.tick().await;
                 scheduler_handle.clone().dispatch_scheduled_fns().await;
+                // Wake when the next scheduled function is due, or after the
+                // interval at the latest to pick up newly scheduled functions.
+                let wait = scheduler_handle
+                    .next_scheduled_run()
+                    .await
+                    .and_then(|next| (next - Timestamp::now()).ok())
+                    .map(|until_next| until_next.to_std().unwrap_or_default())
+                    .unwrap_or(interval_period)
+                    .clamp(interval_period.min(SCHEDULER_MIN_WAIT), interval_period);
+                tokio::time::sleep(wait).await;
             }
         });
     }
@@ -1040,6 +1418,58 @@ impl ConductorHandleT for ConductorHandleImpl {
         futures::future::join_all(tasks).await;
     }
 
+    async fn list_scheduled_functions(
+        &self,
+        cell_id: Option<CellId>,
+    ) -> ConductorApiResult<Vec<ScheduledFunctionInfo>> {
+        let cell_ids = match cell_id {
+            Some(cell_id) => vec![cell_id],
+            None => self.conductor.list_cell_ids(None),
+        };
+        let mut scheduled = vec![];
+        for cell_id in cell_ids {
+            let cell_arc = self.cell_by_id(&cell_id)?;
+            for (scheduled_fn, schedule, next_run) in cell_arc.scheduled_fns().await? {
+                scheduled.push(ScheduledFunctionInfo {
+                    cell_id: cell_id.clone(),
+                    scheduled_fn,
+                    schedule,
+                    next_run,
+                });
+            }
+        }
+        scheduled.sort_by_key(|info| info.next_run);
+        Ok(scheduled)
+    }
+
+    async fn countersigning_session_state(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<Option<CountersigningSessionState>> {
+        Ok(self
+            .cell_by_id(cell_id)?
+            .countersigning_session_state()
+            .await?)
+    }
+
+    async fn abandon_countersigning_session(&self, cell_id: &CellId) -> ConductorApiResult<bool> {
+        Ok(self
+            .cell_by_id(cell_id)?
+            .abandon_countersigning_session()
+            .await?)
+    }
+
+    async fn rotate_db_key(&self) -> ConductorResult<()> {
+        if !cfg!(feature = "db-encryption") {
+            return Err(ConductorError::DbEncryptionNotEnabled);
+        }
+        crate::conductor::db_key::stage_db_key_rotation(
+            self.conductor.config.environment_path.as_ref(),
+            self.conductor.keystore(),
+        )
+        .await
+    }
+
     async fn post_commit_permit(&self) -> Result<OwnedPermit<PostCommitArgs>, SendError<()>> {
         self.conductor.post_commit_permit().await
     }
@@ -1131,6 +1561,9 @@ impl ConductorHandleT for ConductorHandleImpl {
         let self_clone = self.clone();
         let app = self.conductor.remove_app_from_db(installed_app_id).await?;
         tracing::debug!(msg = "Removed app from db.", app = ?app);
+        self.conductor
+            .interface_auth_tokens
+            .revoke_app(installed_app_id);
 
         // Remove cells which may now be dangling due to the removed app
         self_clone
@@ -1466,6 +1899,122 @@ impl ConductorHandleT for ConductorHandleImpl {
         Ok(())
     }
 
+    async fn export_cell_chain(&self, cell_id: &CellId) -> ConductorApiResult<CellChainArchive> {
+        let authored_db = self
+            .conductor
+            .get_or_create_authored_db(cell_id.dna_hash())?;
+        let author = cell_id.agent_pubkey().clone();
+
+        let records =
+            source_chain::chain_records(authored_db.clone().into(), author.clone()).await?;
+        if records.is_empty() {
+            return Err(SourceChainError::ChainEmpty.into());
+        }
+        let scheduled_fns = authored_db
+            .async_reader({
+                let author = author.clone();
+                move |txn| holochain_state::schedule::all_scheduled_fns(&txn, &author)
+            })
+            .await?;
+        let warrants = self
+            .conductor
+            .get_or_create_dht_db(cell_id.dna_hash())?
+            .async_reader(move |txn| {
+                holochain_state::warrant::get_warrants_for_agent(&txn, &author)
+            })
+            .await?;
+
+        let content = CellChainArchiveContent {
+            version: CELL_CHAIN_ARCHIVE_VERSION,
+            cell_id: cell_id.clone(),
+            exported_at: Timestamp::now(),
+            records,
+            warrants,
+            scheduled_fns,
+        };
+        let signature = cell_id
+            .agent_pubkey()
+            .sign(self.keystore(), &content)
+            .await?;
+        Ok(CellChainArchive { content, signature })
+    }
+
+    async fn import_cell_chain(
+        self: Arc<Self>,
+        archive: CellChainArchive,
+        truncate: bool,
+        validate: bool,
+    ) -> ConductorApiResult<()> {
+        let CellChainArchive { content, signature } = archive;
+        if content.version != CELL_CHAIN_ARCHIVE_VERSION {
+            return Err(ConductorApiError::InvalidCellChainArchive(format!(
+                "Unsupported archive version {}, expected {}",
+                content.version, CELL_CHAIN_ARCHIVE_VERSION
+            )));
+        }
+        let cell_id = content.cell_id.clone();
+        if !cell_id
+            .agent_pubkey()
+            .verify_signature(&signature, content.clone())
+            .await
+        {
+            return Err(ConductorApiError::InvalidCellChainArchive(
+                "The archive was not signed by the archived cell's agent".to_string(),
+            ));
+        }
+        self.check_archive_genesis(&cell_id, &content.records)?;
+        for warrant in &content.warrants {
+            if warrant.warrantee() != cell_id.agent_pubkey() || !verify_warrant(warrant).await {
+                return Err(ConductorApiError::InvalidCellChainArchive(format!(
+                    "The warrant issued by {} is not a valid warrant against the archived cell's agent",
+                    warrant.author
+                )));
+            }
+        }
+
+        let CellChainArchiveContent {
+            records,
+            warrants,
+            scheduled_fns,
+            ..
+        } = content;
+        self.clone()
+            .insert_records_into_source_chain(cell_id.clone(), truncate, validate, records)
+            .await?;
+
+        // Keep the warrants held against the agent with the rest of its activity.
+        self.conductor
+            .get_or_create_dht_db(cell_id.dna_hash())?
+            .async_commit(move |txn| {
+                for warrant in warrants {
+                    holochain_state::mutations::insert_warrant(txn, &warrant)?;
+                }
+                StateMutationResult::Ok(())
+            })
+            .await?;
+
+        // Reschedule the archived functions from now.
+        let authored_db = self
+            .conductor
+            .get_or_create_authored_db(cell_id.dna_hash())?;
+        authored_db
+            .async_commit(move |txn| {
+                let now = Timestamp::now();
+                for (scheduled_fn, maybe_schedule) in scheduled_fns {
+                    holochain_state::prelude::schedule_fn(
+                        txn,
+                        cell_id.agent_pubkey(),
+                        scheduled_fn,
+                        maybe_schedule,
+                        now,
+                    )?;
+                }
+                StateMutationResult::Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+
     #[cfg(any(test, feature = "test_utils"))]
     fn get_authored_db(&self, dna_hash: &DnaHash) -> ConductorApiResult<DbWrite<DbKindAuthored>> {
         Ok(self.conductor.get_or_create_authored_db(dna_hash)?)
@@ -1558,6 +2107,87 @@ impl ConductorHandleImpl {
         Ok(self.conductor.cell_by_id(cell_id)?)
     }
 
+    /// When the next function scheduled by a running cell is due to run.
+    async fn next_scheduled_run(&self) -> Option<Timestamp> {
+        let tasks = self
+            .conductor
+            .running_cell_ids()
+            .into_iter()
+            .filter_map(|cell_id| self.cell_by_id(&cell_id).ok())
+            .map(|cell_arc| cell_arc.next_scheduled_run());
+        futures::future::join_all(tasks)
+            .await
+            .into_iter()
+            .filter_map(|next| next.ok().flatten())
+            .min()
+    }
+
+    /// Check that an archived chain starts with genesis records for the
+    /// archived cell, and that they pass the DNA's genesis self-check.
+    fn check_archive_genesis(
+        &self,
+        cell_id: &CellId,
+        records: &[Record],
+    ) -> ConductorApiResult<()> {
+        let invalid = |reason: &str| ConductorApiError::InvalidCellChainArchive(reason.to_string());
+        let genesis: Vec<_> = records.iter().take(3).map(|r| r.action()).collect();
+        let membrane_proof = match genesis.as_slice() {
+            [Action::Dna(dna), Action::AgentValidationPkg(avp), Action::Create(create)] => {
+                if dna.hash != *cell_id.dna_hash() {
+                    return Err(invalid(
+                        "The archived Dna action does not match the cell's DnaHash",
+                    ));
+                }
+                if create.entry_type != EntryType::AgentPubKey
+                    || create.entry_hash != cell_id.agent_pubkey().clone().into()
+                {
+                    return Err(invalid(
+                        "The archived agent key does not match the cell's agent",
+                    ));
+                }
+                avp.membrane_proof.clone()
+            }
+            _ => {
+                return Err(invalid(
+                    "The archive does not start with the genesis records",
+                ))
+            }
+        };
+
+        let ribosome = self.get_ribosome(cell_id.dna_hash())?;
+        let DnaDef {
+            name,
+            properties,
+            integrity_zomes,
+            ..
+        } = &ribosome.dna_def().content;
+        let dna_info = DnaInfo {
+            zome_names: integrity_zomes.iter().map(|(n, _)| n.clone()).collect(),
+            name: name.clone(),
+            hash: cell_id.dna_hash().clone(),
+            properties: properties.clone(),
+        };
+        let result = ribosome
+            .run_genesis_self_check(
+                GenesisSelfCheckHostAccess,
+                GenesisSelfCheckInvocation {
+                    payload: Arc::new(GenesisSelfCheckData {
+                        dna_info,
+                        membrane_proof,
+                        agent_key: cell_id.agent_pubkey().clone(),
+                    }),
+                },
+            )
+            .map_err(ConductorError::from)?;
+        if let GenesisSelfCheckResult::Invalid(reason) = result {
+            return Err(ConductorApiError::InvalidCellChainArchive(format!(
+                "The archived genesis failed the DNA's genesis self-check: {}",
+                reason
+            )));
+        }
+        Ok(())
+    }
+
     /// Install just the "code parts" (the wasm and entry defs) of a dna
     async fn register_genotype(&self, ribosome: RealRibosome) -> ConductorResult<()> {
         let entry_defs = self.conductor.register_dna_wasm(ribosome).await?;
@@ -1660,11 +2290,27 @@ impl ConductorHandleImpl {
 
         use holochain_p2p::AgentPubKeyExt;
 
+        // Never join a space without its overrides,
+        // which might keep it off the public network.
+        let network_overrides = match self.network_overrides().await {
+            Ok(network_overrides) => network_overrides,
+            Err(e) => {
+                tracing::error!(error = ?e, "Could not get the network overrides of installed apps");
+                return Vec::new();
+            }
+        };
+        let network_overrides = &network_overrides;
+
         let tasks = self
             .conductor
             .mark_pending_cells_as_joining()
             .into_iter()
             .map(|(cell_id, cell)| async move {
+                let config_override = network_overrides.get(cell_id.dna_hash()).cloned().flatten();
+                if let Err(e) = self.configure_space(&cell, config_override).await {
+                    tracing::error!(error = ?e, cell_id = ?cell_id, "Could not configure the network for the cell's space");
+                    return Err(cell_id);
+                }
                 let p2p_agents_db = cell.p2p_agents_db().clone();
                 let kagent = cell_id.agent_pubkey().to_kitsune();
                 let agent_info = match p2p_agents_db.async_reader(move |tx| {
@@ -1675,6 +2321,18 @@ impl ConductorHandleImpl {
                 };
                 let maybe_initial_arc = agent_info.map(|i| i.storage_arc);
                 let network = cell.holochain_p2p_dna().clone();
+                // Warranted agents stay blocked across restarts.
+                match self.conductor.spaces.get_or_create_space(cell_id.dna_hash()) {
+                    Ok(space) => {
+                        if let Err(e) = restore_blocks(&space.dht_db, &network).await {
+                            tracing::warn!(error = ?e, cell_id = ?cell_id, "Could not restore the blocks of warranted agents");
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!(error = ?e, cell_id = ?cell_id, "Could not get the cell's space");
+                        return Err(cell_id);
+                    }
+                }
                 match tokio::time::timeout(JOIN_NETWORK_TIMEOUT, network.join(cell_id.agent_pubkey().clone(), maybe_initial_arc)).await {
                     Ok(Err(e)) => {
                         tracing::info!(error = ?e, cell_id = ?cell_id, "Error while trying to join the network");
@@ -1711,6 +2369,173 @@ impl ConductorHandleImpl {
         cell_ids
     }
 
+    /// The network config overrides for the space of every cell of every
+    /// installed app. Spaces used by an app without overrides map to `None`.
+    async fn network_overrides(
+        &self,
+    ) -> ConductorResult<HashMap<DnaHash, Option<KitsuneP2pConfigOverride>>> {
+        let state = self.conductor.get_state().await?;
+        Ok(state
+            .installed_apps()
+            .values()
+            .flat_map(|app| app.roles().values())
+            .flat_map(|role| {
+                let network = role.network().filter(|n| !n.is_empty()).cloned();
+                std::iter::once(role.cell_id())
+                    .chain(role.clones())
+                    .chain(role.archived_clones())
+                    .map(move |cell_id| (cell_id.dna_hash().clone(), network.clone()))
+            })
+            .collect())
+    }
+
+    /// Check that the network overrides of the roles of an app being installed
+    /// can be applied, and agree with the overrides of all other apps which
+    /// use the same spaces.
+    async fn check_network_overrides(
+        &self,
+        roles: &[(AppRoleId, AppRoleAssignment)],
+    ) -> ConductorResult<()> {
+        let mut network_overrides = self.network_overrides().await?;
+        for (_, role) in roles {
+            let dna_hash = role.dna_hash();
+            let network = role.network().filter(|n| !n.is_empty()).cloned();
+            if let Some(network) = &network {
+                self.conductor
+                    .spaces
+                    .check_network_override(dna_hash, network)?;
+            }
+            match network_overrides.get(dna_hash) {
+                Some(existing) if existing != &network => {
+                    return Err(ConductorError::InvalidNetworkOverride(
+                        dna_hash.clone(),
+                        "another app uses this DNA with different network overrides".to_string(),
+                    ));
+                }
+                _ => {
+                    network_overrides.insert(dna_hash.clone(), network);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the network overrides for a cell's space, both
+    /// to the conductor's space and to the network.
+    async fn configure_space(
+        &self,
+        cell: &Cell,
+        config_override: Option<KitsuneP2pConfigOverride>,
+    ) -> ConductorResult<()> {
+        let network = cell.holochain_p2p_dna();
+        self.conductor
+            .spaces
+            .set_network_override(&network.dna_hash(), config_override.as_ref())?;
+        network
+            .configure_space(config_override.unwrap_or_default())
+            .await
+            .map_err(ConductorError::other)?;
+        Ok(())
+    }
+
+    /// Install the DPKI app from its bundle if it is not installed yet,
+    /// and make sure it is running.
+    async fn initialize_dpki(self: Arc<Self>) -> ConductorResult<()> {
+        let config = match &self.get_config().dpki {
+            Some(config) => config.clone(),
+            None => return Ok(()),
+        };
+        let installed_app_id = config.instance_id;
+        let state = self.conductor.get_state().await?;
+        let installed = match state.get_app(&installed_app_id) {
+            Ok(app) => Some(app.status().is_running()),
+            Err(_) => None,
+        };
+
+        match installed {
+            Some(true) => (),
+            Some(false) => {
+                self.clone().enable_app(installed_app_id).await?;
+            }
+            None => {
+                let bundle_path = config.bundle_path.ok_or_else(|| {
+                    ConductorError::ConfigError(format!(
+                        "The DPKI app {} is not installed and no bundle_path is configured to install it from",
+                        installed_app_id
+                    ))
+                })?;
+                let agent_key = self.keystore().new_sign_keypair_random().await?;
+                self.clone()
+                    .install_app_bundle(InstallAppBundlePayload {
+                        source: AppBundleSource::Path(bundle_path),
+                        agent_key,
+                        installed_app_id: Some(installed_app_id.clone()),
+                        membrane_proofs: HashMap::new(),
+                        uid: None,
+                        network_overrides: HashMap::new(),
+                        existing_cells: HashMap::new(),
+                    })
+                    .await?;
+                self.clone().enable_app(installed_app_id).await?;
+                self.call_dpki::<_, ()>(DPKI_INIT_FN, &config.init_params)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The cell of the running DPKI app which the conductor calls into.
+    async fn dpki_cell_id(&self) -> ConductorResult<CellId> {
+        let installed_app_id = match &self.get_config().dpki {
+            Some(config) => &config.instance_id,
+            None => return Err(ConductorError::DpkiNotConfigured),
+        };
+        let not_running = || ConductorError::DpkiNotRunning(installed_app_id.clone());
+        let state = self.conductor.get_state().await?;
+        let app = state.get_app(installed_app_id).map_err(|_| not_running())?;
+        if !app.status().is_running() {
+            return Err(not_running());
+        }
+        let cell_id = app
+            .provisioned_cells()
+            .map(|(_, cell_id)| cell_id.clone())
+            .next();
+        cell_id.ok_or_else(not_running)
+    }
+
+    /// Call a function of the DPKI zome as the DPKI app's agent.
+    async fn call_dpki<I, O>(&self, fn_name: &str, payload: &I) -> ConductorResult<O>
+    where
+        I: serde::Serialize + std::fmt::Debug,
+        O: serde::de::DeserializeOwned + std::fmt::Debug,
+    {
+        let cell_id = self.dpki_cell_id().await?;
+        let call = ZomeCall {
+            cell_id: cell_id.clone(),
+            zome_name: DPKI_ZOME_NAME.into(),
+            fn_name: fn_name.into(),
+            cap_secret: None,
+            provenance: cell_id.agent_pubkey().clone(),
+            payload: ExternIO::encode(payload)?,
+        };
+        let failed = |reason: String| ConductorError::DpkiCallFailed(fn_name.to_string(), reason);
+        match self.call_zome(call).await {
+            Ok(Ok(ZomeCallResponse::Ok(output))) => Ok(output.decode()?),
+            Ok(Ok(response)) => Err(failed(format!("{:?}", response))),
+            Ok(Err(e)) => Err(failed(e.to_string())),
+            Err(e) => Err(failed(e.to_string())),
+        }
+    }
+
+    /// Register the agent key of an installed app with DPKI,
+    /// unless DPKI already knows about it or is not in use.
+    async fn dpki_register_app_key(&self, agent_key: AgentPubKey) -> ConductorResult<()> {
+        match self.dpki_key_state(agent_key.clone()).await? {
+            Some(KeyState::NotFound) => self.call_dpki(DPKI_REGISTER_KEY_FN, &agent_key).await,
+            _ => Ok(()),
+        }
+    }
+
     pub(super) fn p2p_agents_db(&self, hash: &DnaHash) -> DbWrite<DbKindP2pAgents> {
         self.conductor
             .spaces
@@ -1732,3 +2557,10 @@ impl ConductorHandleImpl {
             .expect("failed to open p2p_metrics_store database")
     }
 }
+
+/// The raw bytes of an X25519 public key, as used by the keystore.
+fn x25519_bytes(key: &X25519PubKey) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(key.as_ref());
+    bytes
+}
@@ -2,16 +2,23 @@
 //! and dispatch them to the appropriate handlers within Holochain.
 //! They also allow emitting responses and one-way Signals.
 //!
-//! Currently the only InterfaceDriver is a Websocket-based one, whose
-//! implementation can be found in the `websocket` module here.
+//! Every InterfaceDriver is Websocket-based, whether over TCP, TLS or a
+//! unix domain socket. The implementation can be found in the `websocket`
+//! module here.
 
 use crate::conductor::api::*;
 use error::InterfaceError;
 use error::InterfaceResult;
+use holochain_conductor_api::signal_subscription::SignalFilterSet;
+use holochain_types::prelude::*;
 use holochain_types::signal::Signal;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 
+pub mod auth;
 #[allow(missing_docs)]
 pub mod error;
 pub mod websocket;
@@ -98,4 +105,81 @@ impl SignalBroadcaster {
     }
 }
 
+/// The cells of each installed app, which the conductor keeps up to date as
+/// apps are installed and cells are cloned, archived or deleted.
+/// Signal subscriptions are resolved against it when a signal is delivered.
+#[derive(Clone, Debug, Default)]
+pub struct AppCells(Arc<parking_lot::RwLock<HashMap<InstalledAppId, HashSet<CellId>>>>);
+
+impl AppCells {
+    /// Replace the index with the cells of these apps
+    pub fn update<'a>(
+        &self,
+        apps: impl IntoIterator<Item = (&'a InstalledAppId, &'a InstalledApp)>,
+    ) {
+        *self.0.write() = apps
+            .into_iter()
+            .map(|(id, app)| (id.clone(), app.all_cells().cloned().collect()))
+            .collect();
+    }
+
+    /// Whether the cell currently belongs to the app
+    pub fn contains(&self, installed_app_id: &InstalledAppId, cell_id: &CellId) -> bool {
+        self.0
+            .read()
+            .get(installed_app_id)
+            .map_or(false, |cells| cells.contains(cell_id))
+    }
+}
+
+type SignalFilters = HashMap<InstalledAppId, SignalFilterSet>;
+
+/// The signals which a single connection to an app interface has subscribed to.
+/// A connection which has never subscribed receives every signal, unless it
+/// must authenticate, in which case it receives no App signals until it
+/// subscribes to an app or authenticates for one.
+#[derive(Clone, Debug)]
+pub struct SignalSubscriptions {
+    app_cells: AppCells,
+    filters: Arc<parking_lot::RwLock<Option<SignalFilters>>>,
+}
+
+impl SignalSubscriptions {
+    /// Subscriptions which deliver every signal until the connection subscribes
+    pub fn all(app_cells: AppCells) -> Self {
+        Self {
+            app_cells,
+            filters: Default::default(),
+        }
+    }
+
+    /// Subscriptions which deliver no App signals until the connection subscribes
+    pub fn none(app_cells: AppCells) -> Self {
+        Self {
+            app_cells,
+            filters: Arc::new(parking_lot::RwLock::new(Some(HashMap::new()))),
+        }
+    }
+
+    /// Deliver App signals from the app's cells which the filters allow,
+    /// replacing any earlier subscription for the app.
+    /// Cells which are added to the app later are included too.
+    pub fn subscribe(&self, installed_app_id: InstalledAppId, filters: SignalFilterSet) {
+        self.filters
+            .write()
+            .get_or_insert_with(HashMap::new)
+            .insert(installed_app_id, filters);
+    }
+
+    /// Whether this signal should be delivered to the connection
+    pub fn allows(&self, signal: &Signal) -> bool {
+        match (signal, &*self.filters.read()) {
+            (Signal::App(cell_id, _), Some(apps)) => apps.iter().any(|(app_id, filters)| {
+                filters.allows(cell_id) && self.app_cells.contains(app_id, cell_id)
+            }),
+            _ => true,
+        }
+    }
+}
+
 pub use holochain_conductor_api::config::InterfaceDriver;
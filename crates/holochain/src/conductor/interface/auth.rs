@@ -0,0 +1,165 @@
+//! The tokens a conductor has issued for authenticating connections to its
+//! interfaces.
+
+use holochain_conductor_api::AuthenticationToken;
+use holochain_conductor_api::AuthenticationTokenIssued;
+use holochain_types::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The length in bytes of an issued token
+const TOKEN_LEN: usize = 32;
+
+/// What a token authenticates a connection for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TokenGrant {
+    Admin,
+    App(InstalledAppId),
+}
+
+#[derive(Clone, Debug)]
+struct IssuedToken {
+    grant: TokenGrant,
+    expires_at: Option<Timestamp>,
+    single_use: bool,
+}
+
+/// Tokens issued through the admin API, which are only kept in memory
+/// and so don't outlive the conductor.
+#[derive(Clone, Debug, Default)]
+pub struct InterfaceAuthTokens(Arc<parking_lot::Mutex<HashMap<AuthenticationToken, IssuedToken>>>);
+
+impl InterfaceAuthTokens {
+    /// Issue a token for admin interfaces
+    pub fn issue_admin(&self, expiry_seconds: u64, single_use: bool) -> AuthenticationTokenIssued {
+        self.issue(TokenGrant::Admin, expiry_seconds, single_use)
+    }
+
+    /// Issue a token for app interfaces, bound to an app
+    pub fn issue_app(
+        &self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: u64,
+        single_use: bool,
+    ) -> AuthenticationTokenIssued {
+        self.issue(
+            TokenGrant::App(installed_app_id),
+            expiry_seconds,
+            single_use,
+        )
+    }
+
+    fn issue(
+        &self,
+        grant: TokenGrant,
+        expiry_seconds: u64,
+        single_use: bool,
+    ) -> AuthenticationTokenIssued {
+        let mut token = vec![0; TOKEN_LEN];
+        rand::thread_rng().fill(&mut token[..]);
+        let expires_at = (expiry_seconds > 0)
+            .then(|| (Timestamp::now() + Duration::from_secs(expiry_seconds)).ok())
+            .flatten();
+        let mut tokens = self.0.lock();
+        prune_expired(&mut tokens);
+        tokens.insert(
+            token.clone(),
+            IssuedToken {
+                grant,
+                expires_at,
+                single_use,
+            },
+        );
+        AuthenticationTokenIssued { token, expires_at }
+    }
+
+    /// Check a token presented to an admin interface,
+    /// revoking it if it was single use
+    pub fn authenticate_admin(&self, token: &[u8]) -> bool {
+        self.authenticate(token, |grant| *grant == TokenGrant::Admin)
+            .is_some()
+    }
+
+    /// Check a token presented to an app interface,
+    /// revoking it if it was single use.
+    /// Returns the app the token was issued for.
+    pub fn authenticate_app(&self, token: &[u8]) -> Option<InstalledAppId> {
+        match self.authenticate(token, |grant| matches!(grant, TokenGrant::App(_)))? {
+            TokenGrant::App(installed_app_id) => Some(installed_app_id),
+            TokenGrant::Admin => None,
+        }
+    }
+
+    fn authenticate(
+        &self,
+        token: &[u8],
+        accept: impl FnOnce(&TokenGrant) -> bool,
+    ) -> Option<TokenGrant> {
+        let mut tokens = self.0.lock();
+        prune_expired(&mut tokens);
+        let issued = tokens.get(token).filter(|issued| accept(&issued.grant))?;
+        let grant = issued.grant.clone();
+        if issued.single_use {
+            tokens.remove(token);
+        }
+        Some(grant)
+    }
+
+    /// Revoke a token. Returns false if it had not been issued or had expired.
+    pub fn revoke(&self, token: &[u8]) -> bool {
+        let mut tokens = self.0.lock();
+        prune_expired(&mut tokens);
+        tokens.remove(token).is_some()
+    }
+
+    /// Revoke all tokens issued for an app
+    pub fn revoke_app(&self, installed_app_id: &InstalledAppId) {
+        self.0.lock().retain(
+            |_, issued| !matches!(&issued.grant, TokenGrant::App(id) if id == installed_app_id),
+        );
+    }
+}
+
+fn prune_expired(tokens: &mut HashMap<AuthenticationToken, IssuedToken>) {
+    let now = Timestamp::now();
+    tokens.retain(|_, issued| {
+        issued
+            .expires_at
+            .map_or(true, |expires_at| expires_at > now)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_use_tokens_authenticate_once() {
+        let tokens = InterfaceAuthTokens::default();
+        let issued = tokens.issue_app("app".into(), 30, true);
+        assert!(issued.expires_at.is_some());
+        assert!(!tokens.authenticate_admin(&issued.token));
+        assert_eq!(tokens.authenticate_app(&issued.token), Some("app".into()));
+        assert_eq!(tokens.authenticate_app(&issued.token), None);
+
+        let issued = tokens.issue_admin(0, false);
+        assert_eq!(issued.expires_at, None);
+        assert_eq!(tokens.authenticate_app(&issued.token), None);
+        assert!(tokens.authenticate_admin(&issued.token));
+        assert!(tokens.authenticate_admin(&issued.token));
+        assert!(tokens.revoke(&issued.token));
+        assert!(!tokens.authenticate_admin(&issued.token));
+    }
+
+    #[test]
+    fn revoking_an_app_revokes_its_tokens() {
+        let tokens = InterfaceAuthTokens::default();
+        let a = tokens.issue_app("a".into(), 0, false);
+        let b = tokens.issue_app("b".into(), 0, false);
+        tokens.revoke_app(&"a".into());
+        assert_eq!(tokens.authenticate_app(&a.token), None);
+        assert_eq!(tokens.authenticate_app(&b.token), Some("b".into()));
+    }
+}
@@ -27,6 +27,8 @@ pub enum InterfaceError {
     WebsocketError(#[from] holochain_websocket::WebsocketError),
     #[error("Failed to find free port")]
     PortError,
+    #[error("Failed to read TLS certificate or key file {0:?}: {1}")]
+    TlsFile(std::path::PathBuf, std::io::Error),
 }
 
 impl From<String> for InterfaceError {
@@ -1,5 +1,6 @@
 //! Module for establishing Websocket-based Interfaces,
-//! i.e. those configured with `InterfaceDriver::Websocket`
+//! i.e. those configured with `InterfaceDriver::Websocket`,
+//! `InterfaceDriver::TlsWebsocket` or `InterfaceDriver::UnixSocket`
 
 use super::error::InterfaceError;
 use super::error::InterfaceResult;
@@ -7,10 +8,12 @@ use crate::conductor::conductor::StopReceiver;
 use crate::conductor::interface::*;
 use crate::conductor::manager::ManagedTaskHandle;
 use crate::conductor::manager::ManagedTaskResult;
+use holochain_conductor_api::config::TlsConfig;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_types::signal::Signal;
 use holochain_websocket::ListenerHandle;
 use holochain_websocket::ListenerItem;
+use holochain_websocket::ListenerStream;
 use holochain_websocket::WebsocketConfig;
 use holochain_websocket::WebsocketListener;
 use holochain_websocket::WebsocketMessage;
@@ -34,19 +37,55 @@ const MAX_CONNECTIONS: isize = 400;
 
 /// Create a WebsocketListener to be used in interfaces
 pub async fn spawn_websocket_listener(
-    port: u16,
-) -> InterfaceResult<(
-    ListenerHandle,
-    impl futures::stream::Stream<Item = ListenerItem>,
-)> {
+    driver: &InterfaceDriver,
+) -> InterfaceResult<(ListenerHandle, ListenerStream)> {
     trace!("Initializing Admin interface");
-    let listener = WebsocketListener::bind_with_handle(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
-    trace!("LISTENING AT: {}", listener.0.local_addr());
-    Ok(listener)
+    let (handle, listener): (_, ListenerStream) = match driver {
+        InterfaceDriver::Websocket { port } => {
+            let (handle, listener) = WebsocketListener::bind_with_handle(
+                url2!("ws://127.0.0.1:{}", port),
+                Arc::new(WebsocketConfig::default()),
+            )
+            .await?;
+            (handle, Box::pin(listener))
+        }
+        InterfaceDriver::TlsWebsocket { port, tls } => {
+            let cert_pem = read_tls_file(&tls.cert_path).await?;
+            let key_pem = read_tls_file(&tls.key_path).await?;
+            let (handle, listener) = WebsocketListener::bind_tls_with_handle(
+                url2!("wss://0.0.0.0:{}", port),
+                Arc::new(WebsocketConfig::default().scheme("wss")),
+                &cert_pem,
+                &key_pem,
+            )
+            .await?;
+            (handle, Box::pin(listener))
+        }
+        #[cfg(unix)]
+        InterfaceDriver::UnixSocket { path } => {
+            // The socket is created so that only the conductor's user may connect.
+            let (handle, listener) = WebsocketListener::bind_unix_with_handle(
+                path,
+                Arc::new(WebsocketConfig::default()),
+            )
+            .await?;
+            (handle, Box::pin(listener))
+        }
+        #[cfg(not(unix))]
+        InterfaceDriver::UnixSocket { .. } => {
+            return Err(InterfaceError::Other(
+                "Unix domain sockets are not supported on this platform".into(),
+            ))
+        }
+    };
+    trace!("LISTENING AT: {}", handle.local_addr());
+    Ok((handle, listener))
+}
+
+async fn read_tls_file(path: &std::path::Path) -> InterfaceResult<Vec<u8>> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| InterfaceError::TlsFile(path.to_owned(), e))
 }
 
 /// Create an Admin Interface, which only receives AdminRequest messages
@@ -90,20 +129,21 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
 }
 
 /// Create an App Interface, which includes the ability to receive signals
-/// from Cells via a broadcast channel
-pub async fn spawn_app_interface_task<A: InterfaceApi>(
+/// from Cells via a broadcast channel.
+/// Each connection only receives the signals it has subscribed to.
+pub async fn spawn_app_interface_task(
     port: u16,
-    api: A,
+    tls: Option<TlsConfig>,
+    api: RealAppInterfaceApi,
     signal_broadcaster: broadcast::Sender<Signal>,
     mut stop_rx: StopReceiver,
 ) -> InterfaceResult<(u16, ManagedTaskHandle)> {
     trace!("Initializing App interface");
-    let (handle, mut listener) = WebsocketListener::bind_with_handle(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
-    trace!("LISTENING AT: {}", handle.local_addr());
+    let driver = match tls {
+        Some(tls) => InterfaceDriver::TlsWebsocket { port, tls },
+        None => InterfaceDriver::Websocket { port },
+    };
+    let (handle, mut listener) = spawn_websocket_listener(&driver).await?;
     let port = handle
         .local_addr()
         .port()
@@ -116,14 +156,26 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
         // establish a new connection to a client
         while let Some(connection) = listener.next().await {
             match connection {
-                Ok((tx_to_iface, rx_from_iface)) => {
-                    let rx_from_cell = signal_broadcaster.subscribe();
-                    spawn_recv_incoming_msgs_and_outgoing_signals(
-                        api.clone(),
-                        rx_from_iface,
-                        rx_from_cell,
-                        tx_to_iface,
-                    );
+                Ok((tx_to_iface, mut rx_from_iface)) => {
+                    let api = api.for_connection();
+                    let signal_broadcaster = signal_broadcaster.clone();
+                    tokio::task::spawn(async move {
+                        // Dropping both ends of an unauthenticated
+                        // connection closes it.
+                        let api = match authenticate_connection(api, &mut rx_from_iface).await {
+                            Some(api) => api,
+                            None => return,
+                        };
+                        let rx_from_cell = signal_broadcaster.subscribe();
+                        let signal_subscriptions = api.signal_subscriptions().clone();
+                        spawn_recv_incoming_msgs_and_outgoing_signals(
+                            api,
+                            rx_from_iface,
+                            rx_from_cell,
+                            signal_subscriptions,
+                            tx_to_iface,
+                        );
+                    });
                 }
                 Err(err) => {
                     warn!("Admin socket connection failed: {}", err);
@@ -140,24 +192,59 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
 /// Used by Admin interface.
 async fn recv_incoming_admin_msgs<A: InterfaceApi>(
     api: A,
-    rx_from_iface: WebsocketReceiver,
+    mut rx_from_iface: WebsocketReceiver,
     num_connections: Arc<AtomicIsize>,
 ) {
     use futures::stream::StreamExt;
 
-    rx_from_iface
-        .for_each_concurrent(4096, move |msg| {
-            let api = api.clone();
-            async move {
-                if let Err(e) = handle_incoming_message(msg, api.clone()).await {
-                    error!(error = &e as &dyn std::error::Error)
+    if let Some(api) = authenticate_connection(api, &mut rx_from_iface).await {
+        rx_from_iface
+            .for_each_concurrent(4096, move |msg| {
+                let api = api.clone();
+                async move {
+                    if let Err(e) = handle_incoming_message(msg, api.clone()).await {
+                        error!(error = &e as &dyn std::error::Error)
+                    }
                 }
-            }
-        })
-        .await;
+            })
+            .await;
+    }
     num_connections.fetch_sub(1, Ordering::SeqCst);
 }
 
+/// If the interface requires it, authenticates a connection with its first
+/// message. Returns the API to handle the rest of the connection's messages
+/// with, or `None` if the connection failed to authenticate and should be closed.
+async fn authenticate_connection<A: InterfaceApi>(
+    api: A,
+    rx_from_iface: &mut WebsocketReceiver,
+) -> Option<A> {
+    if !api.requires_authentication() {
+        return Some(api);
+    }
+    let (bytes, respond) = rx_from_iface.next().await?;
+    let result = async move {
+        let (response, api) = api.authenticate(bytes.try_into()).await?;
+        respond.respond(response.try_into()?).await?;
+        InterfaceResult::Ok(api)
+    }
+    .await;
+    match result {
+        Ok(Some(api)) => Some(api),
+        Ok(None) => {
+            warn!(
+                "Closing unauthenticated connection from {}",
+                rx_from_iface.remote_addr()
+            );
+            None
+        }
+        Err(err) => {
+            error!(?err, "error authenticating websocket connection");
+            None
+        }
+    }
+}
+
 /// Polls for messages coming in from the external client while simultaneously
 /// polling for signals being broadcast from the Cells associated with this
 /// App interface.
@@ -165,6 +252,7 @@ fn spawn_recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
     api: A,
     rx_from_iface: WebsocketReceiver,
     rx_from_cell: broadcast::Receiver<Signal>,
+    signal_subscriptions: SignalSubscriptions,
     tx_to_iface: WebsocketSender,
 ) {
     use futures::stream::StreamExt;
@@ -180,8 +268,12 @@ fn spawn_recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
     });
 
     tokio::task::spawn(rx_from_cell.for_each_concurrent(4096, move |signal| {
+        let subscribed = signal_subscriptions.allows(&signal);
         let mut tx_to_iface = tx_to_iface.clone();
         async move {
+            if !subscribed {
+                return;
+            }
             trace!(msg = "Sending signal!", ?signal);
             if let Err(err) = async move {
                 let bytes = SerializedBytes::try_from(signal)?;
@@ -235,12 +327,15 @@ pub mod test {
     use crate::test_utils::conductor_setup::ConductorTestData;
     use ::fixt::prelude::*;
     use futures::future::FutureExt;
+    use holochain_conductor_api::signal_subscription::*;
     use holochain_p2p::{AgentPubKeyExt, DnaHashExt};
     use holochain_serialized_bytes::prelude::*;
     use holochain_sqlite::prelude::*;
     use holochain_state::prelude::test_db_dir;
     use holochain_types::prelude::*;
+    use holochain_types::signal::SystemSignal;
     use holochain_types::test_utils::fake_agent_pubkey_1;
+    use holochain_types::test_utils::fake_cell_id;
     use holochain_types::test_utils::fake_dna_hash;
     use holochain_types::test_utils::fake_dna_zomes;
     use holochain_types::{app::InstallAppDnaPayload, prelude::InstallAppPayload};
@@ -414,6 +509,102 @@ pub mod test {
         shutdown.await.unwrap().unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn signal_subscription() {
+        observability::test_run().ok();
+        let cell_ids: Vec<_> = (0..2)
+            .map(|i| {
+                let dna = fake_dna_zomes(
+                    &format!("{}-{}", Uuid::new_v4(), i),
+                    vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+                );
+                let cell_id = CellId::from((dna.dna_hash().clone(), fake_agent_pubkey_1()));
+                (dna, cell_id)
+            })
+            .collect();
+        let cell_data = cell_ids
+            .iter()
+            .enumerate()
+            .map(|(i, (_, cell_id))| {
+                (
+                    InstalledCell::new(cell_id.clone(), format!("role{}", i)),
+                    None,
+                )
+            })
+            .collect();
+        let (dnas, cell_ids): (Vec<_>, Vec<_>) = cell_ids.into_iter().unzip();
+        let (_tmpdir, app_api, handle) = setup_app(dnas, cell_data).await;
+        let app_signal = |cell_id: &CellId| {
+            Signal::App(
+                cell_id.clone(),
+                AppSignal::new(ExternIO::encode(()).unwrap()),
+            )
+        };
+
+        // A connection receives every signal until it subscribes.
+        let app_api = app_api.for_connection();
+        assert!(app_api
+            .signal_subscriptions()
+            .allows(&app_signal(&cell_ids[0])));
+        assert!(app_api
+            .signal_subscriptions()
+            .allows(&app_signal(&cell_ids[1])));
+
+        let filters = SignalFilterSet::Exclude(
+            [(cell_ids[1].clone(), SignalFilter::empty())]
+                .into_iter()
+                .collect(),
+        );
+        let response = app_api
+            .handle_app_request(AppRequest::SignalSubscription(SignalSubscription::new(
+                "test app".into(),
+                filters,
+            )))
+            .await;
+        assert_matches!(response, AppResponse::SignalSubscriptionUpdated);
+        let subscriptions = app_api.signal_subscriptions();
+        assert!(subscriptions.allows(&app_signal(&cell_ids[0])));
+        assert!(!subscriptions.allows(&app_signal(&cell_ids[1])));
+        // Cells outside the app are excluded once a connection has subscribed.
+        assert!(!subscriptions.allows(&app_signal(&fake_cell_id(1))));
+        assert!(subscriptions.allows(&Signal::System(SystemSignal::Test("test".into()))));
+
+        // Other connections are unaffected.
+        assert!(app_api
+            .for_connection()
+            .signal_subscriptions()
+            .allows(&app_signal(&cell_ids[1])));
+
+        // Cells which join the app later, like clones, are included.
+        let new_cell = fake_cell_id(2);
+        assert!(!subscriptions.allows(&app_signal(&new_cell)));
+        let mut cells = handle
+            .get_app_info(&"test app".to_string())
+            .await
+            .unwrap()
+            .unwrap()
+            .cell_data;
+        cells.push(InstalledCell::new(new_cell.clone(), "clone".into()));
+        let app =
+            InstalledApp::new_fresh(InstalledAppCommon::new_legacy("test app", cells).unwrap());
+        handle
+            .app_cells()
+            .update(std::iter::once((&"test app".to_string(), &app)));
+        assert!(subscriptions.allows(&app_signal(&new_cell)));
+
+        let response = app_api
+            .handle_app_request(AppRequest::SignalSubscription(SignalSubscription::new(
+                "no such app".into(),
+                SignalFilterSet::allow_all(),
+            )))
+            .await;
+        assert_matches!(response, AppResponse::Error(_));
+
+        let shutdown = handle.take_shutdown_handle().unwrap();
+        handle.shutdown();
+        shutdown.await.unwrap().unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn enable_disable_app() {
         observability::test_run().ok();
@@ -546,7 +737,12 @@ pub mod test {
         let (_tmpdir, conductor_handle) = setup_admin().await;
         let shutdown = conductor_handle.take_shutdown_handle().unwrap();
         let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
-        let msg = AdminRequest::AttachAppInterface { port: None };
+        let msg = AdminRequest::AttachAppInterface {
+            port: None,
+            rate_limit: None,
+            tls: None,
+            require_auth: false,
+        };
         let msg = msg.try_into().unwrap();
         let respond = |bytes: SerializedBytes| {
             let response: AdminResponse = bytes.try_into().unwrap();
@@ -560,6 +756,180 @@ pub mod test {
         shutdown.await.unwrap().unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn admin_connection_must_authenticate() {
+        observability::test_run().ok();
+        let (_tmpdir, conductor_handle) = setup_admin().await;
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone()).with_auth(Some(
+            AdminInterfaceAuth {
+                secret: Some("secret".into()),
+            },
+        ));
+        assert!(admin_api.requires_authentication());
+
+        let (response, api) = admin_api
+            .authenticate(Ok(AdminRequest::ListDnas))
+            .await
+            .unwrap();
+        assert_matches!(
+            response,
+            AdminResponse::Error(ExternalApiWireError::AuthenticationFailed(_))
+        );
+        assert!(api.is_none());
+
+        let (response, api) = admin_api
+            .authenticate(Ok(AdminRequest::Authenticate {
+                token: b"wrong".to_vec(),
+            }))
+            .await
+            .unwrap();
+        assert_matches!(
+            response,
+            AdminResponse::Error(ExternalApiWireError::AuthenticationFailed(_))
+        );
+        assert!(api.is_none());
+
+        let (response, api) = admin_api
+            .authenticate(Ok(AdminRequest::Authenticate {
+                token: b"secret".to_vec(),
+            }))
+            .await
+            .unwrap();
+        assert_matches!(response, AdminResponse::Authenticated);
+        let api = api.unwrap();
+
+        // An issued token authenticates a single connection
+        let token = match api
+            .handle_admin_request(AdminRequest::IssueAdminAuthenticationToken(
+                Default::default(),
+            ))
+            .await
+        {
+            AdminResponse::AdminAuthenticationTokenIssued(issued) => issued.token,
+            r => panic!("unexpected response {:?}", r),
+        };
+        let authenticate = || AdminRequest::Authenticate {
+            token: token.clone(),
+        };
+        let (response, _) = admin_api.authenticate(Ok(authenticate())).await.unwrap();
+        assert_matches!(response, AdminResponse::Authenticated);
+        let (response, _) = admin_api.authenticate(Ok(authenticate())).await.unwrap();
+        assert_matches!(
+            response,
+            AdminResponse::Error(ExternalApiWireError::AuthenticationFailed(_))
+        );
+        conductor_handle.shutdown();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn app_connection_is_bound_to_app() {
+        observability::test_run().ok();
+        let agent_key = fake_agent_pubkey_1();
+        let dna = fake_dna_zomes("", vec![(TestWasm::Foo.into(), TestWasm::Foo.into())]);
+        let cell_id = CellId::from((dna.dna_hash().clone(), agent_key));
+        let (_tmpdir, conductor_handle) =
+            setup_admin_fake_cells(vec![dna], vec![(cell_id.clone(), None)]).await;
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
+        let app_api = RealAppInterfaceApi::new(conductor_handle.clone())
+            .with_require_auth(true)
+            .for_connection();
+        assert!(app_api.requires_authentication());
+        // An unauthenticated connection receives no App signals.
+        assert!(!app_api.signal_subscriptions().allows(&Signal::App(
+            cell_id.clone(),
+            AppSignal::new(ExternIO::encode(()).unwrap()),
+        )));
+
+        let response = admin_api
+            .handle_admin_request(AdminRequest::IssueAppAuthenticationToken(
+                IssueAppAuthenticationTokenPayload::for_app("other app".into()),
+            ))
+            .await;
+        assert_matches!(response, AdminResponse::Error(_));
+        let token = match admin_api
+            .handle_admin_request(AdminRequest::IssueAppAuthenticationToken(
+                IssueAppAuthenticationTokenPayload::for_app("test app".into()),
+            ))
+            .await
+        {
+            AdminResponse::AppAuthenticationTokenIssued(issued) => issued.token,
+            r => panic!("unexpected response {:?}", r),
+        };
+
+        let (response, api) = app_api
+            .authenticate(Ok(AppRequest::AppInfo {
+                installed_app_id: "test app".into(),
+            }))
+            .await
+            .unwrap();
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AuthenticationFailed(_))
+        );
+        assert!(api.is_none());
+
+        let (response, api) = app_api
+            .authenticate(Ok(AppRequest::Authenticate { token }))
+            .await
+            .unwrap();
+        assert_matches!(response, AppResponse::Authenticated);
+        let api = api.unwrap();
+
+        let response = api
+            .handle_app_request(AppRequest::AppInfo {
+                installed_app_id: "test app".into(),
+            })
+            .await;
+        assert_matches!(response, AppResponse::AppInfo(Some(_)));
+        let response = api
+            .handle_app_request(AppRequest::AppInfo {
+                installed_app_id: "other app".into(),
+            })
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppNotAuthorized(_))
+        );
+        let call = |cell_id: CellId| {
+            AppRequest::ZomeCall(Box::new(ZomeCall {
+                cell_id,
+                zome_name: TestWasm::Foo.into(),
+                fn_name: "foo".into(),
+                cap_secret: None,
+                provenance: fake_agent_pubkey_1(),
+                payload: ExternIO::encode(()).unwrap(),
+            }))
+        };
+        let response = api.handle_app_request(call(fake_cell_id(1))).await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppNotAuthorized(_))
+        );
+        // The call to the app's own cell is let through, though it fails
+        // because the app isn't running.
+        let response = api.handle_app_request(call(cell_id)).await;
+        assert!(!matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppNotAuthorized(_))
+        ));
+
+        // Requests which change an app need a connection authenticated for it
+        let response = RealAppInterfaceApi::new(conductor_handle.clone())
+            .handle_app_request(AppRequest::ProvisionAppRole(Box::new(
+                ProvisionAppRolePayload {
+                    installed_app_id: "test app".into(),
+                    role_id: "role".into(),
+                    membrane_proof: None,
+                },
+            )))
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppNotAuthorized(_))
+        );
+        conductor_handle.shutdown();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn dump_state() {
         observability::test_run().ok();
@@ -616,6 +986,7 @@ pub mod test {
                     .map(TestZomes::from)
                     .map(|z| z.coordinator.into_inner())
                     .collect(),
+                rate_limits: Default::default(),
             },
             zomes.into_iter().flat_map(|t| Vec::<DnaWasm>::from(t)),
         )
@@ -16,14 +16,12 @@ use holochain_types::{db::PermittedConn, prelude::DnaError, share::RwShare};
 use kitsune_p2p::{
     agent_store::AgentInfoSigned, event::GetAgentInfoSignedEvt, KitsuneHost, KitsuneHostResult,
 };
-use kitsune_p2p_types::config::KitsuneP2pTuningParams;
 
 /// Implementation of the Kitsune Host API.
 /// Lets Kitsune make requests of Holochain
 pub struct KitsuneHostImpl {
     spaces: Spaces,
     ribosome_store: RwShare<RibosomeStore>,
-    tuning_params: KitsuneP2pTuningParams,
     strat: ArqStrat,
 }
 
@@ -32,13 +30,11 @@ impl KitsuneHostImpl {
     pub fn new(
         spaces: Spaces,
         ribosome_store: RwShare<RibosomeStore>,
-        tuning_params: KitsuneP2pTuningParams,
         strat: ArqStrat,
     ) -> Arc<Self> {
         Arc::new(Self {
             spaces,
             ribosome_store,
-            tuning_params,
             strat,
         })
     }
@@ -137,8 +133,8 @@ impl KitsuneHost for KitsuneHostImpl {
         let dna_def = self
             .ribosome_store
             .share_mut(|ds| ds.get_dna_def(&dna_hash))
-            .ok_or(DnaError::DnaMissing(dna_hash));
-        let cutoff = self.tuning_params.danger_gossip_recent_threshold();
+            .ok_or_else(|| DnaError::DnaMissing(dna_hash.clone()));
+        let cutoff = self.spaces.recent_threshold(&dna_hash);
         async move { Ok(Topology::standard(dna_def?.origin_time, cutoff)) }
             .boxed()
             .into()
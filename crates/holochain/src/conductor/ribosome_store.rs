@@ -38,6 +38,11 @@ impl RibosomeStore {
         self.ribosomes.extend(ribosomes);
     }
 
+    /// Forget a DNA, returning its ribosome if it was known
+    pub fn remove_dna(&mut self, hash: &DnaHash) -> Option<RealRibosome> {
+        self.ribosomes.remove(hash)
+    }
+
     #[instrument]
     pub fn list(&self) -> Vec<DnaHash> {
         self.ribosomes.keys().cloned().collect()
@@ -3,7 +3,7 @@
 //! Multiple [`Cell`](crate::conductor::Cell)'s could share the same space.
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use holo_hash::{DhtOpHash, DnaHash};
+use holo_hash::{AgentPubKey, DhtOpHash, DnaHash};
 use holochain_conductor_api::conductor::{ConductorConfig, DatabaseRootPath};
 use holochain_p2p::{
     dht::{
@@ -17,16 +17,19 @@ use holochain_p2p::{
     },
     dht_arc::{DhtArcRange, DhtArcSet},
     event::FetchOpDataQuery,
+    HolochainP2pDna,
 };
 use holochain_sqlite::{
     conn::{DbSyncLevel, DbSyncStrategy},
     db::{
-        DbKindAuthored, DbKindCache, DbKindConductor, DbKindDht, DbKindP2pAgents, DbKindP2pMetrics,
-        DbKindWasm, DbWrite, ReadAccess,
+        finish_pending_removals, DbKindAuthored, DbKindCache, DbKindConductor, DbKindDht,
+        DbKindP2pAgents, DbKindP2pMetrics, DbKindWasm, DbWrite, ReadAccess,
     },
+    key::DbKey,
     prelude::{DatabaseError, DatabaseResult},
 };
 use holochain_state::{
+    mutations::insert_warrant,
     prelude::{from_blob, StateQueryResult},
     query::{map_sql_dht_op_common, StateQueryError},
 };
@@ -34,27 +37,30 @@ use holochain_types::{
     db_cache::DhtDbQueryCache,
     dht_op::{DhtOp, DhtOpType},
 };
-use holochain_zome_types::{Entry, EntryVisibility, SignedAction, Timestamp};
+use holochain_zome_types::{Entry, EntryVisibility, SignedAction, SignedWarrant, Timestamp};
 use kitsune_p2p::{
     event::{TimeWindow, TimeWindowInclusive},
-    KitsuneP2pConfig,
+    KitsuneP2pConfig, KitsuneP2pConfigOverride,
 };
 use rusqlite::named_params;
 use tracing::instrument;
 
 use crate::core::{
     queue_consumer::QueueConsumerMap,
+    warrant::{check_warrant_proof, verify_warrant, ProofCheck},
     workflow::{
         countersigning_workflow::{incoming_countersigning, CountersigningWorkspace},
         incoming_dht_ops_workflow::{
             incoming_dht_ops_workflow, IncomingOpHashes, IncomingOpsBatch,
         },
     },
+    KeyStateRetries, RateBucketCache,
 };
 
 use super::{
     conductor::RwShare,
-    error::ConductorResult,
+    error::{ConductorError, ConductorResult},
+    handle::ConductorHandleT,
     p2p_agent_store::{self, P2pBatch},
 };
 use std::convert::TryInto;
@@ -70,11 +76,15 @@ pub struct Spaces {
     map: RwShare<HashMap<DnaHash, Space>>,
     pub(crate) db_dir: Arc<DatabaseRootPath>,
     pub(crate) db_sync_strategy: DbSyncStrategy,
+    /// The key databases are encrypted with.
+    db_key: Option<DbKey>,
     /// The map of running queue consumer workflows.
     pub(crate) queue_consumer_map: QueueConsumerMap,
     pub(crate) conductor_db: DbWrite<DbKindConductor>,
     pub(crate) wasm_db: DbWrite<DbKindWasm>,
-    network_config: KitsuneP2pConfig,
+    network_config: Arc<KitsuneP2pConfig>,
+    /// The network configs of spaces which override the conductor's config.
+    space_network_configs: RwShare<HashMap<DnaHash, Arc<KitsuneP2pConfig>>>,
 }
 
 #[derive(Clone)]
@@ -118,6 +128,12 @@ pub struct Space {
 
     /// Incoming ops batch for this space.
     pub incoming_ops_batch: IncomingOpsBatch,
+
+    /// Ops of this space waiting for their author's key state.
+    pub key_state_retries: KeyStateRetries,
+
+    /// Checkpoints of the authors' rate limiting buckets in this space.
+    pub rate_bucket_cache: RateBucketCache,
 }
 
 #[cfg(test)]
@@ -135,27 +151,85 @@ pub struct TestSpace {
 impl Spaces {
     /// Create a new empty set of [`DnaHash`] spaces.
     pub fn new(config: &ConductorConfig) -> ConductorResult<Self> {
+        Self::with_db_key(config, None)
+    }
+
+    /// Create a new empty set of [`DnaHash`] spaces,
+    /// whose databases are encrypted with this key.
+    pub fn with_db_key(config: &ConductorConfig, db_key: Option<DbKey>) -> ConductorResult<Self> {
         let root_db_dir = config.environment_path.clone();
+        // Delete the databases which were removed while they were in use.
+        finish_pending_removals(root_db_dir.as_ref())?;
         let db_sync_strategy = config.db_sync_strategy;
         let db_sync_level = match db_sync_strategy {
             DbSyncStrategy::Fast => DbSyncLevel::Off,
             DbSyncStrategy::Resilient => DbSyncLevel::Normal,
         };
-        let conductor_db =
-            DbWrite::open_with_sync_level(root_db_dir.as_ref(), DbKindConductor, db_sync_level)?;
-        let wasm_db =
-            DbWrite::open_with_sync_level(root_db_dir.as_ref(), DbKindWasm, db_sync_level)?;
+        let conductor_db = DbWrite::open_with_key(
+            root_db_dir.as_ref(),
+            DbKindConductor,
+            db_sync_level,
+            db_key.clone(),
+        )?;
+        let wasm_db = DbWrite::open_with_key(
+            root_db_dir.as_ref(),
+            DbKindWasm,
+            db_sync_level,
+            db_key.clone(),
+        )?;
         Ok(Spaces {
             map: RwShare::new(HashMap::new()),
             db_dir: Arc::new(root_db_dir),
             db_sync_strategy,
+            db_key,
             queue_consumer_map: QueueConsumerMap::new(),
             conductor_db,
             wasm_db,
-            network_config: config.network.clone().unwrap_or_default(),
+            network_config: Arc::new(config.network.clone().unwrap_or_default()),
+            space_network_configs: RwShare::new(HashMap::new()),
         })
     }
 
+    /// Get the network config for a space, with any overrides applied.
+    pub fn network_config(&self, dna_hash: &DnaHash) -> Arc<KitsuneP2pConfig> {
+        self.space_network_configs
+            .share_ref(|configs| configs.get(dna_hash).cloned())
+            .unwrap_or_else(|| self.network_config.clone())
+    }
+
+    /// Apply overrides of the conductor's network config to a space.
+    /// Passing `None` goes back to the conductor's config.
+    pub fn set_network_override(
+        &self,
+        dna_hash: &DnaHash,
+        config_override: Option<&KitsuneP2pConfigOverride>,
+    ) -> ConductorResult<()> {
+        match config_override {
+            Some(config_override) => {
+                let config = self.check_network_override(dna_hash, config_override)?;
+                self.space_network_configs
+                    .share_mut(|configs| configs.insert(dna_hash.clone(), Arc::new(config)));
+            }
+            None => {
+                self.space_network_configs
+                    .share_mut(|configs| configs.remove(dna_hash));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that overrides can be applied to the conductor's network config,
+    /// returning the resulting config for the space.
+    pub fn check_network_override(
+        &self,
+        dna_hash: &DnaHash,
+        config_override: &KitsuneP2pConfigOverride,
+    ) -> ConductorResult<KitsuneP2pConfig> {
+        self.network_config
+            .with_override(config_override)
+            .map_err(|e| ConductorError::InvalidNetworkOverride(dna_hash.clone(), e.to_string()))
+    }
+
     /// Get something from every space
     pub fn get_from_spaces<R, F: FnMut(&Space) -> R>(&self, f: F) -> Vec<R> {
         self.map
@@ -182,6 +256,7 @@ impl Spaces {
                             Arc::new(dna_hash.clone()),
                             &self.db_dir,
                             self.db_sync_strategy,
+                            self.db_key.clone(),
                         )?;
 
                         let r = f(&space);
@@ -192,6 +267,38 @@ impl Spaces {
         }
     }
 
+    /// Remove a space and permanently delete all of its databases.
+    ///
+    /// All cells in this space must already have been removed from the conductor.
+    /// Databases which the space's workflows still hold open are deleted
+    /// when the conductor next starts, see [`DbWrite::remove`].
+    pub async fn remove_space(&self, dna_hash: &DnaHash) -> ConductorResult<()> {
+        let space = match self.map.share_mut(|spaces| spaces.remove(dna_hash)) {
+            Some(space) => space,
+            // Open the space anyway, so that databases left on disk
+            // by a previous run are deleted too.
+            None => Space::new(
+                Arc::new(dna_hash.clone()),
+                &self.db_dir,
+                self.db_sync_strategy,
+                self.db_key.clone(),
+            )?,
+        };
+        let cache_db = space.cache_db.clone();
+        let authored_db = space.authored_db.clone();
+        let dht_db = space.dht_db.clone();
+        let p2p_agents_db = space.p2p_agents_db.clone();
+        let p2p_metrics_db = space.p2p_metrics_db.clone();
+        // The rest of the space, like the query cache, holds handles too.
+        drop(space);
+        cache_db.remove().await?;
+        authored_db.remove().await?;
+        dht_db.remove().await?;
+        p2p_agents_db.remove().await?;
+        p2p_metrics_db.remove().await?;
+        Ok(())
+    }
+
     /// Get the cache database (this will create the space if it doesn't already exist).
     pub fn cache(&self, dna_hash: &DnaHash) -> ConductorResult<DbWrite<DbKindCache>> {
         self.get_or_create_space_ref(dna_hash, |space| space.cache_db.clone())
@@ -538,19 +645,21 @@ impl Spaces {
         // send it to the countersigning workflow otherwise
         // send it to the incoming ops workflow.
         if countersigning_session {
-            let (workspace, trigger) = self.get_or_create_space_ref(dna_hash, |space| {
-                (
-                    space.countersigning_workspace.clone(),
-                    self.queue_consumer_map
-                        .countersigning_trigger(space.dna_hash.clone()),
-                )
-            })?;
+            let (workspace, authored_db, trigger) =
+                self.get_or_create_space_ref(dna_hash, |space| {
+                    (
+                        space.countersigning_workspace.clone(),
+                        space.authored_db.clone(),
+                        self.queue_consumer_map
+                            .countersigning_trigger(space.dna_hash.clone()),
+                    )
+                })?;
             let trigger = match trigger {
                 Some(t) => t,
                 // If the workflow has not been spawned yet we can't handle incoming messages.
                 None => return Ok(()),
             };
-            incoming_countersigning(ops, &workspace, trigger)?;
+            incoming_countersigning(ops, &workspace, &authored_db, trigger).await?;
         } else {
             let space = self.get_or_create_space(dna_hash)?;
             let trigger = match self
@@ -567,9 +676,46 @@ impl Spaces {
         Ok(())
     }
 
-    /// Get the recent_threshold based on the kitsune network config
-    pub fn recent_threshold(&self) -> Duration {
-        self.network_config
+    #[instrument(skip(self, warrant, conductor, network))]
+    /// We are receiving a warrant from the network.
+    /// If the warrant checks out, and validating the action it holds as proof
+    /// rejects that action here too, it is stored with the rest of the
+    /// warrantee's agent activity, and the warrantee is returned so it can be
+    /// blocked. Forged and unproven warrants are dropped.
+    pub async fn handle_publish_warrant(
+        &self,
+        dna_hash: &DnaHash,
+        warrant: SignedWarrant,
+        conductor: &dyn ConductorHandleT,
+        network: HolochainP2pDna,
+    ) -> ConductorResult<Option<AgentPubKey>> {
+        if !verify_warrant(&warrant).await {
+            tracing::warn!(author = %warrant.author, "Dropping a warrant with an invalid signature");
+            return Ok(None);
+        }
+        let space = self.get_or_create_space(dna_hash)?;
+        match check_warrant_proof(&warrant, &space, conductor, network).await? {
+            ProofCheck::Invalid => (),
+            ProofCheck::Valid => {
+                tracing::warn!(author = %warrant.author, "Dropping a warrant whose proof is valid");
+                return Ok(None);
+            }
+            ProofCheck::Undecided => {
+                tracing::info!(author = %warrant.author, "Dropping a warrant whose proof can't be validated yet");
+                return Ok(None);
+            }
+        }
+        let warrantee = warrant.warrantee().clone();
+        space
+            .dht_db
+            .async_commit(move |txn| insert_warrant(txn, &warrant))
+            .await?;
+        Ok(Some(warrantee))
+    }
+
+    /// Get the recent_threshold of a space based on its kitsune network config
+    pub fn recent_threshold(&self, dna_hash: &DnaHash) -> Duration {
+        self.network_config(dna_hash)
             .tuning_params
             .danger_gossip_recent_threshold()
     }
@@ -580,6 +726,7 @@ impl Space {
         dna_hash: Arc<DnaHash>,
         root_db_dir: &DatabaseRootPath,
         db_sync_strategy: DbSyncStrategy,
+        db_key: Option<DbKey>,
     ) -> ConductorResult<Self> {
         use holochain_p2p::DnaHashExt;
         let space = dna_hash.to_kitsune();
@@ -587,30 +734,35 @@ impl Space {
             DbSyncStrategy::Fast => DbSyncLevel::Off,
             DbSyncStrategy::Resilient => DbSyncLevel::Normal,
         };
-        let cache = DbWrite::open_with_sync_level(
+        let cache = DbWrite::open_with_key(
             root_db_dir.as_ref(),
             DbKindCache(dna_hash.clone()),
             db_sync_level,
+            db_key.clone(),
         )?;
-        let authored_db = DbWrite::open_with_sync_level(
+        let authored_db = DbWrite::open_with_key(
             root_db_dir.as_ref(),
             DbKindAuthored(dna_hash.clone()),
             DbSyncLevel::Normal,
+            db_key.clone(),
         )?;
-        let dht_db = DbWrite::open_with_sync_level(
+        let dht_db = DbWrite::open_with_key(
             root_db_dir.as_ref(),
             DbKindDht(dna_hash.clone()),
             db_sync_level,
+            db_key.clone(),
         )?;
-        let p2p_agents_db = DbWrite::open_with_sync_level(
+        let p2p_agents_db = DbWrite::open_with_key(
             root_db_dir.as_ref(),
             DbKindP2pAgents(space.clone()),
             db_sync_level,
+            db_key.clone(),
         )?;
-        let p2p_metrics_db = DbWrite::open_with_sync_level(
+        let p2p_metrics_db = DbWrite::open_with_key(
             root_db_dir.as_ref(),
             DbKindP2pMetrics(space),
             db_sync_level,
+            db_key,
         )?;
 
         let (tx, rx) = tokio::sync::mpsc::channel(100);
@@ -636,6 +788,8 @@ impl Space {
             incoming_op_hashes,
             incoming_ops_batch,
             dht_query_cache,
+            key_state_retries: KeyStateRetries::default(),
+            rate_bucket_cache: RateBucketCache::default(),
         };
         Ok(r)
     }
@@ -693,6 +847,7 @@ impl TestSpace {
                 Arc::new(dna_hash),
                 &temp_dir.path().to_path_buf().into(),
                 Default::default(),
+                None,
             )
             .unwrap(),
             _temp_dir: temp_dir,
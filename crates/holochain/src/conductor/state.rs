@@ -1,6 +1,7 @@
 //! Structs which allow the Conductor's state to be persisted across
 //! startups and shutdowns
 
+use holochain_conductor_api::config::TlsConfig;
 use holochain_conductor_api::signal_subscription::SignalSubscription;
 use holochain_conductor_api::{config::InterfaceDriver, InstalledAppInfo};
 use holochain_types::prelude::*;
@@ -115,6 +116,32 @@ impl ConductorState {
             .filter_map(|(id, app)| StoppedApp::from_app(app).map(|stopped| (id, stopped)))
     }
 
+    /// Iterate over the apps which use a cell, as one of their cells or as an
+    /// archived clone. A cell can be shared by several apps.
+    pub fn apps_using_cell<'a>(
+        &'a self,
+        cell_id: &'a CellId,
+    ) -> impl Iterator<Item = &'a InstalledAppId> + 'a {
+        self.installed_apps
+            .iter()
+            .filter(move |(_, app)| {
+                app.all_cells()
+                    .chain(app.archived_cloned_cells())
+                    .any(|id| id == cell_id)
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// Whether any app uses a cell of this DNA, in which case the data in the
+    /// DNA's space is still needed.
+    pub fn dna_in_use(&self, dna_hash: &DnaHash) -> bool {
+        self.installed_apps.iter().any(|(_, app)| {
+            app.all_cells()
+                .chain(app.archived_cloned_cells())
+                .any(|id| id.dna_hash() == dna_hash)
+        })
+    }
+
     /// Getter for a single app. Returns error if app missing.
     pub fn get_app(&self, id: &InstalledAppId) -> ConductorResult<&InstalledApp> {
         self.installed_apps
@@ -174,9 +201,7 @@ impl ConductorState {
 /// GUIs, browser based web UIs, local native UIs, other local applications and scripts.
 /// We currently have:
 /// * websockets
-///
-/// We will also soon develop
-/// * Unix domain sockets
+/// * websockets wrapped in TLS
 ///
 /// The cells (referenced by ID) that are to be made available via that interface should be listed.
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -187,6 +212,14 @@ pub struct AppInterfaceConfig {
 
     /// The driver for the interface, e.g. Websocket
     pub driver: InterfaceDriver,
+
+    /// The limit on zome calls made through this interface, if any
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Whether connections must authenticate with an app token
+    #[serde(default)]
+    pub require_auth: bool,
 }
 
 impl AppInterfaceConfig {
@@ -195,8 +228,32 @@ impl AppInterfaceConfig {
         Self {
             signal_subscriptions: HashMap::new(),
             driver: InterfaceDriver::Websocket { port },
+            rate_limit: None,
+            require_auth: false,
+        }
+    }
+
+    /// Create config for a websocket interface secured with TLS
+    pub fn tls_websocket(port: u16, tls: TlsConfig) -> Self {
+        Self {
+            signal_subscriptions: HashMap::new(),
+            driver: InterfaceDriver::TlsWebsocket { port, tls },
+            rate_limit: None,
+            require_auth: false,
         }
     }
+
+    /// Limit the zome calls made through this interface
+    pub fn with_rate_limit(mut self, rate_limit: Option<RateLimitConfig>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Require connections to authenticate with an app token
+    pub fn with_require_auth(mut self, require_auth: bool) -> Self {
+        self.require_auth = require_auth;
+        self
+    }
 }
 
 // TODO: Tons of consistency check tests were ripped out in the great legacy code cleanup
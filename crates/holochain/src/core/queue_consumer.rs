@@ -208,7 +208,8 @@ pub async fn spawn_queue_consumer_tasks(
                 dht_query_cache.clone(),
                 cache.clone(),
                 Arc::new(dna_def),
-            ),
+            )
+            .with_rate_bucket_cache(space.rate_bucket_cache.clone()),
             space.clone(),
             conductor_handle.clone(),
             stop.subscribe(),
@@ -17,6 +17,16 @@ pub(crate) fn spawn_countersigning_consumer(
     let (tx, mut rx) = TriggerSender::new();
     let trigger_self = tx.clone();
     let handle = tokio::spawn(async move {
+        // Pick up the sessions which were pending when the conductor stopped.
+        match space
+            .countersigning_workspace
+            .load_pending(&space.authored_db)
+            .await
+        {
+            Ok(true) => trigger_self.trigger(&"load_pending"),
+            Ok(false) => (),
+            Err(err) => handle_workflow_error(err)?,
+        }
         loop {
             // Wait for next job
             if let Job::Shutdown = next_job_or_exit(&mut rx, &mut stop).await {
@@ -27,8 +27,8 @@ use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
+use crate::core::ribosome::guest_callback::weigh::WeighInvocation;
 use crate::core::ribosome::guest_callback::CallIterator;
-use derive_more::Constructor;
 use error::RibosomeResult;
 use guest_callback::entry_defs::EntryDefsHostAccess;
 use guest_callback::init::InitHostAccess;
@@ -36,7 +36,10 @@ use guest_callback::migrate_agent::MigrateAgentHostAccess;
 use guest_callback::post_commit::PostCommitHostAccess;
 use guest_callback::validate::ValidateHostAccess;
 use guest_callback::validation_package::ValidationPackageHostAccess;
+use guest_callback::weigh::WeighHostAccess;
 use holo_hash::AgentPubKey;
+use holochain_conductor_api::conductor::ConductorConfig;
+use holochain_conductor_api::conductor::DEFAULT_MAX_SLEEP_MS;
 use holochain_keystore::MetaLairClient;
 use holochain_p2p::HolochainP2pDna;
 use holochain_serialized_bytes::prelude::*;
@@ -105,6 +108,7 @@ pub enum HostContext {
     PostCommit(PostCommitHostAccess), // MAYBE: add emit_signal access here?
     Validate(ValidateHostAccess),
     ValidationPackage(ValidationPackageHostAccess),
+    Weigh(WeighHostAccess),
     ZomeCall(ZomeCallHostAccess),
 }
 
@@ -119,6 +123,7 @@ impl From<&HostContext> for HostFnAccess {
             HostContext::MigrateAgent(access) => access.into(),
             HostContext::ValidationPackage(access) => access.into(),
             HostContext::PostCommit(access) => access.into(),
+            HostContext::Weigh(access) => access.into(),
         }
     }
 }
@@ -190,6 +195,24 @@ impl HostContext {
         }
     }
 
+    /// How long a call can sleep for and when it times out.
+    /// Only zome calls can time out.
+    pub fn zome_call_timing(&self) -> ZomeCallTiming {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { timing, .. }) => *timing,
+            _ => ZomeCallTiming::default(),
+        }
+    }
+
+    /// The cap grant which authorized a zome call, if this is an authorized
+    /// zome call.
+    pub fn authorization(&self) -> Option<&CapabilityInfo> {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { authorization, .. }) => authorization.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Get the call zome handle, panics if none was provided
     pub fn call_zome_handle(&self) -> &CellConductorReadHandle {
         match self {
@@ -316,24 +339,30 @@ impl ZomeCallInvocation {
     /// - we need to find a live (committed and not deleted) cap grant that matches the secret
     /// - if the live cap grant is for the current author the call is ALWAYS authorized ELSE
     /// - the live cap grant needs to include the invocation's provenance AND zome/function name
+    ///
+    /// Returns the grant which authorizes the call, if any.
     #[allow(clippy::extra_unused_lifetimes)]
-    pub async fn is_authorized<'a>(
+    pub async fn authorization<'a>(
         &self,
         host_access: &ZomeCallHostAccess,
-    ) -> RibosomeResult<bool> {
+    ) -> RibosomeResult<Option<CapabilityInfo>> {
         let check_function = (self.zome.zome_name().clone(), self.fn_name.clone());
         let check_agent = self.provenance.clone();
         let check_secret = self.cap_secret;
 
-        let maybe_grant: Option<CapGrant> = host_access
+        let maybe_grant = host_access
             .workspace
             .source_chain()
             .as_ref()
             .expect("Must have source chain to make zome calls")
-            .valid_cap_grant(check_function, check_agent, check_secret)
+            .authorizing_cap_grant(check_function, check_agent, check_secret)
             .await?;
 
-        Ok(maybe_grant.is_some())
+        Ok(maybe_grant.map(|(cap_grant, action_hash)| CapabilityInfo {
+            provenance: self.provenance.clone(),
+            cap_grant,
+            action_hash,
+        }))
     }
 }
 
@@ -436,13 +465,74 @@ impl From<ZomeCallInvocation> for ZomeCall {
     }
 }
 
-#[derive(Clone, Constructor)]
+#[derive(Clone)]
 pub struct ZomeCallHostAccess {
     pub workspace: HostFnWorkspace,
     pub keystore: MetaLairClient,
     pub network: HolochainP2pDna,
     pub signal_tx: SignalBroadcaster,
     pub call_zome_handle: CellConductorReadHandle,
+    pub timing: ZomeCallTiming,
+    /// The cap grant which authorized the call, set once it is authorized.
+    pub authorization: Option<CapabilityInfo>,
+}
+
+impl ZomeCallHostAccess {
+    /// Constructor for a call which isn't authorized yet.
+    pub fn new(
+        workspace: HostFnWorkspace,
+        keystore: MetaLairClient,
+        network: HolochainP2pDna,
+        signal_tx: SignalBroadcaster,
+        call_zome_handle: CellConductorReadHandle,
+        timing: ZomeCallTiming,
+    ) -> Self {
+        Self {
+            workspace,
+            keystore,
+            network,
+            signal_tx,
+            call_zome_handle,
+            timing,
+            authorization: None,
+        }
+    }
+}
+
+/// How long a zome call can sleep for and when it times out.
+#[derive(Clone, Copy, Debug)]
+pub struct ZomeCallTiming {
+    /// The longest the call can sleep for in one call to `sleep`.
+    pub max_sleep: std::time::Duration,
+    /// When the call times out, if it is limited.
+    pub deadline: Option<tokio::time::Instant>,
+}
+
+impl ZomeCallTiming {
+    /// The timing of a call starting now, limited by the conductor config.
+    pub fn from_config(config: &ConductorConfig) -> Self {
+        Self {
+            max_sleep: config.max_sleep(),
+            deadline: config
+                .zome_call_timeout()
+                .map(|timeout| tokio::time::Instant::now() + timeout),
+        }
+    }
+
+    /// Whether the call has passed its deadline.
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| deadline <= tokio::time::Instant::now())
+    }
+}
+
+impl Default for ZomeCallTiming {
+    fn default() -> Self {
+        Self {
+            max_sleep: std::time::Duration::from_millis(DEFAULT_MAX_SLEEP_MS),
+            deadline: None,
+        }
+    }
 }
 
 impl From<ZomeCallHostAccess> for HostContext {
@@ -525,18 +615,27 @@ pub trait RibosomeT: Sized + std::fmt::Debug + Send + Sync {
     /// values without needing to make holochain a dependency.
     fn get_const_fn(&self, zome: &Zome, name: &str) -> Result<Option<i32>, RibosomeError>;
 
-    /// @todo list out all the available callbacks and maybe cache them somewhere
-    fn list_callbacks(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| e.is_callback())
+    /// List the functions exported by every zome in the dna,
+    /// grouped into callbacks and zome functions.
+    /// This is built once per dna and then cached.
+    fn list_zome_functions(&self) -> RibosomeResult<Arc<Vec<ZomeFunctions>>>;
+
+    /// List the callbacks exported by every zome in the dna.
+    fn list_callbacks(&self) -> RibosomeResult<Vec<(ZomeName, Vec<FunctionName>)>> {
+        Ok(self
+            .list_zome_functions()?
+            .iter()
+            .map(|f| (f.zome_name.clone(), f.callbacks.clone()))
+            .collect())
     }
 
-    /// @todo list out all the available zome functions and maybe cache them somewhere
-    fn list_zome_fns(&self) {
-        unimplemented!()
-        // pseudocode
-        // self.instance().exports().filter(|e| !e.is_callback())
+    /// List the zome functions exported by every zome in the dna.
+    fn list_zome_fns(&self) -> RibosomeResult<Vec<(ZomeName, Vec<FunctionName>)>> {
+        Ok(self
+            .list_zome_functions()?
+            .iter()
+            .map(|f| (f.zome_name.clone(), f.zome_fns.clone()))
+            .collect())
     }
 
     fn run_genesis_self_check(
@@ -575,6 +674,14 @@ pub trait RibosomeT: Sized + std::fmt::Debug + Send + Sync {
         invocation: PostCommitInvocation,
     ) -> RibosomeResult<()>;
 
+    /// Run the `weigh` callback of a single integrity zome.
+    /// Zomes that don't define `weigh` give every action the default weight.
+    fn run_weigh(
+        &self,
+        access: WeighHostAccess,
+        invocation: WeighInvocation,
+    ) -> RibosomeResult<RateWeight>;
+
     /// Helper function for running a validation callback. Calls
     /// private fn `do_callback!` under the hood.
     fn run_validate(
@@ -594,7 +701,9 @@ pub trait RibosomeT: Sized + std::fmt::Debug + Send + Sync {
     fn zome_types(&self) -> &Arc<GlobalZomeTypes>;
 }
 
-/// Placeholder for weighing. Currently produces zero weight.
+/// Weight for countersigned entries. Every party to a session must build the
+/// same actions without running each other's `weigh` callbacks, so these
+/// are currently weightless.
 pub fn weigh_placeholder() -> EntryRateWeight {
     EntryRateWeight::default()
 }
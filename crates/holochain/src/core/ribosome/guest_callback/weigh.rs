@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::FnComponents;
+use crate::core::ribosome::HostContext;
+use crate::core::ribosome::Invocation;
+use crate::core::ribosome::InvocationAuth;
+use crate::core::ribosome::RibosomeT;
+use crate::core::ribosome::ZomesToInvoke;
+use derive_more::Constructor;
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::prelude::*;
+
+/// An invocation of the `weigh` callback of the integrity zome that defines
+/// the type of the action being weighed.
+#[derive(Clone, Debug)]
+pub struct WeighInvocation {
+    zome: IntegrityZome,
+    input: Arc<WeighInput>,
+}
+
+impl WeighInvocation {
+    pub fn new(zome: IntegrityZome, input: WeighInput) -> Self {
+        Self {
+            zome,
+            input: Arc::new(input),
+        }
+    }
+}
+
+/// Weighing must give the same result for every validator,
+/// so it has the same access as `genesis_self_check`.
+#[derive(Clone, Constructor)]
+pub struct WeighHostAccess;
+
+impl From<WeighHostAccess> for HostContext {
+    fn from(host_access: WeighHostAccess) -> Self {
+        Self::Weigh(host_access)
+    }
+}
+
+impl From<&WeighHostAccess> for HostFnAccess {
+    fn from(_: &WeighHostAccess) -> Self {
+        let mut access = Self::none();
+        access.keystore_deterministic = Permission::Allow;
+        access.bindings_deterministic = Permission::Allow;
+        access
+    }
+}
+
+impl Invocation for WeighInvocation {
+    fn zomes(&self) -> ZomesToInvoke {
+        ZomesToInvoke::OneIntegrity(self.zome.clone())
+    }
+    fn fn_components(&self) -> FnComponents {
+        vec!["weigh".into()].into()
+    }
+    fn host_input(self) -> Result<ExternIO, SerializedBytesError> {
+        ExternIO::encode(self.input)
+    }
+    fn auth(&self) -> InvocationAuth {
+        InvocationAuth::LocalCallback
+    }
+}
+
+/// Weigh an action with the `weigh` callback of the integrity zome that
+/// defines its type. Actions that don't belong to an integrity zome,
+/// such as capability entries, get the default weight.
+pub fn weigh_action(
+    ribosome: &impl RibosomeT,
+    zome_id: Option<ZomeId>,
+    input: WeighInput,
+) -> RibosomeResult<RateWeight> {
+    match zome_id.and_then(|zome_id| ribosome.get_integrity_zome(&zome_id)) {
+        Some(zome) => ribosome.run_weigh(WeighHostAccess, WeighInvocation::new(zome, input)),
+        None => Ok(RateWeight::default()),
+    }
+}
+
+/// The entry weight for a weighed create or update.
+/// Entry sizes are already capped by sys validation, so `rate_bytes` is unused.
+pub fn entry_rate_weight(weight: RateWeight) -> EntryRateWeight {
+    EntryRateWeight {
+        bucket_id: weight.bucket_id,
+        units: weight.units,
+        rate_bytes: 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::fixt::prelude::*;
+
+    #[test]
+    fn weigh_invocation_targets_one_integrity_zome() {
+        let zome = IntegrityZome::new(
+            "integrity".into(),
+            IntegrityZomeDefFixturator::new(::fixt::Empty)
+                .next()
+                .unwrap(),
+        );
+        let input = WeighInput::Delete(Delete {
+            author: fixt!(AgentPubKey),
+            timestamp: Timestamp::HOLOCHAIN_EPOCH,
+            action_seq: 1,
+            prev_action: fixt!(ActionHash),
+            deletes_address: fixt!(ActionHash),
+            deletes_entry_address: fixt!(EntryHash),
+            weight: (),
+        });
+        let invocation = WeighInvocation::new(zome.clone(), input.clone());
+
+        assert_eq!(
+            invocation.fn_components().collect::<Vec<_>>(),
+            vec!["weigh".to_string()],
+        );
+        assert!(matches!(
+            invocation.zomes(),
+            ZomesToInvoke::OneIntegrity(z) if z == zome
+        ));
+        assert_eq!(
+            invocation
+                .host_input()
+                .unwrap()
+                .decode::<WeighInput>()
+                .unwrap(),
+            input,
+        );
+    }
+}
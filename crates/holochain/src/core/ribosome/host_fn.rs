@@ -39,7 +39,7 @@ macro_rules! host_fn_api_impls {
             pub(crate) mod $f;
         )*
 
-        impl<Ribosome: RibosomeT> HostFnApiT for HostFnApi<Ribosome> {
+        impl<Ribosome: RibosomeT + 'static> HostFnApiT for HostFnApi<Ribosome> {
             $(
                 fn $f(&self, input: $input) -> Result<$output, HostFnApiError> {
                     $f::$f(
@@ -74,14 +74,14 @@ host_fn_api_impls! {
 
     fn call (Vec<zt::call::Call>) -> Vec<zt::ZomeCallResponse>;
 
-    // @todo List all the local capability claims.
-    fn capability_claims (()) -> ();
+    // List all the live local capability claims.
+    fn capability_claims (zt::capability::CapClaimsInput) -> Vec<zt::capability::CapClaimInfo>;
 
-    // @todo List all the local capability grants.
-    fn capability_grants (()) -> ();
+    // List all the live local capability grants.
+    fn capability_grants (zt::capability::CapGrantsInput) -> Vec<zt::capability::CapGrantInfo>;
 
-    // @todo Get the capability for the current zome call.
-    fn capability_info (()) -> ();
+    // Get the capability for the current zome call.
+    fn capability_info (()) -> zt::capability::CapabilityInfo;
 
     // The EntryDefId determines how a create is handled on the host side.
     // CapGrant and CapClaim are handled natively.
@@ -1,13 +1,64 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
-use std::sync::Arc;
+use holochain_types::prelude::*;
 use holochain_wasmer_host::prelude::*;
+use std::sync::Arc;
 
-/// lists all the local claims filtered by tag
+/// lists all the local claims filtered by grantor and tag
+/// this is only the current claims as per local CRUD
 pub fn capability_claims(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: (),
-) -> Result<(), RuntimeError> {
-    unimplemented!();
+    call_context: Arc<CallContext>,
+    input: CapClaimsInput,
+) -> Result<Vec<CapClaimInfo>, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            read_workspace: Permission::Allow,
+            ..
+        } => tokio_helper::block_forever_on(async move {
+            let records = call_context
+                .host_context
+                .workspace()
+                .source_chain()
+                .as_ref()
+                .expect("Must have source chain to query the source chain")
+                .query_live(EntryType::CapClaim)
+                .await
+                .map_err(|source_chain_error| -> RuntimeError {
+                    wasm_error!(WasmErrorInner::Host(source_chain_error.to_string())).into()
+                })?;
+            Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let cap_claim = record.entry().as_option()?.as_cap_claim()?.clone();
+                    if input
+                        .grantor
+                        .as_ref()
+                        .map_or(false, |grantor| *grantor != cap_claim.grantor)
+                    {
+                        return None;
+                    }
+                    if input.tag.as_ref().map_or(false, |tag| *tag != cap_claim.tag) {
+                        return None;
+                    }
+                    Some(CapClaimInfo {
+                        cap_claim,
+                        action_hash: record.action_address().clone(),
+                        created_at: record.action().timestamp(),
+                    })
+                })
+                .collect())
+        }),
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "capability_claims".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
 }
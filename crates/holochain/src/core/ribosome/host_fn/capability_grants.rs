@@ -1,16 +1,70 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
+use holochain_types::prelude::*;
 use holochain_wasmer_host::prelude::*;
 use std::sync::Arc;
 
-/// list all the grants stored locally in the chain filtered by tag
+/// list all the grants stored locally in the chain filtered by tag and function
 /// this is only the current grants as per local CRUD
 pub fn capability_grants(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: (),
-) -> Result<(), RuntimeError> {
-    unimplemented!();
+    call_context: Arc<CallContext>,
+    input: CapGrantsInput,
+) -> Result<Vec<CapGrantInfo>, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            read_workspace: Permission::Allow,
+            ..
+        } => tokio_helper::block_forever_on(async move {
+            let records = call_context
+                .host_context
+                .workspace()
+                .source_chain()
+                .as_ref()
+                .expect("Must have source chain to query the source chain")
+                .query_live(EntryType::CapGrant)
+                .await
+                .map_err(|source_chain_error| -> RuntimeError {
+                    wasm_error!(WasmErrorInner::Host(source_chain_error.to_string())).into()
+                })?;
+            Ok(records
+                .into_iter()
+                .filter_map(|record| {
+                    let cap_grant = record.entry().to_grant_option()?;
+                    if input
+                        .tag
+                        .as_ref()
+                        .map_or(false, |tag| *tag != cap_grant.tag)
+                    {
+                        return None;
+                    }
+                    if input
+                        .function
+                        .as_ref()
+                        .map_or(false, |function| !cap_grant.functions.contains(function))
+                    {
+                        return None;
+                    }
+                    Some(CapGrantInfo {
+                        cap_grant,
+                        action_hash: record.action_address().clone(),
+                        created_at: record.action().timestamp(),
+                    })
+                })
+                .collect())
+        }),
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "capability_grants".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
 }
 
 #[cfg(test)]
@@ -180,4 +234,161 @@ pub mod wasm_test {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_capability_grants_are_live() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::Capability).await;
+
+        let needs_cap_claim = CapGrantsInput::default().function((
+            TestWasm::Capability.coordinator_zome_name(),
+            "needs_cap_claim".into(),
+        ));
+
+        // Only the unrestricted grant from init exists so far.
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "cap_grants", CapGrantsInput::default())
+            .await;
+        assert_eq!(grants.len(), 1);
+
+        let secret: CapSecret = conductor.call(&alice, "cap_secret", ()).await;
+        let original_grant_hash: ActionHash = conductor
+            .call(&alice, "transferable_cap_grant", secret)
+            .await;
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "cap_grants", CapGrantsInput::default())
+            .await;
+        assert_eq!(grants.len(), 2);
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "cap_grants", needs_cap_claim.clone())
+            .await;
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].action_hash, original_grant_hash);
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(
+                &alice,
+                "cap_grants",
+                CapGrantsInput::default().tag("missing".into()),
+            )
+            .await;
+        assert!(grants.is_empty());
+
+        // The updated grant replaces the original.
+        let new_grant_hash: ActionHash = conductor
+            .call(&alice, "roll_cap_grant", original_grant_hash)
+            .await;
+        let grants: Vec<CapGrantInfo> = conductor
+            .call(&alice, "cap_grants", needs_cap_claim.clone())
+            .await;
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].action_hash, new_grant_hash);
+
+        // A deleted grant is gone.
+        let _: ActionHash = conductor
+            .call(&alice, "delete_cap_grant", new_grant_hash)
+            .await;
+        let grants: Vec<CapGrantInfo> = conductor.call(&alice, "cap_grants", needs_cap_claim).await;
+        assert!(grants.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_capability_claims() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor,
+            alice,
+            alice_pubkey,
+            bob,
+            bob_pubkey,
+            ..
+        } = RibosomeTestFixture::new(TestWasm::Capability).await;
+
+        // Make sure alice has run init and so accepts claims.
+        let claims: Vec<CapClaimInfo> = conductor
+            .call(&alice, "cap_claims", CapClaimsInput::default())
+            .await;
+        assert!(claims.is_empty());
+
+        let _: () = conductor
+            .call(&bob, "send_assigned_cap_claim", alice_pubkey)
+            .await;
+
+        let claims: Vec<CapClaimInfo> = conductor
+            .call(
+                &alice,
+                "cap_claims",
+                CapClaimsInput::default().grantor(bob_pubkey.clone()),
+            )
+            .await;
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].cap_claim.grantor, bob_pubkey);
+        assert_eq!(claims[0].cap_claim.tag, "has_cap_claim");
+
+        let claims: Vec<CapClaimInfo> = conductor
+            .call(
+                &alice,
+                "cap_claims",
+                CapClaimsInput::default()
+                    .grantor(bob_pubkey)
+                    .tag("missing".into()),
+            )
+            .await;
+        assert!(claims.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn ribosome_capability_info() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor,
+            alice,
+            alice_pubkey,
+            bob,
+            bob_pubkey,
+            ..
+        } = RibosomeTestFixture::new(TestWasm::Capability).await;
+
+        // The author is authorized by the implicit author grant.
+        let info: CapabilityInfo = conductor.call(&alice, "cap_info", ()).await;
+        assert_eq!(info.provenance, alice_pubkey);
+        assert_eq!(info.cap_grant, CapGrant::ChainAuthor(alice_pubkey.clone()));
+        assert_eq!(info.action_hash, None);
+
+        #[derive(serde::Serialize, serde::Deserialize, SerializedBytes, Debug)]
+        pub struct CapFor(CapSecret, AgentPubKey);
+
+        // A remote caller is authorized by the committed grant.
+        let secret = CapSecretFixturator::new(Unpredictable).next().unwrap();
+        let grant_hash: ActionHash = conductor.call(&bob, "transferable_cap_grant", secret).await;
+        let response: ZomeCallResponse = conductor
+            .call(&alice, "try_cap_info", CapFor(secret, bob_pubkey.clone()))
+            .await;
+        let info: CapabilityInfo = match response {
+            ZomeCallResponse::Ok(io) => io.decode().unwrap(),
+            other => panic!("unexpected response {:?}", other),
+        };
+        assert_eq!(info.provenance, alice_pubkey);
+        assert_eq!(info.action_hash, Some(grant_hash.clone()));
+        assert_matches!(info.cap_grant, CapGrant::RemoteAgent(_));
+
+        #[derive(serde::Serialize, serde::Deserialize, SerializedBytes, Debug)]
+        pub struct RevokeFor(CapSecret, AgentPubKey, ActionHash);
+
+        // The grant which authorized the call is still reported after the
+        // call deletes it.
+        let response: ZomeCallResponse = conductor
+            .call(
+                &alice,
+                "try_revoke_then_cap_info",
+                RevokeFor(secret, bob_pubkey, grant_hash.clone()),
+            )
+            .await;
+        let revoked_info: CapabilityInfo = match response {
+            ZomeCallResponse::Ok(io) => io.decode().unwrap(),
+            other => panic!("unexpected response {:?}", other),
+        };
+        assert_eq!(revoked_info, info);
+    }
 }
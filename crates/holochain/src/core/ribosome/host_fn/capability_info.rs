@@ -1,14 +1,60 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::InvocationAuth;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
-use std::sync::Arc;
+use holochain_types::prelude::*;
 use holochain_wasmer_host::prelude::*;
+use std::sync::Arc;
 
 /// return the access info used for this call
 /// also return who is originated the call (pubkey)
 pub fn capability_info(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
+    call_context: Arc<CallContext>,
     _input: (),
-) -> Result<(), RuntimeError> {
-    unimplemented!();
+) -> Result<CapabilityInfo, RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            bindings: Permission::Allow,
+            ..
+        } => match call_context.auth() {
+            // The grant which authorized the call is kept for its whole
+            // duration, even if the call deletes it.
+            InvocationAuth::Cap(_, _) => call_context
+                .host_context
+                .authorization()
+                .cloned()
+                .ok_or_else(|| -> RuntimeError {
+                    wasm_error!(WasmErrorInner::Host(
+                        "The call has no capability grant which authorized it".into()
+                    ))
+                    .into()
+                }),
+            InvocationAuth::LocalCallback => {
+                let author = call_context
+                    .host_context
+                    .workspace()
+                    .source_chain()
+                    .as_ref()
+                    .expect("Must have source chain if bindings access is given")
+                    .agent_pubkey()
+                    .clone();
+                Ok(CapabilityInfo {
+                    provenance: author.clone(),
+                    cap_grant: CapGrant::ChainAuthor(author),
+                    action_hash: None,
+                })
+            }
+        },
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "capability_info".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
 }
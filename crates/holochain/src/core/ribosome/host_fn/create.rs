@@ -1,8 +1,11 @@
+use crate::core::ribosome::guest_callback::weigh::entry_rate_weight;
+use crate::core::ribosome::guest_callback::weigh::weigh_action;
 use crate::core::ribosome::weigh_placeholder;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
+use holochain_state::source_chain::SourceChainError;
 use holochain_wasmer_host::prelude::*;
 
 use holochain_types::prelude::*;
@@ -11,7 +14,7 @@ use std::sync::Arc;
 /// create record
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn create<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT>,
     call_context: Arc<CallContext>,
     input: CreateInput,
 ) -> Result<ActionHash, RuntimeError> {
@@ -27,11 +30,10 @@ pub fn create<'a>(
                 chain_top_ordering,
             } = input;
 
-            let weight = weigh_placeholder();
-
             // Countersigned entries have different action handling.
             match entry {
                 Entry::CounterSign(_, _) => tokio_helper::block_forever_on(async move {
+                    let weight = weigh_placeholder();
                     call_context
                         .host_context
                         .workspace_write()
@@ -48,6 +50,14 @@ pub fn create<'a>(
                     // build the entry hash
                     let entry_hash = EntryHash::with_data_sync(&entry);
 
+                    // the integrity zome which weighs this entry, if any
+                    let weigh_zome_id = match &entry_location {
+                        EntryDefLocation::App(AppEntryDefLocation { zome_id, .. }) => {
+                            Some(*zome_id)
+                        }
+                        _ => None,
+                    };
+
                     // extract the entry defs for a zome
                     let entry_type = match entry_location {
                         EntryDefLocation::App(AppEntryDefLocation {
@@ -80,7 +90,20 @@ pub fn create<'a>(
                             .source_chain()
                             .as_ref()
                             .expect("Must have source chain if write_workspace access is given")
-                            .put_weightless(action_builder, Some(entry), chain_top_ordering)
+                            .put_weighed_by(
+                                action_builder,
+                                Some(entry.clone()),
+                                chain_top_ordering,
+                                |create| {
+                                    weigh_action(
+                                        ribosome.as_ref(),
+                                        weigh_zome_id,
+                                        WeighInput::Create(create.clone(), entry),
+                                    )
+                                    .map(entry_rate_weight)
+                                    .map_err(SourceChainError::other)
+                                },
+                            )
                             .await
                             .map_err(|source_chain_error| -> RuntimeError {
                                 wasm_error!(WasmErrorInner::Host(source_chain_error.to_string()))
@@ -1,7 +1,9 @@
+use crate::core::ribosome::guest_callback::weigh::weigh_action;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
+use holochain_state::source_chain::SourceChainError;
 use holochain_wasmer_host::prelude::*;
 
 use holochain_types::prelude::*;
@@ -9,7 +11,7 @@ use std::sync::Arc;
 
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn create_link<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT + 'static>,
     call_context: Arc<CallContext>,
     input: CreateLinkInput,
 ) -> Result<ActionHash, RuntimeError> {
@@ -39,7 +41,14 @@ pub fn create_link<'a>(
                     .source_chain()
                     .as_ref()
                     .expect("Must have source chain if write_workspace access is given")
-                    .put_weightless(action_builder, None, chain_top_ordering)
+                    .put_weighed_by(action_builder, None, chain_top_ordering, |create_link| {
+                        weigh_action(
+                            ribosome.as_ref(),
+                            Some(zome_id),
+                            WeighInput::Link(create_link.clone()),
+                        )
+                        .map_err(SourceChainError::other)
+                    })
                     .await?;
                 Ok::<ActionHash, RibosomeError>(action_hash)
             }))
@@ -1,8 +1,10 @@
 use crate::core::ribosome::error::RibosomeError;
+use crate::core::ribosome::guest_callback::weigh::weigh_action;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::RibosomeT;
 use holochain_cascade::error::CascadeError;
 use holochain_cascade::Cascade;
+use holochain_state::source_chain::SourceChainError;
 use holochain_wasmer_host::prelude::*;
 
 use crate::core::ribosome::HostFnAccess;
@@ -13,7 +15,7 @@ use std::sync::Arc;
 
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn delete<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT>,
     call_context: Arc<CallContext>,
     input: DeleteInput,
 ) -> Result<ActionHash, RuntimeError> {
@@ -26,8 +28,13 @@ pub fn delete<'a>(
                 deletes_action_hash,
                 chain_top_ordering,
             } = input;
-            let (deletes_entry_address, _) =
+            let (deletes_entry_address, entry_type) =
                 get_original_entry_data(call_context.clone(), deletes_action_hash.clone())?;
+            // the deleted entry's integrity zome weighs the delete, if any
+            let weigh_zome_id = match &entry_type {
+                EntryType::App(app_entry_type) => Some(app_entry_type.zome_id()),
+                _ => None,
+            };
 
             let host_access = call_context.host_context();
 
@@ -43,7 +50,14 @@ pub fn delete<'a>(
                     deletes_entry_address,
                 };
                 let action_hash = source_chain
-                    .put_weightless(action_builder, None, chain_top_ordering)
+                    .put_weighed_by(action_builder, None, chain_top_ordering, |delete| {
+                        weigh_action(
+                            ribosome.as_ref(),
+                            weigh_zome_id,
+                            WeighInput::Delete(delete.clone()),
+                        )
+                        .map_err(SourceChainError::other)
+                    })
                     .await
                     .map_err(|source_chain_error| {
                         wasm_error!(WasmErrorInner::Host(source_chain_error.to_string()))
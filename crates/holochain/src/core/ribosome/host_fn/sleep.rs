@@ -1,12 +1,178 @@
 use crate::core::ribosome::CallContext;
+use crate::core::ribosome::HostFnAccess;
+use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
-use std::sync::Arc;
+use holochain_types::access::Permission;
+use holochain_types::prelude::tokio_helper;
 use holochain_wasmer_host::prelude::*;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// How many zome calls may be asleep at once across the conductor.
+pub const MAX_SLEEPING_CALLS: usize = 64;
+
+static SLEEPING_CALLS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(MAX_SLEEPING_CALLS));
 
+/// Suspend the call for a while, e.g. to back off between retries.
+///
+/// The sleep is capped at the conductor's `max_sleep_ms`, and ends early when
+/// the zome call would time out, in which case the call is stopped.
+/// Nothing is written to the source chain until the call returns, so sleeping
+/// never holds the chain lock.
+///
+/// The wasm runtime can't suspend a call part way through a host function, so
+/// a sleeping call stays on its blocking thread. So that sleeping calls can't
+/// use up the threads other zome calls need, at most [`MAX_SLEEPING_CALLS`]
+/// sleep at once, and `sleep` returns an error the guest can handle to any
+/// call beyond that.
 pub fn sleep(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: core::time::Duration,
+    call_context: Arc<CallContext>,
+    input: core::time::Duration,
 ) -> Result<(), RuntimeError> {
-    unimplemented!()
+    sleep_with_permits(&SLEEPING_CALLS, call_context, input)
+}
+
+fn sleep_with_permits(
+    sleeping_calls: &Semaphore,
+    call_context: Arc<CallContext>,
+    input: core::time::Duration,
+) -> Result<(), RuntimeError> {
+    match HostFnAccess::from(&call_context.host_context()) {
+        HostFnAccess {
+            non_determinism: Permission::Allow,
+            ..
+        } => {
+            let _permit = sleeping_calls.try_acquire().map_err(|_| -> RuntimeError {
+                wasm_error!(WasmErrorInner::Host(
+                    "Too many zome calls are sleeping, try again later".to_string()
+                ))
+                .into()
+            })?;
+            let timing = call_context.host_context().zome_call_timing();
+            let wake_at = tokio::time::Instant::now() + input.min(timing.max_sleep);
+            let times_out = timing.deadline.map_or(false, |deadline| deadline < wake_at);
+            tokio_helper::block_forever_on(async move {
+                tokio::time::sleep_until(match timing.deadline {
+                    Some(deadline) if times_out => deadline,
+                    _ => wake_at,
+                })
+                .await
+            });
+            if times_out {
+                // Not a wasm error, so the guest can't carry on past its
+                // deadline by handling it.
+                Err(RuntimeError::new("The zome call timed out while sleeping"))
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err(wasm_error!(WasmErrorInner::Host(
+            RibosomeError::HostFnPermissions(
+                call_context.zome.zome_name().clone(),
+                call_context.function_name().clone(),
+                "sleep".into(),
+            )
+            .to_string(),
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::sleep;
+    use super::sleep_with_permits;
+    use crate::core::ribosome::HostContext;
+    use crate::core::ribosome::ZomeCallTiming;
+    use crate::fixt::CallContextFixturator;
+    use crate::fixt::RealRibosomeFixturator;
+    use crate::fixt::ZomeCallHostAccessFixturator;
+    use ::fixt::prelude::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    fn call_context(timing: ZomeCallTiming) -> Arc<crate::core::ribosome::CallContext> {
+        let mut call_context = CallContextFixturator::new(::fixt::Unpredictable)
+            .next()
+            .unwrap();
+        let mut host_access = fixt!(ZomeCallHostAccess);
+        host_access.timing = timing;
+        call_context.host_context = HostContext::ZomeCall(host_access);
+        Arc::new(call_context)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_is_capped_by_max_sleep() {
+        let ribosome = Arc::new(
+            RealRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap(),
+        );
+        let call_context = call_context(ZomeCallTiming {
+            max_sleep: Duration::from_millis(50),
+            deadline: None,
+        });
+        let start = Instant::now();
+        sleep(ribosome, call_context, Duration::from_secs(10)).unwrap();
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_secs(10));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_past_the_deadline_times_out() {
+        let ribosome = Arc::new(
+            RealRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap(),
+        );
+        let call_context = call_context(ZomeCallTiming {
+            max_sleep: Duration::from_secs(10),
+            deadline: Some(tokio::time::Instant::now() + Duration::from_millis(50)),
+        });
+        let start = Instant::now();
+        let err = sleep(ribosome, call_context.clone(), Duration::from_secs(5)).unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err
+            .downcast::<holochain_wasmer_host::prelude::WasmError>()
+            .is_err());
+        assert!(call_context.host_context().zome_call_timing().timed_out());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sleep_fails_when_too_many_calls_are_sleeping() {
+        let call_context = call_context(ZomeCallTiming {
+            max_sleep: Duration::from_secs(10),
+            deadline: None,
+        });
+        let sleeping_calls = tokio::sync::Semaphore::new(0);
+        let start = Instant::now();
+        let err =
+            sleep_with_permits(&sleeping_calls, call_context, Duration::from_secs(5)).unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(err
+            .downcast::<holochain_wasmer_host::prelude::WasmError>()
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+pub mod wasm_test {
+    use crate::core::ribosome::wasm_test::RibosomeTestFixture;
+    use holochain_wasm_test_utils::TestWasm;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn invoke_import_sleep_test() {
+        observability::test_run().ok();
+        let RibosomeTestFixture {
+            conductor, alice, ..
+        } = RibosomeTestFixture::new(TestWasm::SysTime).await;
+        let start = std::time::Instant::now();
+        let _: () = conductor.call(&alice, "sleep_millis", 200_u64).await;
+        assert!(start.elapsed() >= std::time::Duration::from_millis(200));
+    }
 }
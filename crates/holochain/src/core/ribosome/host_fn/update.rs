@@ -1,9 +1,12 @@
 use super::delete::get_original_entry_data;
+use crate::core::ribosome::guest_callback::weigh::entry_rate_weight;
+use crate::core::ribosome::guest_callback::weigh::weigh_action;
 use crate::core::ribosome::weigh_placeholder;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::HostFnAccess;
 use crate::core::ribosome::RibosomeError;
 use crate::core::ribosome::RibosomeT;
+use holochain_state::source_chain::SourceChainError;
 use holochain_wasmer_host::prelude::*;
 
 use holochain_types::prelude::*;
@@ -11,7 +14,7 @@ use std::sync::Arc;
 
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn update<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT>,
     call_context: Arc<CallContext>,
     input: UpdateInput,
 ) -> Result<ActionHash, RuntimeError> {
@@ -30,11 +33,10 @@ pub fn update<'a>(
             let (original_entry_address, entry_type) =
                 get_original_entry_data(call_context.clone(), original_action_address.clone())?;
 
-            let weight = weigh_placeholder();
-
             // Countersigned entries have different action handling.
             match entry {
                 Entry::CounterSign(_, _) => tokio_helper::block_forever_on(async move {
+                    let weight = weigh_placeholder();
                     call_context
                         .host_context
                         .workspace_write()
@@ -51,6 +53,12 @@ pub fn update<'a>(
                     // build the entry hash
                     let entry_hash = EntryHash::with_data_sync(&entry);
 
+                    // the integrity zome which weighs this entry, if any
+                    let weigh_zome_id = match &entry_type {
+                        EntryType::App(app_entry_type) => Some(app_entry_type.zome_id()),
+                        _ => None,
+                    };
+
                     // build an action for the entry being updated
                     let action_builder = builder::Update {
                         original_entry_address,
@@ -71,7 +79,20 @@ pub fn update<'a>(
                             .expect("Must have source chain if write_workspace access is given");
                         // push the action and the entry into the source chain
                         let action_hash = source_chain
-                            .put_weightless(action_builder, Some(entry), chain_top_ordering)
+                            .put_weighed_by(
+                                action_builder,
+                                Some(entry.clone()),
+                                chain_top_ordering,
+                                |update| {
+                                    weigh_action(
+                                        ribosome.as_ref(),
+                                        weigh_zome_id,
+                                        WeighInput::Update(update.clone(), entry),
+                                    )
+                                    .map(entry_rate_weight)
+                                    .map_err(SourceChainError::other)
+                                },
+                            )
                             .await
                             .map_err(|source_chain_error| -> RuntimeError {
                                 wasm_error!(WasmErrorInner::Host(source_chain_error.to_string()))
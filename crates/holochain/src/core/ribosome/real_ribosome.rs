@@ -24,6 +24,8 @@ use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
+use crate::core::ribosome::guest_callback::weigh::WeighHostAccess;
+use crate::core::ribosome::guest_callback::weigh::WeighInvocation;
 use crate::core::ribosome::guest_callback::CallIterator;
 use crate::core::ribosome::host_fn::accept_countersigning_preflight_request::accept_countersigning_preflight_request;
 use crate::core::ribosome::host_fn::agent_info::agent_info;
@@ -84,6 +86,7 @@ use holochain_types::zome_types::GlobalZomeTypes;
 use holochain_types::zome_types::ZomeTypesError;
 use holochain_wasmer_host::prelude::*;
 use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
@@ -103,6 +106,10 @@ pub struct RealRibosome {
 
     /// Dependencies for every zome.
     pub zome_dependencies: Arc<HashMap<ZomeName, Vec<ZomeId>>>,
+
+    /// The functions exported by every zome.
+    /// Built on first use and shared by all clones of this ribosome.
+    zome_functions: Arc<OnceCell<Arc<Vec<ZomeFunctions>>>>,
 }
 
 struct HostFnBuilder {
@@ -167,6 +174,12 @@ impl HostFnBuilder {
                             })
                             .clone()
                     };
+                    // The conductor has given up on a call which timed out,
+                    // so it is stopped at its next host call instead of
+                    // running on in the background.
+                    if context_arc.host_context().zome_call_timing().timed_out() {
+                        return Err(RuntimeError::new("The zome call timed out"));
+                    }
                     let result = match db.consume_bytes_from_guest(guest_ptr, len) {
                         Ok(input) => host_function(Arc::clone(&ribosome_arc), context_arc, input),
                         Err(runtime_error) => Result::<_, RuntimeError>::Err(runtime_error),
@@ -238,6 +251,7 @@ impl RealRibosome {
             dna_file,
             zome_types: Default::default(),
             zome_dependencies: Default::default(),
+            zome_functions: Default::default(),
         };
 
         // Collect the number of entry and link types
@@ -323,6 +337,7 @@ impl RealRibosome {
             dna_file: ribosome.dna_file,
             zome_types,
             zome_dependencies: Arc::new(zome_dependencies),
+            zome_functions: Default::default(),
         })
     }
 
@@ -332,6 +347,7 @@ impl RealRibosome {
             dna_file,
             zome_types: Default::default(),
             zome_dependencies: Default::default(),
+            zome_functions: Default::default(),
         }
     }
 
@@ -805,6 +821,34 @@ impl RibosomeT for RealRibosome {
         }
     }
 
+    fn list_zome_functions(&self) -> RibosomeResult<Arc<Vec<ZomeFunctions>>> {
+        self.zome_functions
+            .get_or_try_init(|| {
+                self.dna_def()
+                    .all_zomes()
+                    .map(|(zome_name, zome_def)| {
+                        let exports = match zome_def {
+                            ZomeDef::Wasm(_) => self
+                                .module(zome_name)?
+                                .exports()
+                                .functions()
+                                .map(|f| f.name().to_string())
+                                .collect(),
+                            ZomeDef::Inline { inline_zome, .. } => inline_zome
+                                .0
+                                .callbacks()
+                                .into_iter()
+                                .map(|f| f.0)
+                                .collect::<Vec<_>>(),
+                        };
+                        Ok(ZomeFunctions::from_exports(zome_name.clone(), exports))
+                    })
+                    .collect::<RibosomeResult<Vec<_>>>()
+                    .map(Arc::new)
+            })
+            .map(Arc::clone)
+    }
+
     fn call_iterator<I: crate::core::ribosome::Invocation>(
         &self,
         host_context: HostContext,
@@ -856,6 +900,18 @@ impl RibosomeT for RealRibosome {
         do_callback!(self, host_access, invocation, ValidateCallbackResult)
     }
 
+    fn run_weigh(
+        &self,
+        host_access: WeighHostAccess,
+        invocation: WeighInvocation,
+    ) -> RibosomeResult<RateWeight> {
+        match self.call_iterator(host_access.into(), invocation).next() {
+            Ok(Some((_zome, extern_io))) => Ok(extern_io.decode()?),
+            Ok(None) => Ok(RateWeight::default()),
+            Err((_zome, ribosome_error)) => Err(ribosome_error),
+        }
+    }
+
     fn run_validate(
         &self,
         host_access: ValidateHostAccess,
@@ -927,6 +983,7 @@ impl RibosomeT for RealRibosome {
 #[cfg(feature = "slow_tests")]
 pub mod wasm_test {
     use crate::core::ribosome::wasm_test::RibosomeTestFixture;
+    use crate::core::ribosome::RibosomeT;
     use crate::core::ribosome::ZomeCall;
     use crate::sweettest::SweetConductor;
     use crate::sweettest::SweetDnaFile;
@@ -934,6 +991,9 @@ pub mod wasm_test {
     use hdk::prelude::*;
     use holochain_types::prelude::AgentPubKeyFixturator;
     use holochain_wasm_test_utils::TestWasm;
+    use std::sync::Arc;
+
+    use super::RealRibosome;
 
     #[tokio::test(flavor = "multi_thread")]
     /// Basic checks that we can call externs internally and externally the way we want using the
@@ -986,6 +1046,49 @@ pub mod wasm_test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn list_zome_functions_test() {
+        let (dna_file, _, _) =
+            SweetDnaFile::unique_from_test_wasms(vec![TestWasm::HdkExtern, TestWasm::InitPass])
+                .await
+                .unwrap();
+        let ribosome = RealRibosome::new(dna_file).unwrap();
+
+        let zome_functions = ribosome.list_zome_functions().unwrap();
+        let find = |zome_name: ZomeName| {
+            zome_functions
+                .iter()
+                .find(|f| f.zome_name == zome_name)
+                .cloned()
+                .unwrap()
+        };
+
+        let hdk_extern = find(TestWasm::HdkExtern.coordinator_zome_name());
+        assert!(hdk_extern.callbacks.is_empty());
+        assert_eq!(
+            hdk_extern.zome_fns,
+            vec![
+                FunctionName::from("bar"),
+                FunctionName::from("foo"),
+                FunctionName::from("infallible")
+            ]
+        );
+
+        let init_pass = find(TestWasm::InitPass.coordinator_zome_name());
+        assert_eq!(init_pass.callbacks, vec![FunctionName::from("init")]);
+        assert!(init_pass.zome_fns.is_empty());
+
+        // The list is only built once.
+        assert!(Arc::ptr_eq(
+            &zome_functions,
+            &ribosome.list_zome_functions().unwrap()
+        ));
+        assert_eq!(
+            ribosome.list_callbacks().unwrap().len(),
+            zome_functions.len()
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn the_incredible_halt_test() {
         observability::test_run().ok();
@@ -21,9 +21,13 @@ pub use holochain_state::source_chain::SourceChainError;
 pub use holochain_state::source_chain::SourceChainResult;
 pub use holochain_zome_types::ActionHashed;
 pub use holochain_zome_types::Timestamp;
+pub use key_state::*;
+pub use rate_buckets::*;
 
 #[allow(missing_docs)]
 mod error;
+mod key_state;
+mod rate_buckets;
 #[cfg(test)]
 mod tests;
 
@@ -47,11 +51,42 @@ pub async fn verify_action_signature(sig: &Signature, action: &Action) -> SysVal
     }
 }
 
-/// Verify the author key was valid at the time
-/// of signing with dpki
-/// TODO: This is just a stub until we have dpki.
-pub async fn author_key_is_valid(_author: &AgentPubKey) -> SysValidationResult<()> {
-    Ok(())
+/// Verify the author key was valid at the time of signing,
+/// according to each of the key state sources.
+/// Keys are always valid if there are no sources.
+/// If any source doesn't know about the key yet, or has no record of it and
+/// unregistered keys aren't allowed, and none of the others show that it was
+/// invalid, the op waits for the source to catch up.
+///
+/// The time of signing is the action's timestamp, which the author chose.
+pub async fn author_key_is_valid(
+    author: &AgentPubKey,
+    timestamp: Timestamp,
+    key_check: &KeyStateCheck,
+    conductor: &dyn ConductorHandleT,
+) -> SysValidationResult<()> {
+    let mut unknown = false;
+    for key_state in key_check.key_states(conductor, author).await? {
+        match key_state {
+            Some(KeyState::NotFound) if key_check.allow_unregistered_keys() => (),
+            Some(KeyState::NotFound) => unknown = true,
+            Some(key_state) if !key_state.is_valid_at(timestamp) => {
+                return Err(ValidationOutcome::InvalidAuthorKey(
+                    author.clone(),
+                    timestamp,
+                    key_state,
+                )
+                .into());
+            }
+            Some(_) => (),
+            None => unknown = true,
+        }
+    }
+    if unknown {
+        Err(ValidationOutcome::AuthorKeyStateUnknown(author.clone()).into())
+    } else {
+        Ok(())
+    }
 }
 
 /// Verify the countersigning session contains the specified action.
@@ -222,10 +257,74 @@ pub async fn check_chain_rollback(
     }
 }
 
-/// Placeholder for future spam check.
-/// Check action timestamps don't exceed MAX_PUBLISH_FREQUENCY
-pub async fn check_spam(_action: &Action) -> SysValidationResult<()> {
-    Ok(())
+/// Check that this action doesn't overflow the rate limiting bucket its
+/// weight was assigned to, by replaying the author's earlier actions in the
+/// same bucket. Buckets the DNA doesn't define are unlimited.
+///
+/// An authority with a [`RateBucketCache`] carries on from the checkpoint
+/// of the author's bucket, so it only replays the actions since then.
+pub async fn check_spam(
+    action: &Action,
+    workspace: &SysValidationWorkspace,
+) -> SysValidationResult<()> {
+    let weight = action.rate_data();
+    let limit = match workspace.dna_def().rate_limits.get(&weight.bucket_id) {
+        Some(limit) if weight.units > 0 => *limit,
+        _ => return Ok(()),
+    };
+    let cache = workspace.rate_bucket_cache();
+    let checkpoint = cache.and_then(|cache| cache.get(action.author(), weight.bucket_id));
+    let resumed = match checkpoint {
+        Some(checkpoint) => workspace
+            .held_prior_author_actions_since(action, checkpoint.action_seq, &checkpoint.action_hash)
+            .await?
+            .map(|actions| (checkpoint.state, actions)),
+        None => None,
+    };
+    let (mut bucket, prior_actions) = match resumed {
+        Some(resumed) => resumed,
+        None => {
+            let prior_actions = workspace.prior_author_actions(action).await?;
+            let bucket = RateBucketState::new(
+                prior_actions
+                    .first()
+                    .map(|a| a.timestamp())
+                    .unwrap_or_else(|| action.timestamp()),
+            );
+            (bucket, prior_actions)
+        }
+    };
+    for prior in &prior_actions {
+        let prior_weight = prior.rate_data();
+        if prior_weight.bucket_id == weight.bucket_id {
+            // Earlier overflows are rejected when those actions are validated.
+            let _ = bucket.fill(&limit, prior.timestamp(), prior_weight.units);
+        }
+    }
+    if let (Some(cache), Some(last), Some(prev_action)) =
+        (cache, prior_actions.last(), action.prev_action())
+    {
+        cache.advance(
+            action.author().clone(),
+            weight.bucket_id,
+            RateBucketCheckpoint {
+                action_hash: prev_action.clone(),
+                action_seq: last.action_seq(),
+                state: bucket,
+            },
+        );
+    }
+    bucket
+        .fill(&limit, action.timestamp(), weight.units)
+        .map(|_| ())
+        .map_err(|level| {
+            ValidationOutcome::RateLimitExceeded {
+                bucket_id: weight.bucket_id,
+                level,
+                capacity: limit.capacity,
+            }
+            .into()
+        })
 }
 
 /// Check previous action timestamp is before this action
@@ -115,6 +115,16 @@ pub enum ValidationOutcome {
     EntryType,
     #[error("The app entry type {0:?} visibility didn't match the zome")]
     EntryVisibility(AppEntryType),
+    #[error("The author {0} has no key state yet in the DPKI app, so the action can't be validated until it does")]
+    AuthorKeyStateUnknown(AgentPubKey),
+    #[error("The author {0} was not a valid agent key at the time of the action {1:?}: {2:?}")]
+    InvalidAuthorKey(AgentPubKey, Timestamp, KeyState),
+    #[error("The action would fill rate limit bucket {bucket_id} to {level}, over its capacity of {capacity}")]
+    RateLimitExceeded {
+        bucket_id: RateBucketId,
+        level: RateBucketCapacity,
+        capacity: RateBucketCapacity,
+    },
     #[error("The link tag size {0} was bigger then the MAX_TAG_SIZE {1}")]
     TagTooLarge(usize, usize),
     #[error("The action {0:?} was expected to be a link add action")]
@@ -0,0 +1,189 @@
+//! Where sys validation looks up whether an author's key was still valid.
+//!
+//! Keys are checked against the conductor's DPKI app, the only
+//! [`KeyStateSource`] so far. [`KeyStateCheck::for_dna`] picks the sources
+//! which apply to a DNA. Ops whose author's key state is unknown are retried
+//! with a back off, see [`KeyStateRetries`].
+
+use super::SysValidationResult;
+use crate::conductor::api::error::ConductorApiError;
+use crate::conductor::error::ConductorError;
+use crate::conductor::handle::ConductorHandleT;
+use holo_hash::DhtOpHash;
+use holochain_types::prelude::*;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::OnceCell;
+use tracing::*;
+
+/// Something which records when agent keys were revoked or rotated.
+#[async_trait::async_trait]
+pub trait KeyStateSource: Send + Sync {
+    /// The state of the key, or `None` if this source doesn't have the key's
+    /// data yet and should be asked again later.
+    async fn key_state(
+        &self,
+        conductor: &dyn ConductorHandleT,
+        key: &AgentPubKey,
+    ) -> SysValidationResult<Option<KeyState>>;
+}
+
+/// The conductor's DPKI app.
+pub struct DpkiKeyStateSource;
+
+#[async_trait::async_trait]
+impl KeyStateSource for DpkiKeyStateSource {
+    async fn key_state(
+        &self,
+        conductor: &dyn ConductorHandleT,
+        key: &AgentPubKey,
+    ) -> SysValidationResult<Option<KeyState>> {
+        match conductor.dpki_key_state(key.clone()).await {
+            // `None` means DPKI isn't running yet, so ask again later.
+            Ok(key_state) => Ok(key_state),
+            // The call may well succeed when it is retried.
+            Err(ConductorError::DpkiCallFailed(fn_name, reason)) => {
+                warn!(%fn_name, %reason, "DPKI key state call failed");
+                Ok(None)
+            }
+            Err(e) => Err(Box::new(ConductorApiError::from(e)).into()),
+        }
+    }
+}
+
+type KeyStates = Arc<OnceCell<Vec<Option<KeyState>>>>;
+
+/// The key state sources which apply to a DNA, and what they have said about
+/// the authors validated so far. Each source is asked about an author at most
+/// once, however many of the author's ops are validated with this check.
+pub struct KeyStateCheck {
+    sources: Vec<Box<dyn KeyStateSource>>,
+    allow_unregistered_keys: bool,
+    key_states: Mutex<HashMap<AgentPubKey, KeyStates>>,
+}
+
+impl KeyStateCheck {
+    /// Check keys against these sources. Keys which a source has no record
+    /// of are only valid if `allow_unregistered_keys` is set.
+    pub fn new(sources: Vec<Box<dyn KeyStateSource>>, allow_unregistered_keys: bool) -> Self {
+        Self {
+            sources,
+            allow_unregistered_keys,
+            key_states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The conductor's DPKI app, if it has one.
+    ///
+    /// The DPKI app's own ops are not checked against DPKI, which would mean
+    /// calling into the cell being validated.
+    pub async fn for_dna(dna_hash: &DnaHash, conductor: &dyn ConductorHandleT) -> Self {
+        let mut sources: Vec<Box<dyn KeyStateSource>> = Vec::new();
+        let dpki = conductor
+            .get_config()
+            .dpki
+            .as_ref()
+            .map(|dpki| dpki.allow_unregistered_keys);
+        if dpki.is_some() && conductor.dpki_dna_hash().await.as_ref() != Some(dna_hash) {
+            sources.push(Box::new(DpkiKeyStateSource));
+        }
+        Self::new(sources, dpki.unwrap_or(false))
+    }
+
+    /// Whether keys which a source has no record of are trusted.
+    pub fn allow_unregistered_keys(&self) -> bool {
+        self.allow_unregistered_keys
+    }
+
+    /// What each source says about the key. Failed lookups are not cached,
+    /// so they are tried again for the next op of the same author.
+    pub async fn key_states(
+        &self,
+        conductor: &dyn ConductorHandleT,
+        key: &AgentPubKey,
+    ) -> SysValidationResult<Vec<Option<KeyState>>> {
+        if self.sources.is_empty() {
+            return Ok(Vec::new());
+        }
+        let cell = self
+            .key_states
+            .lock()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        cell.get_or_try_init(|| async {
+            let mut key_states = Vec::with_capacity(self.sources.len());
+            for source in &self.sources {
+                key_states.push(source.key_state(conductor, key).await?);
+            }
+            SysValidationResult::Ok(key_states)
+        })
+        .await
+        .map(Clone::clone)
+    }
+}
+
+/// The wait before the first retry, doubled after each attempt.
+const KEY_STATE_RETRY_BASE: Duration = Duration::from_secs(1);
+
+/// The longest wait between two attempts. Ops keep being retried at this
+/// interval for as long as their author's key state is unknown.
+pub const KEY_STATE_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy)]
+struct Retry {
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+/// The ops of a space which are waiting for their author's key state,
+/// and when each may be validated again.
+///
+/// The ops themselves stay in the validation limbo, so only the back off is
+/// lost when the conductor restarts, and they are retried straight away.
+#[derive(Clone, Default)]
+pub struct KeyStateRetries(Arc<Mutex<HashMap<DhtOpHash, Retry>>>);
+
+impl KeyStateRetries {
+    /// Whether the op can be validated now. Ops which haven't waited
+    /// for a key state are always due.
+    pub fn is_due(&self, op_hash: &DhtOpHash) -> bool {
+        self.0
+            .lock()
+            .get(op_hash)
+            .map_or(true, |retry| retry.next_attempt <= Instant::now())
+    }
+
+    /// Record another attempt which found the key state unknown,
+    /// backing off the next one up to [`KEY_STATE_RETRY_MAX`].
+    pub fn record_attempt(&self, op_hash: &DhtOpHash) {
+        let mut retries = self.0.lock();
+        let attempts = retries
+            .get(op_hash)
+            .map_or(0, |retry| retry.attempts)
+            .saturating_add(1);
+        let back_off = KEY_STATE_RETRY_BASE
+            .saturating_mul(2u32.saturating_pow(attempts - 1))
+            .min(KEY_STATE_RETRY_MAX);
+        retries.insert(
+            op_hash.clone(),
+            Retry {
+                attempts,
+                next_attempt: Instant::now() + back_off,
+            },
+        );
+    }
+
+    /// Forget the op, once it no longer waits for a key state.
+    pub fn clear(&self, op_hash: &DhtOpHash) {
+        self.0.lock().remove(op_hash);
+    }
+
+    /// When the next waiting op is due, if any are waiting.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.0.lock().values().map(|retry| retry.next_attempt).min()
+    }
+}
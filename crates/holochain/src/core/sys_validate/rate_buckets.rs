@@ -0,0 +1,60 @@
+//! Checkpoints of the authors' rate limiting buckets, so that checking an
+//! action only replays the author's chain since the last action checked,
+//! rather than from the start, see [`RateBucketCache`].
+
+use holo_hash::ActionHash;
+use holochain_types::prelude::*;
+use parking_lot::Mutex;
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// An author's bucket as it was after one of their actions.
+#[derive(Debug, Clone)]
+pub struct RateBucketCheckpoint {
+    /// The last action replayed into the bucket.
+    pub action_hash: ActionHash,
+    /// The sequence number of that action.
+    pub action_seq: u32,
+    /// The bucket after that action.
+    pub state: RateBucketState,
+}
+
+/// The furthest checkpoint of each author's buckets in a space.
+///
+/// Checkpoints are only taken of chains an authority holds all of, and an
+/// action whose chain doesn't pass through the checkpoint is replayed from the
+/// start, so a checkpoint never lets an action skip part of its own chain.
+#[derive(Clone, Default)]
+pub struct RateBucketCache(Arc<Mutex<HashMap<(AgentPubKey, RateBucketId), RateBucketCheckpoint>>>);
+
+impl RateBucketCache {
+    /// The checkpoint of the author's bucket, if there is one.
+    pub fn get(
+        &self,
+        author: &AgentPubKey,
+        bucket_id: RateBucketId,
+    ) -> Option<RateBucketCheckpoint> {
+        self.0.lock().get(&(author.clone(), bucket_id)).cloned()
+    }
+
+    /// Keep the checkpoint if it is further along the author's chain
+    /// than the one already kept.
+    pub fn advance(
+        &self,
+        author: AgentPubKey,
+        bucket_id: RateBucketId,
+        checkpoint: RateBucketCheckpoint,
+    ) {
+        match self.0.lock().entry((author, bucket_id)) {
+            hash_map::Entry::Occupied(mut kept) => {
+                if kept.get().action_seq < checkpoint.action_seq {
+                    kept.insert(checkpoint);
+                }
+            }
+            hash_map::Entry::Vacant(vacant) => {
+                vacant.insert(checkpoint);
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::conductor::error::ConductorError;
 use crate::conductor::handle::MockConductorHandleT;
 use crate::conductor::space::TestSpaces;
 use crate::core::ribosome::real_ribosome::RealRibosome;
@@ -8,6 +9,7 @@ use error::SysValidationError;
 
 use holochain_keystore::AgentPubKeyExt;
 use holochain_serialized_bytes::SerializedBytes;
+use holochain_state::mutations;
 use holochain_state::prelude::fresh_reader_test;
 use holochain_state::prelude::test_authored_db;
 use holochain_state::prelude::test_cache_db;
@@ -18,6 +20,8 @@ use holochain_zome_types::Action;
 use matches::assert_matches;
 use observability;
 use std::convert::TryFrom;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn verify_action_signature_test() {
@@ -132,6 +136,200 @@ async fn check_valid_if_dna_test() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn check_spam_test() {
+    let tmp = test_authored_db();
+    let tmp_dht = test_dht_db();
+    let tmp_cache = test_cache_db();
+    let author = fake_agent_pubkey_1();
+
+    let mut dna_def = fixt!(DnaDef);
+    dna_def.rate_limits.insert(
+        1,
+        RateBucketLimit {
+            capacity: 10,
+            drain_amount: 5,
+            drain_interval_ms: 1000,
+        },
+    );
+    let workspace = SysValidationWorkspace::new(
+        tmp.to_db().into(),
+        tmp_dht.to_db().into(),
+        tmp_dht.to_db().into(),
+        tmp_cache.to_db(),
+        Arc::new(dna_def),
+    );
+
+    let link =
+        |seq: u32, prev_action: &Action, bucket_id: RateBucketId, units: RateUnits, ms: i64| {
+            let mut action = fixt!(CreateLink);
+            action.author = author.clone();
+            action.action_seq = seq;
+            action.prev_action = ActionHash::with_data_sync(prev_action);
+            action.timestamp = Timestamp::from_micros(ms * 1000);
+            action.weight = RateWeight { bucket_id, units };
+            Action::CreateLink(action)
+        };
+    let hold = |action: &Action| {
+        let op = DhtOpHashed::from_content_sync(DhtOp::RegisterAgentActivity(
+            fixt!(Signature),
+            action.clone(),
+        ));
+        tmp_dht
+            .to_db()
+            .conn()
+            .unwrap()
+            .with_commit_test(|txn| mutations::insert_op(txn, &op).unwrap())
+            .unwrap();
+    };
+    let mut dna = fixt!(Dna);
+    dna.author = author.clone();
+    let dna = Action::Dna(dna);
+
+    // Undefined buckets are unlimited.
+    assert_matches!(
+        check_spam(&link(1, &dna, 2, 255, 0), &workspace).await,
+        Ok(())
+    );
+
+    // The author's earlier actions have to be held to replay the bucket.
+    assert_matches!(
+        check_spam(&link(1, &dna, 1, 8, 0), &workspace).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::NotHoldingDep(_)
+        ))
+    );
+    hold(&dna);
+    assert_matches!(
+        check_spam(&link(1, &dna, 1, 8, 0), &workspace).await,
+        Ok(())
+    );
+
+    // The author's earlier activity fills the bucket,
+    // whether or not it has been validated yet.
+    let prior = link(1, &dna, 1, 8, 0);
+    hold(&prior);
+    assert_matches!(
+        check_spam(&link(2, &prior, 1, 3, 500), &workspace).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::RateLimitExceeded {
+                bucket_id: 1,
+                level: 11,
+                capacity: 10,
+            }
+        ))
+    );
+    // Once the bucket has drained there is room again.
+    assert_matches!(
+        check_spam(&link(2, &prior, 1, 3, 1000), &workspace).await,
+        Ok(())
+    );
+
+    // Only the action's own chain counts, not a fork of it.
+    let fork = link(1, &dna, 1, 0, 0);
+    hold(&fork);
+    assert_matches!(
+        check_spam(&link(2, &fork, 1, 3, 500), &workspace).await,
+        Ok(())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn check_spam_resumes_from_checkpoints() {
+    let tmp = test_authored_db();
+    let tmp_dht = test_dht_db();
+    let tmp_cache = test_cache_db();
+    let author = fake_agent_pubkey_1();
+
+    let mut dna_def = fixt!(DnaDef);
+    dna_def.rate_limits.insert(
+        1,
+        RateBucketLimit {
+            capacity: 10,
+            drain_amount: 5,
+            drain_interval_ms: 1000,
+        },
+    );
+    let rate_bucket_cache = RateBucketCache::default();
+    let workspace = SysValidationWorkspace::new(
+        tmp.to_db().into(),
+        tmp_dht.to_db().into(),
+        tmp_dht.to_db().into(),
+        tmp_cache.to_db(),
+        Arc::new(dna_def),
+    )
+    .with_rate_bucket_cache(rate_bucket_cache.clone());
+
+    let link =
+        |seq: u32, prev_action: &Action, bucket_id: RateBucketId, units: RateUnits, ms: i64| {
+            let mut action = fixt!(CreateLink);
+            action.author = author.clone();
+            action.action_seq = seq;
+            action.prev_action = ActionHash::with_data_sync(prev_action);
+            action.timestamp = Timestamp::from_micros(ms * 1000);
+            action.weight = RateWeight { bucket_id, units };
+            Action::CreateLink(action)
+        };
+    let hold = |action: &Action| {
+        let op = DhtOpHashed::from_content_sync(DhtOp::RegisterAgentActivity(
+            fixt!(Signature),
+            action.clone(),
+        ));
+        tmp_dht
+            .to_db()
+            .conn()
+            .unwrap()
+            .with_commit_test(|txn| mutations::insert_op(txn, &op).unwrap())
+            .unwrap();
+    };
+    let mut dna = fixt!(Dna);
+    dna.author = author.clone();
+    let dna = Action::Dna(dna);
+    hold(&dna);
+    let first = link(1, &dna, 1, 8, 0);
+    hold(&first);
+
+    // Replaying the chain from the start leaves a checkpoint behind.
+    assert_matches!(
+        check_spam(&link(2, &first, 1, 3, 500), &workspace).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::RateLimitExceeded { level: 11, .. }
+        ))
+    );
+    let checkpoint = rate_bucket_cache.get(&author, 1).unwrap();
+    assert_eq!(checkpoint.action_hash, ActionHash::with_data_sync(&first));
+    assert_eq!(checkpoint.action_seq, 1);
+    assert_eq!(checkpoint.state.level, 8);
+
+    // Later actions carry on from the checkpoint and move it along.
+    let second = link(2, &first, 1, 2, 1000);
+    hold(&second);
+    assert_matches!(
+        check_spam(&link(3, &second, 1, 7, 1000), &workspace).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::RateLimitExceeded { level: 12, .. }
+        ))
+    );
+    assert_matches!(
+        check_spam(&link(3, &second, 1, 5, 1000), &workspace).await,
+        Ok(())
+    );
+    let checkpoint = rate_bucket_cache.get(&author, 1).unwrap();
+    assert_eq!(checkpoint.action_hash, ActionHash::with_data_sync(&second));
+    assert_eq!(checkpoint.action_seq, 2);
+    assert_eq!(checkpoint.state.level, 5);
+
+    // A fork doesn't pass through the checkpoint, so it is replayed from the start.
+    let fork = link(1, &dna, 1, 0, 0);
+    hold(&fork);
+    assert_matches!(
+        check_spam(&link(2, &fork, 1, 3, 500), &workspace).await,
+        Ok(())
+    );
+    // Nor does it move the checkpoint back.
+    assert_eq!(rate_bucket_cache.get(&author, 1).unwrap().action_seq, 2);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn check_previous_timestamp() {
     let mut action = fixt!(CreateLink);
@@ -335,6 +533,7 @@ async fn check_app_entry_type_test() {
             coordinator_zomes: vec![TestZomes::from(TestWasm::EntryDefs)
                 .coordinator
                 .into_inner()],
+            rate_limits: Default::default(),
         },
         [integrity, coordinator],
     )
@@ -413,6 +612,206 @@ async fn check_app_entry_type_test() {
     );
 }
 
+struct FixedKeyState(Option<KeyState>, Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl KeyStateSource for FixedKeyState {
+    async fn key_state(
+        &self,
+        _conductor: &dyn ConductorHandleT,
+        _key: &AgentPubKey,
+    ) -> SysValidationResult<Option<KeyState>> {
+        self.1.fetch_add(1, Ordering::SeqCst);
+        Ok(self.0.clone())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn author_key_is_valid_test() {
+    let author = fake_agent_pubkey_1();
+    let revoked_at = Timestamp::from_micros(100);
+    let before = Timestamp::from_micros(50);
+    let after = Timestamp::from_micros(150);
+    let conductor_handle = MockConductorHandleT::new();
+    let check = |key_states: Vec<Option<KeyState>>| {
+        let sources = key_states
+            .into_iter()
+            .map(|key_state| -> Box<dyn KeyStateSource> {
+                Box::new(FixedKeyState(key_state, Default::default()))
+            })
+            .collect();
+        KeyStateCheck::new(sources, false)
+    };
+
+    // # No sources
+    assert_matches!(
+        author_key_is_valid(&author, after, &check(vec![]), &conductor_handle).await,
+        Ok(())
+    );
+
+    // # Revoked key
+    let key_check = check(vec![Some(KeyState::Revoked(revoked_at))]);
+    assert_matches!(
+        author_key_is_valid(&author, before, &key_check, &conductor_handle).await,
+        Ok(())
+    );
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::InvalidAuthorKey(_, _, KeyState::Revoked(_))
+        ))
+    );
+
+    // # Rotated key
+    let key_check = check(vec![Some(KeyState::Rotated(
+        fake_agent_pubkey_2(),
+        revoked_at,
+    ))]);
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::InvalidAuthorKey(_, _, KeyState::Rotated(_, _))
+        ))
+    );
+
+    // # The registry doesn't have the key yet
+    let key_check = check(vec![Some(KeyState::Valid(before)), None]);
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::AuthorKeyStateUnknown(_)
+        ))
+    );
+
+    // # Another source already shows the key was revoked
+    let key_check = check(vec![None, Some(KeyState::Revoked(revoked_at))]);
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::InvalidAuthorKey(_, _, KeyState::Revoked(_))
+        ))
+    );
+
+    // # Unregistered keys wait for registration unless they are allowed
+    let key_check = check(vec![Some(KeyState::NotFound)]);
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::AuthorKeyStateUnknown(_)
+        ))
+    );
+    let source = FixedKeyState(Some(KeyState::NotFound), Default::default());
+    let key_check = KeyStateCheck::new(vec![Box::new(source)], true);
+    assert_matches!(
+        author_key_is_valid(&author, after, &key_check, &conductor_handle).await,
+        Ok(())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn key_states_are_looked_up_once_per_author() {
+    let conductor_handle = MockConductorHandleT::new();
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let source = FixedKeyState(Some(KeyState::Valid(Timestamp::now())), lookups.clone());
+    let key_check = KeyStateCheck::new(vec![Box::new(source)], false);
+
+    for author in [
+        fake_agent_pubkey_1(),
+        fake_agent_pubkey_1(),
+        fake_agent_pubkey_2(),
+    ] {
+        author_key_is_valid(&author, Timestamp::now(), &key_check, &conductor_handle)
+            .await
+            .unwrap();
+    }
+    assert_eq!(lookups.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dpki_key_state_source_test() {
+    let author = fake_agent_pubkey_1();
+    let revoked_at = Timestamp::from_micros(100);
+    let mut conductor_handle = MockConductorHandleT::new();
+
+    // # DPKI isn't running yet
+    conductor_handle
+        .expect_dpki_key_state()
+        .returning(|_| Ok(None));
+    assert_matches!(
+        DpkiKeyStateSource
+            .key_state(&conductor_handle, &author)
+            .await,
+        Ok(None)
+    );
+
+    // # Revoked key
+    conductor_handle.checkpoint();
+    conductor_handle
+        .expect_dpki_key_state()
+        .returning(move |_| Ok(Some(KeyState::Revoked(revoked_at))));
+    assert_matches!(
+        DpkiKeyStateSource
+            .key_state(&conductor_handle, &author)
+            .await,
+        Ok(Some(KeyState::Revoked(_)))
+    );
+
+    // # A failed call is asked again later
+    conductor_handle.checkpoint();
+    conductor_handle.expect_dpki_key_state().returning(|_| {
+        Err(ConductorError::DpkiCallFailed(
+            DPKI_KEY_STATE_FN.to_string(),
+            "the cell is busy".to_string(),
+        ))
+    });
+    assert_matches!(
+        DpkiKeyStateSource
+            .key_state(&conductor_handle, &author)
+            .await,
+        Ok(None)
+    );
+
+    // # A key state which can't be decoded is an error
+    conductor_handle.checkpoint();
+    conductor_handle.expect_dpki_key_state().returning(|_| {
+        Err(ConductorError::SerializedBytesError(
+            SerializedBytesError::Deserialize("not a key state".to_string()),
+        ))
+    });
+    assert_matches!(
+        DpkiKeyStateSource
+            .key_state(&conductor_handle, &author)
+            .await,
+        Err(_)
+    );
+}
+
+#[test]
+fn key_state_retries_back_off_without_giving_up() {
+    let retries = KeyStateRetries::default();
+    let op_hash = fixt!(DhtOpHash);
+    assert!(retries.is_due(&op_hash));
+    assert_eq!(retries.next_due(), None);
+
+    retries.record_attempt(&op_hash);
+    assert!(!retries.is_due(&op_hash));
+    let first = retries.next_due().unwrap();
+    retries.record_attempt(&op_hash);
+    assert!(retries.next_due().unwrap() > first);
+
+    // The op keeps waiting, but never longer than the cap.
+    for _ in 0..100 {
+        retries.record_attempt(&op_hash);
+    }
+    assert!(!retries.is_due(&op_hash));
+    let wait = retries.next_due().unwrap() - std::time::Instant::now();
+    assert!(wait <= KEY_STATE_RETRY_MAX);
+    assert!(wait > KEY_STATE_RETRY_MAX / 2);
+
+    retries.clear(&op_hash);
+    assert!(retries.is_due(&op_hash));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn check_entry_not_private_test() {
     let mut ed = fixt!(EntryDef);
@@ -0,0 +1,402 @@
+//! Issuing and checking warrants.
+//!
+//! When validation rejects an op, the validating authority signs a warrant
+//! against the author of the invalid action and publishes it to that author's
+//! agent activity authorities. Anyone holding the warrant can check it without
+//! trusting the issuer, because it carries the invalid action signed by its
+//! author, and it is only acted on once validating that action here rejects
+//! it too.
+
+use crate::conductor::conductor::CellStatus;
+use crate::conductor::handle::ConductorHandleT;
+use crate::conductor::space::Space;
+use crate::core::workflow::app_validation_workflow;
+use crate::core::workflow::app_validation_workflow::AppValidationWorkspace;
+use crate::core::workflow::error::WorkflowResult;
+use crate::core::workflow::sys_validation_workflow;
+use crate::core::workflow::sys_validation_workflow::SysValidationWorkspace;
+use holochain_cascade::Cascade;
+use holochain_keystore::AgentPubKeyExt;
+use holochain_p2p::HolochainP2pDna;
+use holochain_p2p::HolochainP2pDnaT;
+use holochain_state::prelude::*;
+use holochain_types::prelude::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::*;
+
+/// An action which failed validation, along with why.
+#[derive(Debug, Clone)]
+pub struct InvalidAction {
+    /// The invalid action, signed by its author.
+    pub action: SignedAction,
+    /// Which kind of validation rejected it.
+    pub validation_type: ValidationType,
+    /// Why it was rejected.
+    pub reason: String,
+}
+
+/// What validating the proof of a warrant found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofCheck {
+    /// The proof was rejected in a way which warrants its author.
+    Invalid,
+    /// The proof passed validation, so the warrant is false.
+    Valid,
+    /// The proof could not be validated yet, for example because
+    /// its dependencies or its author's key state are missing.
+    Undecided,
+}
+
+/// Issue a warrant for each of these invalid actions.
+///
+/// Warrants are signed by the first local agent running this DNA, stored in
+/// the DHT database and published to the warrantee's agent activity
+/// authorities. The warrantee is also blocked on our own network.
+/// Failing to warrant is logged but not returned as an error, so it never
+/// holds up validation.
+pub async fn issue_warrants(
+    dna_hash: &DnaHash,
+    conductor: &dyn ConductorHandleT,
+    dht_db: &DbWrite<DbKindDht>,
+    network: &(dyn HolochainP2pDnaT + Send + Sync),
+    invalid: Vec<InvalidAction>,
+) {
+    if invalid.is_empty() {
+        return;
+    }
+    let issuer = conductor
+        .list_cell_ids(Some(CellStatus::Joined))
+        .into_iter()
+        .map(|id| id.into_dna_and_agent())
+        .find_map(|(d, a)| (d == *dna_hash).then_some(a));
+    let issuer = match issuer {
+        Some(issuer) => issuer,
+        None => {
+            warn!(%dna_hash, "No local agent is running this DNA so invalid actions can't be warranted");
+            return;
+        }
+    };
+
+    // Several ops of the same action may have been rejected.
+    let mut warranted = HashSet::new();
+    for InvalidAction {
+        action,
+        validation_type,
+        reason,
+    } in invalid
+    {
+        if !warranted.insert(ActionHash::with_data_sync(&action.0)) {
+            continue;
+        }
+        let warrant = Warrant {
+            proof: WarrantProof::InvalidAction {
+                action,
+                validation_type,
+                reason,
+            },
+            author: issuer.clone(),
+            timestamp: Timestamp::now(),
+        };
+        let signature = match issuer.sign(conductor.keystore(), &warrant).await {
+            Ok(signature) => signature,
+            Err(e) => {
+                warn!(?e, "Failed to sign warrant");
+                continue;
+            }
+        };
+        let warrant = SignedWarrant::new(warrant, signature);
+        let warrantee = warrant.warrantee().clone();
+        warn!(
+            %warrantee,
+            ?validation_type,
+            "Issuing a warrant against an agent who authored invalid data",
+        );
+
+        let stored = dht_db
+            .async_commit({
+                let warrant = warrant.clone();
+                move |txn| insert_warrant(txn, &warrant)
+            })
+            .await;
+        if let Err(e) = stored {
+            warn!(?e, "Failed to store warrant");
+        }
+        if let Err(e) = network.block_agent(warrantee).await {
+            warn!(?e, "Failed to block warranted agent");
+        }
+        // The warrantee's authorities may well be unreachable right now,
+        // and gossiping warrants is not supported yet, so a failed publish
+        // is only logged.
+        if let Err(e) = network.publish_warrant(warrant, None).await {
+            info!(?e, "Failed to publish warrant");
+        }
+    }
+}
+
+/// Check that a warrant was signed by its issuer and that the action it
+/// holds as proof was signed by the warrantee.
+pub async fn verify_warrant(warrant: &SignedWarrant) -> bool {
+    let WarrantProof::InvalidAction { action, .. } = &warrant.proof;
+    warrant
+        .author
+        .verify_signature(&warrant.signature, warrant.warrant.clone())
+        .await
+        && action
+            .0
+            .author()
+            .verify_signature(&action.1, &action.0)
+            .await
+}
+
+/// Validate the action held as proof by a warrant, as if its ops had just
+/// been published to us.
+///
+/// The proof is [`ProofCheck::Invalid`] if sys or app validation rejects any
+/// of its ops in a way which would have earned the author a warrant here.
+pub async fn check_warrant_proof(
+    warrant: &SignedWarrant,
+    space: &Space,
+    conductor: &dyn ConductorHandleT,
+    network: HolochainP2pDna,
+) -> WorkflowResult<ProofCheck> {
+    let WarrantProof::InvalidAction { action, .. } = &warrant.proof;
+    let dna_def = match conductor.get_dna_def(&space.dna_hash) {
+        Some(dna_def) => Arc::new(dna_def),
+        None => return Ok(ProofCheck::Undecided),
+    };
+
+    // Private entries are never published, so only public ones are needed.
+    let entry = match action.0.entry_data() {
+        Some((entry_hash, entry_type)) if *entry_type.visibility() == EntryVisibility::Public => {
+            let mut cascade = Cascade::empty()
+                .with_authored(space.authored_db.clone().into())
+                .with_dht(space.dht_db.clone().into())
+                .with_network(network.clone(), space.cache_db.clone());
+            match cascade
+                .retrieve_entry(entry_hash.clone(), Default::default())
+                .await?
+            {
+                Some(entry) => Some(entry.into_content()),
+                None => return Ok(ProofCheck::Undecided),
+            }
+        }
+        _ => None,
+    };
+    let ops: Vec<_> = action_to_op_types(&action.0)
+        .into_iter()
+        .filter_map(|op_type| DhtOp::from_type(op_type, action.clone(), entry.clone()).ok())
+        .collect();
+
+    let sys_workspace = SysValidationWorkspace::new(
+        space.authored_db.clone().into(),
+        space.dht_db.clone().into(),
+        space.dht_query_cache.clone(),
+        space.cache_db.clone(),
+        dna_def.clone(),
+    )
+    .with_rate_bucket_cache(space.rate_bucket_cache.clone());
+    let mut undecided = false;
+    for op in &ops {
+        match sys_validation_workflow::check_warranted_op(
+            op,
+            &sys_workspace,
+            network.clone(),
+            conductor,
+        )
+        .await?
+        {
+            ProofCheck::Invalid => return Ok(ProofCheck::Invalid),
+            ProofCheck::Undecided => undecided = true,
+            ProofCheck::Valid => (),
+        }
+    }
+    // Ops only reach app validation once they pass sys validation.
+    if undecided {
+        return Ok(ProofCheck::Undecided);
+    }
+
+    let app_workspace = AppValidationWorkspace::new(
+        space.authored_db.clone().into(),
+        space.dht_db.clone(),
+        space.dht_query_cache.clone(),
+        space.cache_db.clone(),
+        conductor.keystore().clone(),
+        dna_def,
+    );
+    for op in ops {
+        match app_validation_workflow::check_warranted_op(op, &app_workspace, &network, conductor)
+            .await?
+        {
+            ProofCheck::Invalid => return Ok(ProofCheck::Invalid),
+            ProofCheck::Undecided => undecided = true,
+            ProofCheck::Valid => (),
+        }
+    }
+    Ok(if undecided {
+        ProofCheck::Undecided
+    } else {
+        ProofCheck::Valid
+    })
+}
+
+/// Block every agent with a warrant stored in this DHT database,
+/// so that the block list survives a restart.
+pub async fn restore_blocks(
+    dht_db: &DbWrite<DbKindDht>,
+    network: &(dyn HolochainP2pDnaT + Send + Sync),
+) -> StateQueryResult<()> {
+    let warrantees = dht_db
+        .async_reader(|txn| holochain_state::warrant::get_warrantees(&txn))
+        .await?;
+    for warrantee in warrantees {
+        if let Err(e) = network.block_agent(warrantee).await {
+            warn!(?e, "Failed to block warranted agent");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::sys_validate::MAX_TAG_SIZE;
+    use crate::sweettest::*;
+    use crate::test_utils::consistency_10s;
+    use ::fixt::prelude::*;
+    use holochain_keystore::MetaLairClient;
+    use holochain_p2p::actor::HolochainP2pRefToDna;
+    use holochain_state::test_utils::test_keystore;
+    use holochain_state::warrant::get_warrants_for_agent;
+    use holochain_wasm_test_utils::TestWasm;
+
+    async fn warrant_for(
+        keystore: &MetaLairClient,
+        issuer: &AgentPubKey,
+        action: SignedAction,
+    ) -> SignedWarrant {
+        let warrant = Warrant {
+            proof: WarrantProof::InvalidAction {
+                action,
+                validation_type: ValidationType::Sys,
+                reason: "invalid".into(),
+            },
+            author: issuer.clone(),
+            timestamp: Timestamp::now(),
+        };
+        let signature = issuer.sign(keystore, &warrant).await.unwrap();
+        SignedWarrant::new(warrant, signature)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn warrants_are_only_kept_when_the_proof_is_invalid() {
+        let (dna, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+            .await
+            .unwrap();
+        let mut conductor = SweetConductor::from_standard_config().await;
+        let keystore = conductor.keystore();
+        let alice = keystore.new_sign_keypair_random().await.unwrap();
+        let issuer = keystore.new_sign_keypair_random().await.unwrap();
+        let (cell,) = conductor
+            .setup_app_for_agent("app", alice.clone(), &[dna])
+            .await
+            .unwrap()
+            .into_tuple();
+        consistency_10s(&[&cell]).await;
+
+        let handle = conductor.inner_handle();
+        let dna_hash = cell.dna_hash().clone();
+        let network = handle.holochain_p2p().to_dna(dna_hash.clone());
+        let head = cell
+            .authored_db()
+            .async_reader(|txn| -> StateQueryResult<SignedAction> {
+                from_blob(txn.query_row(
+                    "SELECT blob FROM Action ORDER BY seq DESC LIMIT 1",
+                    [],
+                    |row| row.get("blob"),
+                )?)
+            })
+            .await
+            .unwrap();
+
+        // Alice's genesis actions are valid, so a warrant against them is false.
+        let false_warrant = warrant_for(&keystore, &issuer, head.clone()).await;
+        let warrantee = conductor
+            .spaces
+            .handle_publish_warrant(&dna_hash, false_warrant, handle.as_ref(), network.clone())
+            .await
+            .unwrap();
+        assert_eq!(warrantee, None);
+
+        // A link tag this large is rejected by every authority.
+        let link = Action::CreateLink(CreateLink {
+            author: alice.clone(),
+            timestamp: Timestamp::now(),
+            action_seq: head.0.action_seq() + 1,
+            prev_action: ActionHash::with_data_sync(&head.0),
+            base_address: alice.clone().into(),
+            target_address: alice.clone().into(),
+            zome_id: 0.into(),
+            link_type: 0.into(),
+            tag: LinkTag::new(vec![0; MAX_TAG_SIZE]),
+            weight: RateWeight::default(),
+        });
+        let signature = alice.sign(&keystore, &link).await.unwrap();
+        let true_warrant = warrant_for(&keystore, &issuer, SignedAction(link, signature)).await;
+        let warrantee = conductor
+            .spaces
+            .handle_publish_warrant(&dna_hash, true_warrant.clone(), handle.as_ref(), network)
+            .await
+            .unwrap();
+        assert_eq!(warrantee, Some(alice.clone()));
+
+        let warrants = cell
+            .dht_db()
+            .async_reader(move |txn| get_warrants_for_agent(&txn, &alice))
+            .await
+            .unwrap();
+        assert_eq!(warrants, vec![true_warrant]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_warrant_checks_both_signatures() {
+        let keystore = test_keystore();
+        let issuer = keystore.new_sign_keypair_random().await.unwrap();
+        let warrantee = keystore.new_sign_keypair_random().await.unwrap();
+
+        let mut create = fixt!(Create);
+        create.author = warrantee.clone();
+        let action = Action::Create(create);
+        let action_signature = warrantee.sign(&keystore, &action).await.unwrap();
+
+        let warrant = Warrant {
+            proof: WarrantProof::InvalidAction {
+                action: SignedAction(action.clone(), action_signature.clone()),
+                validation_type: ValidationType::App,
+                reason: "invalid".into(),
+            },
+            author: issuer.clone(),
+            timestamp: Timestamp::now(),
+        };
+        let signature = issuer.sign(&keystore, &warrant).await.unwrap();
+        let signed = SignedWarrant::new(warrant.clone(), signature.clone());
+        assert!(verify_warrant(&signed).await);
+
+        // The issuer's signature must match.
+        let forged = SignedWarrant::new(warrant, action_signature);
+        assert!(!verify_warrant(&forged).await);
+
+        // The proof must be signed by the warrantee.
+        let framed = Warrant {
+            proof: WarrantProof::InvalidAction {
+                action: SignedAction(action, Signature([0; 64])),
+                validation_type: ValidationType::App,
+                reason: "invalid".into(),
+            },
+            author: issuer.clone(),
+            timestamp: Timestamp::now(),
+        };
+        let signature = issuer.sign(&keystore, &framed).await.unwrap();
+        assert!(!verify_warrant(&SignedWarrant::new(framed, signature)).await);
+    }
+}
@@ -5,14 +5,19 @@ use std::sync::Arc;
 
 use super::error::WorkflowResult;
 use super::sys_validation_workflow::validation_query;
+use crate::conductor::handle::ConductorHandleT;
 use crate::conductor::ConductorHandle;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::queue_consumer::WorkComplete;
 use crate::core::ribosome::guest_callback::validate::ValidateHostAccess;
 use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::ValidateResult;
+use crate::core::ribosome::guest_callback::weigh::weigh_action;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomesToInvoke;
+use crate::core::warrant::issue_warrants;
+use crate::core::warrant::InvalidAction;
+use crate::core::warrant::ProofCheck;
 use error::AppValidationResult;
 pub use error::*;
 use futures::stream::StreamExt;
@@ -97,6 +102,8 @@ async fn app_validation_workflow_inner(
     let iter = sorted_ops.into_iter().map({
         let network = network.clone();
         let workspace = workspace.clone();
+        let conductor_handle = conductor_handle.clone();
+        let dna_hash = dna_hash.clone();
         move |so| {
             let network = network.clone();
             let conductor_handle = conductor_handle.clone();
@@ -108,6 +115,7 @@ async fn app_validation_workflow_inner(
                 let action = op.action();
                 let dependency = get_dependency(op_type, &action);
                 let op_light = op.to_light();
+                let signed_action = SignedAction(action.clone(), op.signature().clone());
 
                 // If this is agent activity, track it for the cache.
                 let activity = matches!(op_type, DhtOpType::RegisterAgentActivity).then(|| {
@@ -127,7 +135,7 @@ async fn app_validation_workflow_inner(
                     }
                     Err(e) => Err(e),
                 };
-                (op_hash, dependency, op_light, r, activity)
+                (op_hash, dependency, op_light, signed_action, r, activity)
             }
         }
     });
@@ -165,15 +173,17 @@ async fn app_validation_workflow_inner(
             "Committing {} ops",
             chunk.iter().map(|c| c.len()).sum::<usize>()
         );
-        let (t, a, r, activity) = workspace
+        let (t, a, r, activity, invalid) = workspace
             .dht_db
             .async_commit(move |txn| {
                 let mut total = 0;
                 let mut awaiting = 0;
                 let mut rejected = 0;
                 let mut agent_activity = Vec::new();
+                let mut invalid = Vec::new();
                 for outcome in chunk.into_iter().flatten() {
-                    let (op_hash, dependency, op_light, outcome, activity) = outcome;
+                    let (op_hash, dependency, op_light, signed_action, outcome, activity) =
+                        outcome;
                     // Get the outcome or return the error
                     let outcome = outcome.or_else(|outcome_or_err| outcome_or_err.try_into())?;
 
@@ -206,9 +216,14 @@ async fn app_validation_workflow_inner(
                             let status = ValidationLimboStatus::AwaitingAppDeps(deps);
                             put_validation_limbo(txn, &op_hash, status)?;
                         }
-                        Outcome::Rejected(_) => {
+                        Outcome::Rejected(reason) => {
                             rejected += 1;
-                            tracing::warn!("Received invalid op! Warrants aren't implemented yet, so we can't do anything about this right now, but be warned that somebody on the network has maliciously hacked their node.\nOp: {:?}", op_light);
+                            tracing::warn!("Received invalid op, a warrant will be issued against its author.\nOp: {:?}", op_light);
+                            invalid.push(InvalidAction {
+                                action: signed_action,
+                                validation_type: ValidationType::App,
+                                reason,
+                            });
                             if let Dependency::Null = dependency {
                                 put_integrated(txn, &op_hash, ValidationStatus::Rejected)?;
                             } else {
@@ -217,9 +232,17 @@ async fn app_validation_workflow_inner(
                         }
                     }
                 }
-                WorkflowResult::Ok((total, awaiting, rejected, agent_activity))
+                WorkflowResult::Ok((total, awaiting, rejected, agent_activity, invalid))
             })
             .await?;
+        issue_warrants(
+            &dna_hash,
+            conductor_handle.as_ref(),
+            &workspace.dht_db,
+            network,
+            invalid,
+        )
+        .await;
         // Once the database transaction is committed, add agent activity to the cache
         // that is ready for integration.
         for (author, seq, has_no_dependency) in activity {
@@ -407,6 +430,33 @@ async fn dhtop_to_op(op: DhtOp, cascade: &mut Cascade) -> AppValidationOutcome<O
     Ok(op)
 }
 
+/// App validate an op which a warrant holds up as proof of invalid data.
+pub(crate) async fn check_warranted_op(
+    op: DhtOp,
+    workspace: &AppValidationWorkspace,
+    network: &HolochainP2pDna,
+    conductor_handle: &dyn ConductorHandleT,
+) -> WorkflowResult<ProofCheck> {
+    let dna_hash = network.dna_hash();
+    let mut cascade = workspace.full_cascade(network.clone());
+    let outcome = match dhtop_to_op(op, &mut cascade).await {
+        Ok(op) => {
+            let ribosome = conductor_handle
+                .get_ribosome(&dna_hash)
+                .map_err(|_| AppValidationError::DnaMissing(dna_hash))?;
+            let host_fn_workspace = workspace.validation_workspace().await?;
+            validate_op(&op, host_fn_workspace, network, &ribosome).await
+        }
+        Err(e) => Err(e),
+    };
+    let outcome = outcome.or_else(|outcome_or_err| outcome_or_err.try_into())?;
+    Ok(match outcome {
+        Outcome::Accepted => ProofCheck::Valid,
+        Outcome::Rejected(_) => ProofCheck::Invalid,
+        Outcome::AwaitingDeps(_) => ProofCheck::Undecided,
+    })
+}
+
 async fn validate_op_outer(
     dna_hash: Arc<DnaHash>,
     op: &Op,
@@ -470,6 +520,10 @@ where
         } => create_link_zomes_to_invoke(action, ribosome)?,
     };
 
+    if let Some(reason) = check_weight(op, ribosome)? {
+        return Ok(Outcome::Rejected(reason));
+    }
+
     let invocation = ValidateInvocation::new(zomes_to_invoke, op)
         .map_err(|e| AppValidationError::RibosomeError(e.into()))?;
     let outcome = run_validation_callback_inner(
@@ -484,6 +538,61 @@ where
     Ok(outcome)
 }
 
+/// Re-run the `weigh` callback for the action in this op and compare the
+/// result with the weight the author claimed, so that authors can't dodge
+/// rate limits by under-weighing their actions.
+/// Returns the reason for rejection if the weights differ.
+fn check_weight<R: RibosomeT>(op: &Op, ribosome: &R) -> AppValidationResult<Option<String>> {
+    fn app_zome_id(entry_type: &EntryType) -> Option<ZomeId> {
+        match entry_type {
+            EntryType::App(app_entry_type) => Some(app_entry_type.zome_id()),
+            _ => None,
+        }
+    }
+
+    let (claimed, zome_id, input) = match op {
+        Op::StoreRecord { record } => match (record.action(), record.entry().as_option()) {
+            // Countersigning parties agree on weights without weighing.
+            (_, Some(Entry::CounterSign(_, _))) => return Ok(None),
+            (Action::Create(create), Some(entry)) => (
+                create.weight.clone().into(),
+                app_zome_id(&create.entry_type),
+                WeighInput::Create(create.clone().unweighed(), entry.clone()),
+            ),
+            (Action::Update(update), Some(entry)) => (
+                update.weight.clone().into(),
+                app_zome_id(&update.entry_type),
+                WeighInput::Update(update.clone().unweighed(), entry.clone()),
+            ),
+            (Action::CreateLink(create_link), _) => (
+                create_link.weight.clone(),
+                Some(create_link.zome_id),
+                WeighInput::Link(create_link.clone().unweighed()),
+            ),
+            _ => return Ok(None),
+        },
+        Op::RegisterDelete {
+            delete,
+            original_action,
+            ..
+        } => (
+            delete.hashed.content.weight.clone(),
+            app_zome_id(original_action.entry_type()),
+            WeighInput::Delete(delete.hashed.content.clone().unweighed()),
+        ),
+        _ => return Ok(None),
+    };
+    let weight: RateWeight = weigh_action(ribosome, zome_id, input)?;
+    if weight == claimed {
+        Ok(None)
+    } else {
+        Ok(Some(format!(
+            "Action claimed rate weight {:?} but weighs {:?}",
+            claimed, weight
+        )))
+    }
+}
+
 pub fn entry_creation_zomes_to_invoke(
     action: &EntryCreationAction,
     ribosome: &impl RibosomeT,
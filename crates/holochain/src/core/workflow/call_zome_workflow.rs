@@ -13,6 +13,7 @@ use crate::core::ribosome::guest_callback::post_commit::send_post_commit;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::ribosome::ZomeCallTiming;
 use crate::core::workflow::error::WorkflowError;
 use holochain_keystore::MetaLairClient;
 use holochain_p2p::HolochainP2pDna;
@@ -22,6 +23,7 @@ use holochain_state::source_chain::SourceChainError;
 use holochain_zome_types::record::Record;
 
 use holochain_types::prelude::*;
+use std::sync::Arc;
 use tracing::instrument;
 
 #[cfg(test)]
@@ -37,6 +39,8 @@ pub struct CallZomeWorkflowArgs<RibosomeT> {
     pub conductor_handle: ConductorHandle,
     pub is_root_zome_call: bool,
     pub cell_id: CellId,
+    /// The cell's rate limiter, if it has one
+    pub rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 #[instrument(skip(
@@ -65,6 +69,14 @@ where
         .ok();
     let should_write = args.is_root_zome_call;
     let conductor_handle = args.conductor_handle.clone();
+    let rate_limiter = args.rate_limiter.clone();
+    // Only calls from outside the cell count towards its limit,
+    // since calls between its own zomes are part of the same work.
+    if should_write {
+        if let Some(rate_limiter) = &rate_limiter {
+            rate_limiter.check_zome_call()?;
+        }
+    }
     let result =
         call_zome_workflow_inner(workspace.clone(), network.clone(), keystore.clone(), args)
             .await?;
@@ -73,6 +85,17 @@ where
 
     // commit the workspace
     if should_write {
+        if let Some(rate_limiter) = &rate_limiter {
+            let bytes = workspace
+                .source_chain()
+                .scratch_records()?
+                .iter()
+                .map(|record| holochain_serialized_bytes::encode(record).map(|b| b.len() as u64))
+                .sum::<Result<u64, _>>()?;
+            if bytes > 0 {
+                rate_limiter.check_bytes_committed(bytes)?;
+            }
+        }
         let is_empty = workspace.source_chain().is_empty()?;
         let countersigning_op = workspace.source_chain().countersigning_op()?;
         let flushed_actions: Vec<SignedActionHashed> = HostFnWorkspace::from(workspace.clone())
@@ -139,6 +162,7 @@ where
         network.clone(),
         signal_tx,
         call_zome_handle,
+        ZomeCallTiming::from_config(conductor_handle.get_config()),
     );
     let (ribosome, result) =
         call_zome_function_authorized(ribosome, host_access, invocation).await?;
@@ -186,12 +210,25 @@ pub async fn call_zome_function_authorized<R>(
 where
     R: RibosomeT + 'static,
 {
-    if invocation.is_authorized(&host_access).await? {
-        tokio::task::spawn_blocking(|| {
+    if let Some(authorization) = invocation.authorization(&host_access).await? {
+        let host_access = ZomeCallHostAccess {
+            authorization: Some(authorization),
+            ..host_access
+        };
+        let deadline = host_access.timing.deadline;
+        let call = tokio::task::spawn_blocking(|| {
             let r = ribosome.call_zome_function(host_access, invocation);
             Ok((ribosome, r))
-        })
-        .await?
+        });
+        match deadline {
+            // Nothing the call wrote is committed once it has timed out.
+            // The wasm is stopped at its next host call, and until then
+            // keeps its blocking thread.
+            Some(deadline) => tokio::time::timeout_at(deadline, call)
+                .await
+                .map_err(|_| WorkflowError::ZomeCallTimeout)??,
+            None => call.await?,
+        }
     } else {
         Ok((
             ribosome,
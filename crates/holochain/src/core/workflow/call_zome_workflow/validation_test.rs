@@ -29,6 +29,7 @@ async fn direct_validation_test() {
             origin_time: Timestamp::HOLOCHAIN_EPOCH,
             integrity_zomes: vec![TestZomes::from(TestWasm::Update).integrity.into_inner()],
             coordinator_zomes: vec![TestZomes::from(TestWasm::Update).coordinator.into_inner()],
+            rate_limits: Default::default(),
         },
         [integrity, coordinator],
     )
@@ -5,10 +5,14 @@ use holo_hash::{ActionHash, AgentPubKey, DhtOpHash};
 use holo_hash::{AnyDhtHash, EntryHash};
 use holochain_keystore::AgentPubKeyExt;
 use holochain_p2p::{HolochainP2pDna, HolochainP2pDnaT};
+use holochain_state::countersigning::{
+    pending_countersigning_session_ops, CountersigningSessionOp,
+};
 use holochain_state::integrate::authored_ops_to_dht_db;
 use holochain_state::mutations;
 use holochain_state::prelude::{
-    current_countersigning_session, SourceChainResult, StateMutationResult, Store,
+    current_countersigning_session, DbKindAuthored, DbWrite, SourceChainResult,
+    StateMutationResult, Store,
 };
 use holochain_types::dht_op::DhtOp;
 use holochain_types::signal::{Signal, SystemSignal};
@@ -29,6 +33,8 @@ use super::{error::WorkflowResult, incoming_dht_ops_workflow::incoming_dht_ops_w
 #[derive(Clone)]
 /// A cheaply clonable, thread safe and in-memory store for
 /// active countersigning sessions.
+/// Each session's ops are also kept in the authored database,
+/// so sessions survive a restart.
 pub struct CountersigningWorkspace {
     inner: Share<CountersigningWorkspaceInner>,
 }
@@ -54,12 +60,13 @@ struct Session {
 // TODO: PERF: This takes a lock on the workspace which could
 // block other incoming DhtOps if there are many active sessions.
 // We could create an incoming buffer if this actually becomes an issue.
-pub(crate) fn incoming_countersigning(
+pub(crate) async fn incoming_countersigning(
     ops: Vec<(DhtOpHash, DhtOp)>,
     workspace: &CountersigningWorkspace,
+    authored_db: &DbWrite<DbKindAuthored>,
     trigger: TriggerSender,
 ) -> WorkflowResult<()> {
-    let mut should_trigger = false;
+    let mut to_put = Vec::new();
 
     // For each op check it's the right type and extract the
     // entry hash, required actions and expires time.
@@ -87,10 +94,7 @@ pub(crate) fn incoming_countersigning(
 
                     // Check if already timed out.
                     if holochain_zome_types::Timestamp::now() < expires {
-                        // Put this op in the pending map.
-                        workspace.put(entry_hash, hash, op, required_actions, expires);
-                        // We have new ops so we should trigger the workflow.
-                        should_trigger = true;
+                        to_put.push((entry_hash, hash, op, required_actions, expires));
                     }
                 }
             }
@@ -98,7 +102,27 @@ pub(crate) fn incoming_countersigning(
     }
 
     // Trigger the workflow if we have new ops.
-    if should_trigger {
+    if !to_put.is_empty() {
+        // Persist the ops before putting them in the pending map,
+        // so the session can be picked up again after a restart.
+        let to_put = authored_db
+            .async_commit(move |txn| {
+                for (entry_hash, hash, op, required_actions, expires) in &to_put {
+                    mutations::insert_countersigning_session_op(
+                        txn,
+                        entry_hash,
+                        hash,
+                        op,
+                        required_actions,
+                        *expires,
+                    )?;
+                }
+                StateMutationResult::Ok(to_put)
+            })
+            .await?;
+        for (entry_hash, hash, op, required_actions, expires) in to_put {
+            workspace.put(entry_hash, hash, op, required_actions, expires);
+        }
         trigger.trigger(&"incoming_countersigning");
     }
     Ok(())
@@ -116,7 +140,8 @@ pub(crate) async fn countersigning_workflow(
     let mut notify_agents = Vec::with_capacity(complete_sessions.len());
 
     // For each complete session send the ops to validation.
-    for (agents, ops, actions) in complete_sessions {
+    let mut complete_entry_hashes = Vec::with_capacity(complete_sessions.len());
+    for (entry_hash, agents, ops, actions) in complete_sessions {
         let non_enzymatic_ops: Vec<_> = ops
             .into_iter()
             .filter(|(_hash, dht_op)| dht_op.enzymatic_countersigning_enzyme().is_none())
@@ -131,8 +156,22 @@ pub(crate) async fn countersigning_workflow(
             .await?;
         }
         notify_agents.push((agents, actions));
+        complete_entry_hashes.push(entry_hash);
     }
 
+    // The complete sessions have been validated and the expired ones
+    // can't complete, so neither needs to be kept any more.
+    let now = Timestamp::now();
+    space
+        .authored_db
+        .async_commit(move |txn| {
+            for entry_hash in &complete_entry_hashes {
+                mutations::delete_countersigning_session(txn, entry_hash)?;
+            }
+            mutations::delete_expired_countersigning_sessions(txn, now)
+        })
+        .await?;
+
     // For each complete session notify the agents of success.
     for (agents, actions) in notify_agents {
         if let Err(e) = network
@@ -339,6 +378,7 @@ pub async fn countersigning_publish(
     Ok(())
 }
 
+type CountersigningSessionOps = Vec<CountersigningSessionOp>;
 type AgentsToNotify = Vec<AgentPubKey>;
 type Ops = Vec<(DhtOpHash, DhtOp)>;
 type SignedActions = Vec<SignedAction>;
@@ -351,6 +391,35 @@ impl CountersigningWorkspace {
         }
     }
 
+    /// Put the pending sessions kept in the authored database into the
+    /// workspace, e.g. after a restart.
+    /// Returns whether there were any.
+    pub async fn load_pending(
+        &self,
+        authored_db: &DbWrite<DbKindAuthored>,
+    ) -> WorkflowResult<bool> {
+        let now = Timestamp::now();
+        let pending: CountersigningSessionOps = authored_db
+            .async_reader(move |txn| pending_countersigning_session_ops(&txn, now))
+            .await?;
+        let any_pending = !pending.is_empty();
+        for (entry_hash, op_hash, op, required_actions, expires) in pending {
+            self.put(entry_hash, op_hash, op, required_actions, expires);
+        }
+        Ok(any_pending)
+    }
+
+    /// Stop collecting the ops of a session.
+    pub fn remove(&self, entry_hash: &EntryHash) {
+        self.inner
+            .share_mut(|i, _| {
+                i.pending.remove(entry_hash);
+                Ok(())
+            })
+            // We don't close this share so we can ignore this error.
+            .ok();
+    }
+
     /// Put a single signers store entry op in the workspace.
     fn put(
         &self,
@@ -380,7 +449,7 @@ impl CountersigningWorkspace {
             .ok();
     }
 
-    fn get_complete_sessions(&self) -> Vec<(AgentsToNotify, Ops, SignedActions)> {
+    fn get_complete_sessions(&self) -> Vec<(EntryHash, AgentsToNotify, Ops, SignedActions)> {
         let now = holochain_zome_types::Timestamp::now();
         self.inner
             .share_mut(|i, _| {
@@ -416,7 +485,7 @@ impl CountersigningWorkspace {
                 for hash in complete {
                     if let Some(session) = i.pending.remove(&hash) {
                         let map = session.map;
-                        let r = map.into_iter().fold(
+                        let (agents, ops, actions) = map.into_iter().fold(
                             (Vec::new(), Vec::new(), Vec::new()),
                             |(mut agents, mut ops, mut actions), (_, (op_hash, op, _))| {
                                 let action = op.action();
@@ -430,7 +499,7 @@ impl CountersigningWorkspace {
                                 (agents, ops, actions)
                             },
                         );
-                        ret.push(r);
+                        ret.push((hash, agents, ops, actions));
                     }
                 }
                 Ok(ret)
@@ -534,4 +603,55 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    /// Test that the pending sessions kept in the authored database
+    /// are put back in a new workspace, as happens after a restart.
+    async fn load_pending_rehydrates_sessions() {
+        let mut u = arbitrary::Unstructured::new(&holochain_zome_types::NOISE);
+        let test_db = holochain_state::test_utils::test_authored_db();
+        let db = test_db.to_db();
+
+        // - Keep a session which expires in an hour in the database.
+        let op_hash = DhtOpHash::arbitrary(&mut u).unwrap();
+        let op = DhtOp::arbitrary(&mut u).unwrap();
+        let entry_hash = EntryHash::arbitrary(&mut u).unwrap();
+        let required_actions = vec![ActionHash::with_data_sync(&op.action())];
+        let expires = (Timestamp::now() + std::time::Duration::from_secs(60 * 60)).unwrap();
+        db.test_commit(|txn| {
+            holochain_state::mutations::insert_countersigning_session_op(
+                txn,
+                &entry_hash,
+                &op_hash,
+                &op,
+                &required_actions,
+                expires,
+            )
+            .unwrap();
+        });
+
+        // - Load it into a fresh workspace.
+        let workspace = CountersigningWorkspace::new();
+        assert!(workspace.load_pending(&db).await.unwrap());
+        workspace
+            .inner
+            .share_mut(|i, _| {
+                assert!(i.pending.contains_key(&entry_hash));
+                Ok(())
+            })
+            .unwrap();
+
+        // - Expect the session to complete with its op.
+        let r = workspace.get_complete_sessions();
+        assert_eq!(r.len(), 1);
+        assert_eq!(r[0].0, entry_hash);
+        assert_eq!(r[0].2, vec![(op_hash, op)]);
+
+        // - Expect an empty database to load nothing.
+        let empty_db = holochain_state::test_utils::test_authored_db();
+        assert!(!CountersigningWorkspace::new()
+            .load_pending(&empty_db.to_db())
+            .await
+            .unwrap());
+    }
 }
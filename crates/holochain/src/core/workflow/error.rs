@@ -27,6 +27,12 @@ pub enum WorkflowError {
     #[error("Agent is invalid: {0:?}")]
     AgentInvalid(AgentPubKey),
 
+    #[error(transparent)]
+    RateLimited(#[from] RateLimitError),
+
+    #[error("The zome call timed out")]
+    ZomeCallTimeout,
+
     #[error("Conductor API error: {0}")]
     ConductorApi(#[from] Box<ConductorApiError>),
 
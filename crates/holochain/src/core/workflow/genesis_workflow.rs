@@ -96,14 +96,16 @@ where
         return Err(WorkflowError::GenesisFailure(reason));
     }
 
-    // NB: this is just a placeholder for a real DPKI request to show intent
-    if api
-        .dpki_request("is_agent_pubkey_valid".into(), agent_pubkey.to_string())
+    // Don't create a source chain for a key which DPKI says is no longer valid.
+    // Keys DPKI has no record of are left to the DPKI app to register.
+    if let Some(key_state) = api
+        .dpki_key_state(agent_pubkey.clone())
         .await
-        .expect("TODO: actually implement this")
-        == "INVALID"
+        .map_err(Box::new)?
     {
-        return Err(WorkflowError::AgentInvalid(agent_pubkey.clone()));
+        if key_state != KeyState::NotFound && !key_state.is_valid_at(Timestamp::now()) {
+            return Err(WorkflowError::AgentInvalid(agent_pubkey.clone()));
+        }
     }
 
     source_chain::genesis(
@@ -187,9 +189,8 @@ pub mod tests {
         {
             let workspace = GenesisWorkspace::new(vault.clone().into(), dht_db.to_db()).unwrap();
             let mut api = MockCellConductorApiT::new();
-            api.expect_dpki_request().returning(|_, _| {
-                async move { Ok("mocked dpki request response".to_string()) }.boxed()
-            });
+            api.expect_dpki_key_state()
+                .returning(|_| async move { Ok(None) }.boxed());
             api.expect_keystore().return_const(keystore.clone());
             let mut ribosome = MockRibosomeT::new();
             ribosome
@@ -10,6 +10,9 @@ use crate::core::queue_consumer::WorkComplete;
 use crate::core::sys_validate::check_and_hold_store_record;
 use crate::core::sys_validate::*;
 use crate::core::validation::*;
+use crate::core::warrant::issue_warrants;
+use crate::core::warrant::InvalidAction;
+use crate::core::warrant::ProofCheck;
 use error::WorkflowResult;
 use holo_hash::DhtOpHash;
 use holochain_cascade::Cascade;
@@ -25,6 +28,7 @@ use holochain_types::prelude::*;
 use holochain_zome_types::Entry;
 use holochain_zome_types::ValidationStatus;
 use rusqlite::Transaction;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::Arc;
 use tracing::*;
@@ -84,15 +88,26 @@ async fn sys_validation_workflow_inner(
     sys_validation_trigger: TriggerSender,
 ) -> WorkflowResult<WorkComplete> {
     let db = workspace.dht_db.clone();
-    let sorted_ops = validation_query::get_ops_to_sys_validate(&db).await?;
+    let mut sorted_ops = validation_query::get_ops_to_sys_validate(&db).await?;
+    // Ops waiting for their author's key state are only retried once due.
+    let key_state_retries = space.key_state_retries.clone();
+    sorted_ops.retain(|op| key_state_retries.is_due(op.as_hash()));
+    let retry_trigger = sys_validation_trigger.clone();
     let start_len = sorted_ops.len();
     tracing::debug!("Validating {} ops", start_len);
     let start = (start_len >= NUM_CONCURRENT_OPS).then(std::time::Instant::now);
     let saturated = start.is_some();
 
+    // Look up which key state sources apply, and each author's key state,
+    // once for all the ops.
+    let key_check =
+        Arc::new(KeyStateCheck::for_dna(&space.dna_hash, conductor_handle.as_ref()).await);
+
     // Process each op
     let iter = sorted_ops.into_iter().map({
         let space = space.clone();
+        let network = network.clone();
+        let conductor_handle = conductor_handle.clone();
         move |so| {
             // Create an incoming ops sender for any dependencies we find
             // that we are meant to be holding but aren't.
@@ -102,22 +117,25 @@ async fn sys_validation_workflow_inner(
             let network = network.clone();
             let workspace = workspace.clone();
             let conductor_handle = conductor_handle.clone();
+            let key_check = key_check.clone();
             async move {
                 let (op, op_hash) = so.into_inner();
                 let op_type = op.get_type();
                 let action = op.action();
 
                 let dependency = get_dependency(op_type, &action);
+                let signed_action = SignedAction(action, op.signature().clone());
 
                 let r = validate_op(
                     &op,
                     &(*workspace),
                     network,
+                    &key_check,
                     conductor_handle.as_ref(),
                     Some(incoming_dht_ops_sender),
                 )
                 .await;
-                r.map(|o| (op_hash, o, dependency))
+                r.map(|o| (op_hash, o, dependency, signed_action))
             }
         }
     });
@@ -154,15 +172,22 @@ async fn sys_validation_workflow_inner(
     while let Some(chunk) = iter.next().await {
         let num_ops: usize = chunk.iter().map(|c| c.len()).sum();
         tracing::debug!("Committing {} ops", num_ops);
-        let (t, a, m, r) = space
+        let key_state_retries = key_state_retries.clone();
+        let (t, a, m, r, invalid) = space
             .dht_db
             .async_commit(move |txn| {
                 let mut total = 0;
                 let mut awaiting = 0;
                 let mut missing = 0;
                 let mut rejected = 0;
+                let mut invalid = Vec::new();
                 for outcome in chunk.into_iter().flatten() {
-                    let (op_hash, outcome, dependency) = outcome?;
+                    let (op_hash, outcome, dependency, signed_action) = outcome?;
+                    if let Outcome::AwaitingKeyState = outcome {
+                        key_state_retries.record_attempt(&op_hash);
+                    } else {
+                        key_state_retries.clear(&op_hash);
+                    }
                     match outcome {
                         Outcome::Accepted => {
                             total += 1;
@@ -191,8 +216,33 @@ async fn sys_validation_workflow_inner(
                             // TODO: Not sure what missing dht dep is. Check if we need this.
                             put_validation_limbo(txn, &op_hash, ValidationLimboStatus::Pending)?;
                         }
-                        Outcome::Rejected => {
+                        Outcome::AwaitingKeyState => {
+                            awaiting += 1;
+                            // Validated again from scratch on a later run once
+                            // its retry is due, by which time the key's data
+                            // may have arrived.
+                            put_validation_limbo(txn, &op_hash, ValidationLimboStatus::Pending)?;
+                        }
+                        Outcome::Rejected(reason) => {
+                            rejected += 1;
+                            invalid.push(InvalidAction {
+                                action: signed_action,
+                                validation_type: ValidationType::Sys,
+                                reason,
+                            });
+                            if let Dependency::Null = dependency {
+                                put_integrated(txn, &op_hash, ValidationStatus::Rejected)?;
+                            } else {
+                                put_integration_limbo(txn, &op_hash, ValidationStatus::Rejected)?;
+                            }
+                        }
+                        Outcome::RejectedWithoutWarrant(reason) => {
                             rejected += 1;
+                            tracing::info!(
+                                ?op_hash,
+                                %reason,
+                                "Rejected an op without warranting its author"
+                            );
                             if let Dependency::Null = dependency {
                                 put_integrated(txn, &op_hash, ValidationStatus::Rejected)?;
                             } else {
@@ -201,9 +251,17 @@ async fn sys_validation_workflow_inner(
                         }
                     }
                 }
-                WorkflowResult::Ok((total, awaiting, missing, rejected))
+                WorkflowResult::Ok((total, awaiting, missing, rejected, invalid))
             })
             .await?;
+        issue_warrants(
+            &space.dna_hash,
+            conductor_handle.as_ref(),
+            &space.dht_db,
+            &network,
+            invalid,
+        )
+        .await;
 
         total += t;
         if let (Some(start), Some(round_time)) = (start, &mut round_time) {
@@ -222,6 +280,15 @@ async fn sys_validation_workflow_inner(
     }
     jh.await?;
     tracing::debug!("Accepted {} ops", total);
+
+    // Nothing else may trigger this workflow by the time
+    // the next op waiting for a key state is due.
+    if let Some(due) = key_state_retries.next_due() {
+        tokio::spawn(async move {
+            tokio::time::sleep_until(due.into()).await;
+            retry_trigger.trigger(&"key_state_retry");
+        });
+    }
     Ok(if saturated {
         WorkComplete::Incomplete
     } else {
@@ -233,6 +300,7 @@ async fn validate_op(
     op: &DhtOp,
     workspace: &SysValidationWorkspace,
     network: HolochainP2pDna,
+    key_check: &KeyStateCheck,
     conductor_handle: &dyn ConductorHandleT,
     incoming_dht_ops_sender: Option<IncomingDhtOpSender>,
 ) -> WorkflowResult<Outcome> {
@@ -240,6 +308,7 @@ async fn validate_op(
         op,
         workspace,
         network,
+        key_check,
         conductor_handle,
         incoming_dht_ops_sender,
     )
@@ -256,7 +325,7 @@ async fn validate_op(
                 error_msg = %e
             );
             let outcome = handle_failed(e);
-            if let Outcome::Rejected = outcome {
+            if let Outcome::Rejected(_) | Outcome::RejectedWithoutWarrant(_) = outcome {
                 warn!(
                     dna = %workspace.dna_hash(),
                     msg = "DhtOp was rejected during system validation.",
@@ -269,37 +338,62 @@ async fn validate_op(
     }
 }
 
+/// Sys validate an op which a warrant holds up as proof of invalid data.
+/// Only a rejection which would have earned its author a warrant here
+/// counts as proof.
+pub(crate) async fn check_warranted_op(
+    op: &DhtOp,
+    workspace: &SysValidationWorkspace,
+    network: HolochainP2pDna,
+    conductor_handle: &dyn ConductorHandleT,
+) -> WorkflowResult<ProofCheck> {
+    let key_check = KeyStateCheck::for_dna(workspace.dna_hash(), conductor_handle).await;
+    let outcome = validate_op(op, workspace, network, &key_check, conductor_handle, None).await?;
+    Ok(match outcome {
+        Outcome::Accepted => ProofCheck::Valid,
+        Outcome::Rejected(_) => ProofCheck::Invalid,
+        Outcome::RejectedWithoutWarrant(_)
+        | Outcome::AwaitingOpDep(_)
+        | Outcome::MissingDhtDep
+        | Outcome::AwaitingKeyState => ProofCheck::Undecided,
+    })
+}
+
 /// For now errors result in an outcome but in the future
 /// we might find it useful to include the reason something
 /// was rejected etc.
 /// This is why the errors contain data but is currently unread.
 fn handle_failed(error: ValidationOutcome) -> Outcome {
     use Outcome::*;
+    let reason = error.to_string();
     match error {
         ValidationOutcome::Counterfeit(_, _) => {
             unreachable!("Counterfeit ops are dropped before sys validation")
         }
-        ValidationOutcome::ActionNotInCounterSigningSession(_, _) => Rejected,
+        ValidationOutcome::ActionNotInCounterSigningSession(_, _) => Rejected(reason.clone()),
         ValidationOutcome::DepMissingFromDht(_) => MissingDhtDep,
-        ValidationOutcome::EntryDefId(_) => Rejected,
-        ValidationOutcome::EntryHash => Rejected,
-        ValidationOutcome::EntryTooLarge(_, _) => Rejected,
-        ValidationOutcome::EntryType => Rejected,
-        ValidationOutcome::EntryVisibility(_) => Rejected,
-        ValidationOutcome::TagTooLarge(_, _) => Rejected,
-        ValidationOutcome::NotCreateLink(_) => Rejected,
-        ValidationOutcome::NotNewEntry(_) => Rejected,
+        ValidationOutcome::EntryDefId(_) => Rejected(reason.clone()),
+        ValidationOutcome::EntryHash => Rejected(reason.clone()),
+        ValidationOutcome::EntryTooLarge(_, _) => Rejected(reason.clone()),
+        ValidationOutcome::EntryType => Rejected(reason.clone()),
+        ValidationOutcome::EntryVisibility(_) => Rejected(reason.clone()),
+        ValidationOutcome::AuthorKeyStateUnknown(_) => AwaitingKeyState,
+        ValidationOutcome::InvalidAuthorKey(_, _, _) => RejectedWithoutWarrant(reason.clone()),
+        ValidationOutcome::RateLimitExceeded { .. } => RejectedWithoutWarrant(reason.clone()),
+        ValidationOutcome::TagTooLarge(_, _) => Rejected(reason.clone()),
+        ValidationOutcome::NotCreateLink(_) => Rejected(reason.clone()),
+        ValidationOutcome::NotNewEntry(_) => Rejected(reason.clone()),
         ValidationOutcome::NotHoldingDep(dep) => AwaitingOpDep(dep),
         ValidationOutcome::PrevActionError(PrevActionError::MissingMeta(dep)) => {
             AwaitingOpDep(dep.into())
         }
-        ValidationOutcome::PrevActionError(_) => Rejected,
-        ValidationOutcome::PrivateEntry => Rejected,
-        ValidationOutcome::PreflightResponseSignature(_) => Rejected,
-        ValidationOutcome::UpdateTypeMismatch(_, _) => Rejected,
-        ValidationOutcome::VerifySignature(_, _) => Rejected,
-        ValidationOutcome::ZomeId(_) => Rejected,
-        ValidationOutcome::CounterSigningError(_) => Rejected,
+        ValidationOutcome::PrevActionError(_) => Rejected(reason.clone()),
+        ValidationOutcome::PrivateEntry => Rejected(reason.clone()),
+        ValidationOutcome::PreflightResponseSignature(_) => Rejected(reason.clone()),
+        ValidationOutcome::UpdateTypeMismatch(_, _) => Rejected(reason.clone()),
+        ValidationOutcome::VerifySignature(_, _) => Rejected(reason.clone()),
+        ValidationOutcome::ZomeId(_) => Rejected(reason.clone()),
+        ValidationOutcome::CounterSigningError(_) => Rejected(reason.clone()),
     }
 }
 
@@ -307,9 +401,18 @@ async fn validate_op_inner(
     op: &DhtOp,
     workspace: &SysValidationWorkspace,
     network: HolochainP2pDna,
+    key_check: &KeyStateCheck,
     conductor_handle: &dyn ConductorHandleT,
     incoming_dht_ops_sender: Option<IncomingDhtOpSender>,
 ) -> SysValidationResult<()> {
+    let action = op.action();
+    author_key_is_valid(
+        action.author(),
+        action.timestamp(),
+        key_check,
+        conductor_handle,
+    )
+    .await?;
     match op {
         DhtOp::StoreRecord(_, action, entry) => {
             store_record(action, workspace, network.clone()).await?;
@@ -480,6 +583,22 @@ async fn sys_validate_record_inner(
     let action = record.action();
     let maybe_entry = record.entry().as_option();
     counterfeit_check(signature, action).await?;
+    let key_check = KeyStateCheck::for_dna(workspace.dna_hash(), conductor_handle).await;
+    match author_key_is_valid(
+        action.author(),
+        action.timestamp(),
+        &key_check,
+        conductor_handle,
+    )
+    .await
+    {
+        // We are the author, so there's nothing to wait for. The registry may
+        // well not know our key yet because this is the record registering it.
+        Err(SysValidationError::ValidationOutcome(ValidationOutcome::AuthorKeyStateUnknown(_))) => {
+        }
+        r => r?,
+    }
+    check_spam(action, workspace).await?;
 
     async fn validate(
         action: &Action,
@@ -548,11 +667,10 @@ async fn sys_validate_record_inner(
     }
 }
 
-/// Check if the op has valid signature and author.
+/// Check if the op has a valid signature.
 /// Ops that fail this check should be dropped.
 pub async fn counterfeit_check(signature: &Signature, action: &Action) -> SysValidationResult<()> {
     verify_action_signature(signature, action).await?;
-    author_key_is_valid(action.author()).await?;
     Ok(())
 }
 
@@ -579,6 +697,7 @@ async fn register_agent_activity(
         .await?;
     }
     check_chain_rollback(action, workspace).await?;
+    check_spam(action, workspace).await?;
     Ok(())
 }
 
@@ -787,6 +906,7 @@ pub struct SysValidationWorkspace {
     dht_query_cache: Option<DhtDbQueryCache>,
     cache: DbWrite<DbKindCache>,
     pub(crate) dna_def: Arc<DnaDef>,
+    rate_bucket_cache: Option<RateBucketCache>,
 }
 
 impl SysValidationWorkspace {
@@ -804,6 +924,23 @@ impl SysValidationWorkspace {
             cache,
             dna_def,
             scratch: None,
+            rate_bucket_cache: None,
+        }
+    }
+
+    /// Carry on replaying the authors' rate limiting buckets from the
+    /// checkpoints in this cache.
+    pub fn with_rate_bucket_cache(mut self, rate_bucket_cache: RateBucketCache) -> Self {
+        self.rate_bucket_cache = Some(rate_bucket_cache);
+        self
+    }
+
+    /// The checkpoints of the authors' rate limiting buckets.
+    /// An author's workspace has none, as its chain isn't committed yet.
+    pub fn rate_bucket_cache(&self) -> Option<&RateBucketCache> {
+        match &self.scratch {
+            Some(_) => None,
+            None => self.rate_bucket_cache.as_ref(),
         }
     }
 
@@ -901,6 +1038,164 @@ impl SysValidationWorkspace {
         };
         Ok(!action_seq_is_not_empty)
     }
+    /// The actions an author made before this action, in chain order.
+    ///
+    /// An authority follows the chain back from the action's previous action
+    /// through the activity it holds, whether or not that has been validated
+    /// yet, and has to wait if any of it is missing. The author also sees
+    /// their own uncommitted actions.
+    pub async fn prior_author_actions(&self, action: &Action) -> SysValidationResult<Vec<Action>> {
+        let author = action.author().clone();
+        let seq = action.action_seq();
+        let scratch = match &self.scratch {
+            Some(scratch) => scratch,
+            None => {
+                return self
+                    .held_prior_author_actions(action, None)
+                    .await
+                    .map(Option::unwrap_or_default)
+            }
+        };
+        let blobs: Vec<Vec<u8>> = self
+            .authored_db
+            .async_reader({
+                let author = author.clone();
+                move |txn| {
+                    let mut stmt = txn.prepare(
+                        "
+                        SELECT Action.blob
+                        FROM Action
+                        WHERE
+                        Action.author = :author
+                        AND
+                        Action.seq < :seq
+                        ORDER BY Action.seq
+                        ",
+                    )?;
+                    let blobs = stmt
+                        .query_map(
+                            named_params! {
+                                ":author": author,
+                                ":seq": seq,
+                            },
+                            |row| row.get("blob"),
+                        )?
+                        .collect::<Result<Vec<_>, _>>()?;
+                    DatabaseResult::Ok(blobs)
+                }
+            })
+            .await?;
+        let mut actions = blobs
+            .into_iter()
+            .map(|blob| from_blob::<SignedAction>(blob).map(|a| a.0))
+            .collect::<StateQueryResult<Vec<_>>>()
+            .map_err(SourceChainError::from)?;
+        scratch
+            .apply(|scratch| {
+                actions.extend(
+                    scratch
+                        .actions()
+                        .map(|shh| shh.action().clone())
+                        .filter(|a| a.author() == &author && a.action_seq() < seq),
+                );
+            })
+            .map_err(SourceChainError::from)?;
+        actions.sort_by_key(|a| a.action_seq());
+        Ok(actions)
+    }
+
+    /// The author's chain before this action and after the action at
+    /// `since_seq` with the hash `since`, followed back like
+    /// [`Self::prior_author_actions`] on an authority.
+    ///
+    /// Returns `None` if the chain doesn't pass through that action,
+    /// or if some of the chain after it is missing.
+    pub async fn held_prior_author_actions_since(
+        &self,
+        action: &Action,
+        since_seq: u32,
+        since: &ActionHash,
+    ) -> SysValidationResult<Option<Vec<Action>>> {
+        self.held_prior_author_actions(action, Some((since_seq, since)))
+            .await
+    }
+
+    /// The author's chain before this action, followed back from the
+    /// action's previous action through the activity this authority holds,
+    /// as far as the `since` action if one is given.
+    async fn held_prior_author_actions(
+        &self,
+        action: &Action,
+        since: Option<(u32, &ActionHash)>,
+    ) -> SysValidationResult<Option<Vec<Action>>> {
+        let author = action.author().clone();
+        let seq = action.action_seq();
+        let since_seq = since.map_or(-1, |(since_seq, _)| since_seq as i64);
+        let held: HashMap<ActionHash, Action> = self
+            .dht_db
+            .async_reader(move |txn| {
+                let mut stmt = txn.prepare(
+                    "
+                    SELECT DISTINCT Action.hash, Action.blob
+                    FROM Action
+                    JOIN
+                    DhtOp ON Action.hash = DhtOp.action_hash
+                    WHERE
+                    Action.author = :author
+                    AND
+                    Action.seq < :seq
+                    AND
+                    Action.seq > :since_seq
+                    AND
+                    DhtOp.type = :activity
+                    ",
+                )?;
+                let held = stmt
+                    .query_and_then(
+                        named_params! {
+                            ":author": author,
+                            ":seq": seq,
+                            ":since_seq": since_seq,
+                            ":activity": DhtOpType::RegisterAgentActivity,
+                        },
+                        |row| {
+                            let action = from_blob::<SignedAction>(row.get("blob")?)?;
+                            StateQueryResult::Ok((row.get("hash")?, action.0))
+                        },
+                    )?
+                    .collect::<StateQueryResult<_>>()?;
+                StateQueryResult::Ok(held)
+            })
+            .await
+            .map_err(SourceChainError::from)?;
+        let mut actions = Vec::new();
+        let mut prev_action = action.prev_action();
+        loop {
+            match (prev_action, since) {
+                (Some(hash), Some((_, since))) if hash == since => break,
+                (Some(hash), _) => {
+                    let prior = match held.get(hash) {
+                        Some(prior) => prior,
+                        // The chain may fork before `since`, so this
+                        // doesn't mean the author's action is missing.
+                        None if since.is_some() => return Ok(None),
+                        None => {
+                            return Err(ValidationOutcome::NotHoldingDep(hash.clone().into()).into())
+                        }
+                    };
+                    prev_action = prior.prev_action();
+                    actions.push(prior.clone());
+                }
+                // The start of the chain, which is only reached by going
+                // through `since` if there is one.
+                (None, Some(_)) => return Ok(None),
+                (None, None) => break,
+            }
+        }
+        actions.reverse();
+        Ok(Some(actions))
+    }
+
     /// Create a cascade with local data only
     pub fn local_cascade(&self) -> Cascade {
         let cascade = Cascade::empty().with_dht(self.dht_db.clone());
@@ -983,6 +1278,7 @@ impl From<&HostFnWorkspace> for SysValidationWorkspace {
             dht_query_cache: None,
             cache,
             dna_def: h.dna_def(),
+            rate_bucket_cache: None,
         }
     }
 }
@@ -45,6 +45,101 @@ async fn sys_validation_workflow_test() {
     run_test(alice_cell_id, bob_cell_id, conductors, dna_file).await;
 }
 
+/// Bob's links overflow a rate limiting bucket of the DNA. Alice rejects
+/// the link which overflows it, and accepts the ones before it.
+#[tokio::test(flavor = "multi_thread")]
+async fn sys_validation_rejects_ops_over_the_rate_limit() {
+    observability::test_run().ok();
+
+    let (dna_file, _, _) = SweetDnaFile::unique_from_test_wasms(vec![TestWasm::Create])
+        .await
+        .unwrap();
+    let mut dna_def = dna_file.dna_def().clone();
+    dna_def.rate_limits.insert(
+        1,
+        RateBucketLimit {
+            capacity: 10,
+            drain_amount: 1,
+            drain_interval_ms: 60 * 60 * 1000,
+        },
+    );
+    let dna_file = DnaFile::new(dna_def, dna_file.code().values().cloned())
+        .await
+        .unwrap();
+
+    let mut conductors = SweetConductorBatch::from_standard_config(2).await;
+    let apps = conductors
+        .setup_app(&"test_app", &[dna_file.clone()])
+        .await
+        .unwrap();
+    let ((alice,), (bob,)) = apps.into_tuples();
+    conductors.exchange_peer_info().await;
+
+    // Bob weighs his links himself and pushes them straight on to his
+    // source chain, so they aren't checked before they are published.
+    let bob_handle = conductors[1].handle();
+    let call_data = HostFnCaller::create(bob.cell_id(), &bob_handle, &dna_file).await;
+    let zome_id = call_data
+        .get_entry_type(TestWasm::Create, POST_INDEX)
+        .zome_id;
+    let (_ribosome, call_context, workspace_lock) = call_data.unpack().await;
+    let source_chain = call_context
+        .host_context
+        .workspace_write()
+        .source_chain()
+        .as_ref()
+        .expect("Must have source chain if write_workspace access is given");
+    let mut links = Vec::new();
+    for _ in 0..3 {
+        let action_builder = builder::CreateLink {
+            base_address: fixt!(EntryHash).into(),
+            target_address: fixt!(EntryHash).into(),
+            zome_id,
+            link_type: LinkType(0),
+            tag: fixt!(LinkTag),
+        };
+        let weight = RateWeight {
+            bucket_id: 1,
+            units: 4,
+        };
+        links.push(
+            source_chain
+                .put_weighed(action_builder, None, ChainTopOrdering::default(), weight)
+                .await
+                .unwrap(),
+        );
+    }
+    workspace_lock.flush(&call_data.network).await.unwrap();
+    let triggers = bob_handle.get_cell_triggers(bob.cell_id()).unwrap();
+    triggers.publish_dht_ops.trigger(&"bob_overflows_a_bucket");
+
+    // 14 ops for genesis and 3 for each link.
+    let alice_dht_db = conductors[0]
+        .get_dht_db(alice.cell_id().dna_hash())
+        .unwrap();
+    wait_for_integration(&alice_dht_db, 14 + 9, 100, Duration::from_millis(100)).await;
+
+    let activity_status = |txn: &Transaction, action_hash: &ActionHash| -> ValidationStatus {
+        txn.query_row(
+            "
+            SELECT validation_status FROM DhtOp
+            WHERE action_hash = :action_hash AND type = :activity
+            ",
+            named_params! {
+                ":action_hash": action_hash,
+                ":activity": DhtOpType::RegisterAgentActivity,
+            },
+            |row| row.get(0),
+        )
+        .unwrap()
+    };
+    fresh_reader_test(alice_dht_db, |txn| {
+        assert_eq!(activity_status(&txn, &links[0]), ValidationStatus::Valid);
+        assert_eq!(activity_status(&txn, &links[1]), ValidationStatus::Valid);
+        assert_eq!(activity_status(&txn, &links[2]), ValidationStatus::Rejected);
+    });
+}
+
 async fn run_test(
     alice_cell_id: CellId,
     bob_cell_id: CellId,
@@ -12,6 +12,14 @@ pub(super) enum Outcome {
     /// be found currently on the DHT.
     /// Note this is not proof it doesn't exist.
     MissingDhtDep,
-    /// Moves to integration with status rejected
-    Rejected,
+    /// Stays in limbo because the DPKI app
+    /// doesn't know the state of the author's key yet.
+    AwaitingKeyState,
+    /// Moves to integration with status rejected.
+    /// Holds the reason, for the warrant against the author.
+    Rejected(String),
+    /// Moves to integration with status rejected, but the author
+    /// is not warranted, because another authority with a different
+    /// view of the author's key or chain could accept the op.
+    RejectedWithoutWarrant(String),
 }
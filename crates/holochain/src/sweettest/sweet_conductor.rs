@@ -54,6 +54,7 @@ pub fn standard_config() -> ConductorConfig {
     }];
     let admin_interface = AdminInterfaceConfig {
         driver: InterfaceDriver::Websocket { port: 0 },
+        auth: None,
     };
     ConductorConfig {
         network: Some(network),
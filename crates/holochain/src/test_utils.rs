@@ -373,6 +373,7 @@ pub async fn setup_app_inner(
         .config(ConductorConfig {
             admin_interfaces: Some(vec![AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port: 0 },
+                auth: None,
             }]),
             network,
             ..Default::default()
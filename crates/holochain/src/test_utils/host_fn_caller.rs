@@ -13,6 +13,7 @@ use crate::core::ribosome::InvocationAuth;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::ribosome::ZomeCallTiming;
 use hdk::prelude::*;
 use holo_hash::ActionHash;
 use holo_hash::AgentPubKey;
@@ -192,6 +193,7 @@ impl HostFnCaller {
             network,
             signal_tx,
             call_zome_handle,
+            ZomeCallTiming::default(),
         );
         let ribosome = Arc::new(ribosome);
         let zome = ribosome.dna_def().get_zome(&zome_name).unwrap();
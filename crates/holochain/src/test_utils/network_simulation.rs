@@ -239,6 +239,9 @@ fn cache_data(in_memory: bool, data: &MockNetworkData, is_cached: bool) -> Conne
     holochain_sqlite::schema::SCHEMA_CELL
         .initialize(&mut conn, None)
         .unwrap();
+    // Both schemas share this database, so the second must not see the
+    // version recorded by the first.
+    conn.pragma_update(None, "user_version", &0).unwrap();
     holochain_sqlite::schema::SCHEMA_P2P_STATE
         .initialize(&mut conn, None)
         .unwrap();
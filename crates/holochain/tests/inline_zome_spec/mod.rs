@@ -15,8 +15,11 @@ use holochain::{
     core::ribosome::guest_callback::validate::ValidateResult, test_utils::wait_for_integration_1m,
 };
 use holochain::{core::SourceChainError, test_utils::display_agent_infos};
+use holochain_conductor_api::CELL_CHAIN_ARCHIVE_VERSION;
+use holochain_keystore::AgentPubKeyExt;
 use holochain_keystore::MetaLairClient;
 use holochain_state::prelude::{fresh_reader_test, StateMutationError, Store, Txn};
+use holochain_state::{mutations::insert_warrant, warrant::get_warrants_for_agent};
 use holochain_types::{inline_zome::InlineZomeSet, prelude::*};
 use holochain_wasm_test_utils::TestWasm;
 use holochain_zome_types::{op::Op, record::RecordEntry};
@@ -712,6 +715,138 @@ async fn insert_source_chain() {
     assert_eq!(chain.last().unwrap().1, 3);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+/// Test that a source chain can be exported and imported on another conductor.
+async fn export_import_cell_chain() {
+    let (dna_file, _, _) = SweetDnaFile::unique_from_inline_zomes(simple_crud_zome())
+        .await
+        .unwrap();
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let apps = conductor
+        .setup_app("app", &[dna_file.clone()])
+        .await
+        .unwrap();
+    let (alice,) = apps.into_tuple();
+
+    let zome = alice.zome(SweetEasyInline::COORDINATOR);
+    let hash: ActionHash = conductor.call(&zome, "create_unit", ()).await;
+
+    // Another agent holds a warrant against the created entry.
+    let keystore = conductor.keystore();
+    let issuer = SweetAgents::one(keystore.clone()).await;
+    let warranted = conductor
+        .export_cell_chain(alice.cell_id())
+        .await
+        .unwrap()
+        .content
+        .records
+        .last()
+        .unwrap()
+        .clone();
+    let warrant = Warrant {
+        proof: WarrantProof::InvalidAction {
+            action: SignedAction(warranted.action().clone(), warranted.signature().clone()),
+            validation_type: ValidationType::App,
+            reason: "for testing".into(),
+        },
+        author: issuer.clone(),
+        timestamp: Timestamp::now(),
+    };
+    let signature = issuer.sign(&keystore, &warrant).await.unwrap();
+    let warrant = SignedWarrant::new(warrant, signature);
+    conductor
+        .get_dht_db(alice.cell_id().dna_hash())
+        .unwrap()
+        .async_commit({
+            let warrant = warrant.clone();
+            move |txn| insert_warrant(txn, &warrant)
+        })
+        .await
+        .unwrap();
+
+    let archive = conductor.export_cell_chain(alice.cell_id()).await.unwrap();
+    assert_eq!(archive.content.version, CELL_CHAIN_ARCHIVE_VERSION);
+    assert_eq!(archive.content.warrants, vec![warrant.clone()]);
+    assert_eq!(&archive.content.cell_id, alice.cell_id());
+    // Genesis, init and the created entry.
+    assert_eq!(archive.content.records.len(), 5);
+    assert_eq!(
+        archive.content.records.last().unwrap().action_address(),
+        &hash
+    );
+
+    // Start a second conductor.
+    let mut conductor = SweetConductor::from_standard_config().await;
+
+    // The archive is rejected before the dna is installed.
+    let result = conductor
+        .clone()
+        .import_cell_chain(archive.clone(), true, true)
+        .await;
+    assert!(result.is_err());
+
+    conductor.register_dna(dna_file.clone()).await.unwrap();
+
+    // An archive with a bad signature is rejected.
+    let mut forged = archive.clone();
+    forged.content.exported_at = Timestamp::from_micros(0);
+    let result = conductor
+        .clone()
+        .import_cell_chain(forged, true, true)
+        .await;
+    assert_matches!(result, Err(ConductorApiError::InvalidCellChainArchive(_)));
+
+    // So is an archive with a forged warrant, even when the agent signs it.
+    let mut forged = archive.clone();
+    forged.content.warrants[0].warrant.timestamp = Timestamp::from_micros(0);
+    forged.signature = alice
+        .agent_pubkey()
+        .sign(&keystore, &forged.content)
+        .await
+        .unwrap();
+    let result = conductor
+        .clone()
+        .import_cell_chain(forged, true, true)
+        .await;
+    assert_matches!(result, Err(ConductorApiError::InvalidCellChainArchive(_)));
+
+    conductor
+        .clone()
+        .import_cell_chain(archive.clone(), true, true)
+        .await
+        .expect("Can restore from an archive");
+
+    // The warrant is restored with the chain.
+    let warrants = fresh_reader_test(
+        conductor.get_dht_db(alice.cell_id().dna_hash()).unwrap(),
+        |txn| get_warrants_for_agent(&txn, alice.agent_pubkey()).unwrap(),
+    );
+    assert_eq!(warrants, vec![warrant]);
+
+    let apps = conductor
+        .setup_app_for_agent("restored", alice.agent_pubkey().clone(), &[dna_file])
+        .await
+        .unwrap();
+    let (alice_backup,) = apps.into_tuple();
+
+    // The restored chain matches the original.
+    let restored: Vec<ActionHash> = fresh_reader_test(alice_backup.authored_db().clone(), |txn| {
+        txn.prepare("SELECT hash FROM Action ORDER BY seq")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    });
+    let archived: Vec<ActionHash> = archive
+        .content
+        .records
+        .iter()
+        .map(|r| r.action_address().clone())
+        .collect();
+    assert_eq!(restored, archived);
+}
+
 async fn make_record(keystore: &MetaLairClient, action: Action) -> Record {
     let shh = SignedActionHashed::sign(
         keystore,
@@ -173,6 +173,7 @@ async fn get_from_another_agent() {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::Create.into()].into(),
+            rate_limits: Default::default(),
         },
         vec![TestWasm::Create.into()],
     )
@@ -307,6 +308,7 @@ async fn get_links_from_another_agent() {
             uid: "ba1d046d-ce29-4778-914b-47e6010d2faf".to_string(),
             properties: SerializedBytes::try_from(()).unwrap(),
             zomes: vec![TestWasm::Create.into()].into(),
+            rate_limits: Default::default(),
         },
         vec![TestWasm::Create.into()],
     )
@@ -516,6 +518,7 @@ async fn run_fixt_network(
                             updates: btreeset! {},
                             invalid_actions: btreeset! {},
                             entry_dht_status: None,
+                            link_count: None,
                         };
                         respond.respond(Ok(async move { Ok(metadata.try_into().unwrap()) }
                             .boxed()
@@ -54,6 +54,7 @@ async fn ser_regression_test() {
             coordinator_zomes: vec![TestZomes::from(TestWasm::SerRegression)
                 .coordinator
                 .into_inner()],
+            rate_limits: Default::default(),
         },
         <Vec<DnaWasm>>::from(TestWasm::SerRegression),
     )
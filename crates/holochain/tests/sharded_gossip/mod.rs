@@ -404,6 +404,9 @@ async fn mock_network_sharded_gossip() {
                         holochain_p2p::WireMessage::CountersigningSessionNegotiation { .. } => {
                             debug!("countersigning_session_negotiation")
                         }
+                        holochain_p2p::WireMessage::PublishWarrant { .. } => {
+                            debug!("publish_warrant")
+                        }
                     },
                     HolochainP2pMockMsg::CallResp(_) => debug!("CallResp"),
                     HolochainP2pMockMsg::PeerGet(_) => debug!("PeerGet"),
@@ -919,6 +922,9 @@ async fn mock_network_sharding() {
                         holochain_p2p::WireMessage::CountersigningSessionNegotiation { .. } => {
                             debug!("countersigning_session_negotiation")
                         }
+                        holochain_p2p::WireMessage::PublishWarrant { .. } => {
+                            debug!("publish_warrant")
+                        }
                     },
                     HolochainP2pMockMsg::CallResp(_) => debug!("CallResp"),
                     HolochainP2pMockMsg::MetricExchange(_) => debug!("MetricExchange"),
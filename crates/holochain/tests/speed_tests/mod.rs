@@ -130,6 +130,7 @@ async fn speed_test(n: Option<usize>) -> Arc<TempDir> {
             origin_time: Timestamp::HOLOCHAIN_EPOCH,
             integrity_zomes: vec![TestZomes::from(TestWasm::Anchor).integrity.into_inner()],
             coordinator_zomes: vec![TestZomes::from(TestWasm::Anchor).coordinator.into_inner()],
+            rate_limits: Default::default(),
         },
         vec![TestWasm::Anchor.into()],
     )
@@ -176,7 +177,12 @@ async fn speed_test(n: Option<usize>) -> Arc<TempDir> {
 
     // Setup websocket handle and app interface
     let (mut client, _) = websocket_client(&handle).await.unwrap();
-    let request = AdminRequest::AttachAppInterface { port: None };
+    let request = AdminRequest::AttachAppInterface {
+        port: None,
+        rate_limit: None,
+        tls: None,
+        require_auth: false,
+    };
     let response = client.request(request);
     let response = response.await.unwrap();
     let app_port = match response {
@@ -25,6 +25,8 @@ use assert_cmd::prelude::*;
 use futures::Future;
 use holochain_conductor_api::conductor::ConductorConfig;
 use holochain_conductor_api::conductor::KeystoreConfig;
+use holochain_conductor_api::signal_subscription::SignalFilterSet;
+use holochain_conductor_api::signal_subscription::SignalSubscription;
 use holochain_conductor_api::AdminInterfaceConfig;
 use holochain_conductor_api::InterfaceDriver;
 use matches::assert_matches;
@@ -115,8 +117,22 @@ pub async fn call_zome_fn<S>(
     assert_matches!(call_response, AppResponse::ZomeCall(_));
 }
 
+pub async fn subscribe_to_signals(app_tx: &mut WebsocketSender, installed_app_id: &str) {
+    let subscription =
+        SignalSubscription::new(installed_app_id.to_string(), SignalFilterSet::allow_all());
+    let request = AppRequest::SignalSubscription(subscription);
+    let response = app_tx.request(request);
+    let response = check_timeout(response, 3000).await;
+    assert_matches!(response, AppResponse::SignalSubscriptionUpdated);
+}
+
 pub async fn attach_app_interface(client: &mut WebsocketSender, port: Option<u16>) -> u16 {
-    let request = AdminRequest::AttachAppInterface { port };
+    let request = AdminRequest::AttachAppInterface {
+        port,
+        rate_limit: None,
+        tls: None,
+        require_auth: false,
+    };
     let response = client.request(request);
     let response = check_timeout(response, 3000).await;
     match response {
@@ -272,12 +288,17 @@ pub fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
     ConductorConfig {
         admin_interfaces: Some(vec![AdminInterfaceConfig {
             driver: InterfaceDriver::Websocket { port },
+            auth: None,
         }]),
         environment_path: environment_path.into(),
         network: None,
         dpki: None,
         keystore: KeystoreConfig::DangerTestKeystoreLegacyDeprecated,
         db_sync_strategy: DbSyncStrategy::default(),
+        cell_rate_limit: None,
+        schedule_catch_up: Default::default(),
+        zome_call_timeout_ms: None,
+        max_sleep_ms: None,
     }
 }
 
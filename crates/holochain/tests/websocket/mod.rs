@@ -319,7 +319,9 @@ async fn emit_signals() {
     // Emit signals (the real test!)
 
     let (mut app_tx_1, app_rx_1) = websocket_client_by_port(app_port).await.unwrap();
-    let (_, app_rx_2) = websocket_client_by_port(app_port).await.unwrap();
+    let (mut app_tx_2, app_rx_2) = websocket_client_by_port(app_port).await.unwrap();
+    subscribe_to_signals(&mut app_tx_1, "test").await;
+    subscribe_to_signals(&mut app_tx_2, "test").await;
 
     call_zome_fn(
         &mut app_tx_1,
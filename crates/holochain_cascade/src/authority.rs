@@ -1,6 +1,7 @@
 use self::get_agent_activity_query::hashes::GetAgentActivityQuery;
 use self::get_entry_ops_query::GetEntryOpsQuery;
 use self::get_links_ops_query::GetLinksOpsQuery;
+use self::get_meta_query::GetMetaQuery;
 use self::{
     get_agent_activity_query::deterministic::DeterministicGetAgentActivityQuery,
     get_record_query::GetRecordOpsQuery,
@@ -21,6 +22,7 @@ mod test;
 pub(crate) mod get_agent_activity_query;
 pub(crate) mod get_entry_ops_query;
 pub(crate) mod get_links_ops_query;
+pub(crate) mod get_meta_query;
 pub(crate) mod get_record_query;
 
 #[instrument(skip(db))]
@@ -56,9 +58,13 @@ pub async fn handle_get_agent_activity(
     query: ChainQueryFilter,
     options: holochain_p2p::event::GetActivityOptions,
 ) -> CascadeResult<AgentActivityResponse<ActionHash>> {
-    let query = GetAgentActivityQuery::new(agent, query, options);
+    let query = GetAgentActivityQuery::new(agent.clone(), query, options);
     let results = env
-        .async_reader(move |txn| query.run(Txn::from(&txn)))
+        .async_reader(move |txn| {
+            let mut results = query.run(Txn::from(&txn))?;
+            results.warrants = holochain_state::warrant::get_warrants_for_agent(&txn, &agent)?;
+            CascadeResult::Ok(results)
+        })
         .await?;
     Ok(results)
 }
@@ -77,6 +83,19 @@ pub async fn handle_get_agent_activity_deterministic(
     Ok(results)
 }
 
+#[instrument(skip(env, options))]
+pub async fn handle_get_meta(
+    env: DbRead<DbKindDht>,
+    hash: AnyDhtHash,
+    options: holochain_p2p::event::GetMetaOptions,
+) -> CascadeResult<MetadataSet> {
+    let query = GetMetaQuery::new(hash, options.metadata_request);
+    let results = env
+        .async_reader(move |txn| query.run(Txn::from(&txn)))
+        .await?;
+    Ok(results)
+}
+
 #[instrument(skip(env, _options))]
 pub async fn handle_get_links(
     env: DbRead<DbKindDht>,
@@ -0,0 +1,216 @@
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use holo_hash::ActionHash;
+use holo_hash::AnyDhtHash;
+use holochain_sqlite::rusqlite::named_params;
+use holochain_sqlite::rusqlite::Row;
+use holochain_state::query::prelude::*;
+use holochain_state::query::StateQueryError;
+use holochain_types::dht_op::DhtOpType;
+use holochain_types::metadata::EntryDhtStatus;
+use holochain_types::metadata::MetadataSet;
+use holochain_types::metadata::TimedActionHash;
+use holochain_zome_types::request::MetadataRequest;
+use holochain_zome_types::HasValidationStatus;
+use holochain_zome_types::Judged;
+use holochain_zome_types::Timestamp;
+use holochain_zome_types::ValidationStatus;
+
+#[derive(Debug, Clone)]
+pub struct GetMetaQuery {
+    hash: AnyDhtHash,
+    request: MetadataRequest,
+}
+
+impl GetMetaQuery {
+    pub fn new(hash: AnyDhtHash, request: MetadataRequest) -> Self {
+        Self { hash, request }
+    }
+}
+
+#[derive(Debug)]
+pub struct Item {
+    op_type: DhtOpType,
+    action: TimedActionHash,
+    deletes_action_hash: Option<ActionHash>,
+    create_link_hash: Option<ActionHash>,
+}
+
+#[derive(Debug, Default)]
+pub struct State {
+    meta: MetadataSet,
+    /// Whether any op stored the basis as an entry.
+    is_entry: bool,
+    /// Actions that have been deleted by a valid delete.
+    deleted: HashSet<ActionHash>,
+    create_links: HashSet<ActionHash>,
+    delete_links: HashSet<ActionHash>,
+}
+
+impl Query for GetMetaQuery {
+    type Item = Judged<Item>;
+    type State = State;
+    type Output = MetadataSet;
+
+    fn query(&self) -> String {
+        "
+        SELECT DhtOp.type AS dht_type, DhtOp.validation_status AS status,
+        DhtOp.action_hash, DhtOp.authored_timestamp,
+        Action.deletes_action_hash, Action.create_link_hash
+        FROM DhtOp
+        JOIN Action On DhtOp.action_hash = Action.hash
+        WHERE DhtOp.type IN (
+            :store_entry, :store_record, :delete_entry, :delete_record,
+            :update_content, :update_record, :create_link, :delete_link
+        )
+        AND
+        DhtOp.basis_hash = :hash
+        AND
+        DhtOp.when_integrated IS NOT NULL
+        "
+        .into()
+    }
+
+    fn params(&self) -> Vec<Params> {
+        let params = named_params! {
+            ":store_entry": DhtOpType::StoreEntry,
+            ":store_record": DhtOpType::StoreRecord,
+            ":delete_entry": DhtOpType::RegisterDeletedEntryAction,
+            ":delete_record": DhtOpType::RegisterDeletedBy,
+            ":update_content": DhtOpType::RegisterUpdatedContent,
+            ":update_record": DhtOpType::RegisterUpdatedRecord,
+            ":create_link": DhtOpType::RegisterAddLink,
+            ":delete_link": DhtOpType::RegisterRemoveLink,
+            ":hash": self.hash,
+        };
+        params.to_vec()
+    }
+
+    fn as_map(&self) -> Arc<dyn Fn(&Row) -> StateQueryResult<Self::Item>> {
+        let f = |row: &Row| {
+            let op_type = row.get("dht_type")?;
+            let validation_status = row.get("status")?;
+            let action_hash: ActionHash = row.get("action_hash")?;
+            let timestamp: Timestamp = row.get("authored_timestamp")?;
+            let item = Item {
+                op_type,
+                action: TimedActionHash {
+                    timestamp,
+                    action_hash,
+                },
+                deletes_action_hash: row.get("deletes_action_hash")?,
+                create_link_hash: row.get("create_link_hash")?,
+            };
+            Ok(Judged::raw(item, validation_status))
+        };
+        Arc::new(f)
+    }
+
+    fn init_fold(&self) -> StateQueryResult<Self::State> {
+        Ok(Default::default())
+    }
+
+    fn fold(&self, mut state: Self::State, item: Self::Item) -> StateQueryResult<Self::State> {
+        let status = item.validation_status();
+        let Item {
+            op_type,
+            action,
+            deletes_action_hash,
+            create_link_hash,
+        } = item.data;
+        match (op_type, status) {
+            (DhtOpType::StoreEntry, Some(ValidationStatus::Valid)) => {
+                state.is_entry = true;
+                state.meta.actions.insert(action);
+            }
+            (DhtOpType::StoreRecord, Some(ValidationStatus::Valid)) => {
+                state.meta.actions.insert(action);
+            }
+            (DhtOpType::StoreEntry, Some(ValidationStatus::Rejected)) => {
+                state.is_entry = true;
+                state.meta.invalid_actions.insert(action);
+            }
+            (DhtOpType::StoreRecord, Some(ValidationStatus::Rejected)) => {
+                state.meta.invalid_actions.insert(action);
+            }
+            (
+                DhtOpType::RegisterDeletedEntryAction | DhtOpType::RegisterDeletedBy,
+                Some(ValidationStatus::Valid),
+            ) => {
+                state.deleted.extend(deletes_action_hash);
+                state.meta.deletes.insert(action);
+            }
+            (
+                DhtOpType::RegisterUpdatedContent | DhtOpType::RegisterUpdatedRecord,
+                Some(ValidationStatus::Valid),
+            ) => {
+                state.meta.updates.insert(action);
+            }
+            (DhtOpType::RegisterAddLink, Some(ValidationStatus::Valid)) => {
+                state.create_links.insert(action.action_hash);
+            }
+            (DhtOpType::RegisterRemoveLink, Some(ValidationStatus::Valid)) => {
+                state.delete_links.extend(create_link_hash);
+            }
+            (
+                DhtOpType::StoreEntry
+                | DhtOpType::StoreRecord
+                | DhtOpType::RegisterDeletedEntryAction
+                | DhtOpType::RegisterDeletedBy
+                | DhtOpType::RegisterUpdatedContent
+                | DhtOpType::RegisterUpdatedRecord
+                | DhtOpType::RegisterAddLink
+                | DhtOpType::RegisterRemoveLink,
+                _,
+            ) => (),
+            (op_type, _) => return Err(StateQueryError::UnexpectedOp(op_type)),
+        }
+        Ok(state)
+    }
+
+    fn render<S>(&self, state: Self::State, _stores: S) -> StateQueryResult<Self::Output>
+    where
+        S: Store,
+    {
+        let State {
+            mut meta,
+            is_entry,
+            deleted,
+            create_links,
+            delete_links,
+        } = state;
+        let request = &self.request;
+
+        if request.entry_dht_status && is_entry {
+            let live = meta
+                .actions
+                .iter()
+                .any(|a| !deleted.contains(&a.action_hash));
+            meta.entry_dht_status = Some(if live {
+                EntryDhtStatus::Live
+            } else if meta.actions.is_empty() {
+                EntryDhtStatus::Rejected
+            } else {
+                EntryDhtStatus::Dead
+            });
+        }
+        if request.link_count {
+            meta.link_count = Some(create_links.difference(&delete_links).count());
+        }
+        if !request.all_valid_actions {
+            meta.actions = BTreeSet::new();
+        }
+        if !request.all_invalid_actions {
+            meta.invalid_actions = BTreeSet::new();
+        }
+        if !request.all_deletes {
+            meta.deletes = BTreeSet::new();
+        }
+        if !request.all_updates {
+            meta.updates = BTreeSet::new();
+        }
+        Ok(meta)
+    }
+}
@@ -6,6 +6,7 @@ use holochain_p2p::actor;
 use holochain_p2p::event::GetRequest;
 use holochain_state::prelude::test_dht_db;
 use holochain_types::activity::ChainItems;
+use std::collections::BTreeSet;
 
 fn options() -> holochain_p2p::event::GetOptions {
     holochain_p2p::event::GetOptions {
@@ -224,6 +225,7 @@ async fn get_agent_activity() {
         rejected_activity: ChainItems::NotRequested,
         status: ChainStatus::Valid(td.chain_head.clone()),
         highest_observed: Some(td.highest_observed.clone()),
+        warrants: Vec::new(),
     };
     assert_eq!(result, expected);
 
@@ -246,3 +248,61 @@ async fn get_agent_activity() {
 
     assert_eq!(result, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_meta() {
+    observability::test_run().ok();
+    let db = test_dht_db();
+
+    let td = EntryTestData::create();
+    let timed = |op: &DhtOpHashed, hash: &ActionHash| TimedActionHash {
+        timestamp: op.as_content().action().timestamp(),
+        action_hash: hash.clone(),
+    };
+    let options = holochain_p2p::event::GetMetaOptions {
+        metadata_request: MetadataRequest {
+            entry_dht_status: true,
+            link_count: true,
+            ..Default::default()
+        },
+    };
+
+    fill_db(&db.to_db(), td.store_entry_op.clone());
+    fill_db(&db.to_db(), td.create_link_op.clone());
+
+    let result = handle_get_meta(db.to_db().into(), td.hash.clone().into(), options.clone())
+        .await
+        .unwrap();
+    let mut expected = MetadataSet {
+        actions: BTreeSet::from([timed(&td.store_entry_op, &td.create_hash)]),
+        entry_dht_status: Some(EntryDhtStatus::Live),
+        link_count: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(result, expected);
+
+    fill_db(&db.to_db(), td.update_content_op.clone());
+    fill_db(&db.to_db(), td.delete_entry_action_op.clone());
+    fill_db(&db.to_db(), td.delete_link_op.clone());
+
+    let result = handle_get_meta(db.to_db().into(), td.hash.clone().into(), options.clone())
+        .await
+        .unwrap();
+    expected.updates = BTreeSet::from([timed(&td.update_content_op, &td.update_hash)]);
+    expected.deletes = BTreeSet::from([timed(&td.delete_entry_action_op, &td.delete_hash)]);
+    expected.entry_dht_status = Some(EntryDhtStatus::Dead);
+    expected.link_count = Some(0);
+    assert_eq!(result, expected);
+
+    // - Only the requested metadata is returned.
+    let result = handle_get_meta(
+        db.to_db().into(),
+        td.hash.clone().into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    expected.entry_dht_status = None;
+    expected.link_count = None;
+    assert_eq!(result, expected);
+}
@@ -14,6 +14,7 @@ use holo_hash::AnyDhtHash;
 use holo_hash::EntryHash;
 use holochain_p2p::actor::GetActivityOptions;
 use holochain_p2p::actor::GetLinksOptions;
+use holochain_p2p::actor::GetMetaOptions;
 use holochain_p2p::actor::GetOptions as NetworkGetOptions;
 use holochain_p2p::HolochainP2pDna;
 use holochain_p2p::HolochainP2pDnaT;
@@ -290,6 +291,16 @@ where
         Ok(())
     }
 
+    #[instrument(skip(self, options))]
+    async fn fetch_meta(
+        &mut self,
+        hash: AnyDhtHash,
+        options: GetMetaOptions,
+    ) -> CascadeResult<Vec<MetadataSet>> {
+        let network = ok_or_return!(self.network.as_mut(), Vec::with_capacity(0));
+        Ok(network.get_meta(hash, options).await?)
+    }
+
     #[instrument(skip(self, options))]
     async fn fetch_agent_activity(
         &mut self,
@@ -807,6 +818,7 @@ where
             rejected_activity,
             status,
             highest_observed,
+            warrants,
         } = merged_response;
         let valid_activity = match valid_activity {
             ChainItems::Hashes(hashes) => {
@@ -861,10 +873,33 @@ where
             rejected_activity,
             status,
             highest_observed,
+            warrants,
         };
         Ok(r)
     }
 
+    #[instrument(skip(self, options))]
+    /// Get the metadata for a hash without fetching the records.
+    /// If we are an authority for the hash our own dht database is used,
+    /// otherwise the responses of the remote authorities are merged.
+    pub async fn get_meta(
+        &mut self,
+        hash: AnyDhtHash,
+        options: GetMetaOptions,
+    ) -> CascadeResult<MetadataSet> {
+        let authority = self.am_i_an_authority(hash.clone()).await?;
+        match (authority, self.dht.clone()) {
+            (true, Some(vault)) => authority::handle_get_meta(vault, hash, (&options).into()).await,
+            _ => {
+                let mut merged = MetadataSet::default();
+                for meta in self.fetch_meta(hash, options).await? {
+                    merged.merge(meta);
+                }
+                Ok(merged)
+            }
+        }
+    }
+
     /// Get the validation package if it is cached without going to the network
     pub fn get_validation_package_local(
         &self,
@@ -40,6 +40,7 @@ use holochain_zome_types::ActionHashed;
 use holochain_zome_types::QueryFilter;
 use holochain_zome_types::SignedAction;
 use holochain_zome_types::SignedActionHashed;
+use holochain_zome_types::SignedWarrant;
 use holochain_zome_types::Timestamp;
 use holochain_zome_types::TryInto;
 use holochain_zome_types::ValidationStatus;
@@ -129,10 +130,17 @@ impl HolochainP2pDnaT for PassThroughNetwork {
     }
     async fn get_meta(
         &self,
-        _dht_hash: holo_hash::AnyDhtHash,
-        _options: actor::GetMetaOptions,
+        dht_hash: holo_hash::AnyDhtHash,
+        options: actor::GetMetaOptions,
     ) -> actor::HolochainP2pResult<Vec<MetadataSet>> {
-        todo!()
+        let mut out = Vec::new();
+        for env in &self.envs {
+            let r = authority::handle_get_meta(env.clone(), dht_hash.clone(), (&options).into())
+                .await
+                .map_err(|e| HolochainP2pError::Other(e.into()))?;
+            out.push(r);
+        }
+        Ok(out)
     }
     async fn get_links(
         &self,
@@ -203,6 +211,18 @@ impl HolochainP2pDnaT for PassThroughNetwork {
         todo!()
     }
 
+    async fn publish_warrant(
+        &self,
+        _warrant: SignedWarrant,
+        _timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<()> {
+        Ok(())
+    }
+
+    async fn block_agent(&self, _agent: AgentPubKey) -> actor::HolochainP2pResult<()> {
+        Ok(())
+    }
+
     async fn send_validation_receipt(
         &self,
         _to_agent: AgentPubKey,
@@ -223,6 +243,13 @@ impl HolochainP2pDnaT for PassThroughNetwork {
         todo!()
     }
 
+    async fn configure_space(
+        &self,
+        _config_override: holochain_p2p::kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> actor::HolochainP2pResult<()> {
+        todo!()
+    }
+
     async fn join(
         &self,
         _agent: AgentPubKey,
@@ -381,6 +408,22 @@ impl HolochainP2pDnaT for MockNetwork {
         todo!()
     }
 
+    async fn publish_warrant(
+        &self,
+        warrant: SignedWarrant,
+        timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<()> {
+        self.0
+            .lock()
+            .await
+            .publish_warrant(warrant, timeout_ms)
+            .await
+    }
+
+    async fn block_agent(&self, agent: AgentPubKey) -> actor::HolochainP2pResult<()> {
+        self.0.lock().await.block_agent(agent).await
+    }
+
     async fn send_validation_receipt(
         &self,
         _to_agent: AgentPubKey,
@@ -401,6 +444,13 @@ impl HolochainP2pDnaT for MockNetwork {
         todo!()
     }
 
+    async fn configure_space(
+        &self,
+        _config_override: holochain_p2p::kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> actor::HolochainP2pResult<()> {
+        todo!()
+    }
+
     async fn join(
         &self,
         _agent: AgentPubKey,
@@ -0,0 +1,87 @@
+use ghost_actor::dependencies::observability;
+use holochain_cascade::test_utils::*;
+use holochain_cascade::Cascade;
+use holochain_p2p::actor::GetMetaOptions;
+use holochain_state::prelude::test_cache_db;
+use holochain_state::prelude::test_dht_db;
+use holochain_types::metadata::EntryDhtStatus;
+use holochain_zome_types::request::MetadataRequest;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn meta_not_authority() {
+    observability::test_run().ok();
+
+    // Environments
+    let cache = test_cache_db();
+    let authority = test_dht_db();
+
+    // Data
+    let td = EntryTestData::create();
+    fill_db(&authority.to_db(), td.store_entry_op.clone());
+    fill_db(&authority.to_db(), td.create_link_op.clone());
+
+    // Network
+    let network = PassThroughNetwork::authority_for_nothing(vec![authority.to_db().into()]);
+
+    // Cascade
+    let mut cascade = Cascade::empty().with_network(network, cache.to_db());
+
+    let options = GetMetaOptions {
+        metadata_request: MetadataRequest {
+            entry_dht_status: true,
+            link_count: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let r = cascade
+        .get_meta(td.hash.clone().into(), options.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(r.actions.len(), 1);
+    assert_eq!(r.entry_dht_status, Some(EntryDhtStatus::Live));
+    assert_eq!(r.link_count, Some(1));
+
+    fill_db(&authority.to_db(), td.delete_entry_action_op.clone());
+
+    let r = cascade
+        .get_meta(td.hash.clone().into(), options)
+        .await
+        .unwrap();
+
+    assert_eq!(r.deletes.len(), 1);
+    assert_eq!(r.entry_dht_status, Some(EntryDhtStatus::Dead));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn meta_authority() {
+    observability::test_run().ok();
+
+    // Environments
+    let cache = test_cache_db();
+    let vault = test_dht_db();
+
+    // Data
+    let td = EntryTestData::create();
+    fill_db(&vault.to_db(), td.store_entry_op.clone());
+    fill_db(&vault.to_db(), td.update_content_op.clone());
+
+    // Network
+    // - Not expecting any calls to the network.
+    let network = PassThroughNetwork::authority_for_all(vec![]);
+
+    // Cascade
+    let mut cascade = Cascade::empty()
+        .with_dht(vault.to_db().into())
+        .with_network(network, cache.to_db());
+
+    let r = cascade
+        .get_meta(td.hash.clone().into(), Default::default())
+        .await
+        .unwrap();
+
+    assert_eq!(r.actions.len(), 1);
+    assert_eq!(r.updates.len(), 1);
+    assert!(r.deletes.is_empty());
+}
@@ -3,7 +3,10 @@ use holochain_types::prelude::*;
 use holochain_zome_types::cell::CellId;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 
-use crate::{FullStateDump, InstalledAppInfo};
+use crate::{
+    AuthenticationToken, AuthenticationTokenIssued, CellChainArchive, FullStateDump,
+    InstalledAppInfo, IssueAdminAuthenticationTokenPayload, IssueAppAuthenticationTokenPayload,
+};
 
 /// Represents the available conductor functions to call over an admin interface.
 ///
@@ -21,6 +24,8 @@ use crate::{FullStateDump, InstalledAppInfo};
 pub enum AdminRequest {
     /// Set up and register one or more new admin interfaces
     /// as specified by a list of configurations.
+    /// Each can be a websocket, a websocket wrapped in TLS or a unix domain
+    /// socket, see [`InterfaceDriver`](crate::config::InterfaceDriver).
     ///
     /// # Returns
     ///
@@ -47,6 +52,47 @@ pub enum AdminRequest {
     /// [`AdminResponse::CloneCellCreated`]
     CreateCloneCell(Box<CreateCloneCellPayload>),
 
+    /// Archive a clone cell.
+    ///
+    /// The clone cell is removed from the app's running cells, but its data is
+    /// kept, and it still counts towards the clone limit of its role.
+    /// It can be brought back with [`AdminRequest::RestoreCloneCell`].
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CloneCellArchived`]
+    ArchiveCloneCell(Box<CloneCellPayload>),
+
+    /// Restore an archived clone cell.
+    ///
+    /// The clone cell is added back to the app, and started if the app is running.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CloneCellRestored`]
+    RestoreCloneCell(Box<CloneCellPayload>),
+
+    /// Permanently delete an archived clone cell.
+    ///
+    /// All data of the clone cell is removed, and its slot in the clone
+    /// limit of its role is freed. This cannot be undone.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::ArchivedCloneCellDeleted`]
+    DeleteArchivedCloneCell(Box<CloneCellPayload>),
+
+    /// Provision the cell of an app role which was deferred at install time.
+    ///
+    /// Genesis is run on the role's cell, which is started if the app is
+    /// running. Roles which only exist as templates for clones can't be
+    /// provisioned.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AppRoleProvisioned`]
+    ProvisionAppRole(Box<ProvisionAppRolePayload>),
+
     /// Install an app from a list of DNA paths.
     ///
     /// Triggers genesis to be run on all cells and to be stored.
@@ -105,13 +151,54 @@ pub enum AdminRequest {
     /// [`AdminResponse::DnasListed`]
     ListDnas,
 
+    /// List the callbacks and zome functions exported by every zome of a DNA.
+    ///
+    /// Functions which any cell of the DNA has scheduled are listed with the
+    /// callbacks.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::ZomeFunctionsListed`]
+    ListZomeFunctions {
+        /// The hash of the DNA to list the functions of
+        dna_hash: DnaHash,
+    },
+
     /// Generate a new [`AgentPubKey`].
     ///
+    /// If the conductor is configured with DPKI, the key is registered with it.
+    ///
     /// # Returns
     ///
     /// [`AdminResponse::AgentPubKeyGenerated`]
     GenerateAgentPubKey,
 
+    /// Revoke an [`AgentPubKey`] in DPKI.
+    ///
+    /// Actions signed by the key after it was revoked are rejected during
+    /// system validation. Fails if the conductor is not configured with DPKI.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AgentPubKeyRevoked`]
+    RevokeAgentPubKey {
+        /// The key to revoke.
+        agent_key: AgentPubKey,
+    },
+
+    /// Replace an [`AgentPubKey`] with a newly generated key in DPKI.
+    ///
+    /// Actions signed by the old key after the rotation are rejected during
+    /// system validation. Fails if the conductor is not configured with DPKI.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AgentPubKeyRotated`]
+    RotateAgentPubKey {
+        /// The key to replace.
+        agent_key: AgentPubKey,
+    },
+
     /// List all the cell IDs in the conductor.
     ///
     /// # Returns
@@ -193,10 +280,33 @@ pub enum AdminRequest {
     /// a free port is chosen by the conductor.
     /// The response will contain the port chosen by the conductor if `None` was passed.
     ///
+    /// Optionally a `rate_limit` can be passed to limit how fast zome calls can
+    /// be made through this interface. Calls over the limit are rejected with
+    /// [`ExternalApiWireError::RateLimited`].
+    ///
+    /// Optionally a `tls` config can be passed to wrap the interface in TLS.
+    /// A TLS interface listens on all network interfaces rather than only on
+    /// localhost, so that it can be exposed on a LAN.
+    ///
+    /// If `require_auth` is true, each connection to the interface must
+    /// authenticate with [`AppRequest::Authenticate`] before it can make any
+    /// other request, using a token from
+    /// [`AdminRequest::IssueAppAuthenticationToken`].
+    ///
     /// [`AppRequest`]: super::AppRequest
+    /// [`AppRequest::Authenticate`]: super::AppRequest::Authenticate
     AttachAppInterface {
         /// Optional port number
         port: Option<u16>,
+        /// Optional limit on zome calls made through this interface
+        #[serde(default)]
+        rate_limit: Option<RateLimitConfig>,
+        /// Optional certificate and key to secure the interface with TLS
+        #[serde(default)]
+        tls: Option<crate::config::TlsConfig>,
+        /// Whether connections must authenticate with an app token
+        #[serde(default)]
+        require_auth: bool,
     },
 
     /// List all the app interfaces currently attached with [`AttachAppInterface`].
@@ -210,6 +320,54 @@ pub enum AdminRequest {
     /// [`AppRequest`]: super::AppRequest
     ListAppInterfaces,
 
+    /// Authenticate this connection to an admin interface which requires
+    /// authentication. This must be the first request made on the connection,
+    /// and if it fails the connection is closed.
+    ///
+    /// The token is either the secret configured for the interface, or a
+    /// token issued with [`AdminRequest::IssueAdminAuthenticationToken`].
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::Authenticated`]
+    Authenticate {
+        /// The secret or issued token
+        token: AuthenticationToken,
+    },
+
+    /// Issue a token which authenticates connections to admin interfaces
+    /// which require authentication.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AdminAuthenticationTokenIssued`]
+    IssueAdminAuthenticationToken(IssueAdminAuthenticationTokenPayload),
+
+    /// Issue a token which authenticates connections to app interfaces
+    /// which require authentication.
+    ///
+    /// A connection authenticated with the token is bound to the given app,
+    /// and any [`AppRequest`] it makes for another app is rejected.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AppAuthenticationTokenIssued`]
+    ///
+    /// [`AppRequest`]: super::AppRequest
+    IssueAppAuthenticationToken(IssueAppAuthenticationTokenPayload),
+
+    /// Revoke an admin or app token, so that it can no longer be used to
+    /// authenticate. Connections which have already been authenticated
+    /// with it are not affected.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::AuthenticationTokenRevoked`]
+    RevokeAuthenticationToken {
+        /// The token to revoke
+        token: AuthenticationToken,
+    },
+
     /// Dump the state of the cell specified by argument `cell_id`,
     /// including its chain, as a string containing JSON.
     ///
@@ -333,6 +491,100 @@ pub enum AdminRequest {
         /// The records to be inserted into the source chain.
         records: Vec<Record>,
     },
+
+    /// Export the source chain of a cell as a signed [`CellChainArchive`].
+    ///
+    /// The archive holds every record on the chain, including private entries
+    /// such as capability grants and claims, along with any warrants and
+    /// scheduled functions. It is signed by the cell's agent.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CellChainExported`]
+    ExportCellChain {
+        /// The cell whose chain is being exported.
+        cell_id: CellId,
+    },
+
+    /// Restore a source chain from a [`CellChainArchive`] made by
+    /// [`AdminRequest::ExportCellChain`].
+    ///
+    /// The archive's signature and version are checked, and its genesis records
+    /// must match the archived cell's [`DnaFile`] and pass the DNA's genesis
+    /// self-check. The records are then inserted as with
+    /// [`AdminRequest::AddRecords`], and scheduled functions are rescheduled.
+    ///
+    /// The [`DnaFile`] must already be installed on this conductor.
+    /// If the cell is installed, it is best to call [`AdminRequest::DisableApp`]
+    /// before running this command.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CellChainImported`]
+    ImportCellChain {
+        /// The archive to restore.
+        archive: CellChainArchive,
+        /// If this is true then all records in the source chain will be
+        /// removed before the archived records are inserted.
+        /// This is needed when genesis has already run for the cell on this
+        /// conductor.
+        /// **Warning**: this cannot be undone. Use with care!
+        truncate: bool,
+        /// If this is `true`, then the records will be validated before insertion.
+        validate: bool,
+    },
+
+    /// List the functions scheduled by cells, with when each is next due to run.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::ScheduledFunctionsListed`]
+    ListScheduledFunctions {
+        /// The cell to list the scheduled functions of.
+        /// If this is `None`, the functions of every cell the conductor has
+        /// created are listed, whether or not it has joined the network.
+        cell_id: Option<CellId>,
+    },
+
+    /// Look at the countersigning session a cell is taking part in, if any.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CountersigningSessionInspected`]
+    InspectCountersigningSession {
+        /// The cell to inspect.
+        cell_id: CellId,
+    },
+
+    /// Abandon the countersigning session a cell is taking part in and unlock
+    /// its source chain, e.g. when the session is stuck.
+    ///
+    /// The other parties to the session are not told about this, so the
+    /// session can't complete afterwards. A [`SystemSignal::AbandonedCountersigning`]
+    /// is emitted if there was a session to abandon.
+    ///
+    /// [`SystemSignal::AbandonedCountersigning`]: holochain_types::signal::SystemSignal::AbandonedCountersigning
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::CountersigningSessionAbandoned`]
+    AbandonCountersigningSession {
+        /// The cell whose session should be abandoned.
+        cell_id: CellId,
+    },
+
+    /// Rotate the key the conductor's databases are encrypted with.
+    ///
+    /// A new key is sealed by the keystore straight away, and every database
+    /// is re-encrypted with it (using SQLCipher's `PRAGMA rekey`) the next time
+    /// the conductor starts, as databases can't be re-encrypted while in use.
+    /// This is an error if the conductor was built without the `db-encryption`
+    /// feature.
+    ///
+    /// # Returns
+    ///
+    /// [`AdminResponse::DatabaseKeyRotationStaged`]
+    RotateDatabaseKey,
 }
 
 /// Represents the possible responses to an [`AdminRequest`]
@@ -378,6 +630,26 @@ pub enum AdminResponse {
     /// The response contains the [`CellId`] of the newly created clone.
     CloneCellCreated(CellId),
 
+    /// The successful response to an [`AdminRequest::ArchiveCloneCell`].
+    ///
+    /// It means the clone cell was archived successfully.
+    CloneCellArchived,
+
+    /// The successful response to an [`AdminRequest::RestoreCloneCell`].
+    ///
+    /// It means the clone cell was restored successfully.
+    CloneCellRestored,
+
+    /// The successful response to an [`AdminRequest::DeleteArchivedCloneCell`].
+    ///
+    /// It means the clone cell and all of its data were deleted.
+    ArchivedCloneCellDeleted,
+
+    /// The successful response to an [`AdminRequest::ProvisionAppRole`].
+    ///
+    /// The response contains the [`CellId`] of the newly provisioned cell.
+    AppRoleProvisioned(CellId),
+
     /// The successful response to an [`AdminRequest::AddAdminInterfaces`].
     ///
     /// It means the `AdminInterface`s have successfully been added.
@@ -388,11 +660,24 @@ pub enum AdminResponse {
     /// Contains a new [`AgentPubKey`] generated by the keystore.
     AgentPubKeyGenerated(AgentPubKey),
 
+    /// The successful response to an [`AdminRequest::RevokeAgentPubKey`].
+    AgentPubKeyRevoked,
+
+    /// The successful response to an [`AdminRequest::RotateAgentPubKey`].
+    ///
+    /// Contains the new [`AgentPubKey`] which replaces the old one.
+    AgentPubKeyRotated(AgentPubKey),
+
     /// The successful response to an [`AdminRequest::ListDnas`].
     ///
     /// Contains a list of the hashes of all installed DNAs.
     DnasListed(Vec<DnaHash>),
 
+    /// The successful response to an [`AdminRequest::ListZomeFunctions`].
+    ///
+    /// Contains the functions exported by each zome, in zome order.
+    ZomeFunctionsListed(Vec<ZomeFunctions>),
+
     /// The successful response to an [`AdminRequest::ListCellIds`].
     ///
     /// Contains a list of all the cell IDs in the conductor.
@@ -424,6 +709,20 @@ pub enum AdminResponse {
     /// The list of attached app interfaces.
     AppInterfacesListed(Vec<u16>),
 
+    /// The successful response to an [`AdminRequest::Authenticate`].
+    ///
+    /// The connection can now make any other request.
+    Authenticated,
+
+    /// The successful response to an [`AdminRequest::IssueAdminAuthenticationToken`].
+    AdminAuthenticationTokenIssued(AuthenticationTokenIssued),
+
+    /// The successful response to an [`AdminRequest::IssueAppAuthenticationToken`].
+    AppAuthenticationTokenIssued(AuthenticationTokenIssued),
+
+    /// The successful response to an [`AdminRequest::RevokeAuthenticationToken`].
+    AuthenticationTokenRevoked,
+
     /// The successful response to an [`AdminRequest::EnableApp`].
     ///
     /// It means the app was enabled successfully. If it was possible to
@@ -486,6 +785,33 @@ pub enum AdminResponse {
 
     /// The successful response to an [`AdminRequest::AddRecords`].
     RecordsAdded,
+
+    /// The successful response to an [`AdminRequest::ExportCellChain`].
+    CellChainExported(CellChainArchive),
+
+    /// The successful response to an [`AdminRequest::ImportCellChain`].
+    CellChainImported,
+
+    /// The successful response to an [`AdminRequest::ListScheduledFunctions`].
+    ///
+    /// Contains the scheduled functions of each cell, soonest first.
+    ScheduledFunctionsListed(Vec<ScheduledFunctionInfo>),
+
+    /// The successful response to an [`AdminRequest::InspectCountersigningSession`].
+    ///
+    /// Contains the state of the cell's session,
+    /// or `None` if its chain isn't locked for one.
+    CountersigningSessionInspected(Option<CountersigningSessionState>),
+
+    /// The successful response to an [`AdminRequest::AbandonCountersigningSession`].
+    ///
+    /// Contains whether there was a session to abandon.
+    CountersigningSessionAbandoned(bool),
+
+    /// The successful response to an [`AdminRequest::RotateDatabaseKey`].
+    ///
+    /// The databases are re-encrypted with the new key when the conductor restarts.
+    DatabaseKeyRotationStaged,
 }
 
 /// Error type that goes over the websocket wire.
@@ -511,6 +837,16 @@ pub enum ExternalApiWireError {
     ZomeCallUnauthorized(String),
     /// A countersigning session has failed.
     CountersigningSessionError(String),
+    /// A rate limit on an interface or cell was exceeded.
+    RateLimited(String),
+    /// The crypto request is unauthorized.
+    CryptoRequestUnauthorized(String),
+    /// The connection could not be authenticated, or must authenticate
+    /// before making this request.
+    AuthenticationFailed(String),
+    /// The connection is authenticated for a different app than the one
+    /// this request is for.
+    AppNotAuthorized(String),
 }
 
 impl ExternalApiWireError {
@@ -531,3 +867,29 @@ pub enum AppStatusFilter {
     Stopped,
     Paused,
 }
+
+/// A function scheduled by a cell, listed by [`AdminRequest::ListScheduledFunctions`].
+#[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes, Clone, PartialEq)]
+pub struct ScheduledFunctionInfo {
+    /// The cell which scheduled the function.
+    pub cell_id: CellId,
+    /// The zome and name of the function.
+    pub scheduled_fn: ScheduledFn,
+    /// The schedule the function is next run for.
+    /// This is `None` if it was scheduled to run once, as soon as possible.
+    pub schedule: Option<Schedule>,
+    /// When the function is next due to run.
+    pub next_run: Timestamp,
+}
+
+/// The state of a cell's countersigning session,
+/// returned by [`AdminRequest::InspectCountersigningSession`].
+#[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes, Clone, PartialEq)]
+pub struct CountersigningSessionState {
+    /// When the lock on the cell's source chain expires.
+    pub locked_until: Timestamp,
+    /// The entry being countersigned, if the cell has written it.
+    pub entry_hash: Option<EntryHash>,
+    /// The session the entry was written for, if the cell has written it.
+    pub session_data: Option<CounterSigningSessionData>,
+}
@@ -1,4 +1,4 @@
-use crate::{signal_subscription::SignalSubscription, ExternalApiWireError};
+use crate::{signal_subscription::SignalSubscription, AuthenticationToken, ExternalApiWireError};
 use holo_hash::AgentPubKey;
 use holochain_types::prelude::*;
 
@@ -12,6 +12,24 @@ use holochain_types::prelude::*;
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
 pub enum AppRequest {
+    /// Authenticate this connection to an app interface which requires
+    /// authentication, with a token issued by
+    /// [`AdminRequest::IssueAppAuthenticationToken`](crate::AdminRequest::IssueAppAuthenticationToken).
+    /// This must be the first request made on the connection, and if it fails
+    /// the connection is closed.
+    ///
+    /// The connection is then bound to the app the token was issued for.
+    /// Requests it makes for any other app are rejected with
+    /// [`ExternalApiWireError::AppNotAuthorized`], and it only receives the
+    /// signals of the app's cells.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::Authenticated`]
+    Authenticate {
+        /// The issued token
+        token: AuthenticationToken,
+    },
     /// Get info about the app identified by the given `installed_app_id` argument,
     /// including info about each cell installed by this app.
     ///
@@ -25,8 +43,12 @@ pub enum AppRequest {
         /// The app ID for which to get information
         installed_app_id: InstalledAppId,
     },
-    /// Is currently unimplemented and will return
-    /// an [`AppResponse::Unimplemented`].
+    /// Sign, encrypt or decrypt data with the keys of a cell's agent.
+    /// See [`CryptoRequest`] for how these requests are authorized.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::Crypto`]
     Crypto(Box<CryptoRequest>),
     /// Call a zome function. See [`ZomeCall`]
     /// to understand the data that must be provided.
@@ -39,8 +61,46 @@ pub enum AppRequest {
     #[deprecated = "use ZomeCall"]
     ZomeCallInvocation(Box<ZomeCall>),
 
-    /// Is currently unimplemented and will return
-    /// an [`AppResponse::Unimplemented`].
+    /// Archive a clone cell of an app.
+    ///
+    /// The clone cell stops running, but its data is kept and it can be
+    /// restored later with [`AppRequest::RestoreCloneCell`].
+    ///
+    /// Only allowed on a connection authenticated for the app.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::CloneCellArchived`]
+    ArchiveCloneCell(Box<CloneCellPayload>),
+
+    /// Restore an archived clone cell of an app.
+    ///
+    /// Only allowed on a connection authenticated for the app.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::CloneCellRestored`]
+    RestoreCloneCell(Box<CloneCellPayload>),
+
+    /// Provision the cell of an app role which was deferred at install time.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::AppRoleProvisioned`]
+    ProvisionAppRole(Box<ProvisionAppRolePayload>),
+
+    /// Choose which signals from an app's cells are sent over this connection.
+    ///
+    /// A connection receives no app signals until it subscribes, apart from
+    /// a connection authenticated for an app, which receives all of that
+    /// app's signals. It then receives the signals of the subscribed apps'
+    /// cells which its filters allow, with a later subscription for the same
+    /// app replacing the earlier one. Cells which join an app later, such as
+    /// clones, are included. System signals are always sent.
+    ///
+    /// # Returns
+    ///
+    /// [`AppResponse::SignalSubscriptionUpdated`]
     SignalSubscription(SignalSubscription),
 }
 
@@ -56,6 +116,11 @@ pub enum AppResponse {
     /// There has been an error during the handling of the request.
     Error(ExternalApiWireError),
 
+    /// The successful response to an [`AppRequest::Authenticate`].
+    ///
+    /// The connection can now make requests for the app it is bound to.
+    Authenticated,
+
     /// The succesful response to an [`AppRequest::AppInfo`].
     ///
     /// Option will be `None` if there is no installed app with the given `installed_app_id`.
@@ -72,6 +137,23 @@ pub enum AppResponse {
 
     #[deprecated = "use ZomeCall"]
     ZomeCallInvocation(Box<ExternIO>),
+
+    /// The successful response to an [`AppRequest::ArchiveCloneCell`].
+    CloneCellArchived,
+
+    /// The successful response to an [`AppRequest::RestoreCloneCell`].
+    CloneCellRestored,
+
+    /// The successful response to an [`AppRequest::ProvisionAppRole`].
+    ///
+    /// The response contains the [`CellId`] of the newly provisioned cell.
+    AppRoleProvisioned(CellId),
+
+    /// The successful response to an [`AppRequest::SignalSubscription`].
+    SignalSubscriptionUpdated,
+
+    /// The successful response to an [`AppRequest::Crypto`].
+    Crypto(Box<CryptoResponse>),
 }
 
 /// The data provided over an app interface in order to make a zome call
@@ -99,13 +181,88 @@ pub struct ZomeCall {
     pub provenance: AgentPubKey,
 }
 
-#[allow(missing_docs)]
+/// The data provided over an app interface in order to use the keys of a cell's agent.
+///
+/// These requests are authorized like zome calls, with a capability grant for
+/// the function given by [`CryptoOperation::granted_function`]. The cell's own
+/// agent may always sign. Boxing and unboxing use x25519 keys which are not
+/// tied to the agent, so they always need a grant committed by the cell,
+/// which names the key.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CryptoRequest {
+    /// The cell whose agent's keys are used
+    pub cell_id: CellId,
+    /// The capability request authorization
+    pub cap_secret: Option<CapSecret>,
+    /// The provenance (source) of the request
+    pub provenance: AgentPubKey,
+    /// What to do
+    pub operation: CryptoOperation,
+}
+
+/// The operations which can be requested with a [`CryptoRequest`].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "data")]
+pub enum CryptoOperation {
+    /// Sign the data with the agent key of the cell.
+    Sign(Bytes),
+    /// Box the data for a recipient.
+    /// The keystore must hold the private key of the sender.
+    Encrypt(X25519XSalsa20Poly1305Encrypt),
+    /// Open data boxed for a recipient.
+    /// The keystore must hold the private key of the recipient.
+    Decrypt(X25519XSalsa20Poly1305Decrypt),
+}
+
+impl CryptoOperation {
+    /// The name of the operation, as used in capability grants
+    pub fn fn_name(&self) -> FunctionName {
+        match self {
+            CryptoOperation::Sign(_) => "sign",
+            CryptoOperation::Encrypt(_) => "encrypt",
+            CryptoOperation::Decrypt(_) => "decrypt",
+        }
+        .into()
+    }
+
+    /// The function a capability grant must include to allow this operation:
+    /// `(CRYPTO_GRANT_ZOME_NAME, "sign")` for signing, and for boxing and
+    /// unboxing the name of the operation and the hex of the x25519 key whose
+    /// private half is used, the sender's or the recipient's,
+    /// e.g. `(CRYPTO_GRANT_ZOME_NAME, "decrypt:0a1b..")`.
+    pub fn granted_function(&self) -> GrantedFunction {
+        let key = match self {
+            CryptoOperation::Sign(_) => None,
+            CryptoOperation::Encrypt(input) => Some(input.as_sender_ref()),
+            CryptoOperation::Decrypt(input) => Some(input.as_recipient_ref()),
+        };
+        let fn_name = match key {
+            Some(key) => {
+                let hex: String = key.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+                format!("{}:{}", self.fn_name(), hex).into()
+            }
+            None => self.fn_name(),
+        };
+        (CRYPTO_GRANT_ZOME_NAME.into(), fn_name)
+    }
+
+    /// Whether this operation uses the cell's agent key,
+    /// which the cell's own agent may always use
+    pub fn uses_agent_key(&self) -> bool {
+        matches!(self, CryptoOperation::Sign(_))
+    }
+}
+
+/// The result of a [`CryptoOperation`].
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type", content = "data")]
-pub enum CryptoRequest {
-    Sign(String),
-    Decrypt(String),
-    Encrypt(String),
+pub enum CryptoResponse {
+    /// The signature of the data.
+    Signed(Signature),
+    /// The boxed data.
+    Encrypted(XSalsa20Poly1305EncryptedData),
+    /// The opened data.
+    Decrypted(XSalsa20Poly1305Data),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
@@ -113,8 +270,10 @@ pub enum CryptoRequest {
 pub struct InstalledAppInfo {
     /// The unique identifier for an installed app in this conductor
     pub installed_app_id: InstalledAppId,
-    /// Info about the cells installed in this app
+    /// Info about the cells installed in this app, including active clone cells
     pub cell_data: Vec<InstalledCell>,
+    /// Info about the archived clone cells of this app
+    pub archived_clones: Vec<InstalledCell>,
     /// The app's current status, in an API-friendly format
     pub status: InstalledAppInfoStatus,
 }
@@ -126,10 +285,25 @@ impl InstalledAppInfo {
         let cell_data = app
             .provisioned_cells()
             .map(|(role_id, id)| InstalledCell::new(id.clone(), role_id.clone()))
+            .chain(app.roles().iter().flat_map(|(role_id, role)| {
+                role.clones()
+                    .iter()
+                    .map(move |id| InstalledCell::new(id.clone(), role_id.clone()))
+            }))
+            .collect();
+        let archived_clones = app
+            .roles()
+            .iter()
+            .flat_map(|(role_id, role)| {
+                role.archived_clones()
+                    .iter()
+                    .map(move |id| InstalledCell::new(id.clone(), role_id.clone()))
+            })
             .collect();
         Self {
             installed_app_id,
             cell_data,
+            archived_clones,
             status,
         }
     }
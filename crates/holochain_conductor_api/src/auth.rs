@@ -0,0 +1,83 @@
+//! Tokens which authenticate connections to admin and app interfaces.
+//!
+//! An interface which requires authentication only serves a connection
+//! once its first request has presented a valid token, with
+//! [`AdminRequest::Authenticate`](crate::AdminRequest::Authenticate) or
+//! [`AppRequest::Authenticate`](crate::AppRequest::Authenticate).
+
+use holochain_types::app::InstalledAppId;
+use holochain_zome_types::Timestamp;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A token which authenticates a connection to an interface.
+pub type AuthenticationToken = Vec<u8>;
+
+/// How long an issued token can be used for by default, in seconds.
+/// This is enough time for a client to connect with it.
+pub const DEFAULT_TOKEN_EXPIRY_SECONDS: u64 = 30;
+
+fn default_expiry_seconds() -> u64 {
+    DEFAULT_TOKEN_EXPIRY_SECONDS
+}
+
+fn default_single_use() -> bool {
+    true
+}
+
+/// Request a token which authenticates connections to admin interfaces.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueAdminAuthenticationTokenPayload {
+    /// How many seconds the token can be used for.
+    /// If this is 0, the token never expires.
+    #[serde(default = "default_expiry_seconds")]
+    pub expiry_seconds: u64,
+    /// Whether the token is revoked once it has authenticated a connection.
+    #[serde(default = "default_single_use")]
+    pub single_use: bool,
+}
+
+impl Default for IssueAdminAuthenticationTokenPayload {
+    fn default() -> Self {
+        Self {
+            expiry_seconds: DEFAULT_TOKEN_EXPIRY_SECONDS,
+            single_use: true,
+        }
+    }
+}
+
+/// Request a token which authenticates connections to app interfaces,
+/// binding each connection to a single app.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueAppAuthenticationTokenPayload {
+    /// The app which connections authenticated with the token can make requests for.
+    pub installed_app_id: InstalledAppId,
+    /// How many seconds the token can be used for.
+    /// If this is 0, the token never expires.
+    #[serde(default = "default_expiry_seconds")]
+    pub expiry_seconds: u64,
+    /// Whether the token is revoked once it has authenticated a connection.
+    #[serde(default = "default_single_use")]
+    pub single_use: bool,
+}
+
+impl IssueAppAuthenticationTokenPayload {
+    /// A single use token for this app which expires after
+    /// [`DEFAULT_TOKEN_EXPIRY_SECONDS`].
+    pub fn for_app(installed_app_id: InstalledAppId) -> Self {
+        Self {
+            installed_app_id,
+            expiry_seconds: DEFAULT_TOKEN_EXPIRY_SECONDS,
+            single_use: true,
+        }
+    }
+}
+
+/// A newly issued token.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthenticationTokenIssued {
+    /// The token to authenticate with.
+    pub token: AuthenticationToken,
+    /// When the token stops being accepted, if ever.
+    pub expires_at: Option<Timestamp>,
+}
@@ -0,0 +1,40 @@
+//! A portable archive of a cell's source chain, used to back up an agent's
+//! chain and restore it on another conductor.
+
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::prelude::*;
+
+/// The version of the [`CellChainArchive`] format written by this conductor.
+///
+/// Archives with any other version are rejected on import.
+pub const CELL_CHAIN_ARCHIVE_VERSION: u32 = 1;
+
+/// A signed archive of everything needed to restore a cell's source chain.
+///
+/// Produced by [`AdminRequest::ExportCellChain`](crate::AdminRequest::ExportCellChain)
+/// and consumed by [`AdminRequest::ImportCellChain`](crate::AdminRequest::ImportCellChain).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct CellChainArchive {
+    /// The archived data.
+    pub content: CellChainArchiveContent,
+    /// The signature of the cell's agent over the `content`.
+    pub signature: Signature,
+}
+
+/// The data held by a [`CellChainArchive`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct CellChainArchiveContent {
+    /// The archive format version, see [`CELL_CHAIN_ARCHIVE_VERSION`].
+    pub version: u32,
+    /// The cell whose chain was archived.
+    pub cell_id: CellId,
+    /// When the archive was created.
+    pub exported_at: Timestamp,
+    /// Every record on the chain in order, starting with genesis.
+    /// Private entries, including capability grants and claims, are included.
+    pub records: Vec<Record>,
+    /// Warrants held about this agent.
+    pub warrants: Vec<SignedWarrant>,
+    /// The functions this agent had scheduled.
+    pub scheduled_fns: Vec<(ScheduledFn, Option<Schedule>)>,
+}
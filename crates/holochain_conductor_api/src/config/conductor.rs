@@ -2,6 +2,8 @@
 //! This module is used to configure the conductor
 
 use holochain_types::db::DbSyncStrategy;
+use holochain_types::prelude::ScheduleCatchUp;
+use holochain_types::rate_limit::RateLimitConfig;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
@@ -23,6 +25,11 @@ pub use error::*;
 pub use keystore_config::KeystoreConfig;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
+use std::time::Duration;
+
+/// The longest a zome can sleep for in one call to `sleep`, unless
+/// [`ConductorConfig::max_sleep_ms`] says otherwise.
+pub const DEFAULT_MAX_SLEEP_MS: u64 = 10_000;
 
 // TODO change types from "stringly typed" to Url2
 /// All the config information for the conductor
@@ -40,7 +47,8 @@ pub struct ConductorConfig {
     /// keys for new instances.
     pub dpki: Option<DpkiConfig>,
 
-    /// Setup admin interfaces to control this conductor through a websocket connection.
+    /// Setup admin interfaces to control this conductor through a websocket connection,
+    /// which may be wrapped in TLS or run over a unix domain socket.
     pub admin_interfaces: Option<Vec<AdminInterfaceConfig>>,
 
     /// Optional config for the network module.
@@ -56,6 +64,27 @@ pub struct ConductorConfig {
     ///
     /// [sqlite documentation]: https://www.sqlite.org/pragma.html#pragma_synchronous
     pub db_sync_strategy: DbSyncStrategy,
+
+    /// Optional limits on how fast zome calls can be made into each cell,
+    /// and how fast each cell can commit data.
+    #[serde(default)]
+    pub cell_rate_limit: Option<RateLimitConfig>,
+
+    /// What to do about runs of persisted scheduled functions which were
+    /// missed while the conductor wasn't running.
+    /// By default they are skipped.
+    #[serde(default)]
+    pub schedule_catch_up: ScheduleCatchUp,
+
+    /// How long a zome call can run for, including any time it spends
+    /// sleeping, before it fails. Zome calls have no time limit if unset.
+    #[serde(default)]
+    pub zome_call_timeout_ms: Option<u64>,
+
+    /// The longest a zome can sleep for in one call to `sleep`.
+    /// Defaults to [`DEFAULT_MAX_SLEEP_MS`].
+    #[serde(default)]
+    pub max_sleep_ms: Option<u64>,
     //
     //
     // Which signals to emit
@@ -82,6 +111,16 @@ impl ConductorConfig {
         })?;
         config_from_yaml(&config_yaml)
     }
+
+    /// How long a zome call can run for, if it is limited.
+    pub fn zome_call_timeout(&self) -> Option<Duration> {
+        self.zome_call_timeout_ms.map(Duration::from_millis)
+    }
+
+    /// The longest a zome can sleep for in one call to `sleep`.
+    pub fn max_sleep(&self) -> Duration {
+        Duration::from_millis(self.max_sleep_ms.unwrap_or(DEFAULT_MAX_SLEEP_MS))
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +166,10 @@ pub mod tests {
                 keystore: KeystoreConfig::DangerTestKeystoreLegacyDeprecated,
                 admin_interfaces: None,
                 db_sync_strategy: DbSyncStrategy::default(),
+                cell_rate_limit: None,
+                schedule_catch_up: ScheduleCatchUp::Skip,
+                zome_call_timeout_ms: None,
+                max_sleep_ms: None,
             }
         );
     }
@@ -153,6 +196,17 @@ pub mod tests {
       - driver:
           type: websocket
           port: 1234
+      - driver:
+          type: unix_socket
+          path: /path/to/admin.sock
+      - driver:
+          type: tls_websocket
+          port: 1235
+          tls:
+            cert_path: /path/to/cert.pem
+            key_path: /path/to/key.pem
+        auth:
+          secret: admin-secret
 
     network:
       bootstrap_service: https://bootstrap-staging.holo.host
@@ -177,6 +231,11 @@ pub mod tests {
       network_type: quic_bootstrap
 
     db_sync_strategy: Fast
+
+    schedule_catch_up: run_once
+
+    zome_call_timeout_ms: 30000
+    max_sleep_ms: 5000
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
         use holochain_p2p::kitsune_p2p::*;
@@ -210,17 +269,44 @@ pub mod tests {
                 environment_path: PathBuf::from("/path/to/env").into(),
                 dpki: Some(DpkiConfig {
                     instance_id: "some_id".into(),
-                    init_params: "some_params".into()
+                    init_params: "some_params".into(),
+                    bundle_path: None,
+                    allow_unregistered_keys: false,
                 }),
                 keystore: KeystoreConfig::LairServerLegacyDeprecated {
                     keystore_path: None,
                     danger_passphrase_insecure_from_config: "test-passphrase".to_string(),
                 },
-                admin_interfaces: Some(vec![AdminInterfaceConfig {
-                    driver: InterfaceDriver::Websocket { port: 1234 }
-                }]),
+                admin_interfaces: Some(vec![
+                    AdminInterfaceConfig {
+                        driver: InterfaceDriver::Websocket { port: 1234 },
+                        auth: None,
+                    },
+                    AdminInterfaceConfig {
+                        driver: InterfaceDriver::UnixSocket {
+                            path: PathBuf::from("/path/to/admin.sock")
+                        },
+                        auth: None,
+                    },
+                    AdminInterfaceConfig {
+                        driver: InterfaceDriver::TlsWebsocket {
+                            port: 1235,
+                            tls: TlsConfig {
+                                cert_path: PathBuf::from("/path/to/cert.pem"),
+                                key_path: PathBuf::from("/path/to/key.pem"),
+                            }
+                        },
+                        auth: Some(AdminInterfaceAuth {
+                            secret: Some("admin-secret".into()),
+                        }),
+                    },
+                ]),
                 network: Some(network_config),
                 db_sync_strategy: DbSyncStrategy::Fast,
+                cell_rate_limit: None,
+                schedule_catch_up: ScheduleCatchUp::RunOnce,
+                zome_call_timeout_ms: Some(30000),
+                max_sleep_ms: Some(5000),
             }
         );
     }
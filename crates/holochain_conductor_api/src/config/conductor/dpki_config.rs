@@ -1,13 +1,23 @@
-// Legacy config that will probably change
-#![allow(missing_docs)]
-
 use serde::Deserialize;
 use serde::Serialize;
+use std::path::PathBuf;
 
 /// Configure which app instance ID to treat as the DPKI application handler
 /// as well as what parameters to pass it on its initialization.
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub struct DpkiConfig {
+    /// The installed app ID of the DPKI app.
     pub instance_id: String,
+    /// Passed to the DPKI app's `init` function when it is first installed.
     pub init_params: String,
+    /// The app bundle to install as the DPKI app if no app with
+    /// `instance_id` is installed yet.
+    #[serde(default)]
+    pub bundle_path: Option<PathBuf>,
+    /// Whether sys validation trusts keys which DPKI has no record of,
+    /// such as those of agents on conductors which don't use this DPKI.
+    /// If this is `false`, their actions wait for the key to be registered,
+    /// and are rejected if it never is.
+    #[serde(default)]
+    pub allow_unregistered_keys: bool,
 }
@@ -41,6 +41,17 @@ pub enum KeystoreConfig {
         /// This value is also accessible by running `lair-keystore url`.
         connection_url: url2::Url2,
     },
+
+    /// Run a keystore inside the conductor process, persisted to encrypted
+    /// files in the given directory. This needs no external lair-keystore
+    /// process, which suits headless servers.
+    /// The keystore is created on first use.
+    /// This keystore type requires a secure passphrase specified
+    /// to the cli binary entrypoint for this Holochain conductor process.
+    FileKeystore {
+        /// The directory holding the keystore files.
+        keystore_path: std::path::PathBuf,
+    },
     //
     // DISABLED - we can't pull the full lair_keystore crate in as a dep
     //            until we make db-encryption feature the default.
@@ -1,15 +1,32 @@
 use serde::Deserialize;
 use serde::Serialize;
+use std::path::PathBuf;
 
 /// Information neeeded to spawn an admin interface
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub struct AdminInterfaceConfig {
     /// By what means the interface will be exposed.
-    /// Currently the only option is a local websocket running on a configurable port.
+    /// This is a local websocket running on a configurable port, a websocket
+    /// wrapped in TLS, or a unix domain socket.
     pub driver: InterfaceDriver,
-    // How long will this interface be accessible between authentications?
-    // TODO: implement once we have authentication
-    // _session_duration_seconds: Option<u32>,
+    /// If set, each connection must authenticate with
+    /// [`AdminRequest::Authenticate`](crate::AdminRequest::Authenticate)
+    /// before it can make any other request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AdminInterfaceAuth>,
+}
+
+/// How connections to an admin interface can authenticate.
+///
+/// A connection can always authenticate with a token issued through
+/// [`AdminRequest::IssueAdminAuthenticationToken`](crate::AdminRequest::IssueAdminAuthenticationToken),
+/// for instance by a client of another admin interface.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq, Default)]
+pub struct AdminInterfaceAuth {
+    /// A secret which authenticates any connection presenting it as its token,
+    /// encoded as UTF-8.
+    #[serde(default)]
+    pub secret: Option<String>,
 }
 
 /// Configuration for interfaces, specifying the means by which an interface
@@ -29,13 +46,42 @@ pub enum InterfaceDriver {
         /// The port on which to establish the WebsocketListener
         port: u16,
     },
+    /// An interface implemented via websockets wrapped in TLS.
+    /// Unlike a plain websocket it listens on all network interfaces,
+    /// so that it can be reached from the LAN.
+    TlsWebsocket {
+        /// The port on which to establish the WebsocketListener
+        port: u16,
+        /// The certificate and key to secure connections with
+        tls: TlsConfig,
+    },
+    /// An interface implemented via websockets over a unix domain socket.
+    /// No TCP port is opened, and only users who can write to the socket
+    /// file can connect. The file is created readable and writable only by
+    /// the conductor's user.
+    UnixSocket {
+        /// Where to create the socket file
+        path: PathBuf,
+    },
+}
+
+/// Where to find the certificate and private key for a TLS interface.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq, Hash)]
+pub struct TlsConfig {
+    /// A PEM file holding the certificate chain
+    pub cert_path: PathBuf,
+    /// A PEM file holding the PKCS #8 private key
+    pub key_path: PathBuf,
 }
 
 impl InterfaceDriver {
-    /// Get the port for this driver.
-    pub fn port(&self) -> u16 {
+    /// Get the port for this driver, if it listens on one.
+    pub fn port(&self) -> Option<u16> {
         match self {
-            InterfaceDriver::Websocket { port } => *port,
+            InterfaceDriver::Websocket { port } | InterfaceDriver::TlsWebsocket { port, .. } => {
+                Some(*port)
+            }
+            InterfaceDriver::UnixSocket { .. } => None,
         }
     }
 }
@@ -13,6 +13,26 @@ pub struct SignalSubscription {
     filters: SignalFilterSet,
 }
 
+impl SignalSubscription {
+    /// Constructor
+    pub fn new(installed_app_id: InstalledAppId, filters: SignalFilterSet) -> Self {
+        Self {
+            installed_app_id,
+            filters,
+        }
+    }
+
+    /// Accessor
+    pub fn installed_app_id(&self) -> &InstalledAppId {
+        &self.installed_app_id
+    }
+
+    /// Accessor
+    pub fn filters(&self) -> &SignalFilterSet {
+        &self.filters
+    }
+}
+
 /// Associate a SignalFilter with each Cell in an App.
 /// The filtering can be interpreted as inclusive or exclusive,
 /// depending on the use case.
@@ -46,6 +66,14 @@ impl SignalFilterSet {
     pub fn block_all() -> Self {
         SignalFilterSet::Include(HashMap::new())
     }
+
+    /// Whether signals from this Cell are allowed through
+    pub fn allows(&self, cell_id: &CellId) -> bool {
+        match self {
+            SignalFilterSet::Include(filters) => filters.contains_key(cell_id),
+            SignalFilterSet::Exclude(filters) => !filters.contains_key(cell_id),
+        }
+    }
 }
 
 /// Specifies fine-grained filter controls for the signals
@@ -65,3 +93,33 @@ impl SignalFilter {
         SignalFilter
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holo_hash::{AgentPubKey, DnaHash};
+
+    #[test]
+    fn filter_set_allows() {
+        let cell = |i| {
+            CellId::new(
+                DnaHash::from_raw_36(vec![i; 36]),
+                AgentPubKey::from_raw_36(vec![i; 36]),
+            )
+        };
+        let (a, b) = (cell(0), cell(1));
+        let only_a = [(a.clone(), SignalFilter::empty())].into_iter().collect();
+
+        assert!(SignalFilterSet::allow_all().allows(&a));
+        assert!(!SignalFilterSet::block_all().allows(&a));
+        let include = SignalFilterSet::Include(only_a);
+        assert!(include.allows(&a));
+        assert!(!include.allows(&b));
+        let exclude = match include {
+            SignalFilterSet::Include(filters) => SignalFilterSet::Exclude(filters),
+            _ => unreachable!(),
+        };
+        assert!(!exclude.allows(&a));
+        assert!(exclude.allows(&b));
+    }
+}
@@ -1,6 +1,9 @@
 //! Rate limiting data types
 
+use std::collections::BTreeMap;
+
 use holochain_serialized_bytes::prelude::*;
+use kitsune_p2p_timestamp::Timestamp;
 
 use crate::{Create, CreateLink, Delete, Entry, Update};
 
@@ -99,3 +102,157 @@ impl From<EntryRateWeight> for RateWeight {
         }
     }
 }
+
+/// The limits of a single rate limiting bucket, as defined by the DNA.
+///
+/// Each action assigned to a bucket by the `weigh` callback adds its units to
+/// the bucket, and the bucket drains by `drain_amount` units every
+/// `drain_interval_ms` milliseconds. An author whose chain fills a bucket past
+/// its `capacity` is producing invalid actions.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    SerializedBytes,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RateBucketLimit {
+    /// The most units the bucket can hold at once.
+    pub capacity: RateBucketCapacity,
+    /// How many units drain from the bucket every interval.
+    pub drain_amount: RateBucketCapacity,
+    /// The length of a drain interval in milliseconds.
+    pub drain_interval_ms: u64,
+}
+
+/// The rate limiting buckets defined by a DNA.
+/// Buckets without an entry here are unlimited.
+pub type RateLimits = BTreeMap<RateBucketId, RateBucketLimit>;
+
+/// The fill level of a single bucket while replaying an author's chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateBucketState {
+    /// The units currently held by the bucket.
+    pub level: RateBucketCapacity,
+    /// The time up to which draining has been accounted for.
+    pub drained_until: Timestamp,
+}
+
+impl RateBucketState {
+    /// An empty bucket starting at the given time.
+    pub fn new(start: Timestamp) -> Self {
+        Self {
+            level: 0,
+            drained_until: start,
+        }
+    }
+
+    /// Drain the bucket up to `timestamp` and then add `units` to it.
+    ///
+    /// Returns the new level, or the level the bucket would have reached if
+    /// that exceeds the bucket's capacity. On overflow the state is left
+    /// drained but unfilled.
+    pub fn fill(
+        &mut self,
+        limit: &RateBucketLimit,
+        timestamp: Timestamp,
+        units: RateUnits,
+    ) -> Result<RateBucketCapacity, RateBucketCapacity> {
+        self.drain(limit, timestamp);
+        let level = self.level.saturating_add(units as RateBucketCapacity);
+        if level > limit.capacity {
+            Err(level)
+        } else {
+            self.level = level;
+            Ok(level)
+        }
+    }
+
+    fn drain(&mut self, limit: &RateBucketLimit, timestamp: Timestamp) {
+        let elapsed_ms = timestamp
+            .as_micros()
+            .saturating_sub(self.drained_until.as_micros())
+            .max(0) as u64
+            / 1000;
+        if limit.drain_interval_ms == 0 {
+            self.level = 0;
+            self.drained_until = timestamp;
+            return;
+        }
+        let intervals = elapsed_ms / limit.drain_interval_ms;
+        let drained = intervals.saturating_mul(limit.drain_amount as u64);
+        self.level = (self.level as u64).saturating_sub(drained) as RateBucketCapacity;
+        if self.level == 0 {
+            // An empty bucket can't bank drain time for later.
+            self.drained_until = timestamp;
+        } else {
+            let advanced_ms = (intervals * limit.drain_interval_ms).min(i64::MAX as u64 / 1000);
+            self.drained_until = Timestamp::from_micros(
+                self.drained_until
+                    .as_micros()
+                    .saturating_add(advanced_ms as i64 * 1000),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: i64) -> Timestamp {
+        Timestamp::from_micros(ms * 1000)
+    }
+
+    #[test]
+    fn bucket_fills_and_overflows() {
+        let limit = RateBucketLimit {
+            capacity: 10,
+            drain_amount: 1,
+            drain_interval_ms: 1000,
+        };
+        let mut state = RateBucketState::new(ms(0));
+        assert_eq!(state.fill(&limit, ms(0), 6), Ok(6));
+        assert_eq!(state.fill(&limit, ms(100), 4), Ok(10));
+        assert_eq!(state.fill(&limit, ms(200), 1), Err(11));
+        // The failed fill didn't change the level.
+        assert_eq!(state.level, 10);
+    }
+
+    #[test]
+    fn bucket_drains_over_time() {
+        let limit = RateBucketLimit {
+            capacity: 10,
+            drain_amount: 2,
+            drain_interval_ms: 1000,
+        };
+        let mut state = RateBucketState::new(ms(0));
+        assert_eq!(state.fill(&limit, ms(0), 10), Ok(10));
+        // Partial intervals don't drain but are not lost either.
+        assert_eq!(state.fill(&limit, ms(1500), 2), Ok(10));
+        assert_eq!(state.fill(&limit, ms(2000), 2), Ok(10));
+        // A long pause empties the bucket without banking credit.
+        assert_eq!(state.fill(&limit, ms(100_000), 10), Ok(10));
+        assert_eq!(state.fill(&limit, ms(100_001), 1), Err(11));
+    }
+
+    #[test]
+    fn zero_interval_always_drains() {
+        let limit = RateBucketLimit {
+            capacity: 5,
+            drain_amount: 0,
+            drain_interval_ms: 0,
+        };
+        let mut state = RateBucketState::new(ms(0));
+        assert_eq!(state.fill(&limit, ms(0), 5), Ok(5));
+        assert_eq!(state.fill(&limit, ms(0), 5), Ok(5));
+        assert_eq!(state.fill(&limit, ms(0), 6), Err(6));
+    }
+}
@@ -0,0 +1,72 @@
+//! Pluggable backends for keystores that run inside the conductor process.
+//!
+//! An in-process keystore is a lair server whose entries are persisted by a
+//! [`KeystoreBackend`]. The server is reached through the same
+//! [`MetaLairClient::NewLair`] client as an external lair process, so every
+//! backend supports the full keystore api.
+
+use crate::*;
+use ghost_actor::dependencies::futures::future::BoxFuture;
+use ghost_actor::dependencies::futures::future::FutureExt;
+use kitsune_p2p_types::dependencies::lair_keystore_api;
+use lair_keystore_api::in_proc_keystore::InProcKeystore;
+use lair_keystore_api::prelude::*;
+use std::sync::Arc;
+
+/// Decides where an in-process keystore keeps its config and entries.
+pub trait KeystoreBackend: 'static + Send + Sync {
+    /// Load the lair server config for this backend, creating it
+    /// from the passphrase if it does not exist yet.
+    fn get_or_create_config(
+        &self,
+        passphrase: sodoken::BufRead,
+    ) -> BoxFuture<'static, LairResult<LairServerConfig>>;
+
+    /// The factory for the store holding this backend's keystore entries.
+    fn store_factory(&self) -> LairStoreFactory;
+}
+
+/// Run a lair server in this process, persisted by the given backend.
+pub async fn spawn_in_proc_keystore<B: KeystoreBackend>(
+    backend: B,
+    passphrase: sodoken::BufRead,
+) -> LairResult<InProcKeystore> {
+    let config = backend.get_or_create_config(passphrase.clone()).await?;
+    InProcKeystore::new(config, backend.store_factory(), passphrase).await
+}
+
+/// Run a lair server in this process, persisted by the given backend,
+/// and connect a [`MetaLairClient`] to it.
+pub async fn spawn_backend_keystore<B: KeystoreBackend>(
+    backend: B,
+    passphrase: sodoken::BufRead,
+) -> LairResult<MetaLairClient> {
+    let keystore = spawn_in_proc_keystore(backend, passphrase).await?;
+    let client = keystore.new_client().await?;
+    Ok(MetaLairClient::NewLair(client))
+}
+
+/// A backend which keeps everything in memory.
+/// Nothing survives a restart, so this is only useful for testing.
+pub struct MemKeystoreBackend;
+
+impl KeystoreBackend for MemKeystoreBackend {
+    fn get_or_create_config(
+        &self,
+        passphrase: sodoken::BufRead,
+    ) -> BoxFuture<'static, LairResult<LairServerConfig>> {
+        async move {
+            let config = PwHashLimits::Minimum
+                .with_exec(|| {
+                    lair_keystore_api::config::LairServerConfigInner::new("/", passphrase)
+                })
+                .await?;
+            Ok(Arc::new(config))
+        }
+        .boxed()
+    }
+
+    fn store_factory(&self) -> LairStoreFactory {
+        lair_keystore_api::mem_store::create_mem_store_factory()
+    }
+}
@@ -0,0 +1,335 @@
+//! A keystore persisted to an encrypted file, for headless servers
+//! that cannot run a separate lair-keystore process.
+//!
+//! The keystore directory holds two files:
+//! - the lair server config, which holds the salt and encrypted context key
+//!   needed to turn the passphrase back into the store's encryption key.
+//! - the entry store, which is encrypted as a whole with that key. The
+//!   secrets inside it are also encrypted individually, as in any lair store.
+
+use crate::backend::*;
+use crate::*;
+use ghost_actor::dependencies::futures::future::BoxFuture;
+use ghost_actor::dependencies::futures::future::FutureExt;
+use kitsune_p2p_types::dependencies::lair_keystore_api;
+use lair_keystore_api::lair_store::traits::*;
+use lair_keystore_api::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// The name of the lair server config file in the keystore directory.
+pub const CONFIG_FILE_NAME: &str = "keystore-config.yaml";
+
+/// The name of the encrypted entry store in the keystore directory.
+pub const STORE_FILE_NAME: &str = "keystore-store.bin";
+
+/// Spawn a keystore persisted to encrypted files in `keystore_path`.
+/// The directory is created, and the keystore initialized with the
+/// passphrase, if it does not exist yet.
+pub async fn spawn_file_keystore(
+    keystore_path: impl Into<PathBuf>,
+    passphrase: sodoken::BufRead,
+) -> LairResult<MetaLairClient> {
+    spawn_backend_keystore(FileKeystoreBackend::new(keystore_path), passphrase).await
+}
+
+/// A [`KeystoreBackend`] persisted to encrypted files in a directory.
+pub struct FileKeystoreBackend {
+    root: PathBuf,
+}
+
+impl FileKeystoreBackend {
+    /// Use the keystore in this directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl KeystoreBackend for FileKeystoreBackend {
+    fn get_or_create_config(
+        &self,
+        passphrase: sodoken::BufRead,
+    ) -> BoxFuture<'static, LairResult<LairServerConfig>> {
+        let root = self.root.clone();
+        async move {
+            let config_path = root.join(CONFIG_FILE_NAME);
+            if config_path.exists() {
+                let bytes = tokio::fs::read(&config_path).await?;
+                return Ok(Arc::new(LairServerConfigInner::from_bytes(&bytes)?));
+            }
+            tokio::fs::create_dir_all(&root).await?;
+            let config = LairServerConfigInner::new(&root, passphrase).await?;
+            write_file(config_path, config.to_string().into_bytes()).await?;
+            Ok(Arc::new(config))
+        }
+        .boxed()
+    }
+
+    fn store_factory(&self) -> LairStoreFactory {
+        LairStoreFactory(Arc::new(FileStoreFactory {
+            path: self.root.join(STORE_FILE_NAME),
+        }))
+    }
+}
+
+struct FileStoreFactory {
+    path: PathBuf,
+}
+
+impl AsLairStoreFactory for FileStoreFactory {
+    fn connect_to_store(
+        &self,
+        unlock_secret: sodoken::BufReadSized<32>,
+    ) -> BoxFuture<'static, LairResult<LairStore>> {
+        let path = self.path.clone();
+        async move {
+            // The unlock secret is only known to a server given the right
+            // passphrase, so use it directly as the store's encryption key.
+            let mut entries = Entries::default();
+            if path.exists() {
+                let bytes = tokio::fs::read(&path).await?;
+                let cipher: SecretData =
+                    holochain_serialized_bytes::decode(&bytes).map_err(one_err::OneErr::new)?;
+                let data = cipher
+                    .decrypt(unlock_secret.clone())
+                    .await
+                    .map_err(|_| one_err::OneErr::new("could not decrypt the keystore file"))?;
+                let encoded: Vec<serde_bytes::ByteBuf> =
+                    holochain_serialized_bytes::decode(&*data.read_lock())
+                        .map_err(one_err::OneErr::new)?;
+                for entry in encoded {
+                    entries.insert(Arc::new(LairEntryInner::decode(&entry)?))?;
+                }
+            }
+            Ok(LairStore(Arc::new(FileStore {
+                path,
+                bidi_key: unlock_secret,
+                entries: Arc::new(tokio::sync::Mutex::new(entries)),
+            })))
+        }
+        .boxed()
+    }
+}
+
+/// The entries of a store along with their pub key indexes.
+#[derive(Default)]
+struct Entries {
+    by_tag: HashMap<Arc<str>, LairEntry>,
+    ed_pk_to_tag: HashMap<Ed25519PubKey, Arc<str>>,
+    x_pk_to_tag: HashMap<X25519PubKey, Arc<str>>,
+}
+
+impl Entries {
+    fn insert(&mut self, entry: LairEntry) -> LairResult<()> {
+        let (tag, ed, x) = match &*entry {
+            LairEntryInner::Seed { tag, seed_info, .. }
+            | LairEntryInner::DeepLockedSeed { tag, seed_info, .. } => (
+                tag.clone(),
+                Some(seed_info.ed25519_pub_key.clone()),
+                Some(seed_info.x25519_pub_key.clone()),
+            ),
+            LairEntryInner::WkaTlsCert { tag, .. } => (tag.clone(), None, None),
+            _ => return Err("unsupported keystore entry type".into()),
+        };
+        if self.by_tag.contains_key(&tag) {
+            return Err("tag already registered".into());
+        }
+        if ed
+            .as_ref()
+            .map_or(false, |ed| self.ed_pk_to_tag.contains_key(ed))
+        {
+            return Err("ed pk already registered".into());
+        }
+        if x.as_ref()
+            .map_or(false, |x| self.x_pk_to_tag.contains_key(x))
+        {
+            return Err("x pk already registered".into());
+        }
+        if let Some(ed) = ed {
+            self.ed_pk_to_tag.insert(ed, tag.clone());
+        }
+        if let Some(x) = x {
+            self.x_pk_to_tag.insert(x, tag.clone());
+        }
+        self.by_tag.insert(tag, entry);
+        Ok(())
+    }
+
+    fn get(&self, tag: Option<&Arc<str>>) -> LairResult<LairEntry> {
+        tag.and_then(|tag| self.by_tag.get(tag))
+            .cloned()
+            .ok_or_else(|| "pub key not found".into())
+    }
+}
+
+struct FileStore {
+    path: PathBuf,
+    bidi_key: sodoken::BufReadSized<32>,
+    entries: Arc<tokio::sync::Mutex<Entries>>,
+}
+
+impl AsLairStore for FileStore {
+    fn get_bidi_ctx_key(&self) -> sodoken::BufReadSized<32> {
+        self.bidi_key.clone()
+    }
+
+    fn list_entries(&self) -> BoxFuture<'static, LairResult<Vec<LairEntryInfo>>> {
+        let entries = self.entries.clone();
+        async move {
+            Ok(entries
+                .lock()
+                .await
+                .by_tag
+                .values()
+                .filter_map(|e| match &**e {
+                    LairEntryInner::Seed { tag, seed_info, .. } => Some(LairEntryInfo::Seed {
+                        tag: tag.clone(),
+                        seed_info: seed_info.clone(),
+                    }),
+                    LairEntryInner::DeepLockedSeed { tag, seed_info, .. } => {
+                        Some(LairEntryInfo::DeepLockedSeed {
+                            tag: tag.clone(),
+                            seed_info: seed_info.clone(),
+                        })
+                    }
+                    LairEntryInner::WkaTlsCert { tag, cert_info, .. } => {
+                        Some(LairEntryInfo::WkaTlsCert {
+                            tag: tag.clone(),
+                            cert_info: cert_info.clone(),
+                        })
+                    }
+                    _ => None,
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn write_entry(&self, entry: LairEntry) -> BoxFuture<'static, LairResult<()>> {
+        let path = self.path.clone();
+        let key = self.bidi_key.clone();
+        let entries = self.entries.clone();
+        async move {
+            // Hold the lock until the file is written so that
+            // concurrent writes are persisted in order.
+            let mut entries = entries.lock().await;
+            entries.insert(entry)?;
+
+            let encoded = entries
+                .by_tag
+                .values()
+                .map(|e| e.encode().map(|b| serde_bytes::ByteBuf::from(b.into_vec())))
+                .collect::<LairResult<Vec<_>>>()?;
+            let data =
+                holochain_serialized_bytes::encode(&encoded).map_err(one_err::OneErr::new)?;
+            let cipher = SecretData::encrypt(key, data.into()).await?;
+            let bytes =
+                holochain_serialized_bytes::encode(&cipher).map_err(one_err::OneErr::new)?;
+            write_file(path, bytes).await
+        }
+        .boxed()
+    }
+
+    fn get_entry_by_tag(&self, tag: Arc<str>) -> BoxFuture<'static, LairResult<LairEntry>> {
+        let entries = self.entries.clone();
+        async move {
+            entries
+                .lock()
+                .await
+                .by_tag
+                .get(&tag)
+                .cloned()
+                .ok_or_else(|| "tag not found".into())
+        }
+        .boxed()
+    }
+
+    fn get_entry_by_ed25519_pub_key(
+        &self,
+        ed25519_pub_key: Ed25519PubKey,
+    ) -> BoxFuture<'static, LairResult<LairEntry>> {
+        let entries = self.entries.clone();
+        async move {
+            let entries = entries.lock().await;
+            entries.get(entries.ed_pk_to_tag.get(&ed25519_pub_key))
+        }
+        .boxed()
+    }
+
+    fn get_entry_by_x25519_pub_key(
+        &self,
+        x25519_pub_key: X25519PubKey,
+    ) -> BoxFuture<'static, LairResult<LairEntry>> {
+        let entries = self.entries.clone();
+        async move {
+            let entries = entries.lock().await;
+            entries.get(entries.x_pk_to_tag.get(&x25519_pub_key))
+        }
+        .boxed()
+    }
+}
+
+/// Write a file by renaming a temporary file over it,
+/// so a crash never leaves a partially written keystore.
+async fn write_file(path: PathBuf, bytes: Vec<u8>) -> LairResult<()> {
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, bytes).await?;
+    tokio::fs::rename(&tmp, &path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holo_hash::AgentPubKey;
+
+    async fn spawn(path: &std::path::Path, passphrase: &[u8]) -> LairResult<MetaLairClient> {
+        let passphrase = sodoken::BufRead::new_no_lock(passphrase);
+        let path = path.to_owned();
+        PwHashLimits::Minimum
+            .with_exec(|| spawn_file_keystore(path, passphrase))
+            .await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn file_keystore_sign_and_crypto_box() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = spawn(dir.path(), b"passphrase").await.unwrap();
+
+        let agent = AgentPubKey::new_random(&keystore).await.unwrap();
+        let data: Arc<[u8]> = b"signature test data".to_vec().into();
+        let signature = agent.sign_raw(&keystore, data.clone()).await.unwrap();
+        assert!(agent.verify_signature_raw(&signature, data.clone()).await);
+
+        let alice = keystore.new_x25519_keypair_random().await.unwrap();
+        let bob = keystore.new_x25519_keypair_random().await.unwrap();
+        let (nonce, cipher) = keystore
+            .crypto_box_xsalsa(alice.clone(), bob.clone(), data.clone())
+            .await
+            .unwrap();
+        let opened = keystore
+            .crypto_box_xsalsa_open(alice, bob, nonce, cipher)
+            .await
+            .unwrap();
+        assert_eq!(&data[..], &opened[..]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn file_keystore_persists_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = spawn(dir.path(), b"passphrase").await.unwrap();
+        let agent = AgentPubKey::new_random(&keystore).await.unwrap();
+        let tls = keystore.get_or_create_first_tls_cert().await.unwrap();
+        keystore.shutdown().await.unwrap();
+
+        // The wrong passphrase can't open the keystore.
+        assert!(spawn(dir.path(), b"wrong").await.is_err());
+
+        let keystore = spawn(dir.path(), b"passphrase").await.unwrap();
+        let data: Arc<[u8]> = b"signature test data".to_vec().into();
+        let signature = agent.sign_raw(&keystore, data.clone()).await.unwrap();
+        assert!(agent.verify_signature_raw(&signature, data).await);
+        assert_eq!(tls, keystore.get_or_create_first_tls_cert().await.unwrap());
+    }
+}
@@ -49,6 +49,8 @@ use keystore_actor::*;
 mod agent_pubkey_ext;
 pub use agent_pubkey_ext::*;
 
+pub mod backend;
 pub mod crude_mock_keystore;
+pub mod file_keystore;
 pub mod lair_keystore;
 pub mod test_keystore;
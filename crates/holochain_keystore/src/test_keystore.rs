@@ -1,9 +1,6 @@
 //! DANGER! This is a mock keystore for testing, DO NOT USE THIS IN PRODUCTION!
 
 use crate::*;
-use kitsune_p2p_types::dependencies::lair_keystore_api;
-use lair_keystore_api::prelude::*;
-use std::sync::Arc;
 
 /// First Test Agent Pub Key
 pub const TEST_AGENT_PK_1: &str = "uhCAkJCuynkgVdMn_bzZ2ZYaVfygkn0WCuzfFspczxFnZM1QAyXoo";
@@ -110,22 +107,9 @@ pub async fn spawn_test_keystore() -> LairResult<MetaLairClient> {
     let passphrase = sodoken::BufWrite::new_mem_locked(32)?;
     sodoken::random::bytes_buf(passphrase.clone()).await?;
 
-    // in-mem / in-proc config
-    let config = Arc::new(
-        PwHashLimits::Minimum
-            .with_exec(|| {
-                lair_keystore_api::config::LairServerConfigInner::new("/", passphrase.to_read())
-            })
-            .await?,
-    );
-
-    // the keystore
-    let keystore = lair_keystore_api::in_proc_keystore::InProcKeystore::new(
-        config,
-        lair_keystore_api::mem_store::create_mem_store_factory(),
-        passphrase.to_read(),
-    )
-    .await?;
+    // the in-mem / in-proc keystore
+    let keystore =
+        backend::spawn_in_proc_keystore(backend::MemKeystoreBackend, passphrase.to_read()).await?;
 
     // get the store and inject test seeds
     let store = keystore.store().await?;
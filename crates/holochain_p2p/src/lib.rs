@@ -29,6 +29,13 @@ pub trait HolochainP2pDnaT {
     /// owned getter
     fn dna_hash(&self) -> DnaHash;
 
+    /// Override the network config for this dna's space.
+    /// This must happen before the space is first joined.
+    async fn configure_space(
+        &self,
+        config_override: kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> actor::HolochainP2pResult<()>;
+
     /// The p2p module must be informed at runtime which dna/agent pairs it should be tracking.
     async fn join(
         &self,
@@ -75,6 +82,17 @@ pub trait HolochainP2pDnaT {
         timeout_ms: Option<u64>,
     ) -> actor::HolochainP2pResult<usize>;
 
+    /// Publish a warrant to the agent activity authorities of the
+    /// agent it was issued against.
+    async fn publish_warrant(
+        &self,
+        warrant: SignedWarrant,
+        timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<()>;
+
+    /// Stop exchanging data with an agent in this space.
+    async fn block_agent(&self, agent: AgentPubKey) -> actor::HolochainP2pResult<()>;
+
     /// Request a validation package.
     async fn get_validation_package(
         &self,
@@ -150,6 +168,16 @@ impl HolochainP2pDnaT for HolochainP2pDna {
         (*self.dna_hash).clone()
     }
 
+    /// Override the network config for this dna's space.
+    async fn configure_space(
+        &self,
+        config_override: kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .configure_space((*self.dna_hash).clone(), config_override)
+            .await
+    }
+
     /// The p2p module must be informed at runtime which dna/agent pairs it should be tracking.
     async fn join(
         &self,
@@ -236,6 +264,22 @@ impl HolochainP2pDnaT for HolochainP2pDna {
             .await
     }
 
+    async fn publish_warrant(
+        &self,
+        warrant: SignedWarrant,
+        timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .publish_warrant((*self.dna_hash).clone(), warrant, timeout_ms)
+            .await
+    }
+
+    async fn block_agent(&self, agent: AgentPubKey) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .block_agent((*self.dna_hash).clone(), agent)
+            .await
+    }
+
     /// Request a validation package.
     async fn get_validation_package(
         &self,
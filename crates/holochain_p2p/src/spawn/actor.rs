@@ -17,6 +17,7 @@ use ghost_actor::dependencies::tracing_futures::Instrument;
 use holochain_zome_types::zome::FunctionName;
 use kitsune_p2p::actor::KitsuneP2pSender;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::future::Future;
 
@@ -153,6 +154,17 @@ impl WrapEvtSender {
         }, %op_count, "(hp2p:handle) publish")
     }
 
+    fn publish_warrant(
+        &self,
+        dna_hash: DnaHash,
+        warrant: SignedWarrant,
+    ) -> impl Future<Output = HolochainP2pResult<()>> + 'static + Send {
+        timing_trace!(
+            { self.0.publish_warrant(dna_hash, warrant) },
+            "(hp2p:handle) publish_warrant",
+        )
+    }
+
     fn get_validation_package(
         &self,
         dna_hash: DnaHash,
@@ -310,6 +322,8 @@ pub(crate) struct HolochainP2pActor {
     tuning_params: kitsune_p2p_types::config::KitsuneP2pTuningParams,
     evt_sender: WrapEvtSender,
     kitsune_p2p: ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>,
+    /// Agents we have stopped exchanging data with, per space.
+    blocked: HashMap<DnaHash, HashSet<AgentPubKey>>,
 }
 
 impl ghost_actor::GhostControlHandler for HolochainP2pActor {}
@@ -333,9 +347,16 @@ impl HolochainP2pActor {
             tuning_params,
             evt_sender: WrapEvtSender(evt_sender),
             kitsune_p2p,
+            blocked: HashMap::new(),
         })
     }
 
+    fn is_blocked(&self, dna_hash: &DnaHash, agent: &AgentPubKey) -> bool {
+        self.blocked
+            .get(dna_hash)
+            .map_or(false, |agents| agents.contains(agent))
+    }
+
     /// receiving an incoming request from a remote node
     #[allow(clippy::too_many_arguments)]
     fn handle_incoming_call_remote(
@@ -459,8 +480,14 @@ impl HolochainP2pActor {
         dna_hash: DnaHash,
         request_validation_receipt: bool,
         countersigning_session: bool,
-        ops: Vec<holochain_types::dht_op::DhtOp>,
+        mut ops: Vec<holochain_types::dht_op::DhtOp>,
     ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<()> {
+        // Ops authored by blocked agents are dropped on arrival.
+        let op_count = ops.len();
+        ops.retain(|op| !self.is_blocked(&dna_hash, op.action().author()));
+        if op_count > 0 && ops.is_empty() {
+            return Ok(async move { Ok(()) }.boxed().into());
+        }
         let evt_sender = self.evt_sender.clone();
         Ok(async move {
             evt_sender
@@ -520,6 +547,21 @@ impl HolochainP2pActor {
         .into())
     }
 
+    /// receiving an incoming warrant from a remote node
+    fn handle_incoming_publish_warrant(
+        &mut self,
+        dna_hash: DnaHash,
+        warrant: SignedWarrant,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<()> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            evt_sender.publish_warrant(dna_hash, warrant).await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
     fn handle_incoming_countersigning_session_negotiation(
         &mut self,
         dna_hash: DnaHash,
@@ -651,6 +693,15 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
             crate::wire::WireMessage::decode(payload.as_ref()).map_err(HolochainP2pError::from)?;
 
         match request {
+            crate::wire::WireMessage::CallRemote { from_agent, .. }
+                if self.is_blocked(&space, &from_agent) =>
+            {
+                Err(HolochainP2pError::invalid_p2p_message(format!(
+                    "refused call from blocked agent {}",
+                    from_agent
+                ))
+                .into())
+            }
             crate::wire::WireMessage::CallRemote {
                 zome_name,
                 fn_name,
@@ -676,7 +727,8 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
             } => self.handle_incoming_get_agent_activity(space, to_agent, agent, query, options),
             // holochain_p2p never publishes via request
             // these only occur on broadcasts
-            crate::wire::WireMessage::Publish { .. } => {
+            crate::wire::WireMessage::Publish { .. }
+            | crate::wire::WireMessage::PublishWarrant { .. } => {
                 Err(HolochainP2pError::invalid_p2p_message(
                     "invalid: publish is a broadcast type, not a request".to_string(),
                 )
@@ -725,6 +777,12 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
                 )
                 .into())
             }
+            // Signals from blocked agents are silently dropped.
+            crate::wire::WireMessage::CallRemote { from_agent, .. }
+                if self.is_blocked(&space, &from_agent) =>
+            {
+                Ok(async move { Ok(()) }.boxed().into())
+            }
             crate::wire::WireMessage::CallRemote {
                 zome_name,
                 fn_name,
@@ -753,6 +811,9 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
                 countersigning_session,
                 ops,
             ),
+            crate::wire::WireMessage::PublishWarrant { warrant } => {
+                self.handle_incoming_publish_warrant(space, warrant)
+            }
             crate::wire::WireMessage::CountersigningSessionNegotiation { message } => {
                 self.handle_incoming_countersigning_session_negotiation(space, to_agent, message)
             }
@@ -857,6 +918,22 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
 impl ghost_actor::GhostHandler<HolochainP2p> for HolochainP2pActor {}
 
 impl HolochainP2pHandler for HolochainP2pActor {
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_configure_space(
+        &mut self,
+        dna_hash: DnaHash,
+        config_override: kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.configure_space(space, config_override).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
     #[tracing::instrument(skip(self), level = "trace")]
     fn handle_join(
         &mut self,
@@ -991,6 +1068,46 @@ impl HolochainP2pHandler for HolochainP2pActor {
         .into())
     }
 
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_publish_warrant(
+        &mut self,
+        dna_hash: DnaHash,
+        warrant: SignedWarrant,
+        timeout_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        use kitsune_p2p_types::KitsuneTimeout;
+
+        let space = dna_hash.into_kitsune();
+        // Warrants are held by the agent activity authorities of the warrantee.
+        let basis = AnyDhtHash::from(warrant.warrantee().clone()).to_kitsune();
+        let timeout = match timeout_ms {
+            Some(ms) => KitsuneTimeout::from_millis(ms),
+            None => self.tuning_params.implicit_timeout(),
+        };
+
+        let payload = crate::wire::WireMessage::publish_warrant(warrant).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            kitsune_p2p
+                .broadcast(space, basis, timeout, BroadcastTo::Notify, payload)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_block_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        self.blocked.entry(dna_hash).or_default().insert(agent);
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
     #[tracing::instrument(skip(self), level = "trace")]
     fn handle_get_validation_package(
         &mut self,
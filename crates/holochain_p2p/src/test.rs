@@ -13,6 +13,13 @@ impl ghost_actor::GhostControlHandler for StubNetwork {}
 
 #[allow(unused_variables)]
 impl HolochainP2pHandler for StubNetwork {
+    fn handle_configure_space(
+        &mut self,
+        dna_hash: DnaHash,
+        config_override: kitsune_p2p::KitsuneP2pConfigOverride,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
     fn handle_join(
         &mut self,
         dna_hash: DnaHash,
@@ -63,6 +70,21 @@ impl HolochainP2pHandler for StubNetwork {
     ) -> HolochainP2pHandlerResult<usize> {
         Err("stub".into())
     }
+    fn handle_publish_warrant(
+        &mut self,
+        dna_hash: DnaHash,
+        warrant: SignedWarrant,
+        timeout_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
+    fn handle_block_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
     fn handle_get_validation_package(
         &mut self,
         input: actor::GetValidationPackage,
@@ -381,6 +403,170 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    fn test_warrant(
+        warrantee: holo_hash::AgentPubKey,
+        author: holo_hash::AgentPubKey,
+    ) -> SignedWarrant {
+        let action = Action::Dna(Dna {
+            author: warrantee,
+            timestamp: Timestamp::HOLOCHAIN_EPOCH,
+            hash: newhash!(DnaHash, 's'),
+        });
+        SignedWarrant::new(
+            Warrant {
+                proof: WarrantProof::InvalidAction {
+                    action: SignedAction(action, Signature([0; 64])),
+                    validation_type: ValidationType::Sys,
+                    reason: "test".into(),
+                },
+                author,
+                timestamp: Timestamp::HOLOCHAIN_EPOCH,
+            },
+            Signature([0; 64]),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_publish_warrant_workflow() {
+        let (dna, a1, a2, a3) = test_setup();
+
+        let (p2p, mut evt) = spawn_holochain_p2p(
+            KitsuneP2pConfig::default(),
+            TlsConfig::new_ephemeral().await.unwrap(),
+            kitsune_p2p::HostStub::new(),
+        )
+        .await
+        .unwrap();
+
+        let warrant = test_warrant(a3.clone(), a1.clone());
+        let recv_count = Arc::new(std::sync::atomic::AtomicU8::new(0));
+
+        let recv_count_clone = recv_count.clone();
+        let expected = warrant.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio_stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use crate::types::event::HolochainP2pEvent::*;
+                match evt {
+                    PublishWarrant {
+                        respond, warrant, ..
+                    } => {
+                        assert_eq!(expected, warrant);
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                        recv_count_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    SignNetworkData { respond, .. } => {
+                        respond.r(Ok(async move { Ok([0; 64].into()) }.boxed().into()));
+                    }
+                    PutAgentInfoSigned { respond, .. } => {
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    QueryAgentInfoSigned { respond, .. } => {
+                        respond.r(Ok(async move { Ok(vec![]) }.boxed().into()));
+                    }
+                    QueryPeerDensity { respond, .. } => {
+                        let view = test_peer_view();
+                        respond.r(Ok(async move { Ok(view) }.boxed().into()));
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone(), None).await.unwrap();
+        p2p.join(dna.clone(), a2.clone(), None).await.unwrap();
+        p2p.join(dna.clone(), a3.clone(), None).await.unwrap();
+
+        // this will fail because we can't reach any remote nodes
+        // but, it still published locally, so our test will work
+        let _ = p2p.publish_warrant(dna, warrant, Some(200)).await;
+
+        assert_eq!(3, recv_count.load(std::sync::atomic::Ordering::SeqCst));
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_blocked_agent_is_refused() {
+        let (dna, a1, a2, _) = test_setup();
+
+        let (p2p, mut evt) = spawn_holochain_p2p(
+            KitsuneP2pConfig::default(),
+            TlsConfig::new_ephemeral().await.unwrap(),
+            kitsune_p2p::HostStub::new(),
+        )
+        .await
+        .unwrap();
+
+        let r_task = tokio::task::spawn(async move {
+            use tokio_stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use crate::types::event::HolochainP2pEvent::*;
+                match evt {
+                    CallRemote { respond, .. } => {
+                        respond.r(Ok(
+                            async move { Ok(UnsafeBytes::from(b"yada".to_vec()).into()) }
+                                .boxed()
+                                .into(),
+                        ));
+                    }
+                    Publish { .. } => panic!("ops from a blocked agent were delivered"),
+                    SignNetworkData { respond, .. } => {
+                        respond.r(Ok(async move { Ok([0; 64].into()) }.boxed().into()));
+                    }
+                    PutAgentInfoSigned { respond, .. } => {
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    QueryAgentInfoSigned { respond, .. } => {
+                        respond.r(Ok(async move { Ok(vec![]) }.boxed().into()));
+                    }
+                    QueryPeerDensity { respond, .. } => {
+                        let view = test_peer_view();
+                        respond.r(Ok(async move { Ok(view) }.boxed().into()));
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone(), None).await.unwrap();
+        p2p.join(dna.clone(), a2.clone(), None).await.unwrap();
+        p2p.block_agent(dna.clone(), a1.clone()).await.unwrap();
+
+        let res = p2p
+            .call_remote(
+                dna.clone(),
+                a1.clone(),
+                a2,
+                "".into(),
+                "".into(),
+                None,
+                ExternIO::encode(b"yippo").unwrap(),
+            )
+            .await;
+        assert!(res.is_err());
+
+        let op = holochain_types::dht_op::DhtOp::RegisterAgentActivity(
+            Signature([0; 64]),
+            Action::Dna(Dna {
+                author: a1,
+                timestamp: Timestamp::HOLOCHAIN_EPOCH,
+                hash: dna.clone(),
+            }),
+        );
+        let basis = holo_hash::AnyDhtHash::from_raw_36_and_type(
+            b"eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee".to_vec(),
+            holo_hash::hash_type::AnyDht::Action,
+        );
+        let _ = p2p
+            .publish(dna, false, false, basis, vec![op], Some(200))
+            .await;
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_get_workflow() {
         observability::test_run().ok();
@@ -207,6 +207,10 @@ ghost_actor::ghost_chan! {
     /// The HolochainP2pSender struct allows controlling the HolochainP2p
     /// actor instance.
     pub chan HolochainP2p<HolochainP2pError> {
+        /// Override the network config for a dna's space.
+        /// This must happen before the space is first joined.
+        fn configure_space(dna_hash: DnaHash, config_override: kitsune_p2p::KitsuneP2pConfigOverride) -> ();
+
         /// The p2p module must be informed at runtime which dna/agent pairs it should be tracking.
         fn join(dna_hash: DnaHash, agent_pub_key: AgentPubKey, initial_arc: Option<crate::dht_arc::DhtArc>) -> ();
 
@@ -248,6 +252,19 @@ ghost_actor::ghost_chan! {
             timeout_ms: Option<u64>,
         ) -> usize;
 
+        /// Publish a warrant to the agent activity authorities of the
+        /// agent it was issued against.
+        fn publish_warrant(
+            dna_hash: DnaHash,
+            warrant: SignedWarrant,
+            timeout_ms: Option<u64>,
+        ) -> ();
+
+        /// Stop exchanging data with an agent in this space.
+        /// Calls and signals from the agent are refused and any ops
+        /// it authored are dropped when they arrive.
+        fn block_agent(dna_hash: DnaHash, agent: AgentPubKey) -> ();
+
         /// Request a validation package.
         fn get_validation_package(input: GetValidationPackage) -> ValidationPackageResponse;
 
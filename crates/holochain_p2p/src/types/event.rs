@@ -49,12 +49,17 @@ impl Default for GetRequest {
 }
 
 /// GetMeta options help control how the get is processed at various levels.
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct GetMetaOptions {}
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct GetMetaOptions {
+    /// Which metadata the remote-end should return.
+    pub metadata_request: MetadataRequest,
+}
 
 impl From<&actor::GetMetaOptions> for GetMetaOptions {
-    fn from(_a: &actor::GetMetaOptions) -> Self {
-        Self {}
+    fn from(a: &actor::GetMetaOptions) -> Self {
+        Self {
+            metadata_request: a.metadata_request.clone(),
+        }
     }
 }
 
@@ -181,6 +186,13 @@ ghost_actor::ghost_chan! {
             ops: Vec<holochain_types::dht_op::DhtOp>,
         ) -> ();
 
+        /// A remote node is publishing a warrant against an agent whose
+        /// activity we are an authority for.
+        fn publish_warrant(
+            dna_hash: DnaHash,
+            warrant: SignedWarrant,
+        ) -> ();
+
         /// A remote node is requesting a validation package.
         fn get_validation_package(
             // The dna_hash / space_hash context.
@@ -290,6 +302,7 @@ impl HolochainP2pEvent {
     pub fn dna_hash(&self) -> &DnaHash {
         match_p2p_evt!(self => |dna_hash| { dna_hash }, {
             HolochainP2pEvent::Publish { dna_hash, .. } => { dna_hash }
+            HolochainP2pEvent::PublishWarrant { dna_hash, .. } => { dna_hash }
             HolochainP2pEvent::FetchOpData { dna_hash, .. } => { dna_hash }
             HolochainP2pEvent::QueryOpHashes { dna_hash, .. } => { dna_hash }
             HolochainP2pEvent::QueryAgentInfoSigned { dna_hash, .. } => { dna_hash }
@@ -304,6 +317,7 @@ impl HolochainP2pEvent {
     pub fn target_agents(&self) -> &AgentPubKey {
         match_p2p_evt!(self => |to_agent| { to_agent }, {
             HolochainP2pEvent::Publish { .. } => { unimplemented!("There is no single agent target for Publish") }
+            HolochainP2pEvent::PublishWarrant { .. } => { unimplemented!("There is no single agent target for PublishWarrant") }
             HolochainP2pEvent::FetchOpData { .. } => { unimplemented!("There is no single agent target for FetchOpData") }
             HolochainP2pEvent::QueryOpHashes { .. } => { unimplemented!("There is no single agent target for QueryOpHashes") }
             HolochainP2pEvent::QueryAgentInfoSigned { .. } => { unimplemented!("There is no single agent target for QueryAgentInfoSigned") },
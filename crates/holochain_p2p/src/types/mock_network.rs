@@ -349,6 +349,7 @@ impl HolochainP2pMockMsg {
                 | crate::wire::WireMessage::GetAgentActivity { .. }
                 | crate::wire::WireMessage::GetValidationPackage { .. } => next_msg_id().as_req(),
                 crate::wire::WireMessage::Publish { .. }
+                | crate::wire::WireMessage::PublishWarrant { .. }
                 | crate::wire::WireMessage::CountersigningSessionNegotiation { .. } => {
                     MsgId::new_notify()
                 }
@@ -376,6 +377,7 @@ impl HolochainP2pMockMsg {
                     | crate::wire::WireMessage::GetAgentActivity { .. }
                     | crate::wire::WireMessage::GetValidationPackage { .. } => true,
                     crate::wire::WireMessage::Publish { .. }
+                    | crate::wire::WireMessage::PublishWarrant { .. }
                     | crate::wire::WireMessage::CountersigningSessionNegotiation { .. } => false,
                 };
                 let to_agent = to_agent.to_kitsune();
@@ -66,6 +66,9 @@ pub enum WireMessage {
     CountersigningSessionNegotiation {
         message: event::CountersigningSessionNegotiationMessage,
     },
+    PublishWarrant {
+        warrant: SignedWarrant,
+    },
 }
 
 #[allow(missing_docs)]
@@ -149,4 +152,8 @@ impl WireMessage {
     ) -> WireMessage {
         Self::CountersigningSessionNegotiation { message }
     }
+
+    pub fn publish_warrant(warrant: SignedWarrant) -> WireMessage {
+        Self::PublishWarrant { warrant }
+    }
 }
@@ -1,5 +1,6 @@
 //! Functions dealing with obtaining and referencing singleton databases
 
+use crate::key::DbKey;
 use crate::{
     conn::{new_connection_pool, ConnectionPool, DbSyncLevel, PConn, DATABASE_HANDLES},
     prelude::*,
@@ -106,6 +107,9 @@ pub struct DbRead<Kind: DbKindT> {
     read_semaphore: Arc<Semaphore>,
     max_readers: usize,
     num_readers: Arc<AtomicUsize>,
+    /// Shared by every clone of this handle, so that [`DbWrite::remove`]
+    /// can tell when they have all been dropped.
+    handles: Arc<()>,
 }
 
 #[derive(Shrinkwrap)]
@@ -220,9 +224,21 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
         path_prefix: &Path,
         kind: Kind,
         sync_level: DbSyncLevel,
+    ) -> DatabaseResult<Self> {
+        Self::open_with_key(path_prefix, kind, sync_level, None)
+    }
+
+    /// Create or open an existing database reference, encrypted with this key
+    /// if the `db-encryption` feature is enabled.
+    /// A database which is already open keeps the key it was opened with.
+    pub fn open_with_key(
+        path_prefix: &Path,
+        kind: Kind,
+        sync_level: DbSyncLevel,
+        key: Option<DbKey>,
     ) -> DatabaseResult<Self> {
         DATABASE_HANDLES.get_or_insert(&kind, path_prefix, |kind| {
-            Self::new(Some(path_prefix), kind, sync_level)
+            Self::new(Some(path_prefix), kind, sync_level, key)
         })
     }
 
@@ -230,6 +246,7 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
         path_prefix: Option<&Path>,
         kind: Kind,
         sync_level: DbSyncLevel,
+        key: Option<DbKey>,
     ) -> DatabaseResult<Self> {
         let path = match path_prefix {
             Some(path_prefix) => {
@@ -241,12 +258,15 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
                     std::fs::create_dir_all(parent)
                         .map_err(|_e| DatabaseError::DatabaseMissing(parent.to_owned()))?;
                 }
+                if finish_removal(&path)? {
+                    return Err(DatabaseError::PendingRemoval(path));
+                }
                 // Check if the database is valid and take the appropriate
                 // action if it isn't.
                 match Connection::open(&path)
                     // For some reason calling pragma_update is necessary to prove the database file is valid.
                     .and_then(|mut c| {
-                        crate::conn::initialize_connection(&mut c, sync_level)?;
+                        crate::conn::initialize_connection(&mut c, sync_level, key.as_ref())?;
                         c.pragma_update(None, "synchronous", &"0".to_string())
                     }) {
                     Ok(_) => (),
@@ -283,7 +303,7 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
         };
 
         // Now we know the database file is valid we can open a connection pool.
-        let pool = new_connection_pool(path.as_ref().map(|p| p.as_ref()), sync_level);
+        let pool = new_connection_pool(path.as_ref().map(|p| p.as_ref()), sync_level, key);
         let mut conn = pool.get()?;
         // set to faster write-ahead-log mode
         conn.pragma_update(None, "journal_mode", &"WAL".to_string())?;
@@ -294,6 +314,7 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
             read_semaphore: Self::get_read_semaphore(kind.kind()),
             max_readers: num_read_threads(),
             num_readers: Arc::new(AtomicUsize::new(0)),
+            handles: Arc::new(()),
             kind,
             path: path.unwrap_or_default(),
             connection_pool: pool,
@@ -322,21 +343,49 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
     /// connection pool, useful for testing.
     #[cfg(any(test, feature = "test_utils"))]
     pub fn test(path: &Path, kind: Kind) -> DatabaseResult<Self> {
-        Self::new(Some(path), kind, DbSyncLevel::default())
+        Self::new(Some(path), kind, DbSyncLevel::default(), None)
     }
 
     #[cfg(any(test, feature = "test_utils"))]
     pub fn test_in_mem(kind: Kind) -> DatabaseResult<Self> {
-        Self::new(None, kind, DbSyncLevel::default())
+        Self::new(None, kind, DbSyncLevel::default(), None)
     }
 
-    /// Remove the db and directory
-    #[deprecated = "is this used?"]
+    /// Close the db and delete its files from disk.
+    ///
+    /// The db is also forgotten by the static set of open databases, so
+    /// opening the same kind again will create a fresh db.
+    /// The files are only deleted once every other clone of this handle has
+    /// been dropped. If some are still open after [`REMOVE_WAIT`], the files
+    /// are left for [`finish_pending_removals`] to delete on the next start,
+    /// and the db can't be opened again until then.
     pub async fn remove(self) -> DatabaseResult<()> {
-        if let Some(parent) = self.0.path.parent() {
-            std::fs::remove_dir_all(parent)?;
+        self.remove_within(REMOVE_WAIT).await
+    }
+
+    async fn remove_within(self, wait: std::time::Duration) -> DatabaseResult<()> {
+        let path = self.0.path.clone();
+        DATABASE_HANDLES.remove(&path);
+        let handles = Arc::downgrade(&self.0.handles);
+        drop(self);
+        // In-memory databases have no files.
+        if path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let deadline = tokio::time::Instant::now() + wait;
+        while handles.strong_count() > 0 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        if handles.strong_count() > 0 {
+            tracing::warn!(
+                ?path,
+                "The database is still in use, so it will be deleted when the conductor next starts"
+            );
+            std::fs::write(removal_marker(&path), [])?;
+            PENDING_REMOVALS.lock().insert(path, handles);
+            return Ok(());
         }
-        Ok(())
+        remove_db_files(&path)
     }
 
     pub async fn async_commit<E, R, F>(&self, f: F) -> Result<R, E>
@@ -385,6 +434,77 @@ impl<Kind: DbKindT + Send + Sync + 'static> DbWrite<Kind> {
     }
 }
 
+/// How long [`DbWrite::remove`] waits for the other handles to a db to be
+/// dropped before leaving its files to be deleted on the next start.
+pub const REMOVE_WAIT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The handles of removed dbs which were still open when they were removed.
+static PENDING_REMOVALS: once_cell::sync::Lazy<Mutex<HashMap<PathBuf, std::sync::Weak<()>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+const REMOVAL_MARKER_EXTENSION: &str = "remove";
+
+/// The file which marks a db whose files are waiting to be deleted.
+fn removal_marker(path: &Path) -> PathBuf {
+    let mut marker = path.to_owned().into_os_string();
+    marker.push(".");
+    marker.push(REMOVAL_MARKER_EXTENSION);
+    marker.into()
+}
+
+fn remove_db_files(path: &Path) -> DatabaseResult<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.to_owned().into_os_string();
+        file.push(suffix);
+        match std::fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Delete the files of a db which [`DbWrite::remove`] left to be deleted
+/// later, unless this process still has handles to it open.
+/// Returns whether the db is still waiting to be deleted.
+fn finish_removal(path: &Path) -> DatabaseResult<bool> {
+    let marker = removal_marker(path);
+    if !marker.exists() {
+        return Ok(false);
+    }
+    let mut pending = PENDING_REMOVALS.lock();
+    if pending
+        .get(path)
+        .map_or(false, |handles| handles.strong_count() > 0)
+    {
+        return Ok(true);
+    }
+    pending.remove(path);
+    remove_db_files(path)?;
+    std::fs::remove_file(marker)?;
+    Ok(false)
+}
+
+/// Delete the files of every db in this directory, or below it, which
+/// [`DbWrite::remove`] left to be deleted because it was still in use.
+pub fn finish_pending_removals(dir: &Path) -> DatabaseResult<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            finish_pending_removals(&path)?;
+        } else if path
+            .extension()
+            .map_or(false, |e| e == REMOVAL_MARKER_EXTENSION)
+        {
+            finish_removal(&path.with_extension(""))?;
+        }
+    }
+    Ok(())
+}
+
 pub fn num_read_threads() -> usize {
     let num_cpus = num_cpus::get();
     let num_threads = num_cpus.checked_div(2).unwrap_or(0);
@@ -722,3 +842,50 @@ where
         tokio::time::sleep(RETRY_INTERVAL).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remove_waits_for_other_handles() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbWrite::test(dir.path(), DbKindWasm).unwrap();
+        let path = db.path().clone();
+        let other = db.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            drop(other);
+        });
+        db.remove().await.unwrap();
+        assert!(!path.exists());
+        assert!(!removal_marker(&path).exists());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn remove_is_deferred_while_other_handles_are_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbWrite::test(dir.path(), DbKindWasm).unwrap();
+        let path = db.path().clone();
+        let other = db.clone();
+        db.remove_within(Duration::from_millis(100)).await.unwrap();
+        assert!(path.exists());
+        assert!(removal_marker(&path).exists());
+
+        // The db can't be opened again while it is still in use,
+        // nor deleted from under the open handle.
+        assert!(matches!(
+            DbWrite::test(dir.path(), DbKindWasm),
+            Err(DatabaseError::PendingRemoval(_))
+        ));
+        finish_pending_removals(dir.path()).unwrap();
+        assert!(path.exists());
+
+        drop(other);
+        finish_pending_removals(dir.path()).unwrap();
+        assert!(!path.exists());
+        assert!(!removal_marker(&path).exists());
+        DbWrite::test(dir.path(), DbKindWasm).unwrap();
+    }
+}
@@ -52,6 +52,18 @@ pub enum DatabaseError {
 
     #[error("transparent")]
     FailedToJoinBlocking(#[from] tokio::task::JoinError),
+
+    #[error(
+        "The {db_kind} database has schema version {db_version}, but this version of Holochain only supports up to version {supported_version}. It was probably written by a newer version of Holochain."
+    )]
+    SchemaTooNew {
+        db_kind: String,
+        db_version: u16,
+        supported_version: u16,
+    },
+
+    #[error("The database at {0} was removed while it was still in use, so it can't be opened again until the conductor restarts")]
+    PendingRemoval(PathBuf),
 }
 
 impl PartialEq for DatabaseError {
@@ -0,0 +1,153 @@
+//! Keys for the at-rest encryption of databases.
+//!
+//! Databases are only encrypted when this crate is built with the
+//! `db-encryption` feature, which uses SQLCipher. Without it, keys are
+//! accepted but have no effect.
+
+use crate::error::DatabaseResult;
+use rand::RngCore;
+use rusqlite::Connection;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The length of a database key in bytes.
+pub const DB_KEY_LEN: usize = 32;
+
+/// The raw key every database was encrypted with before keys came from the
+/// keystore, made of a 32 byte key followed by a 16 byte salt.
+const LEGACY_RAW_KEY: &str = "98483C6EB40B6C31A448C22A66DED3B5E5E8D5119CAC8327B655C8B5C483648101010101010101010101010101010101";
+
+/// A key which databases are encrypted with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DbKey(Arc<str>);
+
+impl std::fmt::Debug for DbKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DbKey(<redacted>)")
+    }
+}
+
+impl DbKey {
+    /// A new random key.
+    pub fn generate() -> Self {
+        let mut key = [0; DB_KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self::from_bytes(key)
+    }
+
+    /// The key made of these bytes.
+    pub fn from_bytes(key: [u8; DB_KEY_LEN]) -> Self {
+        let mut hex = String::with_capacity(DB_KEY_LEN * 2);
+        for b in &key {
+            // Writing to a string can't fail.
+            let _ = write!(hex, "{:02X}", b);
+        }
+        Self(hex.into())
+    }
+
+    /// The bytes of the key, or `None` for the legacy key.
+    pub fn to_bytes(&self) -> Option<[u8; DB_KEY_LEN]> {
+        if self.0.len() != DB_KEY_LEN * 2 {
+            return None;
+        }
+        let mut key = [0; DB_KEY_LEN];
+        for (i, b) in key.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&self.0[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(key)
+    }
+
+    /// The fixed key databases were encrypted with by earlier versions of
+    /// Holochain, which is only useful for re-encrypting them.
+    pub fn legacy() -> Self {
+        Self(LEGACY_RAW_KEY.into())
+    }
+
+    /// The value of the `key` and `rekey` pragmas, which SQLCipher
+    /// takes as a raw key rather than a passphrase.
+    fn pragma_value(&self) -> String {
+        format!("x'{}'", self.0)
+    }
+}
+
+/// Set the key a connection reads and writes its database with.
+/// This must come before anything else is done with the connection.
+pub(crate) fn apply_key(conn: &Connection, key: Option<&DbKey>) -> rusqlite::Result<()> {
+    // Only SQLCipher knows the key pragma.
+    if cfg!(feature = "db-encryption") {
+        if let Some(key) = key {
+            conn.pragma_update(None, "key", key.pragma_value())?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-encrypt the database file at this path from one key to another.
+///
+/// A file which can already be read with the new key is left alone, so this
+/// can be run again over files which were re-encrypted by an earlier attempt.
+/// Nothing else may have the database open.
+/// Without the `db-encryption` feature this does nothing.
+pub fn rekey_database_file(path: &Path, from: &DbKey, to: &DbKey) -> DatabaseResult<()> {
+    let conn = Connection::open(path)?;
+    apply_key(&conn, Some(to))?;
+    if is_readable(&conn) {
+        return Ok(());
+    }
+    drop(conn);
+
+    let conn = Connection::open(path)?;
+    apply_key(&conn, Some(from))?;
+    // Fail with the error for the wrong key if the old key doesn't work either.
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))?;
+    // SQLCipher can't rekey a database in WAL mode.
+    conn.pragma_update(None, "journal_mode", "DELETE")?;
+    conn.pragma_update(None, "rekey", to.pragma_value())?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    Ok(())
+}
+
+fn is_readable(conn: &Connection) -> bool {
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(()))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_bytes_round_trip() {
+        let key = DbKey::generate();
+        let bytes = key.to_bytes().unwrap();
+        assert_eq!(DbKey::from_bytes(bytes), key);
+        assert_eq!(DbKey::legacy().to_bytes(), None);
+        assert_eq!(format!("{:?}", key), "DbKey(<redacted>)");
+    }
+
+    #[cfg(feature = "db-encryption")]
+    #[test]
+    fn rekey_database_file_reencrypts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.sqlite3");
+        let old = DbKey::generate();
+        let new = DbKey::generate();
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, Some(&old)).unwrap();
+        conn.execute_batch("CREATE TABLE Thing (id INTEGER);")
+            .unwrap();
+        drop(conn);
+
+        rekey_database_file(&path, &old, &new).unwrap();
+        // Running it again leaves the file alone.
+        rekey_database_file(&path, &old, &new).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, Some(&new)).unwrap();
+        assert!(is_readable(&conn));
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, Some(&old)).unwrap();
+        assert!(!is_readable(&conn));
+    }
+}
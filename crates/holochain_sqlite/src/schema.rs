@@ -1,108 +1,130 @@
+//! The schemas of each kind of database and the migrations between their
+//! versions.
+//!
+//! A database's schema version is kept in its `user_version` pragma. It is the
+//! number of migrations which have been run on it, so the first migration of
+//! each schema creates the tables and takes a database to version 1.
+//!
+//! To change a schema, append a [`Migration`] to its list rather than editing
+//! the existing ones, so databases created by earlier versions of Holochain
+//! are migrated instead of having to be wiped.
+
+use std::path::Path;
+use std::path::PathBuf;
+
 use once_cell::sync::Lazy;
 use rusqlite::Connection;
+use rusqlite::Transaction;
 
 use crate::db::DbKind;
+use crate::error::DatabaseError;
+use crate::error::DatabaseResult;
 use crate::sql::*;
 
-pub static SCHEMA_CELL: Lazy<Schema> = Lazy::new(|| {
-    let migration_0 = Migration::initial(sql_cell::SCHEMA);
-
-    Schema {
-        current_index: 0,
-        migrations: vec![migration_0],
-    }
-});
-
-pub static SCHEMA_CONDUCTOR: Lazy<Schema> = Lazy::new(|| {
-    let migration_0 = Migration::initial(sql_conductor::SCHEMA);
+pub static SCHEMA_CELL: Lazy<Schema> =
+    Lazy::new(|| Schema::new(vec![Migration::initial(sql_cell::SCHEMA)]));
 
-    Schema {
-        current_index: 0,
-        migrations: vec![migration_0],
-    }
-});
+pub static SCHEMA_CONDUCTOR: Lazy<Schema> =
+    Lazy::new(|| Schema::new(vec![Migration::initial(sql_conductor::SCHEMA)]));
 
-pub static SCHEMA_WASM: Lazy<Schema> = Lazy::new(|| {
-    let migration_0 = Migration::initial(sql_wasm::SCHEMA);
+pub static SCHEMA_WASM: Lazy<Schema> =
+    Lazy::new(|| Schema::new(vec![Migration::initial(sql_wasm::SCHEMA)]));
 
-    Schema {
-        current_index: 0,
-        migrations: vec![migration_0],
-    }
-});
+pub static SCHEMA_P2P_STATE: Lazy<Schema> =
+    Lazy::new(|| Schema::new(vec![Migration::initial(sql_p2p_agent_store::SCHEMA)]));
 
-pub static SCHEMA_P2P_STATE: Lazy<Schema> = Lazy::new(|| {
-    let migration_0 = Migration::initial(sql_p2p_agent_store::SCHEMA);
-
-    Schema {
-        current_index: 0,
-        migrations: vec![migration_0],
-    }
-});
-
-pub static SCHEMA_P2P_METRICS: Lazy<Schema> = Lazy::new(|| {
-    let migration_0 = Migration::initial(sql_p2p_metrics::SCHEMA);
-
-    Schema {
-        current_index: 0,
-        migrations: vec![migration_0],
-    }
-});
+pub static SCHEMA_P2P_METRICS: Lazy<Schema> =
+    Lazy::new(|| Schema::new(vec![Migration::initial(sql_p2p_metrics::SCHEMA)]));
 
 pub struct Schema {
-    current_index: usize,
     migrations: Vec<Migration>,
 }
 
 impl Schema {
-    /// Determine if any database migrations need to run, and run them if so.
-    /// The decision is based on the difference between this Schema's
-    /// current_index and the user_version pragma value in the database itself.
-    /// NB: The current_index is 0-based, and the user_version is 1-based.
-    pub fn initialize(
-        &self,
-        conn: &mut Connection,
-        db_kind: Option<DbKind>,
-    ) -> rusqlite::Result<()> {
+    /// A schema made by running these migrations in order.
+    pub fn new(migrations: Vec<Migration>) -> Self {
+        assert!(
+            !migrations.is_empty(),
+            "a schema needs an initial migration"
+        );
+        Self { migrations }
+    }
+
+    /// The version databases with this schema are migrated to.
+    pub fn version(&self) -> u16 {
+        self.migrations.len() as u16
+    }
+
+    /// Run any migrations the database hasn't had yet.
+    ///
+    /// Databases which existed before schema versions were recorded have
+    /// version 0, like new databases. They are migrated from the start,
+    /// which is safe because the initial migrations only create what doesn't
+    /// exist yet.
+    ///
+    /// A backup of a database file is taken before migrating it, next to the
+    /// file and named after the version it was migrated from.
+    /// It is an error to open a database with a newer version than this schema,
+    /// as it would have been written by a newer version of Holochain.
+    pub fn initialize(&self, conn: &mut Connection, db_kind: Option<DbKind>) -> DatabaseResult<()> {
         let user_version: u16 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
         let db_kind = db_kind
             .as_ref()
             .map(ToString::to_string)
             .unwrap_or_else(|| "<no name>".to_string());
 
-        if user_version == 0 {
-            // database just needs to be created / initialized
-            self.migrations[self.current_index].initialize(conn)?;
-            tracing::info!("database initialized: {}", db_kind);
-            return Ok(());
-        } else {
-            let current_index = user_version as usize - 1;
-            match current_index.cmp(&self.current_index) {
-                std::cmp::Ordering::Less => {
-                    // run forward migrations
-                    for v in current_index..self.current_index + 1 {
-                        self.migrations[v].run(conn)?;
+        match user_version.cmp(&self.version()) {
+            std::cmp::Ordering::Less => {
+                let has_tables: bool = conn.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table')",
+                    [],
+                    |row| row.get(0),
+                )?;
+                let backup = match database_file(conn)? {
+                    Some(path) if has_tables => Some(backup(conn, &path, user_version)?),
+                    _ => None,
+                };
+                for (index, migration) in self
+                    .migrations
+                    .iter()
+                    .enumerate()
+                    .skip(user_version as usize)
+                {
+                    let version = index as u16 + 1;
+                    if let Err(e) = migration.run(conn, version) {
+                        tracing::error!(
+                            ?e,
+                            ?backup,
+                            "database failed to migrate: {} to {}",
+                            db_kind,
+                            version
+                        );
+                        return Err(e.into());
                     }
-                    // set the DB user_version so that next time we don't run
-                    // the same migration
-                    let new_user_version = (self.current_index + 1) as u16;
-                    conn.pragma_update(None, "user_version", &new_user_version)?;
+                }
+                if has_tables {
                     tracing::info!(
                         "database forward migrated: {} from {} to {}",
                         db_kind,
-                        current_index,
-                        self.current_index
-                    );
-                }
-                std::cmp::Ordering::Equal => {
-                    tracing::debug!(
-                        "database needed no migration or initialization, good to go: {}",
-                        db_kind
+                        user_version,
+                        self.version()
                     );
+                } else {
+                    tracing::info!("database initialized: {}", db_kind);
                 }
-                std::cmp::Ordering::Greater => {
-                    unimplemented!("backward migrations unimplemented");
-                }
+            }
+            std::cmp::Ordering::Equal => {
+                tracing::debug!(
+                    "database needed no migration or initialization, good to go: {}",
+                    db_kind
+                );
+            }
+            std::cmp::Ordering::Greater => {
+                return Err(DatabaseError::SchemaTooNew {
+                    db_kind,
+                    db_version: user_version,
+                    supported_version: self.version(),
+                });
             }
         }
 
@@ -110,29 +132,215 @@ impl Schema {
     }
 }
 
+/// The file a connection's database is kept in, or `None` if it is in memory
+/// or temporary, in which case there is nothing to back up.
+fn database_file(conn: &Connection) -> DatabaseResult<Option<PathBuf>> {
+    match conn.path() {
+        Some(path) if !path.as_os_str().is_empty() && path != Path::new(":memory:") => (),
+        _ => return Ok(None),
+    }
+    // SQLite has no file name for databases opened in memory by URI.
+    let file: String = conn.query_row(
+        "SELECT file FROM pragma_database_list WHERE name = 'main'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok((!file.is_empty()).then(|| PathBuf::from(file)))
+}
+
+/// Copy the database to a file next to it before migrating it from `version`,
+/// returning the path of the copy.
+fn backup(conn: &Connection, path: &Path, version: u16) -> DatabaseResult<PathBuf> {
+    let mut backup_path = path.to_owned().into_os_string();
+    backup_path.push(format!(".v{}.bak", version));
+    let backup_path = PathBuf::from(backup_path);
+    // A backup left by an earlier attempt at this migration is of the same version.
+    if backup_path.exists() {
+        std::fs::remove_file(&backup_path)?;
+    }
+    conn.execute("VACUUM INTO ?", [backup_path.to_string_lossy().as_ref()])?;
+    Ok(backup_path)
+}
+
+/// One step in a schema's history.
 pub struct Migration {
-    schema: Sql,
-    _forward: Sql,
-    _backward: Option<Sql>,
+    step: MigrationStep,
+}
+
+enum MigrationStep {
+    Sql(Sql),
+    Rust(fn(&Transaction) -> rusqlite::Result<()>),
 }
 
 impl Migration {
+    /// The first migration of a schema, which creates its tables.
+    /// The SQL must only create what doesn't exist yet, as it is also run on
+    /// databases from before schema versions were recorded.
     pub fn initial(schema: &str) -> Self {
+        Self::sql(schema)
+    }
+
+    /// A migration which runs these SQL statements.
+    pub fn sql(sql: &str) -> Self {
         Self {
-            schema: schema.into(),
-            _forward: "".into(),
-            _backward: None,
+            step: MigrationStep::Sql(sql.into()),
         }
     }
 
-    pub fn initialize(&self, conn: &mut Connection) -> rusqlite::Result<()> {
-        conn.execute_batch(&self.schema)?;
-        Ok(())
+    /// A migration which runs this function, for changes which can't be
+    /// expressed in SQL alone, like re-encoding blobs.
+    pub fn rust(f: fn(&Transaction) -> rusqlite::Result<()>) -> Self {
+        Self {
+            step: MigrationStep::Rust(f),
+        }
     }
 
-    pub fn run(&self, _conn: &mut Connection) -> rusqlite::Result<()> {
-        unimplemented!("actual migrations not yet implemented")
+    /// Run the migration and record the version it takes the database to,
+    /// in a single transaction.
+    fn run(&self, conn: &mut Connection, version: u16) -> rusqlite::Result<()> {
+        let txn = conn.transaction()?;
+        match &self.step {
+            MigrationStep::Sql(sql) => txn.execute_batch(sql)?,
+            MigrationStep::Rust(f) => f(&txn)?,
+        }
+        txn.pragma_update(None, "user_version", version)?;
+        txn.commit()
     }
 }
 
 type Sql = String;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Migration::initial("CREATE TABLE IF NOT EXISTS Thing (id INTEGER PRIMARY KEY);"),
+            Migration::sql("ALTER TABLE Thing ADD COLUMN name TEXT;"),
+            Migration::rust(|txn| {
+                txn.execute("UPDATE Thing SET name = 'thing ' || id", [])?;
+                Ok(())
+            }),
+        ])
+    }
+
+    fn user_version(conn: &Connection) -> u16 {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn new_database_is_initialized_to_the_latest_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema().initialize(&mut conn, None).unwrap();
+        assert_eq!(user_version(&conn), 3);
+        conn.execute("INSERT INTO Thing (id, name) VALUES (1, 'a')", [])
+            .unwrap();
+        // Nothing runs again once the database is up to date.
+        schema().initialize(&mut conn, None).unwrap();
+        assert_eq!(user_version(&conn), 3);
+    }
+
+    #[test]
+    fn existing_database_is_migrated_and_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.sqlite3");
+        let mut conn = Connection::open(&path).unwrap();
+        Schema::new(vec![Migration::initial(
+            "CREATE TABLE IF NOT EXISTS Thing (id INTEGER PRIMARY KEY);",
+        )])
+        .initialize(&mut conn, None)
+        .unwrap();
+        conn.execute("INSERT INTO Thing (id) VALUES (1)", [])
+            .unwrap();
+
+        schema().initialize(&mut conn, None).unwrap();
+        assert_eq!(user_version(&conn), 3);
+        let name: String = conn
+            .query_row("SELECT name FROM Thing WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "thing 1");
+
+        let backup = Connection::open(dir.path().join("test.sqlite3.v1.bak")).unwrap();
+        assert_eq!(user_version(&backup), 1);
+        let count: u32 = backup
+            .query_row("SELECT COUNT(*) FROM Thing", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn in_memory_and_temporary_databases_are_not_backed_up() {
+        let backups = || {
+            std::fs::read_dir(".")
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".bak"))
+                .collect::<Vec<_>>()
+        };
+        let before = backups();
+        for mut conn in [
+            Connection::open_in_memory().unwrap(),
+            Connection::open("").unwrap(),
+        ] {
+            conn.execute_batch("CREATE TABLE Thing (id INTEGER PRIMARY KEY);")
+                .unwrap();
+            schema().initialize(&mut conn, None).unwrap();
+            assert_eq!(user_version(&conn), 3);
+        }
+        assert_eq!(backups(), before);
+    }
+
+    #[test]
+    fn unversioned_database_is_migrated_from_the_start() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE Thing (id INTEGER PRIMARY KEY);")
+            .unwrap();
+        schema().initialize(&mut conn, None).unwrap();
+        assert_eq!(user_version(&conn), 3);
+    }
+
+    #[test]
+    fn failed_migration_keeps_the_previous_version() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        Schema::new(vec![Migration::initial(
+            "CREATE TABLE IF NOT EXISTS Thing (id INTEGER PRIMARY KEY);",
+        )])
+        .initialize(&mut conn, None)
+        .unwrap();
+        let broken = Schema::new(vec![
+            Migration::initial("CREATE TABLE IF NOT EXISTS Thing (id INTEGER PRIMARY KEY);"),
+            Migration::sql("CREATE TABLE Other (id INTEGER); ALTER TABLE Missing ADD COLUMN x;"),
+        ]);
+        assert!(broken.initialize(&mut conn, None).is_err());
+        assert_eq!(user_version(&conn), 1);
+        let other_exists: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE name = 'Other')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!other_exists);
+    }
+
+    #[test]
+    fn newer_database_is_an_error() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        schema().initialize(&mut conn, None).unwrap();
+        let err = Schema::new(vec![Migration::initial(
+            "CREATE TABLE IF NOT EXISTS Thing (id INTEGER PRIMARY KEY);",
+        )])
+        .initialize(&mut conn, None)
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            DatabaseError::SchemaTooNew {
+                db_version: 3,
+                supported_version: 1,
+                ..
+            }
+        ));
+    }
+}
@@ -29,6 +29,7 @@ pub mod sql_cell {
         pub const UPDATE: &str = include_str!("sql/cell/schedule/update.sql");
         pub const DELETE: &str = include_str!("sql/cell/schedule/delete.sql");
         pub const EXPIRED: &str = include_str!("sql/cell/schedule/expired.sql");
+        pub const DUE: &str = include_str!("sql/cell/schedule/due.sql");
         pub const DELETE_ALL_EPHEMERAL: &str =
             include_str!("sql/cell/schedule/delete_all_ephemeral.sql");
         pub const DELETE_LIVE_EPHEMERAL: &str =
@@ -0,0 +1,13 @@
+SELECT
+  zome_name,
+  scheduled_fn,
+  maybe_schedule,
+  ephemeral,
+END
+FROM
+  ScheduledFunctions
+WHERE
+  START <= :now
+  AND author = :author
+ORDER BY
+  START ASC
@@ -171,3 +171,31 @@ CREATE TABLE IF NOT EXISTS ScheduledFunctions (
     PRIMARY KEY (zome_name, scheduled_fn, author) ON CONFLICT ROLLBACK
 );
 
+
+CREATE TABLE IF NOT EXISTS Warrant (
+    -- The hash of the action the warrant is about.
+    action_hash BLOB NOT NULL,
+    -- The authority which issued the warrant.
+    author BLOB NOT NULL,
+    -- The agent the warrant is against.
+    warrantee BLOB NOT NULL,
+    timestamp INTEGER NOT NULL,
+    blob BLOB NOT NULL,
+    PRIMARY KEY (action_hash, author) ON CONFLICT IGNORE
+);
+CREATE INDEX IF NOT EXISTS Warrant_warrantee_idx ON Warrant ( warrantee );
+
+-- The ops of countersigning sessions this node is an authority for,
+-- which are kept until the session completes or expires.
+CREATE TABLE IF NOT EXISTS CountersigningSessionOp (
+    -- The hash of the countersigned entry, which identifies the session.
+    entry_hash BLOB NOT NULL,
+    -- The hash of the signer's action.
+    action_hash BLOB NOT NULL,
+    op_hash BLOB NOT NULL,
+    -- The op and the hashes of the actions the session requires.
+    blob BLOB NOT NULL,
+    -- When the session expires as a Timestamp (microseconds)
+    expires_at_timestamp INTEGER NOT NULL,
+    PRIMARY KEY (entry_hash, action_hash) ON CONFLICT REPLACE
+);
@@ -66,3 +66,23 @@ pub fn is_lock_expired(
     // If there's no lock then it's expired.
     Ok(r.unwrap_or(true))
 }
+
+/// When the lock on the chain expires, if it is currently locked.
+pub fn chain_lock_expires_at(
+    txn: &Transaction,
+    author: &AgentPubKey,
+) -> StateMutationResult<Option<Timestamp>> {
+    Ok(txn.query_row(
+        "
+        SELECT MAX(expires_at_timestamp)
+        FROM ChainLock
+        WHERE expires_at_timestamp >= :now
+        AND author = :author
+        ",
+        named_params! {
+            ":author": author,
+            ":now": holochain_zome_types::Timestamp::now()
+        },
+        |row| row.get(0),
+    )?)
+}
@@ -0,0 +1,97 @@
+//! Queries for the countersigning sessions this node is an authority for.
+
+use holo_hash::ActionHash;
+use holo_hash::DhtOpHash;
+use holo_hash::EntryHash;
+use holochain_sqlite::rusqlite::named_params;
+use holochain_sqlite::rusqlite::Transaction;
+use holochain_types::dht_op::DhtOp;
+use holochain_zome_types::Timestamp;
+
+use crate::prelude::from_blob;
+use crate::prelude::StateQueryResult;
+
+/// An op held for a countersigning session, with the hashes of the actions
+/// the session requires and when it expires.
+pub type CountersigningSessionOp = (EntryHash, DhtOpHash, DhtOp, Vec<ActionHash>, Timestamp);
+
+/// Get the ops of every countersigning session which hasn't expired yet.
+pub fn pending_countersigning_session_ops(
+    txn: &Transaction,
+    now: Timestamp,
+) -> StateQueryResult<Vec<CountersigningSessionOp>> {
+    let mut stmt = txn.prepare(
+        "
+        SELECT entry_hash, op_hash, blob, expires_at_timestamp
+        FROM CountersigningSessionOp
+        WHERE expires_at_timestamp > :now
+        ",
+    )?;
+    let iter = stmt.query_and_then(
+        named_params! {
+            ":now": now
+        },
+        |row| {
+            let (op, required_actions) = from_blob::<(DhtOp, Vec<ActionHash>)>(row.get("blob")?)?;
+            Ok((
+                row.get("entry_hash")?,
+                row.get("op_hash")?,
+                op,
+                required_actions,
+                row.get("expires_at_timestamp")?,
+            ))
+        },
+    )?;
+    iter.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutations;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+    use holochain_zome_types::fixt::*;
+    use std::time::Duration;
+
+    #[test]
+    fn pending_sessions_are_kept_until_they_complete_or_expire() {
+        let db = crate::test_utils::test_authored_db();
+        let now = Timestamp::now();
+        let op = DhtOp::RegisterAgentActivity(fixt!(Signature), fixt!(Action));
+        let op_hash = DhtOpHash::with_data_sync(&op);
+        let required_actions = vec![ActionHash::with_data_sync(&op.action())];
+        let pending = fixt!(EntryHash);
+        let expired = fixt!(EntryHash);
+
+        db.to_db().test_commit(|txn| {
+            let later = (now + Duration::from_secs(60)).unwrap();
+            let earlier = (now - Duration::from_secs(60)).unwrap();
+            for (entry_hash, expires) in [(&pending, later), (&expired, earlier)] {
+                mutations::insert_countersigning_session_op(
+                    txn,
+                    entry_hash,
+                    &op_hash,
+                    &op,
+                    &required_actions,
+                    expires,
+                )
+                .unwrap();
+            }
+            let ops = pending_countersigning_session_ops(txn, now).unwrap();
+            assert_eq!(ops.len(), 1);
+            assert_eq!(ops[0].0, pending);
+            assert_eq!(ops[0].2, op);
+            assert_eq!(ops[0].3, required_actions);
+
+            mutations::delete_expired_countersigning_sessions(txn, now).unwrap();
+            mutations::delete_countersigning_session(txn, &pending).unwrap();
+            let remaining: usize = txn
+                .query_row("SELECT COUNT(*) FROM CountersigningSessionOp", [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(remaining, 0);
+        });
+    }
+}
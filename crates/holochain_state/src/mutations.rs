@@ -3,6 +3,7 @@ use crate::prelude::SignedValidationReceipt;
 use crate::query::from_blob;
 use crate::query::to_blob;
 use crate::schedule::fn_is_scheduled;
+use crate::schedule::next_persisted_run;
 use crate::scratch::Scratch;
 use crate::validation_db::ValidationLimboStatus;
 use holo_hash::encode::blake2b_256;
@@ -10,6 +11,7 @@ use holo_hash::*;
 use holochain_sqlite::rusqlite::named_params;
 use holochain_sqlite::rusqlite::types::Null;
 use holochain_sqlite::rusqlite::Transaction;
+use holochain_types::dht_op::DhtOp;
 use holochain_types::dht_op::DhtOpLight;
 use holochain_types::dht_op::OpOrder;
 use holochain_types::dht_op::{DhtOpHashed, DhtOpType};
@@ -19,7 +21,6 @@ use holochain_types::prelude::DnaWasmHashed;
 use holochain_types::sql::AsSql;
 use holochain_zome_types::entry::EntryHashed;
 use holochain_zome_types::*;
-use std::str::FromStr;
 
 pub use error::*;
 
@@ -209,6 +210,69 @@ pub fn insert_validation_receipt(
     Ok(())
 }
 
+/// Insert a [`SignedWarrant`] into the database.
+/// A second warrant from the same authority about the same action is ignored.
+pub fn insert_warrant(txn: &mut Transaction, warrant: &SignedWarrant) -> StateMutationResult<()> {
+    let action_hash = match &warrant.proof {
+        WarrantProof::InvalidAction { action, .. } => ActionHash::with_data_sync(&action.0),
+    };
+    sql_insert!(txn, Warrant, {
+        "action_hash": action_hash,
+        "author": warrant.author,
+        "warrantee": warrant.warrantee(),
+        "timestamp": warrant.timestamp,
+        "blob": to_blob(warrant)?,
+    })?;
+    Ok(())
+}
+
+/// Insert an op held for a countersigning session this node is an authority for.
+pub fn insert_countersigning_session_op(
+    txn: &mut Transaction,
+    entry_hash: &EntryHash,
+    op_hash: &DhtOpHash,
+    op: &DhtOp,
+    required_actions: &[ActionHash],
+    expires: Timestamp,
+) -> StateMutationResult<()> {
+    sql_insert!(txn, CountersigningSessionOp, {
+        "entry_hash": entry_hash,
+        "action_hash": ActionHash::with_data_sync(&op.action()),
+        "op_hash": op_hash,
+        "blob": to_blob(&(op, required_actions))?,
+        "expires_at_timestamp": expires,
+    })?;
+    Ok(())
+}
+
+/// Delete the ops held for a countersigning session.
+pub fn delete_countersigning_session(
+    txn: &mut Transaction,
+    entry_hash: &EntryHash,
+) -> StateMutationResult<()> {
+    txn.execute(
+        "DELETE FROM CountersigningSessionOp WHERE entry_hash = :entry_hash",
+        named_params! {
+            ":entry_hash": entry_hash,
+        },
+    )?;
+    Ok(())
+}
+
+/// Delete the ops held for countersigning sessions which have expired.
+pub fn delete_expired_countersigning_sessions(
+    txn: &mut Transaction,
+    now: Timestamp,
+) -> StateMutationResult<()> {
+    txn.execute(
+        "DELETE FROM CountersigningSessionOp WHERE expires_at_timestamp <= :now",
+        named_params! {
+            ":now": now,
+        },
+    )?;
+    Ok(())
+}
+
 /// Insert a [`DnaWasm`](holochain_types::prelude::DnaWasm) into the database.
 pub fn insert_wasm(txn: &mut Transaction, wasm: DnaWasmHashed) -> StateMutationResult<()> {
     let (wasm, hash) = wasm.into_inner();
@@ -652,6 +716,84 @@ pub fn reschedule_expired(
     Ok(())
 }
 
+/// Take this author's scheduled functions which are due to run at `now`,
+/// so that each runs once each time it is due. Ephemeral schedules are
+/// removed and persisted schedules advance to the next time they fire.
+///
+/// A persisted schedule which missed its run by more than
+/// [`PERSISTED_TIMEOUT`](holochain_zome_types::schedule::PERSISTED_TIMEOUT)
+/// is only run according to the `catch_up` policy.
+pub fn take_due_scheduled_fns(
+    txn: &mut Transaction,
+    now: Timestamp,
+    author: &AgentPubKey,
+    catch_up: ScheduleCatchUp,
+) -> StateMutationResult<Vec<(ScheduledFn, Option<Schedule>)>> {
+    let rows = {
+        let mut stmt = txn.prepare(holochain_sqlite::sql::sql_cell::schedule::DUE)?;
+        let rows = stmt.query_map(
+            named_params! {
+                ":now": now,
+                ":author" : author,
+            },
+            |row| {
+                Ok((
+                    ScheduledFn::new(
+                        ZomeName(row.get::<_, String>(0)?.into()),
+                        FunctionName(row.get(1)?),
+                    ),
+                    row.get(2)?,
+                    row.get::<_, bool>(3)?,
+                    row.get::<_, Timestamp>(4)?,
+                ))
+            },
+        )?;
+        let mut ret = vec![];
+        for row in rows {
+            ret.push(row?);
+        }
+        ret
+    };
+    let mut due = vec![];
+    for (scheduled_fn, maybe_schedule, ephemeral, end) in rows {
+        let maybe_schedule: Option<Schedule> = from_blob(maybe_schedule)?;
+        if ephemeral {
+            due.push((scheduled_fn, maybe_schedule));
+            continue;
+        }
+        let missed = end < now;
+        schedule_fn(
+            txn,
+            author,
+            scheduled_fn.clone(),
+            maybe_schedule.clone(),
+            now,
+        )?;
+        if !missed || catch_up == ScheduleCatchUp::RunOnce {
+            due.push((scheduled_fn, maybe_schedule));
+        }
+    }
+    delete_live_ephemeral_scheduled_fns(txn, now, author)?;
+    Ok(due)
+}
+
+/// Remove a function's schedule, if it has one.
+pub fn unschedule_fn(
+    txn: &mut Transaction,
+    author: &AgentPubKey,
+    scheduled_fn: ScheduledFn,
+) -> StateMutationResult<()> {
+    txn.execute(
+        holochain_sqlite::sql::sql_cell::schedule::DELETE,
+        named_params! {
+            ":zome_name": scheduled_fn.zome_name().to_string(),
+            ":scheduled_fn": scheduled_fn.fn_name().to_string(),
+            ":author" : author,
+        },
+    )?;
+    Ok(())
+}
+
 pub fn schedule_fn(
     txn: &mut Transaction,
     author: &AgentPubKey,
@@ -663,32 +805,16 @@ pub fn schedule_fn(
         Some(Schedule::Persisted(ref schedule_string)) => {
             // If this cron doesn't parse cleanly we don't even want to
             // write it to the db.
-            let start = if let Some(start) = cron::Schedule::from_str(schedule_string)
-                .map_err(|e| ScheduleError::Cron(e.to_string()))?
-                .after(
-                    &chrono::DateTime::<chrono::Utc>::try_from(now)
-                        .map_err(ScheduleError::Timestamp)?,
-                )
-                .next()
-            {
+            let start = if let Some(start) = next_persisted_run(schedule_string, now)? {
                 start
             } else {
                 // If there are no further executions then scheduling is a
                 // delete and bail.
-                let _ = txn.execute(
-                    holochain_sqlite::sql::sql_cell::schedule::DELETE,
-                    named_params! {
-                        ":zome_name": scheduled_fn.zome_name().to_string(),
-                        ":scheduled_fn": scheduled_fn.fn_name().to_string(),
-                        ":author" : author,
-                    },
-                )?;
-                return Ok(());
+                return unschedule_fn(txn, author, scheduled_fn);
             };
-            let end = start
-                + chrono::Duration::from_std(holochain_zome_types::schedule::PERSISTED_TIMEOUT)
-                    .map_err(|e| ScheduleError::Cron(e.to_string()))?;
-            (Timestamp::from(start), Timestamp::from(end), false)
+            let end = (start + holochain_zome_types::schedule::PERSISTED_TIMEOUT)
+                .map_err(ScheduleError::Timestamp)?;
+            (start, end, false)
         }
         Some(Schedule::Ephemeral(duration)) => (
             (now + duration).map_err(ScheduleError::Timestamp)?,
@@ -5,9 +5,11 @@ use holochain_sqlite::rusqlite::OptionalExtension;
 use holochain_sqlite::rusqlite::{named_params, Transaction};
 use holochain_zome_types::FunctionName;
 use holochain_zome_types::Schedule;
+use holochain_zome_types::ScheduleError;
 use holochain_zome_types::ScheduledFn;
 use holochain_zome_types::Timestamp;
 use holochain_zome_types::ZomeName;
+use std::str::FromStr;
 
 pub fn fn_is_scheduled(
     txn: &Transaction,
@@ -76,3 +78,288 @@ pub fn live_scheduled_fns(
     }
     Ok(ret)
 }
+
+/// All functions scheduled by this author, whether or not they are currently
+/// live.
+pub fn all_scheduled_fns(
+    txn: &Transaction,
+    author: &AgentPubKey,
+) -> StateMutationResult<Vec<(ScheduledFn, Option<Schedule>)>> {
+    let mut stmt = txn.prepare(
+        "
+        SELECT
+        zome_name,
+        scheduled_fn,
+        maybe_schedule
+        FROM ScheduledFunctions
+        WHERE
+        author = :author
+        ORDER BY start ASC",
+    )?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":author": author,
+        },
+        |row| {
+            Ok((
+                ScheduledFn::new(
+                    ZomeName(row.get::<_, String>(0)?.into()),
+                    FunctionName(row.get(1)?),
+                ),
+                row.get(2)?,
+            ))
+        },
+    )?;
+    let mut ret = vec![];
+    for row in rows {
+        let (scheduled_fn, maybe_schedule_serialized) = row?;
+        ret.push((scheduled_fn, from_blob(maybe_schedule_serialized)?));
+    }
+    Ok(ret)
+}
+
+/// The next time a persisted schedule's cron expression fires after the
+/// given time, or `None` if it never fires again.
+///
+/// Standard five field crontabs fire on the first second of the minute, and
+/// number the days of the week from 0 or 7 for Sunday to 6 for Saturday.
+/// Expressions with six or seven fields start with a seconds field, and
+/// number the days of the week from 1 for Sunday to 7 for Saturday.
+pub fn next_persisted_run(
+    cron_expression: &str,
+    after: Timestamp,
+) -> Result<Option<Timestamp>, ScheduleError> {
+    let fields: Vec<_> = cron_expression.split_whitespace().collect();
+    let expression = match fields.as_slice() {
+        [minutes, hours, days_of_month, months, days_of_week] => format!(
+            "0 {} {} {} {} {}",
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            standard_days_of_week(days_of_week)
+        ),
+        _ => cron_expression.to_string(),
+    };
+    let next = cron::Schedule::from_str(&expression)
+        .map_err(|e| ScheduleError::Cron(e.to_string()))?
+        .after(&chrono::DateTime::<chrono::Utc>::try_from(after).map_err(ScheduleError::Timestamp)?)
+        .next();
+    Ok(next.map(Timestamp::from))
+}
+
+/// Rewrite the numeric days in a standard crontab's day of week field as
+/// names, which mean the same to the `cron` crate.
+/// Anything which isn't a number, range or step of numbers is left as is.
+fn standard_days_of_week(field: &str) -> String {
+    const DAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+    field
+        .split(',')
+        .map(|item| {
+            let (base, step) = match item.split_once('/') {
+                Some((base, step)) => (base, step.parse::<usize>().ok()),
+                None => (item, Some(1)),
+            };
+            let range = match base.split_once('-') {
+                _ if base == "*" => (item != "*").then_some((0, 6)),
+                Some((first, last)) => first.parse::<usize>().ok().zip(last.parse().ok()),
+                // `n/step` steps from `n` to the end of the week.
+                None if base != item => base.parse::<usize>().ok().map(|first| (first, 6)),
+                None => base.parse::<usize>().ok().map(|day| (day, day)),
+            };
+            match (range, step) {
+                (Some((first, last)), Some(step)) if first <= last && last <= 7 && step > 0 => {
+                    (first..=last)
+                        .step_by(step)
+                        .map(|day| DAYS[day % 7])
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }
+                _ => item.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// When the next of this author's scheduled functions is due to run,
+/// if any are scheduled.
+pub fn next_scheduled_run(
+    txn: &Transaction,
+    author: &AgentPubKey,
+) -> StateMutationResult<Option<Timestamp>> {
+    Ok(txn.query_row(
+        "
+        SELECT MIN(start)
+        FROM ScheduledFunctions
+        WHERE author = :author
+        ",
+        named_params! {
+            ":author": author,
+        },
+        |row| row.get(0),
+    )?)
+}
+
+/// All functions scheduled by this author, with when each is next due to run.
+pub fn scheduled_fns_with_next_run(
+    txn: &Transaction,
+    author: &AgentPubKey,
+) -> StateMutationResult<Vec<(ScheduledFn, Option<Schedule>, Timestamp)>> {
+    let mut stmt = txn.prepare(
+        "
+        SELECT
+        zome_name,
+        scheduled_fn,
+        maybe_schedule,
+        start
+        FROM ScheduledFunctions
+        WHERE
+        author = :author
+        ORDER BY start ASC",
+    )?;
+    let rows = stmt.query_map(
+        named_params! {
+            ":author": author,
+        },
+        |row| {
+            Ok((
+                ScheduledFn::new(
+                    ZomeName(row.get::<_, String>(0)?.into()),
+                    FunctionName(row.get(1)?),
+                ),
+                row.get(2)?,
+                row.get(3)?,
+            ))
+        },
+    )?;
+    let mut ret = vec![];
+    for row in rows {
+        let (scheduled_fn, maybe_schedule_serialized, next_run) = row?;
+        ret.push((
+            scheduled_fn,
+            from_blob(maybe_schedule_serialized)?,
+            next_run,
+        ));
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutations::schedule_fn;
+    use crate::mutations::take_due_scheduled_fns;
+    use holochain_sqlite::prelude::*;
+    use holochain_zome_types::test_utils::fake_agent_pubkey_1;
+    use holochain_zome_types::ScheduleCatchUp;
+    use std::time::Duration;
+
+    #[test]
+    fn five_field_crontabs_fire_on_the_minute() {
+        let noon = Timestamp::from_str("2022-02-11T12:00:30Z").unwrap();
+        assert_eq!(
+            next_persisted_run("*/5 * * * *", noon).unwrap(),
+            Some(Timestamp::from_str("2022-02-11T12:05:00Z").unwrap()),
+        );
+        assert_eq!(
+            next_persisted_run("15 * * * * *", noon).unwrap(),
+            Some(Timestamp::from_str("2022-02-11T12:01:15Z").unwrap()),
+        );
+        assert_eq!(next_persisted_run("0 0 0 1 1 * 2000", noon).unwrap(), None);
+
+        // Noon is on a Friday. Five field crontabs count Sunday as 0 or 7.
+        let at_nine = |day| Timestamp::from_str(&format!("2022-02-{}T09:00:00Z", day)).unwrap();
+        for (days_of_week, day) in [
+            ("1", 14),
+            ("0", 13),
+            ("7", 13),
+            ("1-5", 14),
+            ("5-7", 12),
+            ("*/2", 12),
+            ("1/2", 14),
+            ("0,3", 13),
+            ("MON", 14),
+        ] {
+            assert_eq!(
+                next_persisted_run(&format!("0 9 * * {}", days_of_week), noon).unwrap(),
+                Some(at_nine(day)),
+                "{}",
+                days_of_week
+            );
+        }
+        // Six field expressions count Sunday as 1.
+        assert_eq!(
+            next_persisted_run("0 0 9 * * 1", noon).unwrap(),
+            Some(at_nine(13))
+        );
+        assert!(next_persisted_run("not a cron", noon).is_err());
+    }
+
+    #[test]
+    fn due_persisted_fns_run_once_and_missed_runs_follow_catch_up() {
+        let test_db = crate::test_utils::test_authored_db();
+        let author = fake_agent_pubkey_1();
+        let scheduled_fn = ScheduledFn::new("foo".into(), "bar".into());
+        let schedule = Some(Schedule::Persisted("* * * * *".to_string()));
+        let noon = Timestamp::from_str("2022-02-11T12:00:30Z").unwrap();
+        let next_run = Timestamp::from_str("2022-02-11T12:01:00Z").unwrap();
+
+        test_db
+            .to_db()
+            .conn()
+            .unwrap()
+            .with_commit_sync(|txn| {
+                schedule_fn(txn, &author, scheduled_fn.clone(), schedule.clone(), noon)?;
+                assert_eq!(next_scheduled_run(txn, &author)?, Some(next_run));
+
+                // Not due yet.
+                assert!(
+                    take_due_scheduled_fns(txn, noon, &author, ScheduleCatchUp::Skip)?.is_empty()
+                );
+
+                // Due, and only taken once.
+                let due_at = (next_run + Duration::from_secs(1)).unwrap();
+                assert_eq!(
+                    take_due_scheduled_fns(txn, due_at, &author, ScheduleCatchUp::Skip)?,
+                    vec![(scheduled_fn.clone(), schedule.clone())],
+                );
+                assert!(
+                    take_due_scheduled_fns(txn, due_at, &author, ScheduleCatchUp::Skip)?.is_empty()
+                );
+                assert_eq!(
+                    scheduled_fns_with_next_run(txn, &author)?,
+                    vec![(
+                        scheduled_fn.clone(),
+                        schedule.clone(),
+                        Timestamp::from_str("2022-02-11T12:02:00Z").unwrap()
+                    )],
+                );
+
+                // An hour of runs were missed.
+                let later = Timestamp::from_str("2022-02-11T13:00:30Z").unwrap();
+                assert!(
+                    take_due_scheduled_fns(txn, later, &author, ScheduleCatchUp::Skip)?.is_empty()
+                );
+                assert_eq!(
+                    next_scheduled_run(txn, &author)?,
+                    Some(Timestamp::from_str("2022-02-11T13:01:00Z").unwrap())
+                );
+
+                let much_later = Timestamp::from_str("2022-02-11T14:00:30Z").unwrap();
+                assert_eq!(
+                    take_due_scheduled_fns(txn, much_later, &author, ScheduleCatchUp::RunOnce)?,
+                    vec![(scheduled_fn.clone(), schedule.clone())],
+                );
+                assert!(take_due_scheduled_fns(
+                    txn,
+                    much_later,
+                    &author,
+                    ScheduleCatchUp::RunOnce
+                )?
+                .is_empty());
+                StateMutationResult::Ok(())
+            })
+            .unwrap();
+    }
+}
@@ -210,6 +210,26 @@ impl SourceChain {
         chain_top_ordering: ChainTopOrdering,
         weight: W,
     ) -> SourceChainResult<ActionHash> {
+        self.put_weighed_by(action_builder, maybe_entry, chain_top_ordering, |_| {
+            Ok(weight)
+        })
+        .await
+    }
+
+    /// Put a new record at the end of the source chain, using a ActionBuilder
+    /// and a function which weighs the built, unweighed action for rate limiting.
+    pub async fn put_weighed_by<W, U, B, F>(
+        &self,
+        action_builder: B,
+        maybe_entry: Option<Entry>,
+        chain_top_ordering: ChainTopOrdering,
+        weigh: F,
+    ) -> SourceChainResult<ActionHash>
+    where
+        U: ActionUnweighed<Weight = W>,
+        B: ActionBuilder<U>,
+        F: FnOnce(&U) -> SourceChainResult<W>,
+    {
         let (prev_action, chain_head_seq, chain_head_timestamp) = self.chain_head()?;
         let action_seq = chain_head_seq + 1;
 
@@ -230,8 +250,10 @@ impl SourceChain {
             action_seq,
             prev_action,
         };
+        let unweighed = action_builder.build(common);
+        let weight = weigh(&unweighed)?;
         self.put_with_action(
-            action_builder.build(common).weighed(weight).into(),
+            unweighed.weighed(weight).into(),
             maybe_entry,
             chain_top_ordering,
         )
@@ -577,10 +599,50 @@ where
         check_agent: AgentPubKey,
         check_secret: Option<CapSecret>,
     ) -> SourceChainResult<Option<CapGrant>> {
+        Ok(self
+            .authorizing_cap_grant(check_function, check_agent, check_secret)
+            .await?
+            .map(|(grant, _)| grant))
+    }
+
+    /// Like [`Self::valid_cap_grant`], but also returns the hash of the action
+    /// which committed the grant. The author's implicit grant has none.
+    pub async fn authorizing_cap_grant(
+        &self,
+        check_function: GrantedFunction,
+        check_agent: AgentPubKey,
+        check_secret: Option<CapSecret>,
+    ) -> SourceChainResult<Option<(CapGrant, Option<ActionHash>)>> {
         let author_grant = CapGrant::from(self.agent_pubkey().clone());
         if author_grant.is_valid(&check_function, &check_agent, check_secret.as_ref()) {
-            return Ok(Some(author_grant));
+            return Ok(Some((author_grant, None)));
         }
+        Ok(self
+            .committed_cap_grant_with_action(check_function, check_agent, check_secret)
+            .await?
+            .map(|(grant, action_hash)| (grant, Some(action_hash))))
+    }
+
+    /// Like [`Self::valid_cap_grant`], but only considers the grants committed
+    /// to the chain, so the chain's author is not authorized by default.
+    pub async fn committed_cap_grant(
+        &self,
+        check_function: GrantedFunction,
+        check_agent: AgentPubKey,
+        check_secret: Option<CapSecret>,
+    ) -> SourceChainResult<Option<CapGrant>> {
+        Ok(self
+            .committed_cap_grant_with_action(check_function, check_agent, check_secret)
+            .await?
+            .map(|(grant, _)| grant))
+    }
+
+    async fn committed_cap_grant_with_action(
+        &self,
+        check_function: GrantedFunction,
+        check_agent: AgentPubKey,
+        check_secret: Option<CapSecret>,
+    ) -> SourceChainResult<Option<(CapGrant, ActionHash)>> {
         let author = self.author.clone();
         // TODO: SQL_PERF: This query could have a fast upper bound if we add indexes.
         let valid_cap_grant = self
@@ -599,7 +661,7 @@ where
             ";
                 let sql = format!(
                     "
-                SELECT DISTINCT Entry.blob
+                SELECT DISTINCT Entry.blob, Action.hash
                 FROM Entry
                 JOIN Action ON Action.entry_hash = Entry.hash
                 JOIN DhtOp ON Action.hash = DhtOp.action_hash
@@ -617,16 +679,20 @@ where
                         named_params! {
                             ":author": author,
                         },
-                        |row| from_blob(row.get("blob")?),
+                        |row| {
+                            let entry: Entry = from_blob(row.get("blob")?)?;
+                            let action_hash: ActionHash = row.get("hash")?;
+                            StateQueryResult::Ok((entry, action_hash))
+                        },
                     )?
-                    .filter_map(|result: StateQueryResult<Entry>| match result {
-                        Ok(entry) => entry
+                    .filter_map(|result| match result {
+                        Ok((entry, action_hash)) => entry
                             .as_cap_grant()
                             .filter(|grant| !matches!(grant, CapGrant::ChainAuthor(_)))
                             .filter(|grant| {
                                 grant.is_valid(&check_function, &check_agent, check_secret.as_ref())
                             })
-                            .map(|cap| Some(Ok(cap)))
+                            .map(|cap| Some(Ok((cap, action_hash))))
                             .unwrap_or(None),
                         Err(e) => Some(Err(e)),
                     })
@@ -634,15 +700,16 @@ where
                     // authorship > assigned > transferable > unrestricted
                     .fold(
                         Ok(None),
-                        |acc: StateQueryResult<Option<CapGrant>>, grant| {
+                        |acc: StateQueryResult<Option<(CapGrant, ActionHash)>>, grant| {
                             let grant = grant?;
                             let acc = acc?;
-                            let acc = match &grant {
+                            let acc = match &grant.0 {
                                 CapGrant::RemoteAgent(zome_call_cap_grant) => {
                                     match &zome_call_cap_grant.access {
                                         CapAccess::Assigned { .. } => match &acc {
-                                            Some(CapGrant::RemoteAgent(
-                                                acc_zome_call_cap_grant,
+                                            Some((
+                                                CapGrant::RemoteAgent(acc_zome_call_cap_grant),
+                                                _,
                                             )) => {
                                                 match acc_zome_call_cap_grant.access {
                                                     // an assigned acc takes precedence
@@ -656,8 +723,9 @@ where
                                             _ => unreachable!(),
                                         },
                                         CapAccess::Transferable { .. } => match &acc {
-                                            Some(CapGrant::RemoteAgent(
-                                                acc_zome_call_cap_grant,
+                                            Some((
+                                                CapGrant::RemoteAgent(acc_zome_call_cap_grant),
+                                                _,
                                             )) => {
                                                 match acc_zome_call_cap_grant.access {
                                                     // an assigned acc takes precedence
@@ -846,6 +914,42 @@ where
         Ok(query.filter_records(records))
     }
 
+    /// Query the Records of an entry type in the source chain that are still
+    /// live as per local CRUD, i.e. not yet updated or deleted by this author.
+    pub async fn query_live(
+        &self,
+        entry_type: action::EntryType,
+    ) -> SourceChainResult<Vec<Record>> {
+        let records = self
+            .query(
+                QueryFilter::new()
+                    .entry_type(entry_type)
+                    .include_entries(true),
+            )
+            .await?;
+        let mut dead = std::collections::HashSet::new();
+        for action_type in [action::ActionType::Update, action::ActionType::Delete] {
+            for record in self
+                .query(QueryFilter::new().action_type(action_type))
+                .await?
+            {
+                match record.action() {
+                    Action::Update(update) => {
+                        dead.insert(update.original_action_address.clone());
+                    }
+                    Action::Delete(delete) => {
+                        dead.insert(delete.deletes_address.clone());
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(records
+            .into_iter()
+            .filter(|record| !dead.contains(record.action_address()))
+            .collect())
+    }
+
     pub async fn is_chain_locked(&self, lock: Vec<u8>) -> SourceChainResult<bool> {
         let author = self.author.clone();
         Ok(self
@@ -1236,6 +1340,49 @@ pub async fn dump_state(
         .await?)
 }
 
+/// Get every record authored by this agent, including private entries,
+/// in chain order.
+pub async fn chain_records(
+    vault: DbRead<DbKindAuthored>,
+    author: AgentPubKey,
+) -> Result<Vec<Record>, SourceChainError> {
+    Ok(vault
+        .async_reader(move |txn| {
+            txn.prepare(
+                "
+                SELECT DISTINCT
+                Action.blob AS action_blob, Entry.blob AS entry_blob,
+                Action.hash AS action_hash
+                FROM Action
+                JOIN DhtOp ON DhtOp.action_hash = Action.hash
+                LEFT JOIN Entry ON Action.entry_hash = Entry.hash
+                WHERE
+                Action.author = :author
+                ORDER BY Action.seq ASC
+                ",
+            )?
+            .query_and_then(
+                named_params! {
+                    ":author": author,
+                },
+                |row| {
+                    let SignedAction(action, signature) = from_blob(row.get("action_blob")?)?;
+                    let hash: ActionHash = row.get("action_hash")?;
+                    let action = ActionHashed::with_pre_hashed(action, hash);
+                    let shh = SignedActionHashed::with_presigned(action, signature);
+                    let entry: Option<Vec<u8>> = row.get("entry_blob")?;
+                    let entry = match entry {
+                        Some(entry) => Some(from_blob::<Entry>(entry)?),
+                        None => None,
+                    };
+                    StateQueryResult::Ok(Record::new(shh, entry))
+                },
+            )?
+            .collect::<StateQueryResult<Vec<_>>>()
+        })
+        .await?)
+}
+
 impl From<SourceChain> for SourceChainRead {
     fn from(chain: SourceChain) -> Self {
         SourceChainRead {
@@ -1592,6 +1739,19 @@ pub mod tests {
                     .await?,
                 Some(grant.clone().into())
             );
+            // along with the action which committed it
+            assert_eq!(
+                chain
+                    .authorizing_cap_grant(function.clone(), bob.clone(), secret.clone())
+                    .await?,
+                Some((grant.clone().into(), Some(original_action_address.clone())))
+            );
+            assert_eq!(
+                chain
+                    .authorizing_cap_grant(function.clone(), alice.clone(), secret.clone())
+                    .await?,
+                Some((CapGrant::ChainAuthor(alice.clone()), None))
+            );
         }
 
         // let's roll the secret and assign the grant to bob specifically
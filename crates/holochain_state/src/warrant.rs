@@ -0,0 +1,97 @@
+//! Queries for the warrants held against agents.
+
+use holo_hash::AgentPubKey;
+use holochain_sqlite::rusqlite::named_params;
+use holochain_sqlite::rusqlite::Transaction;
+use holochain_zome_types::warrant::SignedWarrant;
+
+use crate::prelude::from_blob;
+use crate::prelude::StateQueryResult;
+
+/// Get every warrant stored against an agent, oldest first.
+pub fn get_warrants_for_agent(
+    txn: &Transaction,
+    warrantee: &AgentPubKey,
+) -> StateQueryResult<Vec<SignedWarrant>> {
+    let mut stmt = txn.prepare(
+        "
+        SELECT blob FROM Warrant WHERE warrantee = :warrantee
+        ORDER BY timestamp
+        ",
+    )?;
+    let iter = stmt.query_and_then(
+        named_params! {
+            ":warrantee": warrantee
+        },
+        |row| from_blob::<SignedWarrant>(row.get("blob")?),
+    )?;
+    iter.collect()
+}
+
+/// Get every agent which has a warrant stored against them.
+pub fn get_warrantees(txn: &Transaction) -> StateQueryResult<Vec<AgentPubKey>> {
+    let mut stmt = txn.prepare("SELECT DISTINCT warrantee FROM Warrant")?;
+    let iter = stmt.query_and_then([], |row| Ok(row.get("warrantee")?))?;
+    iter.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutations;
+    use ::fixt::prelude::*;
+    use holochain_sqlite::prelude::*;
+    use holochain_zome_types::fixt::*;
+    use holochain_zome_types::prelude::*;
+
+    fn fake_warrant(action: Action, author: AgentPubKey) -> SignedWarrant {
+        SignedWarrant::new(
+            Warrant {
+                proof: WarrantProof::InvalidAction {
+                    action: SignedAction(action, fixt!(Signature)),
+                    validation_type: ValidationType::App,
+                    reason: "invalid".into(),
+                },
+                author,
+                timestamp: Timestamp::now(),
+            },
+            fixt!(Signature),
+        )
+    }
+
+    #[test]
+    fn warrants_are_stored_per_warrantee_and_deduplicated() {
+        let db = crate::test_utils::test_dht_db();
+        let action = fixt!(Action);
+        let warrantee = action.author().clone();
+        let issuer = fixt!(AgentPubKey);
+        let warrant = fake_warrant(action.clone(), issuer.clone());
+        let other_issuer = fake_warrant(action, fixt!(AgentPubKey));
+        let noise = fake_warrant(fixt!(Action), issuer);
+
+        db.to_db()
+            .conn()
+            .unwrap()
+            .with_commit_sync::<crate::mutations::StateMutationError, _, _>(|txn| {
+                mutations::insert_warrant(txn, &warrant)?;
+                mutations::insert_warrant(txn, &warrant)?;
+                mutations::insert_warrant(txn, &other_issuer)?;
+                mutations::insert_warrant(txn, &noise)
+            })
+            .unwrap();
+
+        let mut conn = db.to_db().conn().unwrap();
+        let warrants = conn
+            .with_reader_test(|txn| get_warrants_for_agent(&txn, &warrantee))
+            .unwrap();
+        assert_eq!(warrants.len(), 2);
+        assert!(warrants.contains(&warrant));
+        assert!(warrants.contains(&other_issuer));
+
+        let mut warrantees = conn.with_reader_test(|txn| get_warrantees(&txn)).unwrap();
+        warrantees.sort();
+        let mut expected = vec![warrantee, noise.warrantee().clone()];
+        expected.sort();
+        assert_eq!(warrantees, expected);
+    }
+}
@@ -20,6 +20,9 @@ pub struct AgentActivityResponse<T = SignedActionHashed> {
     /// The highest chain action that has
     /// been observed by this authority.
     pub highest_observed: Option<HighestObserved>,
+    /// Warrants this authority holds against the agent.
+    #[serde(default)]
+    pub warrants: Vec<SignedWarrant>,
 }
 
 holochain_serial!(AgentActivityResponse<ActionHash>);
@@ -38,6 +41,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: convert_activity(&other.rejected_activity),
             status: ChainStatus::Empty,
             highest_observed: other.highest_observed,
+            warrants: other.warrants,
         }
     }
 
@@ -49,6 +53,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: ChainItems::NotRequested,
             status: ChainStatus::Empty,
             highest_observed: other.highest_observed,
+            warrants: other.warrants,
         }
     }
 
@@ -65,6 +70,7 @@ impl<A> AgentActivityResponse<A> {
             rejected_activity: convert_activity(other.rejected_activity),
             status: other.status,
             highest_observed: other.highest_observed,
+            warrants: other.warrants,
         }
     }
 }
@@ -103,7 +109,7 @@ impl From<AgentActivityResponse<Record>> for holochain_zome_types::query::AgentA
             rejected_activity,
             status: a.status,
             highest_observed: a.highest_observed,
-            warrants: Vec::with_capacity(0),
+            warrants: a.warrants,
         }
     }
 }
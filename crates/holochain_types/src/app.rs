@@ -22,6 +22,7 @@ use holochain_serialized_bytes::prelude::*;
 use holochain_util::ffs;
 use holochain_zome_types::prelude::*;
 use itertools::Itertools;
+use kitsune_p2p_types::config::KitsuneP2pConfigOverride;
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
@@ -82,6 +83,30 @@ impl CreateCloneCellPayload {
     }
 }
 
+/// Identifies an existing clone cell of an app, in order to archive,
+/// restore or delete it
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CloneCellPayload {
+    /// The App which the clone cell belongs to
+    pub installed_app_id: InstalledAppId,
+    /// The Role ID under which the clone cell was created
+    pub role_id: AppRoleId,
+    /// The CellId of the clone cell
+    pub cell_id: CellId,
+}
+
+/// Identifies a role of an app whose cell was deferred at install time,
+/// in order to create that cell
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ProvisionAppRolePayload {
+    /// The App which the role belongs to
+    pub installed_app_id: InstalledAppId,
+    /// The Role ID whose cell is to be created
+    pub role_id: AppRoleId,
+    /// Proof-of-membership, if required by the role's DNA
+    pub membrane_proof: Option<MembraneProof>,
+}
+
 /// A collection of [DnaHash]es paired with an [AgentPubKey] and an app id
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct InstallAppPayload {
@@ -117,6 +142,20 @@ pub struct InstallAppBundlePayload {
     /// The app can still use existing Cells, i.e. this does not require that
     /// all Cells have DNAs with the same overridden DNA.
     pub uid: Option<Uid>,
+
+    /// Optional: overrides of the conductor's network config,
+    /// keyed by the AppRoleId specified in the app bundle manifest.
+    /// These replace the overrides given for those roles in the manifest.
+    #[serde(default)]
+    pub network_overrides: HashMap<AppRoleId, KitsuneP2pConfigOverride>,
+
+    /// Optional: the existing Cells to use for roles with the `use_existing`
+    /// or `create_if_not_exists` provisioning strategy, keyed by the AppRoleId
+    /// specified in the app bundle manifest. Each Cell must belong to
+    /// `agent_key`, and its DNA must match the version spec of its role.
+    /// The Cell is shared with the apps which already use it.
+    #[serde(default)]
+    pub existing_cells: HashMap<AppRoleId, CellId>,
 }
 
 /// The possible locations of an AppBundle
@@ -423,7 +462,15 @@ impl InstalledAppCommon {
             .flat_map(|(_, role)| &role.clones)
     }
 
-    /// Iterator of all cells, both provisioned and cloned
+    /// Accessor
+    pub fn archived_cloned_cells(&self) -> impl Iterator<Item = &CellId> {
+        self.role_assignments
+            .iter()
+            .flat_map(|(_, role)| &role.archived_clones)
+    }
+
+    /// Iterator of all cells, both provisioned and cloned.
+    /// Archived clones are not included.
     pub fn all_cells(&self) -> impl Iterator<Item = &CellId> {
         self.provisioned_cells()
             .map(|(_, c)| c)
@@ -463,7 +510,7 @@ impl InstalledAppCommon {
             role.agent_key(),
             "A clone cell must use the same agent key as the role it is added to"
         );
-        if role.clones.len() as u32 >= role.clone_limit {
+        if role.clone_count() >= role.clone_limit {
             return Err(AppError::CloneLimitExceeded(role.clone_limit, role.clone()));
         }
         let _ = role.clones.insert(cell_id);
@@ -476,6 +523,86 @@ impl InstalledAppCommon {
         Ok(role.clones.remove(cell_id))
     }
 
+    /// Mark the cell of a role which was deferred at install time as being
+    /// provisioned, returning the id of the cell. The role can't be
+    /// provisioned again until [`Self::abort_provisioning_role`] is called.
+    pub fn start_provisioning_role(&mut self, role_id: &AppRoleId) -> AppResult<CellId> {
+        let role = self.role_mut(role_id)?;
+        if role.is_provisioned {
+            return Err(AppError::AppRoleAlreadyProvisioned(role_id.clone()));
+        }
+        if role.is_provisioning {
+            return Err(AppError::AppRoleBeingProvisioned(role_id.clone()));
+        }
+        if role.clone_only {
+            return Err(AppError::AppRoleCloneOnly(role_id.clone()));
+        }
+        role.is_provisioning = true;
+        Ok(role.base_cell_id.clone())
+    }
+
+    /// Stop provisioning the cell of a role, so that it can be provisioned again
+    pub fn abort_provisioning_role(&mut self, role_id: &AppRoleId) -> AppResult<()> {
+        self.role_mut(role_id)?.is_provisioning = false;
+        Ok(())
+    }
+
+    /// Mark the cell of a role which was deferred at install time as
+    /// provisioned, returning the id of the cell
+    pub fn provision_role(&mut self, role_id: &AppRoleId) -> AppResult<CellId> {
+        let role = self.role_mut(role_id)?;
+        if role.is_provisioned {
+            return Err(AppError::AppRoleAlreadyProvisioned(role_id.clone()));
+        }
+        if role.clone_only {
+            return Err(AppError::AppRoleCloneOnly(role_id.clone()));
+        }
+        role.is_provisioned = true;
+        role.is_provisioning = false;
+        Ok(role.base_cell_id.clone())
+    }
+
+    /// Archive a cloned cell. An archived clone is no longer part of the app's
+    /// running cells, but still counts towards the role's clone limit.
+    pub fn archive_clone(&mut self, role_id: &AppRoleId, cell_id: &CellId) -> AppResult<()> {
+        let role = self.role_mut(role_id)?;
+        if !role.clones.remove(cell_id) {
+            return Err(AppError::CloneCellMissing(role_id.clone(), cell_id.clone()));
+        }
+        let _ = role.archived_clones.insert(cell_id.clone());
+        Ok(())
+    }
+
+    /// Restore an archived clone cell, making it part of the app's cells again
+    pub fn restore_clone(&mut self, role_id: &AppRoleId, cell_id: &CellId) -> AppResult<()> {
+        let role = self.role_mut(role_id)?;
+        if !role.archived_clones.remove(cell_id) {
+            return Err(AppError::ArchivedCloneCellMissing(
+                role_id.clone(),
+                cell_id.clone(),
+            ));
+        }
+        let _ = role.clones.insert(cell_id.clone());
+        Ok(())
+    }
+
+    /// Permanently remove an archived clone cell, freeing up its slot in the
+    /// role's clone limit
+    pub fn delete_archived_clone(
+        &mut self,
+        role_id: &AppRoleId,
+        cell_id: &CellId,
+    ) -> AppResult<()> {
+        let role = self.role_mut(role_id)?;
+        if !role.archived_clones.remove(cell_id) {
+            return Err(AppError::ArchivedCloneCellMissing(
+                role_id.clone(),
+                cell_id.clone(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Accessor
     pub fn _agent_key(&self) -> &AgentPubKey {
         &self._agent_key
@@ -528,8 +655,12 @@ impl InstalledAppCommon {
                 let role = AppRoleAssignment {
                     base_cell_id: cell_id,
                     is_provisioned: true,
+                    is_provisioning: false,
                     clones: HashSet::new(),
+                    archived_clones: HashSet::new(),
                     clone_limit: 0,
+                    clone_only: false,
+                    network: None,
                 };
                 (role_id, role)
             })
@@ -754,11 +885,28 @@ pub struct AppRoleAssignment {
     /// If false, then `base_cell_id` is just recording what that cell will be
     /// called in the future.
     is_provisioned: bool,
+    /// Records whether the base cell is going through genesis after being
+    /// deferred at install time, so that it is only provisioned once.
+    #[serde(default)]
+    is_provisioning: bool,
     /// The number of cloned cells allowed
     clone_limit: u32,
-    /// Cells which were cloned at runtime. The length cannot grow beyond
-    /// `clone_limit`
+    /// If true, the base cell is never provisioned, and the role's DNA only
+    /// serves as the template which clones are made from.
+    #[serde(default)]
+    clone_only: bool,
+    /// Cells which were cloned at runtime. Together with `archived_clones`,
+    /// the length cannot grow beyond `clone_limit`
     clones: HashSet<CellId>,
+    /// Cloned cells which have been archived. They are not running, but
+    /// their data is kept until they are deleted, and they still occupy a
+    /// slot in the `clone_limit`.
+    #[serde(default)]
+    archived_clones: HashSet<CellId>,
+    /// Overrides of the conductor's network config for the spaces of
+    /// this role's cells.
+    #[serde(default)]
+    network: Option<KitsuneP2pConfigOverride>,
 }
 
 impl AppRoleAssignment {
@@ -767,11 +915,45 @@ impl AppRoleAssignment {
         Self {
             base_cell_id,
             is_provisioned,
+            is_provisioning: false,
             clone_limit,
+            clone_only: false,
             clones: HashSet::new(),
+            archived_clones: HashSet::new(),
+            network: None,
         }
     }
 
+    /// Make this a role whose base cell is never provisioned,
+    /// and which only has clones.
+    pub fn into_clone_only(mut self) -> Self {
+        self.is_provisioned = false;
+        self.clone_only = true;
+        self
+    }
+
+    /// Set the network config overrides for this role's cells.
+    pub fn with_network(mut self, network: Option<KitsuneP2pConfigOverride>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// The number of slots of the `clone_limit` which are in use,
+    /// counting both active and archived clones
+    pub fn clone_count(&self) -> u32 {
+        (self.clones.len() + self.archived_clones.len()) as u32
+    }
+
+    /// Accessor
+    pub fn clones(&self) -> &HashSet<CellId> {
+        &self.clones
+    }
+
+    /// Accessor
+    pub fn archived_clones(&self) -> &HashSet<CellId> {
+        &self.archived_clones
+    }
+
     /// Accessor
     pub fn cell_id(&self) -> &CellId {
         &self.base_cell_id
@@ -787,6 +969,16 @@ impl AppRoleAssignment {
         self.base_cell_id.agent_pubkey()
     }
 
+    /// Accessor
+    pub fn network(&self) -> Option<&KitsuneP2pConfigOverride> {
+        self.network.as_ref()
+    }
+
+    /// Accessor
+    pub fn is_clone_only(&self) -> bool {
+        self.clone_only
+    }
+
     /// Accessor
     pub fn provisioned_cell(&self) -> Option<&CellId> {
         if self.is_provisioned {
@@ -860,4 +1052,117 @@ mod tests {
             app.all_cells().collect::<HashSet<_>>()
         );
     }
+
+    #[test]
+    fn deferred_role_provisioning() {
+        let agent = fixt!(AgentPubKey);
+        let deferred_cell_id = CellId::new(fixt!(DnaHash), agent.clone());
+        let deferred: AppRoleId = "deferred".into();
+        let template: AppRoleId = "template".into();
+        let mut app: RunningApp = InstalledAppCommon::new(
+            "app",
+            agent.clone(),
+            vec![
+                (
+                    deferred.clone(),
+                    AppRoleAssignment::new(deferred_cell_id.clone(), false, 0),
+                ),
+                (
+                    template.clone(),
+                    AppRoleAssignment::new(fixt!(CellId), false, 1).into_clone_only(),
+                ),
+            ],
+        )
+        .into();
+        assert_eq!(app.all_cells().count(), 0);
+
+        // The cell isn't part of the app while it is being provisioned
+        assert_eq!(
+            app.start_provisioning_role(&deferred).unwrap(),
+            deferred_cell_id
+        );
+        assert_eq!(app.all_cells().count(), 0);
+        matches::assert_matches!(
+            app.start_provisioning_role(&deferred),
+            Err(AppError::AppRoleBeingProvisioned(_))
+        );
+        app.abort_provisioning_role(&deferred).unwrap();
+        app.start_provisioning_role(&deferred).unwrap();
+
+        assert_eq!(app.provision_role(&deferred).unwrap(), deferred_cell_id);
+        assert_eq!(
+            app.all_cells().collect::<HashSet<_>>(),
+            maplit::hashset! { &deferred_cell_id }
+        );
+        matches::assert_matches!(
+            app.provision_role(&deferred),
+            Err(AppError::AppRoleAlreadyProvisioned(_))
+        );
+        matches::assert_matches!(
+            app.provision_role(&template),
+            Err(AppError::AppRoleCloneOnly(_))
+        );
+        matches::assert_matches!(
+            app.start_provisioning_role(&deferred),
+            Err(AppError::AppRoleAlreadyProvisioned(_))
+        );
+        matches::assert_matches!(
+            app.start_provisioning_role(&template),
+            Err(AppError::AppRoleCloneOnly(_))
+        );
+    }
+
+    #[test]
+    fn archived_clone_management() {
+        let base_cell_id = fixt!(CellId);
+        let agent = base_cell_id.agent_pubkey().clone();
+        let new_clone = || CellId::new(fixt!(DnaHash), agent.clone());
+        let role1 = AppRoleAssignment::new(base_cell_id, false, 2);
+        let role_id: AppRoleId = "role_id".into();
+        let mut app: RunningApp =
+            InstalledAppCommon::new("app", agent.clone(), vec![(role_id.clone(), role1)]).into();
+
+        let clones: Vec<_> = vec![new_clone(), new_clone()];
+        app.add_clone(&role_id, clones[0].clone()).unwrap();
+        app.add_clone(&role_id, clones[1].clone()).unwrap();
+
+        // Archived clones are no longer part of the app's cells
+        app.archive_clone(&role_id, &clones[0]).unwrap();
+        assert_eq!(
+            app.all_cells().collect::<HashSet<_>>(),
+            maplit::hashset! { &clones[1] }
+        );
+        assert_eq!(
+            app.archived_cloned_cells().collect::<HashSet<_>>(),
+            maplit::hashset! { &clones[0] }
+        );
+        matches::assert_matches!(
+            app.archive_clone(&role_id, &clones[0]),
+            Err(AppError::CloneCellMissing(_, _))
+        );
+
+        // Archived clones still count towards the clone limit
+        matches::assert_matches!(
+            app.add_clone(&role_id, new_clone()),
+            Err(AppError::CloneLimitExceeded(2, _))
+        );
+
+        // Restoring makes the clone part of the app again
+        app.restore_clone(&role_id, &clones[0]).unwrap();
+        assert_eq!(app.all_cells().count(), 2);
+        assert_eq!(app.archived_cloned_cells().count(), 0);
+
+        // Only archived clones can be deleted
+        matches::assert_matches!(
+            app.delete_archived_clone(&role_id, &clones[0]),
+            Err(AppError::ArchivedCloneCellMissing(_, _))
+        );
+        app.archive_clone(&role_id, &clones[0]).unwrap();
+        app.delete_archived_clone(&role_id, &clones[0]).unwrap();
+        assert_eq!(app.archived_cloned_cells().count(), 0);
+
+        // Deleting frees up the slot
+        app.add_clone(&role_id, new_clone()).unwrap();
+        assert_eq!(app.all_cells().count(), 2);
+    }
 }
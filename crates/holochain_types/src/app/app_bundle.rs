@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use self::error::AppBundleResult;
 
@@ -52,12 +56,40 @@ impl AppBundle {
         agent: AgentPubKey,
         _gamut: DnaGamut,
         membrane_proofs: HashMap<AppRoleId, MembraneProof>,
+        mut existing_cells: HashMap<AppRoleId, CellId>,
     ) -> AppBundleResult<AppRoleResolution> {
         let AppManifestValidated { name: _, roles } = self.manifest().clone().validate()?;
+        if let Some(role_id) = existing_cells
+            .keys()
+            .find(|role_id| !roles.contains_key(*role_id))
+        {
+            return Err(AppManifestError::UnknownRole(role_id.clone()).into());
+        }
+        // An app's cells all belong to its agent, so it can only share cells
+        // which already do.
+        if let Some((role_id, cell_id)) = existing_cells
+            .iter()
+            .find(|(_, cell_id)| *cell_id.agent_pubkey() != agent)
+        {
+            return Err(AppBundleError::ExistingCellAgentMismatch(
+                role_id.clone(),
+                cell_id.clone(),
+            ));
+        }
+        let networks: HashMap<AppRoleId, _> = self
+            .manifest()
+            .app_roles()
+            .into_iter()
+            .filter_map(|role| Some((role.id, role.network?)))
+            .collect();
         let bundle = Arc::new(self);
-        let tasks = roles.into_iter().map(|(role_id, role)| async {
+        let tasks = roles.into_iter().map(|(role_id, role)| {
+            let existing_cell = existing_cells.remove(&role_id);
             let bundle = bundle.clone();
-            Ok((role_id, bundle.resolve_cell(role).await?))
+            async move {
+                let op = bundle.resolve_cell(&role_id, role, existing_cell).await?;
+                Ok((role_id, op))
+            }
         });
         let resolution = futures::future::join_all(tasks)
             .await
@@ -73,29 +105,48 @@ impl AppBundle {
                                 let agent = resolution.agent.clone();
                                 let dna_hash = dna.dna_hash().clone();
                                 let cell_id = CellId::new(dna_hash, agent);
-                                let role = AppRoleAssignment::new(cell_id, true, clone_limit);
+                                let role = AppRoleAssignment::new(cell_id, true, clone_limit)
+                                    .with_network(networks.get(&role_id).cloned());
                                 // TODO: could sequentialize this to remove the clone
                                 let proof = membrane_proofs.get(&role_id).cloned();
                                 resolution.dnas_to_register.push((dna, proof));
                                 resolution.role_assignments.push((role_id, role));
                             }
                             CellProvisioningOp::Existing(cell_id, clone_limit) => {
-                                let role = AppRoleAssignment::new(cell_id, true, clone_limit);
+                                let role = AppRoleAssignment::new(cell_id, true, clone_limit)
+                                    .with_network(networks.get(&role_id).cloned());
+                                resolution.role_assignments.push((role_id, role));
+                            }
+                            CellProvisioningOp::Deferred(dna, clone_limit) => {
+                                let agent = resolution.agent.clone();
+                                let dna_hash = dna.dna_hash().clone();
+                                let cell_id = CellId::new(dna_hash, agent);
+                                let role = AppRoleAssignment::new(cell_id, false, clone_limit)
+                                    .with_network(networks.get(&role_id).cloned());
+                                // The membrane proof is given again when the
+                                // role is provisioned.
+                                resolution.dnas_to_register.push((dna, None));
+                                resolution.role_assignments.push((role_id, role));
+                            }
+                            CellProvisioningOp::CloneOnly(dna, clone_limit) => {
+                                let agent = resolution.agent.clone();
+                                let dna_hash = dna.dna_hash().clone();
+                                let cell_id = CellId::new(dna_hash, agent);
+                                let role = AppRoleAssignment::new(cell_id, false, clone_limit)
+                                    .into_clone_only()
+                                    .with_network(networks.get(&role_id).cloned());
+                                resolution.dnas_to_register.push((dna, None));
                                 resolution.role_assignments.push((role_id, role));
                             }
                             CellProvisioningOp::Noop(cell_id, clone_limit) => {
-                                resolution.role_assignments.push((
-                                    role_id,
-                                    AppRoleAssignment::new(cell_id, false, clone_limit),
-                                ));
+                                let role = AppRoleAssignment::new(cell_id, false, clone_limit)
+                                    .with_network(networks.get(&role_id).cloned());
+                                resolution.role_assignments.push((role_id, role));
                             }
-                            other => {
-                                tracing::error!(
-                                    "Encountered unexpected CellProvisioningOp: {:?}",
-                                    other
-                                );
-                                unimplemented!()
+                            CellProvisioningOp::NoMatch => {
+                                return Err(AppBundleError::CellResolutionFailure(role_id))
                             }
+                            CellProvisioningOp::Conflict(conflict) => match conflict {},
                         }
                         Ok(resolution)
                     } else {
@@ -110,8 +161,19 @@ impl AppBundle {
 
     async fn resolve_cell(
         &self,
+        role_id: &AppRoleId,
         role: AppRoleManifestValidated,
+        existing_cell: Option<CellId>,
     ) -> AppBundleResult<CellProvisioningOp> {
+        if existing_cell.is_some()
+            && !matches!(
+                role,
+                AppRoleManifestValidated::UseExisting { .. }
+                    | AppRoleManifestValidated::CreateIfNotExists { .. }
+            )
+        {
+            return Err(AppBundleError::ExistingCellNotAllowed(role_id.clone()));
+        }
         Ok(match role {
             AppRoleManifestValidated::Create {
                 location,
@@ -119,43 +181,52 @@ impl AppBundle {
                 clone_limit,
                 properties,
                 uid,
-                deferred: _,
-            } => {
-                self.resolve_cell_create(&location, version.as_ref(), clone_limit, uid, properties)
-                    .await?
-            }
+                deferred,
+            } => self
+                .resolve_cell_create(&location, version.as_ref(), clone_limit, uid, properties)
+                .await?
+                .deferred_if(deferred),
 
-            AppRoleManifestValidated::CreateClone { .. } => {
-                unimplemented!("`create_clone` provisioning strategy is currently unimplemented")
-            }
+            AppRoleManifestValidated::CreateClone {
+                location,
+                version,
+                clone_limit,
+                properties,
+                deferred,
+            } => self
+                .resolve_cell_create(
+                    &location,
+                    version.as_ref(),
+                    clone_limit,
+                    Some(random_uid()),
+                    properties,
+                )
+                .await?
+                .deferred_if(deferred),
+            // Existing cells have already been through genesis,
+            // so there is nothing to defer.
             AppRoleManifestValidated::UseExisting {
                 version,
                 clone_limit,
                 deferred: _,
-            } => self.resolve_cell_existing(&version, clone_limit),
+            } => self.resolve_cell_existing(role_id, &version, clone_limit, existing_cell)?,
             AppRoleManifestValidated::CreateIfNotExists {
                 location,
                 version,
                 clone_limit,
                 properties,
                 uid,
-                deferred: _,
-            } => match self.resolve_cell_existing(&version, clone_limit) {
+                deferred,
+            } => match self.resolve_cell_existing(role_id, &version, clone_limit, existing_cell)? {
                 op @ CellProvisioningOp::Existing(_, _) => op,
-                CellProvisioningOp::NoMatch => {
-                    self.resolve_cell_create(
-                        &location,
-                        Some(&version),
-                        clone_limit,
-                        uid,
-                        properties,
-                    )
+                CellProvisioningOp::NoMatch => self
+                    .resolve_cell_create(&location, Some(&version), clone_limit, uid, properties)
                     .await?
-                }
-                CellProvisioningOp::Conflict(_) => {
-                    unimplemented!("conflicts are not handled, or even possible yet")
-                }
-                CellProvisioningOp::Create(_, _) => {
+                    .deferred_if(deferred),
+                CellProvisioningOp::Conflict(conflict) => match conflict {},
+                CellProvisioningOp::Create(_, _)
+                | CellProvisioningOp::Deferred(_, _)
+                | CellProvisioningOp::CloneOnly(_, _) => {
                     unreachable!("resolve_cell_existing will never return a Create op")
                 }
                 CellProvisioningOp::Noop(_, _) => {
@@ -163,12 +234,20 @@ impl AppBundle {
                 }
             },
             AppRoleManifestValidated::Disabled {
-                version: _,
-                clone_limit: _,
-            } => {
-                unimplemented!("`disabled` provisioning strategy is currently unimplemented")
-                // CellProvisioningOp::Noop(clone_limit)
-            }
+                location,
+                version,
+                clone_limit,
+                properties,
+                uid,
+            } => match self
+                .resolve_cell_create(&location, version.as_ref(), clone_limit, uid, properties)
+                .await?
+            {
+                CellProvisioningOp::Create(dna, clone_limit) => {
+                    CellProvisioningOp::CloneOnly(dna, clone_limit)
+                }
+                op => op,
+            },
         })
     }
 
@@ -191,12 +270,27 @@ impl AppBundle {
         Ok(CellProvisioningOp::Create(dna_file, clone_limit))
     }
 
+    /// Use the existing cell named in the install payload, if any.
+    ///
+    /// Without DPKI there is no way to find the cells of agents associated
+    /// with the app's agent, so existing cells are never picked automatically.
     fn resolve_cell_existing(
         &self,
-        _version: &DnaVersionSpec,
-        _clone_limit: u32,
-    ) -> CellProvisioningOp {
-        unimplemented!("Reusing existing cells is not yet implemented")
+        role_id: &AppRoleId,
+        version: &DnaVersionSpec,
+        clone_limit: u32,
+        existing_cell: Option<CellId>,
+    ) -> AppBundleResult<CellProvisioningOp> {
+        match existing_cell {
+            Some(cell_id) if version.matches(cell_id.dna_hash().clone()) => {
+                Ok(CellProvisioningOp::Existing(cell_id, clone_limit))
+            }
+            Some(cell_id) => Err(AppBundleError::ExistingCellVersionMismatch(
+                role_id.clone(),
+                cell_id,
+            )),
+            None => Ok(CellProvisioningOp::NoMatch),
+        }
     }
 }
 
@@ -230,8 +324,15 @@ impl AppRoleResolution {
     }
 
     /// Return the IDs of new cells to be created as part of the resolution.
-    /// Does not return existing cells to be reused.
+    /// Does not return existing cells to be reused, nor the cells of roles
+    /// which are deferred or only used for cloning, whose DNAs are registered
+    /// without creating a cell.
     pub fn cells_to_create(&self) -> Vec<(CellId, Option<MembraneProof>)> {
+        let provisioned: HashSet<_> = self
+            .role_assignments
+            .iter()
+            .filter_map(|(_, role)| role.provisioned_cell())
+            .collect();
         self.dnas_to_register
             .iter()
             .map(|(dna, proof)| {
@@ -240,6 +341,22 @@ impl AppRoleResolution {
                     proof.clone(),
                 )
             })
+            .filter(|(cell_id, _)| provisioned.contains(cell_id))
+            .collect()
+    }
+
+    /// Return the IDs of existing cells to be reused as part of the resolution,
+    /// which are shared with the apps already using them.
+    pub fn existing_cells(&self) -> Vec<&CellId> {
+        let created: HashSet<_> = self
+            .dnas_to_register
+            .iter()
+            .map(|(dna, _)| CellId::new(dna.dna_hash().clone(), self.agent.clone()))
+            .collect();
+        self.role_assignments
+            .iter()
+            .filter_map(|(_, role)| role.provisioned_cell())
+            .filter(|cell_id| !created.contains(cell_id))
             .collect()
     }
 }
@@ -250,6 +367,12 @@ impl AppRoleResolution {
 pub enum CellProvisioningOp {
     /// Create a new Cell
     Create(DnaFile, u32),
+    /// Register the DNA, but only create the Cell once the role is
+    /// explicitly provisioned
+    Deferred(DnaFile, u32),
+    /// Register the DNA as the template for clones, without ever creating
+    /// a Cell for the role itself
+    CloneOnly(DnaFile, u32),
     /// Use an existing Cell
     Existing(CellId, u32),
     /// No provisioning needed, but there might be a clone_limit, and so we need
@@ -261,6 +384,16 @@ pub enum CellProvisioningOp {
     Conflict(CellProvisioningConflict),
 }
 
+impl CellProvisioningOp {
+    /// Defer the creation of a new Cell, if asked to
+    fn deferred_if(self, deferred: bool) -> Self {
+        match self {
+            Self::Create(dna, clone_limit) if deferred => Self::Deferred(dna, clone_limit),
+            op => op,
+        }
+    }
+}
+
 /// Uninhabitable placeholder
 #[derive(Debug)]
 pub enum CellProvisioningConflict {}
@@ -1,7 +1,7 @@
 use holochain_util::ffs;
 use mr_bundle::error::MrBundleError;
 
-use crate::prelude::{AppManifestError, AppRoleId, DnaError};
+use crate::prelude::{AppManifestError, AppRoleId, CellId, DnaError};
 
 /// Errors occurring while installing an AppBundle
 #[derive(thiserror::Error, Debug)]
@@ -9,6 +9,17 @@ pub enum AppBundleError {
     #[error("Could not resolve the app role '{0}'")]
     CellResolutionFailure(AppRoleId),
 
+    #[error("The app role '{0}' can't use an existing cell, because its provisioning strategy is neither `use_existing` nor `create_if_not_exists`")]
+    ExistingCellNotAllowed(AppRoleId),
+
+    #[error("The existing cell {1:?} given for the app role '{0}' doesn't match the role's DNA version spec")]
+    ExistingCellVersionMismatch(AppRoleId, CellId),
+
+    #[error(
+        "The existing cell {1:?} given for the app role '{0}' doesn't belong to the app's agent"
+    )]
+    ExistingCellAgentMismatch(AppRoleId, CellId),
+
     #[error(transparent)]
     AppManifestError(#[from] AppManifestError),
 
@@ -1,12 +1,24 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::prelude::*;
 use ::fixt::prelude::*;
 use app_manifest_v1::tests::{app_manifest_fixture, app_manifest_properties_fixture};
 
-use super::AppBundle;
+use super::{AppBundle, AppBundleError};
 
-async fn app_bundle_fixture() -> (AppBundle, DnaFile) {
+/// Apply the phenotype overrides specified in the manifest fixture
+async fn with_fixture_phenotype(dna: DnaFile) -> DnaFile {
+    dna.with_uid("uid".to_string())
+        .await
+        .unwrap()
+        .with_properties(SerializedBytes::try_from(app_manifest_properties_fixture()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn app_bundle_fixture(
+    provisioning: CellProvisioning,
+) -> (AppBundle, DnaFile, Vec<DnaHashB64>) {
     let dna_wasm = DnaWasmHashed::from_content(DnaWasm::new_invalid()).await;
     let fake_wasms = vec![dna_wasm.clone().into_content()];
     let fake_zomes = vec![IntegrityZome::new(
@@ -21,18 +33,20 @@ async fn app_bundle_fixture() -> (AppBundle, DnaFile) {
 
     let path1 = PathBuf::from(format!("{}", dna1.dna_hash()));
 
-    let (manifest, _dna_hashes) = app_manifest_fixture(
+    let (mut manifest, dna_hashes) = app_manifest_fixture(
         Some(DnaLocation::Bundled(path1.clone())),
         vec![dna1.dna_def().clone(), dna2.dna_def().clone()],
     )
     .await;
+    let AppManifest::V1(manifest_v1) = &mut manifest;
+    manifest_v1.roles[0].provisioning = Some(provisioning);
 
     let resources = vec![(path1, DnaBundle::from_dna_file(dna1.clone()).await.unwrap())];
 
     let bundle = AppBundle::new(manifest, resources, PathBuf::from("."))
         .await
         .unwrap();
-    (bundle, dna1)
+    (bundle, dna1, dna_hashes)
 }
 
 /// Test that an app with a single Created cell can be provisioned
@@ -40,21 +54,18 @@ async fn app_bundle_fixture() -> (AppBundle, DnaFile) {
 async fn provisioning_1_create() {
     observability::test_run().ok();
     let agent = fixt!(AgentPubKey);
-    let (bundle, dna) = app_bundle_fixture().await;
-
-    // Apply the phenotype overrides specified in the manifest fixture
-    let dna = dna
-        .with_uid("uid".to_string())
-        .await
-        .unwrap()
-        .with_properties(SerializedBytes::try_from(app_manifest_properties_fixture()).unwrap())
-        .await
-        .unwrap();
+    let (bundle, dna, _) = app_bundle_fixture(CellProvisioning::Create { deferred: false }).await;
+    let dna = with_fixture_phenotype(dna).await;
 
     let cell_id = CellId::new(dna.dna_hash().to_owned(), agent.clone());
 
     let resolution = bundle
-        .resolve_cells(agent.clone(), DnaGamut::placeholder(), Default::default())
+        .resolve_cells(
+            agent.clone(),
+            DnaGamut::placeholder(),
+            Default::default(),
+            Default::default(),
+        )
         .await
         .unwrap();
 
@@ -69,3 +80,176 @@ async fn provisioning_1_create() {
     };
     assert_eq!(resolution, expected);
 }
+
+/// Test that a deferred cell's DNA is registered, but the cell isn't created
+#[tokio::test]
+async fn provisioning_deferred_create() {
+    observability::test_run().ok();
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna, _) = app_bundle_fixture(CellProvisioning::Create { deferred: true }).await;
+    let dna = with_fixture_phenotype(dna).await;
+    let cell_id = CellId::new(dna.dna_hash().to_owned(), agent.clone());
+
+    let resolution = bundle
+        .resolve_cells(
+            agent.clone(),
+            DnaGamut::placeholder(),
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+
+    let role = AppRoleAssignment::new(cell_id, false, 50);
+    let expected = AppRoleResolution {
+        agent,
+        dnas_to_register: vec![(dna, None)],
+        role_assignments: vec![("role_id".into(), role)],
+    };
+    assert_eq!(resolution, expected);
+    assert!(resolution.cells_to_create().is_empty());
+}
+
+/// Test that a CreateClone cell gets a fresh UID every time it's installed
+#[tokio::test]
+async fn provisioning_create_clone() {
+    observability::test_run().ok();
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna, _) =
+        app_bundle_fixture(CellProvisioning::CreateClone { deferred: false }).await;
+    let dna = with_fixture_phenotype(dna).await;
+    let bytes = bundle.encode().unwrap();
+
+    let resolve = || async {
+        AppBundle::decode(&bytes)
+            .unwrap()
+            .resolve_cells(
+                agent.clone(),
+                DnaGamut::placeholder(),
+                Default::default(),
+                Default::default(),
+            )
+            .await
+            .unwrap()
+    };
+    let resolution_1 = resolve().await;
+    let resolution_2 = resolve().await;
+
+    let cells_1 = resolution_1.cells_to_create();
+    let cells_2 = resolution_2.cells_to_create();
+    assert_eq!(cells_1.len(), 1);
+    assert_eq!(cells_2.len(), 1);
+    assert_ne!(cells_1[0].0, cells_2[0].0);
+    assert_ne!(cells_1[0].0.dna_hash(), dna.dna_hash());
+}
+
+/// Test that a Disabled role only registers its DNA as a clone template
+#[tokio::test]
+async fn provisioning_disabled() {
+    observability::test_run().ok();
+    let agent = fixt!(AgentPubKey);
+    let (bundle, dna, _) = app_bundle_fixture(CellProvisioning::Disabled).await;
+    let dna = with_fixture_phenotype(dna).await;
+    let cell_id = CellId::new(dna.dna_hash().to_owned(), agent.clone());
+
+    let resolution = bundle
+        .resolve_cells(
+            agent.clone(),
+            DnaGamut::placeholder(),
+            Default::default(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+
+    let role = AppRoleAssignment::new(cell_id, false, 50).into_clone_only();
+    let expected = AppRoleResolution {
+        agent,
+        dnas_to_register: vec![(dna, None)],
+        role_assignments: vec![("role_id".into(), role)],
+    };
+    assert_eq!(resolution, expected);
+    assert!(resolution.cells_to_create().is_empty());
+}
+
+/// Test that a UseExisting role uses the cell named in the install payload,
+/// as long as it matches the role's DNA version spec
+#[tokio::test]
+async fn provisioning_use_existing() {
+    observability::test_run().ok();
+    let agent = fixt!(AgentPubKey);
+    let (bundle, _, dna_hashes) =
+        app_bundle_fixture(CellProvisioning::UseExisting { deferred: false }).await;
+    let bytes = bundle.encode().unwrap();
+    let resolve = |existing_cells: HashMap<AppRoleId, CellId>| {
+        let agent = agent.clone();
+        let bundle = AppBundle::decode(&bytes).unwrap();
+        async move {
+            bundle
+                .resolve_cells(
+                    agent,
+                    DnaGamut::placeholder(),
+                    Default::default(),
+                    existing_cells,
+                )
+                .await
+        }
+    };
+
+    let existing = CellId::new(dna_hashes[1].clone().into(), agent.clone());
+    let resolution = resolve(maplit::hashmap! { "role_id".into() => existing.clone() })
+        .await
+        .unwrap();
+    let role = AppRoleAssignment::new(existing.clone(), true, 50);
+    let expected = AppRoleResolution {
+        agent: agent.clone(),
+        dnas_to_register: vec![],
+        role_assignments: vec![("role_id".into(), role)],
+    };
+    assert_eq!(resolution, expected);
+    assert!(resolution.cells_to_create().is_empty());
+    assert_eq!(resolution.existing_cells(), vec![&existing]);
+
+    // The existing cell must belong to the app's agent
+    let other_agents = CellId::new(dna_hashes[1].clone().into(), fixt!(AgentPubKey));
+    matches::assert_matches!(
+        resolve(maplit::hashmap! { "role_id".into() => other_agents }).await,
+        Err(AppBundleError::ExistingCellAgentMismatch(_, _))
+    );
+    let other_dna = CellId::new(fixt!(DnaHash), agent.clone());
+    matches::assert_matches!(
+        resolve(maplit::hashmap! { "role_id".into() => other_dna }).await,
+        Err(AppBundleError::ExistingCellVersionMismatch(_, _))
+    );
+    matches::assert_matches!(
+        resolve(maplit::hashmap! { "other_role_id".into() => fixt!(CellId) }).await,
+        Err(AppBundleError::AppManifestError(
+            AppManifestError::UnknownRole(_)
+        ))
+    );
+    matches::assert_matches!(
+        resolve(Default::default()).await,
+        Err(AppBundleError::CellResolutionFailure(_))
+    );
+}
+
+/// Test that only roles which can use existing cells accept one
+#[tokio::test]
+async fn provisioning_existing_cell_for_create() {
+    observability::test_run().ok();
+    let agent = fixt!(AgentPubKey);
+    let (bundle, _, dna_hashes) =
+        app_bundle_fixture(CellProvisioning::Create { deferred: false }).await;
+    let existing = CellId::new(dna_hashes[0].clone().into(), agent.clone());
+    matches::assert_matches!(
+        bundle
+            .resolve_cells(
+                agent,
+                DnaGamut::placeholder(),
+                Default::default(),
+                maplit::hashmap! { "role_id".into() => existing },
+            )
+            .await,
+        Err(AppBundleError::ExistingCellNotAllowed(_))
+    );
+}
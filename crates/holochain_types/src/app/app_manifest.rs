@@ -2,8 +2,11 @@
 
 //! Defines the hApp Manifest YAML format, including validation.
 
+use crate::prelude::AppRoleId;
 use holochain_zome_types::Uid;
+use kitsune_p2p_types::config::KitsuneP2pConfigOverride;
 use mr_bundle::{Location, Manifest};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 pub(crate) mod app_manifest_v1;
@@ -70,6 +73,17 @@ impl AppManifest {
         }
     }
 
+    /// Replace the network overrides of the given roles.
+    /// Fails if any of the roles is not in the manifest.
+    pub fn set_network_overrides(
+        &mut self,
+        overrides: HashMap<AppRoleId, KitsuneP2pConfigOverride>,
+    ) -> AppManifestResult<()> {
+        match self {
+            Self::V1(manifest) => manifest.set_network_overrides(overrides),
+        }
+    }
+
     /// Returns the list of app roles that this manifest declares
     pub fn app_roles(&self) -> Vec<AppRoleManifest> {
         match self {
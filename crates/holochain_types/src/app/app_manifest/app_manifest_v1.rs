@@ -10,6 +10,7 @@ use super::{
 use crate::prelude::{AppRoleId, YamlProperties};
 use holo_hash::{DnaHash, DnaHashB64};
 use holochain_zome_types::Uid;
+use kitsune_p2p_types::config::KitsuneP2pConfigOverride;
 use std::collections::HashMap;
 
 /// Version 1 of the App manifest schema
@@ -48,6 +49,12 @@ pub struct AppRoleManifest {
     /// Declares where to find the DNA, and options to modify it before
     /// inclusion in a Cell
     pub dna: AppRoleDnaManifest,
+
+    /// Optional overrides of the conductor's network config for the DNA's
+    /// space, e.g. to keep a private DNA on the LAN.
+    /// May be overridden during installation.
+    #[serde(default)]
+    pub network: Option<KitsuneP2pConfigOverride>,
 }
 
 impl AppRoleManifest {
@@ -57,6 +64,7 @@ impl AppRoleManifest {
             id,
             provisioning: Some(CellProvisioning::default()),
             dna: AppRoleDnaManifest::sample(),
+            network: None,
         }
     }
 }
@@ -177,8 +185,9 @@ pub enum CellProvisioning {
     UseExisting { deferred: bool },
     /// Try `UseExisting`, and if that fails, fallback to `Create`
     CreateIfNotExists { deferred: bool },
-    /// Disallow provisioning altogether. In this case, we expect
-    /// `clone_limit > 0`: otherwise, no Cells will ever be created.
+    /// Disallow provisioning altogether. The DNA is only installed as the
+    /// template for clones, and so we expect `clone_limit > 0`: otherwise,
+    /// no Cells will ever be created.
     Disabled,
 }
 
@@ -204,6 +213,22 @@ impl AppManifestV1 {
         }
     }
 
+    /// Replace the network overrides of the given roles.
+    pub fn set_network_overrides(
+        &mut self,
+        mut overrides: HashMap<AppRoleId, KitsuneP2pConfigOverride>,
+    ) -> AppManifestResult<()> {
+        for role in self.roles.iter_mut() {
+            if let Some(network) = overrides.remove(&role.id) {
+                role.network = Some(network);
+            }
+        }
+        match overrides.into_keys().next() {
+            Some(role_id) => Err(AppManifestError::UnknownRole(role_id)),
+            None => Ok(()),
+        }
+    }
+
     /// Convert this human-focused manifest into a validated, concise representation
     pub fn validate(self) -> AppManifestResult<AppManifestValidated> {
         let AppManifestV1 {
@@ -218,6 +243,7 @@ impl AppManifestV1 {
                      id,
                      provisioning,
                      dna,
+                     network: _,
                  }| {
                     let AppRoleDnaManifest {
                         location,
@@ -265,7 +291,10 @@ impl AppManifestV1 {
                         }
                         CellProvisioning::Disabled => AppRoleManifestValidated::Disabled {
                             clone_limit,
-                            version: Self::require(version, "roles.dna.version")?,
+                            location: Self::require(location, "roles.dna.(path|url)")?,
+                            properties,
+                            uid,
+                            version,
                         },
                     };
                     Ok((id, validated))
@@ -329,6 +358,7 @@ pub mod tests {
                 clone_limit: 50,
             },
             provisioning: Some(CellProvisioning::Create { deferred: false }),
+            network: None,
         }];
         let manifest = AppManifest::V1(AppManifestV1 {
             name: "Test app".to_string(),
@@ -416,4 +446,58 @@ roles:
         assert_ne!(manifest.roles[2].dna.uid.as_ref(), Some(&uid));
         assert_ne!(manifest.roles[3].dna.uid.as_ref(), Some(&uid));
     }
+
+    #[test]
+    fn manifest_v1_network_overrides() {
+        let yaml = r#"---
+name: "Test app"
+description: ~
+roles:
+  - id: "lan"
+    dna:
+      path: ./lan.dna
+    network:
+      network_type: quic_mdns
+      tuning_params:
+        gossip_loop_iteration_delay_ms: "100"
+  - id: "public"
+    dna:
+      path: ./public.dna
+"#;
+        let mut manifest: AppManifestV1 = serde_yaml::from_str(yaml).unwrap();
+        let lan = manifest.roles[0].network.clone().unwrap();
+        assert_eq!(
+            lan.network_type,
+            Some(kitsune_p2p_types::config::NetworkType::QuicMdns)
+        );
+        assert_eq!(lan.bootstrap_service, None);
+        assert_eq!(
+            lan.tuning_params.get("gossip_loop_iteration_delay_ms"),
+            Some(&"100".to_string())
+        );
+        assert_eq!(manifest.roles[1].network, None);
+
+        // Install-time overrides replace those in the manifest.
+        let bootstrap = KitsuneP2pConfigOverride {
+            bootstrap_service: Some(kitsune_p2p_types::dependencies::url2::url2!(
+                "https://bootstrap.example.com"
+            )),
+            ..Default::default()
+        };
+        manifest
+            .set_network_overrides(maplit::hashmap! {
+                "lan".to_string() => KitsuneP2pConfigOverride::default(),
+                "public".to_string() => bootstrap.clone(),
+            })
+            .unwrap();
+        assert_eq!(manifest.roles[0].network, Some(Default::default()));
+        assert_eq!(manifest.roles[1].network, Some(bootstrap));
+
+        matches::assert_matches!(
+            manifest.set_network_overrides(maplit::hashmap! {
+                "nope".to_string() => KitsuneP2pConfigOverride::default(),
+            }),
+            Err(AppManifestError::UnknownRole(role)) if role == "nope"
+        );
+    }
 }
@@ -81,10 +81,14 @@ pub enum AppRoleManifestValidated {
         uid: Option<String>,
         version: DnaVersionSpec,
     },
-    /// Disallow provisioning altogether. In this case, we expect
-    /// `clone_limit > 0`: otherwise, no cells will ever be created.
+    /// Disallow provisioning altogether. The DNA is only installed as the
+    /// template for clones, and so we expect `clone_limit > 0`: otherwise,
+    /// no cells will ever be created.
     Disabled {
-        version: DnaVersionSpec,
         clone_limit: u32,
+        location: DnaLocation,
+        properties: Option<YamlProperties>,
+        uid: Option<String>,
+        version: Option<DnaVersionSpec>,
     },
 }
@@ -10,6 +10,9 @@ pub enum AppManifestError {
 
     #[error("Invalid manifest for app role '{0}': Using strategy 'disabled' with clone_limit == 0 is pointless")]
     InvalidStrategyDisabled(AppRoleId),
+
+    #[error("No app role '{0}' in app manifest")]
+    UnknownRole(AppRoleId),
 }
 
 pub type AppManifestResult<T> = Result<T, AppManifestError>;
@@ -11,6 +11,23 @@ pub enum AppError {
     #[error("Tried to access missing role id: '{0}'")]
     AppRoleIdMissing(AppRoleId),
 
+    #[error("No clone cell {1:?} exists under role id '{0}'")]
+    CloneCellMissing(AppRoleId, CellId),
+
+    #[error("No archived clone cell {1:?} exists under role id '{0}'")]
+    ArchivedCloneCellMissing(AppRoleId, CellId),
+
+    #[error("The cell of role id '{0}' has already been provisioned")]
+    AppRoleAlreadyProvisioned(AppRoleId),
+
+    #[error("The cell of role id '{0}' is already being provisioned")]
+    AppRoleBeingProvisioned(AppRoleId),
+
+    #[error(
+        "The role id '{0}' only exists as a template for clones, so its cell can't be provisioned"
+    )]
+    AppRoleCloneOnly(AppRoleId),
+
     #[error("Tried to install app '{0}' which contains duplicate role ids. The following role ids have duplicates: {1:?}")]
     DuplicateAppRoleIds(InstalledAppId, Vec<AppRoleId>),
 }
@@ -7,6 +7,7 @@ mod dna_bundle;
 mod dna_file;
 mod dna_manifest;
 mod ribosome_store;
+mod zome_functions;
 
 #[allow(missing_docs)]
 pub mod error;
@@ -17,3 +18,4 @@ pub use dna_manifest::*;
 pub use error::DnaError;
 pub use holo_hash::*;
 pub use ribosome_store::*;
+pub use zome_functions::*;
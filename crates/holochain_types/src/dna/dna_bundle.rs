@@ -130,6 +130,7 @@ impl DnaBundle {
                     origin_time: manifest.integrity.origin_time.into(),
                     integrity_zomes,
                     coordinator_zomes,
+                    rate_limits: manifest.integrity.rate_limits.clone(),
                 };
 
                 if uid.is_none() && properties.is_none() {
@@ -231,6 +232,7 @@ impl DnaBundle {
                 })?),
                 origin_time: dna_def.origin_time.into(),
                 zomes: integrity,
+                rate_limits: dna_def.rate_limits,
             },
             coordinator: CoordinatorManifest { zomes: coordinator },
         }
@@ -305,6 +307,7 @@ mod tests {
                         dependencies: Default::default(),
                     },
                 ],
+                rate_limits: Default::default(),
             },
             coordinator: CoordinatorManifest { zomes: vec![] },
         };
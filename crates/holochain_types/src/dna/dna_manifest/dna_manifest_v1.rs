@@ -126,6 +126,12 @@ pub struct IntegrityManifest {
     /// The order is significant: it determines initialization order.
     /// The integrity zome manifests.
     pub zomes: Vec<ZomeManifest>,
+
+    /// The rate limiting buckets which the `weigh` callback assigns actions to,
+    /// keyed by bucket id.
+    #[serde(default, skip_serializing_if = "RateLimits::is_empty")]
+    #[builder(default)]
+    pub rate_limits: RateLimits,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
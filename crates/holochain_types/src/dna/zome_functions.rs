@@ -0,0 +1,123 @@
+//! Introspection of the functions exported by the zomes of a dna.
+
+use holochain_zome_types::zome::FunctionName;
+use holochain_zome_types::zome::ZomeName;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The names of the functions the conductor calls on a zome by itself.
+pub const CALLBACK_NAMES: &[&str] = &[
+    "entry_defs",
+    "genesis_self_check",
+    "init",
+    "migrate_agent",
+    "migrate_agent_open",
+    "migrate_agent_close",
+    "post_commit",
+    "recv_remote_signal",
+    "validate",
+    "validation_package",
+    "weigh",
+];
+
+/// The functions exported by a single zome, grouped by how they are called.
+///
+/// Scheduled functions can't be told apart from zome functions by their
+/// exports alone, so they are only listed as callbacks once they have been
+/// marked with [`ZomeFunctions::mark_scheduled`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZomeFunctions {
+    /// The zome that exports these functions.
+    pub zome_name: ZomeName,
+    /// Callbacks such as `init`, `validate` and scheduled functions that the
+    /// conductor calls itself.
+    pub callbacks: Vec<FunctionName>,
+    /// Functions that can be called with a zome call.
+    pub zome_fns: Vec<FunctionName>,
+}
+
+impl ZomeFunctions {
+    /// Sort the exported function names of a zome into callbacks and zome functions.
+    /// Names starting with `__` are internal to the HDK and are skipped.
+    pub fn from_exports<I>(zome_name: ZomeName, exports: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut callbacks = Vec::new();
+        let mut zome_fns = Vec::new();
+        for name in exports.into_iter().map(Into::into) {
+            if name.starts_with("__") {
+                continue;
+            }
+            if CALLBACK_NAMES.contains(&name.as_str()) {
+                callbacks.push(FunctionName(name));
+            } else {
+                zome_fns.push(FunctionName(name));
+            }
+        }
+        callbacks.sort();
+        zome_fns.sort();
+        Self {
+            zome_name,
+            callbacks,
+            zome_fns,
+        }
+    }
+
+    /// Move a function which has been scheduled from the zome functions
+    /// to the callbacks.
+    pub fn mark_scheduled(&mut self, fn_name: &FunctionName) {
+        if let Some(i) = self.zome_fns.iter().position(|f| f == fn_name) {
+            let fn_name = self.zome_fns.remove(i);
+            if let Err(i) = self.callbacks.binary_search(&fn_name) {
+                self.callbacks.insert(i, fn_name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_exports() {
+        let functions = ZomeFunctions::from_exports(
+            "foo".into(),
+            ["validate", "create_post", "__allocate", "init", "get_post"],
+        );
+        assert_eq!(
+            functions.callbacks,
+            vec![FunctionName::from("init"), FunctionName::from("validate")]
+        );
+        assert_eq!(
+            functions.zome_fns,
+            vec![
+                FunctionName::from("create_post"),
+                FunctionName::from("get_post")
+            ]
+        );
+    }
+
+    #[test]
+    fn scheduled_fns_are_callbacks() {
+        let mut functions = ZomeFunctions::from_exports(
+            "foo".into(),
+            ["validate", "create_post", "tidy_up", "init"],
+        );
+        functions.mark_scheduled(&"tidy_up".into());
+        // Marking a function which isn't a zome function does nothing
+        functions.mark_scheduled(&"init".into());
+        functions.mark_scheduled(&"missing".into());
+        assert_eq!(
+            functions.callbacks,
+            vec![
+                FunctionName::from("init"),
+                FunctionName::from("tidy_up"),
+                FunctionName::from("validate")
+            ]
+        );
+        assert_eq!(functions.zome_fns, vec![FunctionName::from("create_post")]);
+    }
+}
@@ -17,13 +17,12 @@ pub struct TimedActionHash {
 
 /// Metadata returned from a GetMeta request.
 /// The Ord derive on TimedActionHash means each set is ordered by time.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, SerializedBytes)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, SerializedBytes)]
 pub struct MetadataSet {
     /// Actions that created or updated an entry.
     /// These are the actions that show the entry exists.
     pub actions: BTreeSet<TimedActionHash>,
-    // TODO: Implement after validation
-    /// Placeholder
+    /// Actions that were rejected by validation.
     pub invalid_actions: BTreeSet<TimedActionHash>,
     /// Deletes on an action
     pub deletes: BTreeSet<TimedActionHash>,
@@ -33,6 +32,36 @@ pub struct MetadataSet {
     /// This is simply a faster way of determining if
     /// there are any live actions on an entry.
     pub entry_dht_status: Option<EntryDhtStatus>,
+    /// The number of live links on this base.
+    /// Only set if it was requested.
+    pub link_count: Option<usize>,
+}
+
+impl MetadataSet {
+    /// Combine the metadata returned by multiple authorities.
+    /// Sets are unioned, an entry is live if any authority sees it as live
+    /// and the highest link count wins.
+    pub fn merge(&mut self, other: MetadataSet) {
+        let MetadataSet {
+            actions,
+            invalid_actions,
+            deletes,
+            updates,
+            entry_dht_status,
+            link_count,
+        } = other;
+        self.actions.extend(actions);
+        self.invalid_actions.extend(invalid_actions);
+        self.deletes.extend(deletes);
+        self.updates.extend(updates);
+        self.entry_dht_status = match (self.entry_dht_status.take(), entry_dht_status) {
+            (Some(EntryDhtStatus::Live), _) | (_, Some(EntryDhtStatus::Live)) => {
+                Some(EntryDhtStatus::Live)
+            }
+            (a, b) => a.or(b),
+        };
+        self.link_count = self.link_count.max(link_count);
+    }
 }
 
 impl From<ActionHashed> for TimedActionHash {
@@ -1,3 +1,177 @@
 //! Types for rate limiting
 
 pub use holochain_zome_types::rate_limit::*;
+
+use std::time::Duration;
+use std::time::Instant;
+
+/// Limits on how fast zome calls can be made and data can be committed.
+/// Unset limits are not enforced.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RateLimitConfig {
+    /// The most zome calls which may be made per second.
+    #[serde(default)]
+    pub zome_calls_per_second: Option<u32>,
+    /// The most bytes of actions and entries which may be committed per minute.
+    /// Only enforced for cells, since an interface doesn't know
+    /// what its calls commit.
+    #[serde(default)]
+    pub bytes_committed_per_minute: Option<u64>,
+}
+
+/// A rate limit was exceeded.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RateLimitError {
+    /// Too many zome calls were made.
+    #[error("Exceeded the limit of {0} zome calls per second")]
+    ZomeCalls(u32),
+    /// Too many bytes were committed.
+    #[error("Committing {0} bytes would exceed the limit of {1} bytes per minute")]
+    BytesCommitted(u64, u64),
+}
+
+/// Enforces a [`RateLimitConfig`].
+///
+/// Each limit is a bucket which holds up to a second's worth of calls or a
+/// minute's worth of bytes, and refills continuously, so short bursts are
+/// allowed as long as the average rate stays under the limit.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    zome_calls: Option<parking_lot::Mutex<Bucket>>,
+    bytes_committed: Option<parking_lot::Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with full buckets.
+    pub fn new(config: RateLimitConfig) -> Self {
+        let now = Instant::now();
+        let bucket =
+            |capacity: u64, per: Duration| parking_lot::Mutex::new(Bucket::new(capacity, per, now));
+        Self {
+            zome_calls: config
+                .zome_calls_per_second
+                .map(|calls| bucket(calls as u64, Duration::from_secs(1))),
+            bytes_committed: config
+                .bytes_committed_per_minute
+                .map(|bytes| bucket(bytes, Duration::from_secs(60))),
+            config,
+        }
+    }
+
+    /// Accessor
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Account for a zome call, failing if there have been too many.
+    pub fn check_zome_call(&self) -> Result<(), RateLimitError> {
+        self.check_zome_call_at(Instant::now())
+    }
+
+    /// Account for committing some bytes, failing if that would be too many.
+    /// Nothing is accounted for if this fails.
+    pub fn check_bytes_committed(&self, bytes: u64) -> Result<(), RateLimitError> {
+        self.check_bytes_committed_at(bytes, Instant::now())
+    }
+
+    fn check_zome_call_at(&self, now: Instant) -> Result<(), RateLimitError> {
+        match &self.zome_calls {
+            Some(bucket) if !bucket.lock().take(1, now) => Err(RateLimitError::ZomeCalls(
+                self.config.zome_calls_per_second.unwrap_or_default(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_bytes_committed_at(&self, bytes: u64, now: Instant) -> Result<(), RateLimitError> {
+        match &self.bytes_committed {
+            Some(bucket) if !bucket.lock().take(bytes, now) => Err(RateLimitError::BytesCommitted(
+                bytes,
+                self.config.bytes_committed_per_minute.unwrap_or_default(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A token bucket which refills `capacity` tokens every `per`.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(capacity: u64, per: Duration, now: Instant) -> Self {
+        let capacity = capacity as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / per.as_secs_f64(),
+            last_refill: now,
+        }
+    }
+
+    /// Take `amount` tokens if there are enough.
+    fn take(&mut self, amount: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        let amount = amount as f64;
+        if amount <= self.tokens {
+            self.tokens -= amount;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_refills() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            zome_calls_per_second: Some(2),
+            bytes_committed_per_minute: Some(600),
+        });
+        let start = Instant::now();
+
+        assert_eq!(limiter.check_zome_call_at(start), Ok(()));
+        assert_eq!(limiter.check_zome_call_at(start), Ok(()));
+        assert_eq!(
+            limiter.check_zome_call_at(start),
+            Err(RateLimitError::ZomeCalls(2))
+        );
+        // One call's worth of refill.
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.check_zome_call_at(later), Ok(()));
+        assert!(limiter.check_zome_call_at(later).is_err());
+
+        assert_eq!(limiter.check_bytes_committed_at(500, start), Ok(()));
+        assert_eq!(
+            limiter.check_bytes_committed_at(200, start),
+            Err(RateLimitError::BytesCommitted(200, 600))
+        );
+        // A failed check takes nothing.
+        assert_eq!(limiter.check_bytes_committed_at(100, start), Ok(()));
+        // Ten seconds refills 100 bytes.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(limiter.check_bytes_committed_at(100, later), Ok(()));
+    }
+
+    #[test]
+    fn unset_limits_are_not_enforced() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        for _ in 0..1000 {
+            assert_eq!(limiter.check_zome_call(), Ok(()));
+        }
+        assert_eq!(limiter.check_bytes_committed(u64::MAX), Ok(()));
+    }
+}
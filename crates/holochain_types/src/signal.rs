@@ -29,6 +29,15 @@ pub enum SystemSignal {
     Test(String),
     /// A countersigning session has successfully completed.
     SuccessfulCountersigning(holo_hash::EntryHash),
+    /// A cell's countersigning session was abandoned through the admin API,
+    /// unlocking its source chain.
+    AbandonedCountersigning {
+        /// The cell whose session was abandoned.
+        cell_id: CellId,
+        /// The entry being countersigned, if the session had got far enough
+        /// to write it.
+        entry_hash: Option<holo_hash::EntryHash>,
+    },
 }
 
 /// Create a test signal
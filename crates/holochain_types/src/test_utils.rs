@@ -40,6 +40,7 @@ pub fn fake_dna_zomes_named(uid: &str, name: &str, zomes: Vec<(ZomeName, DnaWasm
         origin_time: Timestamp::HOLOCHAIN_EPOCH,
         integrity_zomes: Vec::new(),
         coordinator_zomes: Vec::new(),
+        rate_limits: Default::default(),
     };
     tokio_helper::block_forever_on(async move {
         let mut wasm_code = Vec::new();
@@ -12,6 +12,11 @@
 //! [`WebsocketReceiver`]
 //! ).
 //!
+//! Listeners can also serve websockets wrapped in TLS, with
+//! [`WebsocketListener::bind_tls_with_handle`], or over a unix domain socket,
+//! with [`WebsocketListener::bind_unix_with_handle`]. Connect to them with
+//! [`connect_tls`] and [`connect_unix`].
+//!
 //! If you want to be able to shutdown the stream use [`WebsocketListener::bind_with_handle`]
 //! which will give you a tuple ([`ListenerHandle`], [`ListenerStream`]).
 //! You can use [`ListenerHandle::close`] to close immediately or
@@ -84,6 +89,7 @@ use holochain_serialized_bytes::prelude::*;
 use stream_cancel::Valve;
 use tracing::instrument;
 use url2::Url2;
+use util::addr_to_url;
 use util::url_to_addr;
 use websocket::Websocket;
 
@@ -119,20 +125,71 @@ pub async fn connect(
     // socket.set_keepalive(Some(std::time::Duration::from_secs(
     //     config.tcp_keepalive_s as u64,
     // )))?;
-    let (socket, _) = tokio_tungstenite::client_async_with_config(
-        url.as_str(),
-        socket,
-        Some(config.to_tungstenite()),
-    )
-    .await
-    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    let peer_addr = addr_to_url(socket.peer_addr()?, config.scheme);
+    client_handshake(url.as_str(), Box::new(socket), peer_addr, config).await
+}
+
+#[instrument(skip(config, ca_cert_pem))]
+/// Create a new external websocket connection wrapped in TLS.
+/// The server's certificate must be valid for the host in the url, and is
+/// also trusted if it was issued by the PEM encoded `ca_cert_pem`, which is
+/// useful for self-signed certificates on a LAN.
+/// The config's scheme should be "wss".
+pub async fn connect_tls(
+    url: Url2,
+    config: Arc<WebsocketConfig>,
+    ca_cert_pem: Option<&[u8]>,
+) -> WebsocketResult<(WebsocketSender, WebsocketReceiver)> {
+    use tokio_native_tls::native_tls;
+    let addr = url_to_addr(&url, config.scheme).await?;
+    let domain = url.host_str().unwrap_or_default().to_string();
+    let mut connector = native_tls::TlsConnector::builder();
+    if let Some(ca_cert_pem) = ca_cert_pem {
+        connector.add_root_certificate(
+            native_tls::Certificate::from_pem(ca_cert_pem).map_err(util::tls_err)?,
+        );
+    }
+    let connector = tokio_native_tls::TlsConnector::from(connector.build().map_err(util::tls_err)?);
+    let socket = tokio::net::TcpStream::connect(addr).await?;
+    let peer_addr = addr_to_url(socket.peer_addr()?, config.scheme);
+    let socket = connector
+        .connect(&domain, socket)
+        .await
+        .map_err(util::tls_err)?;
+    client_handshake(url.as_str(), Box::new(socket), peer_addr, config).await
+}
+
+#[cfg(unix)]
+#[instrument(skip(config))]
+/// Create a new websocket connection over the unix domain socket at `path`.
+pub async fn connect_unix(
+    path: &std::path::Path,
+    config: Arc<WebsocketConfig>,
+) -> WebsocketResult<(WebsocketSender, WebsocketReceiver)> {
+    let socket = tokio::net::UnixStream::connect(path).await?;
+    // The handshake needs a url but there is no host to put in it.
+    let url = format!("{}://localhost/", config.scheme);
+    let peer_addr = util::unix_path_to_url(path);
+    client_handshake(&url, Box::new(socket), peer_addr, config).await
+}
+
+async fn client_handshake(
+    url: &str,
+    socket: Box<dyn util::RawSocket>,
+    peer_addr: Url2,
+    config: Arc<WebsocketConfig>,
+) -> WebsocketResult<(WebsocketSender, WebsocketReceiver)> {
+    let (socket, _) =
+        tokio_tungstenite::client_async_with_config(url, socket, Some(config.to_tungstenite()))
+            .await
+            .map_err(|e| Error::new(ErrorKind::Other, e))?;
     tracing::debug!("Client connected");
 
     // Noop valve because we don't have a listener to shutdown the
     // ends when creating a client
     let (exit, valve) = Valve::new();
     exit.disable();
-    Websocket::create_ends(config, socket, valve)
+    Websocket::create_ends(config, socket, peer_addr, valve)
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
@@ -6,7 +6,16 @@ use url2::{url2, Url2};
 
 use std::io::{Error, ErrorKind, Result};
 
-pub(crate) type ToFromSocket = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+/// A stream a websocket can run over: a tcp stream, a tls stream
+/// or a unix domain socket.
+pub(crate) trait RawSocket:
+    tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin
+{
+}
+
+impl<T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin> RawSocket for T {}
+
+pub(crate) type ToFromSocket = tokio_tungstenite::WebSocketStream<Box<dyn RawSocket>>;
 
 /// Amount of time to spend waiting for channels to empty before forcing them to close.
 pub(crate) const CLOSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
@@ -16,6 +25,18 @@ pub(crate) fn addr_to_url(a: SocketAddr, scheme: &str) -> Url2 {
     url2!("{}://{}", scheme, a)
 }
 
+/// internal helper to name the peer of a unix domain socket,
+/// which has no address of its own
+#[cfg(unix)]
+pub(crate) fn unix_path_to_url(path: &std::path::Path) -> Url2 {
+    url2!("unix://{}", path.display())
+}
+
+/// internal helper to turn tls errors into io errors
+pub(crate) fn tls_err(e: tokio_native_tls::native_tls::Error) -> Error {
+    Error::new(ErrorKind::Other, e)
+}
+
 /// internal helper convert urls to socket addrs for binding / connection
 pub(crate) async fn url_to_addr(url: &Url2, scheme: &str) -> Result<SocketAddr> {
     if url.scheme() != scheme || url.host_str().is_none() || url.port().is_none() {
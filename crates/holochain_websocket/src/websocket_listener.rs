@@ -12,7 +12,11 @@ use tracing::instrument;
 use url2::Url2;
 
 use crate::util::addr_to_url;
+use crate::util::tls_err;
+#[cfg(unix)]
+use crate::util::unix_path_to_url;
 use crate::util::url_to_addr;
+use crate::util::RawSocket;
 use crate::websocket::Websocket;
 use crate::WebsocketConfig;
 use crate::WebsocketError;
@@ -104,7 +108,106 @@ impl WebsocketListener {
         ListenerHandle,
         impl futures::stream::Stream<Item = ListenerItem>,
     )> {
-        websocket_bind(addr, config).await
+        let (listener, local_addr) = tcp_bind(addr, &config).await?;
+        let scheme = config.scheme;
+        Ok(serve(
+            listener,
+            local_addr,
+            config,
+            move |socket| async move {
+                let peer_addr = addr_to_url(socket.peer_addr()?, scheme);
+                Ok((Box::new(socket) as Box<dyn RawSocket>, peer_addr))
+            },
+        ))
+    }
+
+    #[instrument(skip(config, addr, cert_pem, key_pem))]
+    /// Same as [`WebsocketListener::bind_with_handle`] but every connection
+    /// is wrapped in TLS, using a PEM encoded certificate chain and PKCS #8
+    /// private key. The config's scheme should be "wss".
+    pub async fn bind_tls_with_handle(
+        addr: Url2,
+        config: Arc<WebsocketConfig>,
+        cert_pem: &[u8],
+        key_pem: &[u8],
+    ) -> WebsocketResult<(
+        ListenerHandle,
+        impl futures::stream::Stream<Item = ListenerItem>,
+    )> {
+        let identity = tokio_native_tls::native_tls::Identity::from_pkcs8(cert_pem, key_pem)
+            .map_err(tls_err)?;
+        let acceptor = tokio_native_tls::native_tls::TlsAcceptor::new(identity).map_err(tls_err)?;
+        let acceptor = Arc::new(tokio_native_tls::TlsAcceptor::from(acceptor));
+        let (listener, local_addr) = tcp_bind(addr, &config).await?;
+        let scheme = config.scheme;
+        Ok(serve(listener, local_addr, config, move |socket| {
+            let acceptor = acceptor.clone();
+            async move {
+                let peer_addr = addr_to_url(socket.peer_addr()?, scheme);
+                let socket = acceptor.accept(socket).await.map_err(tls_err)?;
+                Ok((Box::new(socket) as Box<dyn RawSocket>, peer_addr))
+            }
+        }))
+    }
+
+    #[cfg(unix)]
+    #[instrument(skip(config))]
+    /// Same as [`WebsocketListener::bind_with_handle`] but listens on a unix
+    /// domain socket at `path` instead of a tcp port. The socket file is only
+    /// accessible to the current user.
+    /// A socket file left at `path` by a listener which is gone is replaced,
+    /// but binding fails if `path` is anything else or a listener is live.
+    pub async fn bind_unix_with_handle(
+        path: &std::path::Path,
+        config: Arc<WebsocketConfig>,
+    ) -> WebsocketResult<(
+        ListenerHandle,
+        impl futures::stream::Stream<Item = ListenerItem>,
+    )> {
+        use std::os::unix::fs::DirBuilderExt;
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket()
+                || std::os::unix::net::UnixStream::connect(path).is_ok()
+            {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AddrInUse,
+                    format!("{} is in use", path.display()),
+                )
+                .into());
+            }
+            std::fs::remove_file(path)?;
+        }
+
+        // Bind inside a directory only we can enter, so nobody can connect
+        // before the socket is made owner only, then move it into place.
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let private_dir = parent.join(format!(".socket-{}", nanoid::nanoid!()));
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&private_dir)?;
+        let bound = (|| {
+            let private_path = private_dir.join("socket");
+            let listener = std::os::unix::net::UnixListener::bind(&private_path)?;
+            std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&private_path, path)?;
+            std::io::Result::Ok(listener)
+        })();
+        let _ = std::fs::remove_dir_all(&private_dir);
+        let listener = bound?;
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::UnixListener::from_std(listener)?;
+        let listener = tokio_stream::wrappers::UnixListenerStream::new(listener);
+        let local_addr = unix_path_to_url(path);
+        let peer_addr = local_addr.clone();
+        Ok(serve(listener, local_addr, config, move |socket| {
+            let peer_addr = peer_addr.clone();
+            async move { Ok((Box::new(socket) as Box<dyn RawSocket>, peer_addr)) }
+        }))
     }
     /// Shutdown the listener stream.
     pub fn close(self) {
@@ -184,13 +287,10 @@ impl futures::stream::Stream for WebsocketListener {
     }
 }
 
-async fn websocket_bind(
+async fn tcp_bind(
     addr: Url2,
-    config: Arc<WebsocketConfig>,
-) -> WebsocketResult<(
-    ListenerHandle,
-    impl futures::stream::Stream<Item = ListenerItem>,
-)> {
+    config: &WebsocketConfig,
+) -> WebsocketResult<(tokio_stream::wrappers::TcpListenerStream, Url2)> {
     let addr = url_to_addr(&addr, config.scheme).await?;
     let socket = match &addr {
         SocketAddr::V4(_) => net2::TcpBuilder::new_v4()?,
@@ -202,8 +302,28 @@ async fn websocket_bind(
     socket.set_nonblocking(true)?;
     let local_addr = addr_to_url(socket.local_addr()?, config.scheme);
     let listener = tokio::net::TcpListener::from_std(socket)?;
-    let listener_stream = tokio_stream::wrappers::TcpListenerStream::new(listener);
+    Ok((
+        tokio_stream::wrappers::TcpListenerStream::new(listener),
+        local_addr,
+    ))
+}
 
+/// Accept websocket connections from a stream of incoming sockets,
+/// after `prepare` has wrapped each one and named its peer.
+fn serve<S, T, F, Fut>(
+    listener_stream: S,
+    local_addr: Url2,
+    config: Arc<WebsocketConfig>,
+    prepare: F,
+) -> (
+    ListenerHandle,
+    impl futures::stream::Stream<Item = ListenerItem>,
+)
+where
+    S: futures::stream::Stream<Item = std::io::Result<T>>,
+    F: Fn(T) -> Fut,
+    Fut: std::future::Future<Output = WebsocketResult<(Box<dyn RawSocket>, Url2)>>,
+{
     // Setup proper shutdown
     let (shutdown, valve) = Valve::new();
 
@@ -212,7 +332,15 @@ async fn websocket_bind(
         .map_ok({
             let config = config.clone();
             let valve = valve.clone();
-            move |socket_result| connect(config.clone(), socket_result, valve.clone())
+            move |socket| {
+                let prepared = prepare(socket);
+                let config = config.clone();
+                let valve = valve.clone();
+                async move {
+                    let (socket, peer_addr) = prepared.await?;
+                    connect(config, socket, peer_addr, valve).await
+                }
+            }
         })
         .try_buffer_unordered(config.max_pending_connections);
     tracing::debug!(sever_listening_on = ?local_addr);
@@ -224,13 +352,14 @@ async fn websocket_bind(
         config,
         local_addr,
     };
-    Ok((listener_handle, stream))
+    (listener_handle, stream)
 }
 
 #[instrument(skip(config, socket, valve))]
 async fn connect(
     config: Arc<WebsocketConfig>,
-    socket: tokio::net::TcpStream,
+    socket: Box<dyn RawSocket>,
+    peer_addr: Url2,
     valve: Valve,
 ) -> WebsocketResult<Pair> {
     // TODO: find alternative to set the keepalive
@@ -239,7 +368,7 @@ async fn connect(
     // )))?;
     tracing::debug!(
         message = "accepted incoming raw socket",
-        remote_addr = %socket.peer_addr()?,
+        remote_addr = %peer_addr,
     );
     let socket = tokio_tungstenite::accept_async_with_config(
         socket,
@@ -253,5 +382,5 @@ async fn connect(
     .await
     .map_err(|e| Error::new(ErrorKind::Other, e))?;
 
-    Websocket::create_ends(config, socket, valve)
+    Websocket::create_ends(config, socket, peer_addr, valve)
 }
@@ -3,6 +3,9 @@ use std::sync::Arc;
 use futures::StreamExt;
 use holochain_serialized_bytes::prelude::*;
 use holochain_websocket::connect;
+use holochain_websocket::connect_tls;
+#[cfg(unix)]
+use holochain_websocket::connect_unix;
 use holochain_websocket::ListenerHandle;
 use holochain_websocket::ListenerItem;
 use holochain_websocket::WebsocketConfig;
@@ -452,3 +455,115 @@ async fn cancel_response() {
     c_jh.await.unwrap();
     s_jh.await.unwrap();
 }
+
+/// Answer one request from the first client to connect successfully.
+fn server_echo(
+    mut listener: impl futures::stream::Stream<Item = ListenerItem> + Unpin + Send + 'static,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let (_, mut receiver) = loop {
+            if let Ok(pair) = listener
+                .next()
+                .instrument(tracing::debug_span!("next_server_connection"))
+                .await
+                .unwrap()
+            {
+                break pair;
+            }
+        };
+
+        let (msg, resp) = receiver
+            .next()
+            .instrument(tracing::debug_span!("next_server_recv"))
+            .await
+            .unwrap();
+        let msg: TestString = msg.try_into().unwrap();
+        resp.respond(TestString(format!("echo: {}", msg.0)).try_into().unwrap())
+            .await
+            .unwrap();
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_request_over_tls() {
+    observability::test_run().ok();
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert_pem = cert.serialize_pem().unwrap();
+    let key_pem = cert.serialize_private_key_pem();
+    let config = Arc::new(WebsocketConfig::default().scheme("wss"));
+    let (handle, listener) = WebsocketListener::bind_tls_with_handle(
+        url2!("wss://127.0.0.1:0"),
+        config.clone(),
+        cert_pem.as_bytes(),
+        key_pem.as_bytes(),
+    )
+    .await
+    .unwrap();
+    let jh = server_echo(Box::pin(listener));
+
+    let port = handle.local_addr().port().unwrap();
+    let url = url2!("wss://localhost:{}", port);
+
+    // - The self-signed certificate isn't trusted by default
+    assert!(connect_tls(url.clone(), config.clone(), None)
+        .await
+        .is_err());
+
+    let (mut sender, _receiver) = connect_tls(url, config, Some(cert_pem.as_bytes()))
+        .await
+        .unwrap();
+    let msg: TestString = sender
+        .request(TestString("Hey from client".into()))
+        .await
+        .unwrap();
+    assert_eq!(msg.0, "echo: Hey from client");
+
+    jh.await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_request_over_unix_socket() {
+    observability::test_run().ok();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("admin.sock");
+    let config = Arc::new(WebsocketConfig::default());
+
+    // - A socket file left by an earlier listener is replaced
+    let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    drop(stale);
+
+    let (_handle, listener) = WebsocketListener::bind_unix_with_handle(&path, config.clone())
+        .await
+        .unwrap();
+    let jh = server_echo(Box::pin(listener));
+
+    // - Only the current user can connect
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // - A live socket is never replaced
+    assert!(
+        WebsocketListener::bind_unix_with_handle(&path, config.clone())
+            .await
+            .is_err()
+    );
+
+    let (mut sender, _receiver) = connect_unix(&path, config.clone()).await.unwrap();
+    let msg: TestString = sender
+        .request(TestString("Hey from client".into()))
+        .await
+        .unwrap();
+    assert_eq!(msg.0, "echo: Hey from client");
+
+    jh.await.unwrap();
+
+    // - Nor is a file which isn't a socket
+    let file = dir.path().join("not_a_socket");
+    std::fs::write(&file, b"keep me").unwrap();
+    assert!(WebsocketListener::bind_unix_with_handle(&file, config)
+        .await
+        .is_err());
+    assert_eq!(std::fs::read(&file).unwrap(), b"keep me");
+}
@@ -25,6 +25,8 @@
 //! to send to the Grantor.
 
 mod grant;
+mod info;
 pub use grant::*;
+pub use info::*;
 
 pub use holochain_integrity_types::capability::*;
@@ -2,6 +2,13 @@ use super::*;
 use holochain_serialized_bytes::SerializedBytes;
 use std::collections::BTreeMap;
 
+/// The zome name used in a [`GrantedFunction`] to grant access to the
+/// conductor's crypto functions over an app interface, instead of a zome function.
+///
+/// For example a grant of `(CRYPTO_GRANT_ZOME_NAME.into(), "sign".into())` lets
+/// its holder ask the conductor to sign data with the agent's key.
+pub const CRYPTO_GRANT_ZOME_NAME: &str = "__crypto";
+
 #[derive(Default, PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
 /// @todo Ability to forcibly curry payloads into functions that are called with a claim.
 pub struct CurryPayloads(pub BTreeMap<GrantedFunction, SerializedBytes>);
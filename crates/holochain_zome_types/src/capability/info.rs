@@ -0,0 +1,86 @@
+use super::*;
+use crate::Timestamp;
+use holo_hash::ActionHash;
+use holo_hash::AgentPubKey;
+
+/// Filter for the `capability_grants` host function.
+/// Every filter that is set must match for a grant to be returned.
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapGrantsInput {
+    /// Only return grants with exactly this tag.
+    pub tag: Option<String>,
+    /// Only return grants that include this zome function.
+    pub function: Option<GrantedFunction>,
+}
+
+impl CapGrantsInput {
+    /// Filter by grant tag.
+    pub fn tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Filter by granted zome function.
+    pub fn function(mut self, function: GrantedFunction) -> Self {
+        self.function = Some(function);
+        self
+    }
+}
+
+/// A live capability grant on the local source chain.
+#[derive(PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapGrantInfo {
+    /// The grant itself.
+    pub cap_grant: ZomeCallCapGrant,
+    /// The action that committed the grant.
+    pub action_hash: ActionHash,
+    /// When the grant was committed.
+    pub created_at: Timestamp,
+}
+
+/// Filter for the `capability_claims` host function.
+/// Every filter that is set must match for a claim to be returned.
+#[derive(Default, PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapClaimsInput {
+    /// Only return claims on grants from this agent.
+    pub grantor: Option<AgentPubKey>,
+    /// Only return claims with exactly this tag.
+    pub tag: Option<String>,
+}
+
+impl CapClaimsInput {
+    /// Filter by grantor.
+    pub fn grantor(mut self, grantor: AgentPubKey) -> Self {
+        self.grantor = Some(grantor);
+        self
+    }
+
+    /// Filter by claim tag.
+    pub fn tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+}
+
+/// A live capability claim on the local source chain.
+#[derive(PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapClaimInfo {
+    /// The claim itself.
+    pub cap_claim: CapClaim,
+    /// The action that committed the claim.
+    pub action_hash: ActionHash,
+    /// When the claim was committed.
+    pub created_at: Timestamp,
+}
+
+/// The capability that authorized the current zome call.
+#[derive(PartialEq, Eq, Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CapabilityInfo {
+    /// The agent that made the call.
+    pub provenance: AgentPubKey,
+    /// The grant that authorized the call.
+    pub cap_grant: CapGrant,
+    /// The action that committed the grant.
+    /// This is `None` for the implicit grant that the chain author holds.
+    pub action_hash: Option<ActionHash>,
+}
@@ -56,6 +56,12 @@ pub struct DnaDef {
     /// A vector of zomes that do not affect
     /// the [`DnaHash`].
     pub coordinator_zomes: CoordinatorZomes,
+
+    /// The rate limiting buckets which actions are weighed into by the
+    /// `weigh` callback. Buckets that are not defined here are unlimited.
+    #[serde(default, skip_serializing_if = "RateLimits::is_empty")]
+    #[cfg_attr(feature = "full-dna-def", builder(default))]
+    pub rate_limits: RateLimits,
 }
 
 #[derive(Serialize, Debug, PartialEq, Eq)]
@@ -65,6 +71,8 @@ struct DnaDefHash<'a> {
     uid: &'a String,
     properties: &'a SerializedBytes,
     integrity_zomes: &'a IntegrityZomes,
+    #[serde(skip_serializing_if = "RateLimits::is_empty")]
+    rate_limits: &'a RateLimits,
 }
 
 #[cfg(feature = "test_utils")]
@@ -223,6 +231,7 @@ impl HashableContent for DnaDef {
             uid: &self.uid,
             properties: &self.properties,
             integrity_zomes: &self.integrity_zomes,
+            rate_limits: &self.rate_limits,
         };
         HashableContentBytes::Content(
             holochain_serialized_bytes::UnsafeBytes::from(
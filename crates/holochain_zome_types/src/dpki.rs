@@ -0,0 +1,89 @@
+//! Types for the DPKI app which a conductor uses to manage agent keys.
+//!
+//! A DPKI app is a regular hApp which the conductor installs and calls into.
+//! It must have a zome named [`DPKI_ZOME_NAME`] exposing the functions named
+//! below, taking and returning the types in this module.
+
+use crate::timestamp::Timestamp;
+use holo_hash::AgentPubKey;
+use holochain_serialized_bytes::prelude::*;
+
+/// The name of the zome in the DPKI app which the conductor calls.
+pub const DPKI_ZOME_NAME: &str = "dpki";
+
+/// Called once after the DPKI app is installed, with the `init_params`
+/// from the conductor config as a string.
+pub const DPKI_INIT_FN: &str = "init";
+
+/// Called with an [`AgentPubKey`] when the conductor generates a key
+/// or installs an app for a key it didn't generate.
+pub const DPKI_REGISTER_KEY_FN: &str = "register_key";
+
+/// Called with an [`AgentPubKey`] to revoke it.
+pub const DPKI_REVOKE_KEY_FN: &str = "revoke_key";
+
+/// Called with a [`RotateKeyInput`] to replace a key with a new one.
+pub const DPKI_ROTATE_KEY_FN: &str = "rotate_key";
+
+/// Called with an [`AgentPubKey`], returning its [`KeyState`].
+pub const DPKI_KEY_STATE_FN: &str = "key_state";
+
+/// The input to [`DPKI_ROTATE_KEY_FN`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub struct RotateKeyInput {
+    /// The key being replaced, which is no longer valid after the rotation.
+    pub old_key: AgentPubKey,
+    /// The key replacing it.
+    pub new_key: AgentPubKey,
+}
+
+/// The state of an agent key as recorded by DPKI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, SerializedBytes)]
+pub enum KeyState {
+    /// DPKI has no record of this key.
+    NotFound,
+    /// The key was registered at this time and is still valid.
+    Valid(Timestamp),
+    /// The key was revoked at this time.
+    Revoked(Timestamp),
+    /// The key was replaced by another key at this time.
+    Rotated(AgentPubKey, Timestamp),
+}
+
+impl KeyState {
+    /// Whether something signed by this key at the given time should be trusted.
+    ///
+    /// Nothing vouches for a key DPKI has no record of, so it is never valid.
+    /// Whether to trust such keys anyway is up to the conductor's policy.
+    ///
+    /// The timestamp is the one the author put on what it signed, so a
+    /// compromised key can still sign things dated before its revocation.
+    pub fn is_valid_at(&self, timestamp: Timestamp) -> bool {
+        match self {
+            KeyState::NotFound => false,
+            KeyState::Valid(_) => true,
+            KeyState::Revoked(at) | KeyState::Rotated(_, at) => timestamp < *at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_state_is_valid_at() {
+        let before = Timestamp::from_micros(10);
+        let at = Timestamp::from_micros(20);
+        let after = Timestamp::from_micros(30);
+        let key = AgentPubKey::from_raw_36(vec![0; 36]);
+
+        assert!(!KeyState::NotFound.is_valid_at(after));
+        assert!(KeyState::Valid(at).is_valid_at(after));
+        for state in [KeyState::Revoked(at), KeyState::Rotated(key, at)] {
+            assert!(state.is_valid_at(before));
+            assert!(!state.is_valid_at(at));
+            assert!(!state.is_valid_at(after));
+        }
+    }
+}
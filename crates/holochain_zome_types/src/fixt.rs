@@ -745,6 +745,7 @@ fixturator!(
         coordinator_zomes: CoordinatorZomesFixturator::new_indexed(Empty, get_fixt_index!())
             .next()
             .unwrap(),
+        rate_limits: Default::default(),
     };
 
     curve Unpredictable DnaDef {
@@ -764,6 +765,7 @@ fixturator!(
         coordinator_zomes: CoordinatorZomesFixturator::new_indexed(Empty, get_fixt_index!())
             .next()
             .unwrap(),
+        rate_limits: Default::default(),
     };
 
     curve Predictable DnaDef {
@@ -783,6 +785,7 @@ fixturator!(
         coordinator_zomes: CoordinatorZomesFixturator::new_indexed(Empty, get_fixt_index!())
             .next()
             .unwrap(),
+        rate_limits: Default::default(),
     };
 );
 
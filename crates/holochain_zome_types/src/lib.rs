@@ -22,6 +22,7 @@ pub mod countersigning;
 #[allow(missing_docs)]
 pub mod crdt;
 pub mod dna_def;
+pub mod dpki;
 pub mod entry;
 #[allow(missing_docs)]
 pub mod entry_def;
@@ -10,6 +10,7 @@ pub use crate::cell::*;
 pub use crate::countersigning::*;
 pub use crate::crdt::*;
 pub use crate::dna_def::*;
+pub use crate::dpki::*;
 pub use crate::entry::*;
 pub use crate::entry_def::*;
 pub use crate::genesis::*;
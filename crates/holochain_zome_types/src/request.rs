@@ -5,11 +5,10 @@ use holochain_serialized_bytes::prelude::*;
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
 /// Metadata that can be requested on a basis
 pub struct MetadataRequest {
-    /// Get all the actions on an entry.
-    /// Invalid request on an action.
+    /// Get all the valid actions that created an entry,
+    /// or the action itself if this is an action hash.
     pub all_valid_actions: bool,
-    // TODO: Implement after validation
-    /// Placeholder
+    /// Get all the actions that were rejected by validation.
     pub all_invalid_actions: bool,
     /// Get all the deletes on an action
     pub all_deletes: bool,
@@ -21,6 +20,8 @@ pub struct MetadataRequest {
     /// This is faster then getting all the actions
     /// and checking for live actions.
     pub entry_dht_status: bool,
+    /// Count the live links that use this hash as their base.
+    pub link_count: bool,
 }
 
 impl Default for MetadataRequest {
@@ -32,6 +33,7 @@ impl Default for MetadataRequest {
             all_updates: true,
             follow_redirects: false,
             entry_dht_status: false,
+            link_count: false,
         }
     }
 }
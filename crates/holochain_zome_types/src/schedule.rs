@@ -2,10 +2,17 @@ use crate::FunctionName;
 use crate::ZomeName;
 use std::time::Duration;
 
-/// Tick the scheduler every this many millis.
+/// The longest the scheduler waits between runs. It wakes earlier when a
+/// scheduled function is due sooner.
 pub const SCHEDULER_INTERVAL: Duration = Duration::from_millis(10000);
 
-/// Expire persisted schedules after this long.
+/// The shortest the scheduler waits between runs, so that functions which
+/// keep scheduling themselves straight away can't keep it busy.
+pub const SCHEDULER_MIN_WAIT: Duration = Duration::from_millis(100);
+
+/// A persisted schedule's run is missed if it hasn't happened this long
+/// after it was due, e.g. because the conductor wasn't running.
+/// See [`ScheduleCatchUp`].
 pub const PERSISTED_TIMEOUT: Duration = Duration::from_millis(20000);
 
 /// Scheduling errors.
@@ -22,9 +29,21 @@ pub enum ScheduleError {
 /// Defines either a persisted or ephemeral schedule for a schedule function.
 /// Persisted schedules survive a conductor reboot, ephemeral will not.
 /// Persisted schedules continue beyond irrecoverable errors, ephemeral do not.
+///
+/// A scheduled function returns its next schedule, which replaces the one
+/// it was run for, or `None` to be unscheduled.
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
 pub enum Schedule {
-    /// Persisted schedules are defined by a crontab syntax string.
+    /// Persisted schedules are defined by a cron expression, and run each
+    /// time it fires, in UTC.
+    ///
+    /// The expression can be a standard five field crontab
+    /// (`minute hour day-of-month month day-of-week`), which fires on the
+    /// first second of the minute, or have a leading seconds field and an
+    /// optional trailing year field.
+    ///
+    /// Runs missed while the conductor wasn't running are handled according
+    /// to its [`ScheduleCatchUp`] policy.
     Persisted(String),
     /// Ephemeral schedules are defined by a Duration.
     Ephemeral(Duration),
@@ -43,7 +62,7 @@ impl From<Duration> for Schedule {
 }
 
 /// A fully qualified scheduled function.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
 pub struct ScheduledFn(ZomeName, FunctionName);
 
 impl ScheduledFn {
@@ -62,3 +81,17 @@ impl ScheduledFn {
         &self.1
     }
 }
+
+/// What the scheduler does with runs of persisted schedules which were
+/// missed, because the conductor or the cell wasn't running when they were due.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleCatchUp {
+    /// Don't run the function for missed runs, only at the next time
+    /// its schedule fires.
+    #[default]
+    Skip,
+    /// Run the function once as soon as possible, however many runs were
+    /// missed, and then at the next time its schedule fires.
+    RunOnce,
+}
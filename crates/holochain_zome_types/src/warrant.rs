@@ -1,6 +1,81 @@
 //! Types for warrants
+//!
+//! A warrant is issued by an authority when an op fails validation.
+//! It names the author of the invalid data and carries the evidence, so that
+//! anyone who receives it can check the claim for themselves.
+use crate::record::SignedAction;
+use crate::signature::Signature;
+use crate::timestamp::Timestamp;
+use holo_hash::AgentPubKey;
 pub use holochain_serialized_bytes::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
-/// Placeholder for warrant type
-pub struct Warrant;
+/// A claim by a validating authority that an agent authored invalid data.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct Warrant {
+    /// The evidence that the warranted agent authored invalid data.
+    pub proof: WarrantProof,
+    /// The authority which issued this warrant.
+    pub author: AgentPubKey,
+    /// When the warrant was issued.
+    pub timestamp: Timestamp,
+}
+
+/// The evidence a [`Warrant`] is based on.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub enum WarrantProof {
+    /// An action failed validation.
+    /// The signed action lets anyone check that the warranted agent authored
+    /// it, and re-run validation on it.
+    InvalidAction {
+        /// The action that failed validation, with its author's signature.
+        action: SignedAction,
+        /// Which kind of validation rejected the action.
+        validation_type: ValidationType,
+        /// Why the action was rejected.
+        reason: String,
+    },
+}
+
+/// The kind of validation that rejected some data.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, SerializedBytes,
+)]
+pub enum ValidationType {
+    /// Rejected by the system validation rules shared by every DNA.
+    Sys,
+    /// Rejected by the DNA's own validation callbacks.
+    App,
+}
+
+impl Warrant {
+    /// The agent this warrant is about.
+    pub fn warrantee(&self) -> &AgentPubKey {
+        match &self.proof {
+            WarrantProof::InvalidAction { action, .. } => action.0.author(),
+        }
+    }
+}
+
+/// A [`Warrant`] signed by the authority which issued it.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct SignedWarrant {
+    /// The warrant.
+    pub warrant: Warrant,
+    /// The issuing authority's signature of the warrant.
+    pub signature: Signature,
+}
+
+impl SignedWarrant {
+    /// Pair a warrant with its signature.
+    pub fn new(warrant: Warrant, signature: Signature) -> Self {
+        Self { warrant, signature }
+    }
+}
+
+impl std::ops::Deref for SignedWarrant {
+    type Target = Warrant;
+
+    fn deref(&self) -> &Self::Target {
+        &self.warrant
+    }
+}
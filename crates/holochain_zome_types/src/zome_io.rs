@@ -51,14 +51,14 @@ wasm_io_types! {
 
     fn call (Vec<zt::call::Call>) -> Vec<zt::ZomeCallResponse>;
 
-    // @todo List all the local capability claims.
-    fn capability_claims (()) -> ();
+    // List all the live local capability claims.
+    fn capability_claims (zt::capability::CapClaimsInput) -> Vec<zt::capability::CapClaimInfo>;
 
-    // @todo List all the local capability grants.
-    fn capability_grants (()) -> ();
+    // List all the live local capability grants.
+    fn capability_grants (zt::capability::CapGrantsInput) -> Vec<zt::capability::CapGrantInfo>;
 
-    // @todo Get the capability for the current zome call.
-    fn capability_info (()) -> ();
+    // Get the capability for the current zome call.
+    fn capability_info (()) -> zt::capability::CapabilityInfo;
 
     // Returns ActionHash of the newly created record.
     fn create (zt::entry::CreateInput) -> holo_hash::ActionHash;
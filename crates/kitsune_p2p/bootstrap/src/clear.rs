@@ -13,7 +13,7 @@ pub(crate) fn clear(
 }
 
 async fn clear_info(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
-    store.clear();
+    store.clear().map_err(|_| warp::reject())?;
     Ok(warp::reply())
 }
 
@@ -27,7 +27,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_clear() {
-        let store = Store::new(vec![]);
+        let store = Store::new(vec![], crate::store::tests::test_limits());
 
         let filter = super::clear(store.clone());
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
@@ -44,7 +44,7 @@ mod tests {
             )
             .await
             .unwrap();
-            store.put(info);
+            store.put(info).unwrap();
         }
 
         let res = warp::test::request()
@@ -1,9 +1,10 @@
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicUsize;
 
 use kitsune_p2p_types::codec::rmp_decode;
 use kitsune_p2p_types::codec::rmp_encode;
-use store::Store;
+use store::{Store, StoreLimits};
 use warp::{hyper::body::Bytes, Filter};
 
 static NOW: AtomicUsize = AtomicUsize::new(0);
@@ -15,6 +16,7 @@ mod now;
 mod proxy_list;
 mod put;
 mod random;
+mod status;
 mod store;
 
 /// No reason to accept a peer data bigger then 1KB.
@@ -24,6 +26,48 @@ const SIZE_LIMIT: u64 = 1024;
 /// how often should we prune the expired entries?
 pub const PRUNE_EXPIRED_FREQ: std::time::Duration = std::time::Duration::from_secs(5);
 
+/// The default for [`BootstrapConfig::max_peers_per_space`].
+pub const DEFAULT_MAX_PEERS_PER_SPACE: usize = 10_000;
+
+/// The default for [`BootstrapConfig::max_peers`].
+pub const DEFAULT_MAX_PEERS: usize = 1_000_000;
+
+/// The default for [`BootstrapConfig::max_random_limit`].
+pub const DEFAULT_MAX_RANDOM_LIMIT: u32 = 64;
+
+/// Configuration of a bootstrap server.
+#[derive(Clone, Debug)]
+pub struct BootstrapConfig {
+    /// Proxy server addresses returned by the `proxy_list` call.
+    pub proxy_list: Vec<String>,
+    /// How often expired peer records are pruned.
+    pub prune_frequency: std::time::Duration,
+    /// Persist peer records in an SQLite database at this path, so that they
+    /// survive restarts. If `None`, they are only kept in memory.
+    pub db_path: Option<PathBuf>,
+    /// The most peer records kept for a single space.
+    /// Puts for new agents in a full space are dropped.
+    pub max_peers_per_space: usize,
+    /// The most peer records kept across all spaces.
+    /// Puts for new agents are dropped once it is reached.
+    pub max_peers: usize,
+    /// The most peers a `random` query returns, whatever limit it asks for.
+    pub max_random_limit: u32,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            proxy_list: Vec::new(),
+            prune_frequency: PRUNE_EXPIRED_FREQ,
+            db_path: None,
+            max_peers_per_space: DEFAULT_MAX_PEERS_PER_SPACE,
+            max_peers: DEFAULT_MAX_PEERS,
+            max_random_limit: DEFAULT_MAX_RANDOM_LIMIT,
+        }
+    }
+}
+
 pub type BootstrapDriver = futures::future::BoxFuture<'static, ()>;
 
 pub type BootstrapShutdown = Box<dyn FnOnce() + 'static + Send>;
@@ -42,7 +86,40 @@ pub async fn run_with_prune_freq(
     proxy_list: Vec<String>,
     prune_frequency: std::time::Duration,
 ) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
-    let store = Store::new(proxy_list);
+    run_with_config(
+        addr,
+        BootstrapConfig {
+            proxy_list,
+            prune_frequency,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Run a bootstrap server with the given config.
+pub async fn run_with_config(
+    addr: impl Into<SocketAddr> + 'static,
+    config: BootstrapConfig,
+) -> Result<(BootstrapDriver, SocketAddr, BootstrapShutdown), String> {
+    let BootstrapConfig {
+        proxy_list,
+        prune_frequency,
+        db_path,
+        max_peers_per_space,
+        max_peers,
+        max_random_limit,
+    } = config;
+    let limits = StoreLimits {
+        max_peers_per_space,
+        max_peers,
+        max_random_limit,
+    };
+    let store = match db_path {
+        Some(path) => Store::open(proxy_list, limits, &path)
+            .map_err(|e| format!("Failed to open the peer database: {:?}", e))?,
+        None => Store::new(proxy_list, limits),
+    };
 
     {
         let store = store.clone();
@@ -58,7 +135,8 @@ pub async fn run_with_prune_freq(
         .or(put::put(store.clone()))
         .or(random::random(store.clone()))
         .or(proxy_list::proxy_list(store.clone()))
-        .or(clear::clear(store));
+        .or(clear::clear(store.clone()))
+        .or(status::status(store));
 
     let (s, r) = tokio::sync::oneshot::channel();
     let shutdown = Box::new(move || {
@@ -12,6 +12,24 @@ struct Args {
     /// multiple times
     #[clap(short, long, verbatim_doc_comment)]
     proxy: Vec<String>,
+
+    /// persist peer records in an SQLite
+    /// database at this path, so that they
+    /// survive restarts
+    #[clap(long, verbatim_doc_comment)]
+    db_path: Option<std::path::PathBuf>,
+
+    /// the most peer records kept for a single space
+    #[clap(long, default_value_t = kitsune_p2p_bootstrap::DEFAULT_MAX_PEERS_PER_SPACE)]
+    max_peers_per_space: usize,
+
+    /// the most peer records kept across all spaces
+    #[clap(long, default_value_t = kitsune_p2p_bootstrap::DEFAULT_MAX_PEERS)]
+    max_peers: usize,
+
+    /// the most peers returned by a `random` call
+    #[clap(long, default_value_t = kitsune_p2p_bootstrap::DEFAULT_MAX_RANDOM_LIMIT)]
+    max_random_limit: u32,
 }
 
 #[tokio::main(flavor = "multi_thread")]
@@ -27,7 +45,16 @@ async fn main() {
         .next()
         .unwrap();
 
-    match kitsune_p2p_bootstrap::run(addr, args.proxy).await {
+    let config = kitsune_p2p_bootstrap::BootstrapConfig {
+        proxy_list: args.proxy,
+        db_path: args.db_path,
+        max_peers_per_space: args.max_peers_per_space,
+        max_peers: args.max_peers,
+        max_random_limit: args.max_random_limit,
+        ..Default::default()
+    };
+
+    match kitsune_p2p_bootstrap::run_with_config(addr, config).await {
         Ok((driver, addr, _shutdown)) => {
             println!("http://{}", addr);
             driver.await;
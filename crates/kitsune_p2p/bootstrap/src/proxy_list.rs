@@ -25,10 +25,10 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_proxy_list() {
-        let store = Store::new(vec![
-            "https://test1.test".into(),
-            "https://test2.test".into(),
-        ]);
+        let store = Store::new(
+            vec!["https://test1.test".into(), "https://test2.test".into()],
+            crate::store::tests::test_limits(),
+        );
         let filter = super::proxy_list(store.clone());
 
         let res = warp::test::request()
@@ -20,7 +20,9 @@ async fn put_info(peer: Bytes, store: Store) -> Result<impl warp::Reply, warp::R
         rmp_decode(&mut AsRef::<[u8]>::as_ref(&peer)).map_err(|_| warp::reject())?;
     // TODO: Return rejection if agent info was invalid?
     if valid(&peer) {
-        store.put(peer);
+        store.put(peer).map_err(|_| warp::reject())?;
+    } else {
+        store.record_invalid_put();
     }
     PUT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     let mut buf = Vec::with_capacity(1);
@@ -48,7 +50,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_put() {
-        let store = Store::new(vec![]);
+        let store = Store::new(vec![], crate::store::tests::test_limits());
         let filter = put(store.clone());
 
         let info = AgentInfoSigned::sign(
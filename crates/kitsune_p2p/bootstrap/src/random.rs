@@ -54,7 +54,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_random() {
-        let store = Store::new(vec![]);
+        let store = Store::new(vec![], crate::store::tests::test_limits());
         let filter = super::random(store.clone());
         let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace));
         let mut peers = Vec::new();
@@ -0,0 +1,80 @@
+use crate::store::{Store, StoreStatus};
+
+use super::*;
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use warp::Filter;
+
+/// What the server holds and has done, for monitoring.
+#[derive(Serialize)]
+struct Status {
+    /// The number of requests handled for each operation.
+    requests: Requests,
+    #[serde(flatten)]
+    store: StoreStatus,
+}
+
+#[derive(Serialize)]
+struct Requests {
+    now: usize,
+    put: usize,
+    random: usize,
+}
+
+/// `GET /status` returns the [`Status`] as JSON.
+pub(crate) fn status(
+    store: Store,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("status"))
+        .and(with_store(store))
+        .and_then(get_status)
+}
+
+async fn get_status(store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = Status {
+        requests: Requests {
+            now: NOW.load(Ordering::Relaxed),
+            put: PUT.load(Ordering::Relaxed),
+            random: RANDOM.load(Ordering::Relaxed),
+        },
+        store: store.status(),
+    };
+    Ok(warp::reply::json(&status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::tests::{test_info, test_limits};
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+    use kitsune_p2p::KitsuneSpace;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_status() {
+        let store = Store::new(vec![], test_limits());
+        let space: Arc<KitsuneSpace> = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        for _ in 0..3 {
+            store.put(test_info(space.clone(), 60_000).await).unwrap();
+        }
+        store.prune();
+        let filter = super::status(store);
+
+        let res = warp::test::request()
+            .method("GET")
+            .path("/status")
+            .reply(&filter)
+            .await;
+        assert_eq!(res.status(), 200);
+        let status: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(status["peers"], 3);
+        assert_eq!(status["spaces"][space.to_string()], 3);
+        assert_eq!(status["puts"]["stored"], 3);
+        assert_eq!(status["puts_last_minute"], 3);
+        assert_eq!(status["prune"]["runs"], 1);
+        assert_eq!(status["persistent"], false);
+        assert!(status["requests"]["put"].is_number());
+    }
+}
@@ -1,65 +1,276 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
 
 use kitsune_p2p_types::{
     agent_info::AgentInfoSigned,
     bin_types::{KitsuneAgent, KitsuneSpace},
     bootstrap::RandomQuery,
-    codec::rmp_encode,
+    codec::{rmp_decode, rmp_encode},
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rand::seq::IteratorRandom;
+use rusqlite::{params, Connection};
+use serde::Serialize;
 
 type AgentMap = HashMap<Arc<KitsuneAgent>, AgentInfoSigned>;
 type SpaceMap = HashMap<Arc<KitsuneSpace>, AgentMap>;
 
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS Peer (
+    space         BLOB    NOT NULL,
+    agent         BLOB    NOT NULL,
+    expires_at_ms INTEGER NOT NULL,
+    info          BLOB    NOT NULL,
+    PRIMARY KEY (space, agent)
+);
+";
+
+/// Limits on the peer records a store holds and returns.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct StoreLimits {
+    pub max_peers_per_space: usize,
+    pub max_peers: usize,
+    pub max_random_limit: u32,
+}
+
+/// What a put did to the store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PutOutcome {
+    /// The record was stored.
+    Stored,
+    /// A record signed at the same time or later was already stored.
+    Stale,
+    /// The record was for a new agent in a space which is full.
+    SpaceFull,
+    /// The record was for a new agent and the store is full.
+    StoreFull,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub(crate) struct PutStats {
+    pub stored: u64,
+    pub stale: u64,
+    pub invalid: u64,
+    pub rejected_space_full: u64,
+    pub rejected_store_full: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub(crate) struct PruneStats {
+    pub runs: u64,
+    pub last_run_at_ms: Option<u64>,
+    pub last_removed: usize,
+    pub total_removed: u64,
+    /// The error of the last prune of the database, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Counts events over the last minute, in one second buckets.
+#[derive(Debug)]
+struct RateWindow([(u64, u64); 60]);
+
+impl Default for RateWindow {
+    fn default() -> Self {
+        Self([(0, 0); 60])
+    }
+}
+
+impl RateWindow {
+    fn record(&mut self, now_s: u64) {
+        let bucket = &mut self.0[(now_s % 60) as usize];
+        if bucket.0 != now_s {
+            *bucket = (now_s, 0);
+        }
+        bucket.1 += 1;
+    }
+
+    fn last_minute(&self, now_s: u64) -> u64 {
+        self.0
+            .iter()
+            .filter(|(s, _)| now_s.saturating_sub(*s) < 60)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    puts: PutStats,
+    put_rate: RateWindow,
+    prune: PruneStats,
+}
+
+/// A snapshot of what a store holds and has done.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct StoreStatus {
+    pub uptime_s: u64,
+    pub persistent: bool,
+    pub peers: usize,
+    /// The number of peers in each space, keyed by the base64 space.
+    pub spaces: BTreeMap<String, usize>,
+    pub puts: PutStats,
+    pub puts_last_minute: u64,
+    pub prune: PruneStats,
+    pub limits: StoreLimits,
+}
+
 #[derive(Clone, Debug)]
-pub(crate) struct Store(Arc<RwLock<SpaceMap>>, Arc<Vec<String>>);
+pub(crate) struct Store {
+    peers: Arc<RwLock<SpaceMap>>,
+    proxy_list: Arc<Vec<String>>,
+    limits: Arc<StoreLimits>,
+    /// Where peer records are persisted, if anywhere.
+    db: Option<Arc<Mutex<Connection>>>,
+    stats: Arc<Mutex<Stats>>,
+    started_at: Instant,
+}
 
 impl Store {
-    pub fn new(proxy_list: Vec<String>) -> Self {
-        Self(Arc::new(RwLock::new(HashMap::new())), Arc::new(proxy_list))
+    /// A store which only keeps peer records in memory.
+    pub fn new(proxy_list: Vec<String>, limits: StoreLimits) -> Self {
+        Self {
+            peers: Arc::new(RwLock::new(HashMap::new())),
+            proxy_list: Arc::new(proxy_list),
+            limits: Arc::new(limits),
+            db: None,
+            stats: Arc::new(Mutex::new(Stats::default())),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// A store which persists peer records in the SQLite database at this
+    /// path, starting with the unexpired records already in it.
+    pub fn open(
+        proxy_list: Vec<String>,
+        limits: StoreLimits,
+        path: &Path,
+    ) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        conn.execute(
+            "DELETE FROM Peer WHERE expires_at_ms < ?",
+            params![now_ms() as i64],
+        )?;
+
+        let mut store = Self::new(proxy_list, limits);
+        {
+            let mut stmt = conn.prepare("SELECT info FROM Peer")?;
+            let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?;
+            let mut peers = store.peers.write();
+            for info in rows {
+                // A record which can't be decoded is left to expire.
+                if let Ok(info) = rmp_decode::<_, AgentInfoSigned>(&mut info?.as_slice()) {
+                    if store.check_put(&peers, &info) == PutOutcome::Stored {
+                        insert(&mut peers, info);
+                    }
+                }
+            }
+        }
+        store.db = Some(Arc::new(Mutex::new(conn)));
+        Ok(store)
     }
 
     pub fn proxy_list(&self) -> Arc<Vec<String>> {
-        self.1.clone()
+        self.proxy_list.clone()
     }
 
     pub fn prune(&self) {
-        let now = std::time::UNIX_EPOCH
-            .elapsed()
-            .expect("Bootstrap server time set before epoch")
-            .as_millis() as u64;
+        let now = now_ms();
 
-        self.0.write().retain(|_, map| {
+        let mut removed = 0;
+        self.peers.write().retain(|_, map| {
+            let len = map.len();
             map.retain(|_, info| info.expires_at_ms >= now);
+            removed += len - map.len();
             !map.is_empty()
         });
+        let result = self.db.as_ref().map(|db| {
+            db.lock()
+                .execute(
+                    "DELETE FROM Peer WHERE expires_at_ms < ?",
+                    params![now as i64],
+                )
+                .map_err(|e| e.to_string())
+        });
+
+        let prune = &mut self.stats.lock().prune;
+        prune.runs += 1;
+        prune.last_run_at_ms = Some(now);
+        prune.last_removed = removed;
+        prune.total_removed += removed as u64;
+        prune.last_error = result.and_then(Result::err);
     }
 
-    pub fn put(&self, info: AgentInfoSigned) {
-        let mut lock = self.0.write();
-        let space_map = lock.entry(info.space.clone()).or_insert_with(HashMap::new);
-        match space_map.entry(info.agent.clone()) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                if info.signed_at_ms > e.get().signed_at_ms {
-                    e.insert(info);
-                }
+    pub fn put(&self, info: AgentInfoSigned) -> rusqlite::Result<PutOutcome> {
+        let mut lock = self.peers.write();
+        let outcome = self.check_put(&lock, &info);
+        if outcome == PutOutcome::Stored {
+            if let Some(db) = &self.db {
+                let mut buf = Vec::new();
+                rmp_encode(&mut buf, &info)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+                db.lock().execute(
+                    "INSERT OR REPLACE INTO Peer (space, agent, expires_at_ms, info)
+                    VALUES (?, ?, ?, ?)",
+                    params![
+                        AsRef::<[u8]>::as_ref(info.space.as_ref()),
+                        AsRef::<[u8]>::as_ref(info.agent.as_ref()),
+                        info.expires_at_ms as i64,
+                        buf
+                    ],
+                )?;
             }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(info);
+            insert(&mut lock, info);
+        }
+        drop(lock);
+
+        let mut stats = self.stats.lock();
+        stats.put_rate.record(now_ms() / 1000);
+        let puts = &mut stats.puts;
+        match outcome {
+            PutOutcome::Stored => puts.stored += 1,
+            PutOutcome::Stale => puts.stale += 1,
+            PutOutcome::SpaceFull => puts.rejected_space_full += 1,
+            PutOutcome::StoreFull => puts.rejected_store_full += 1,
+        }
+        Ok(outcome)
+    }
+
+    /// Count a put of a record which wasn't valid.
+    pub fn record_invalid_put(&self) {
+        let mut stats = self.stats.lock();
+        stats.put_rate.record(now_ms() / 1000);
+        stats.puts.invalid += 1;
+    }
+
+    /// What putting this record would do, given the records already stored.
+    fn check_put(&self, peers: &SpaceMap, info: &AgentInfoSigned) -> PutOutcome {
+        let space = peers.get(info.space.as_ref());
+        match space.and_then(|space| space.get(info.agent.as_ref())) {
+            Some(existing) if info.signed_at_ms <= existing.signed_at_ms => PutOutcome::Stale,
+            Some(_) => PutOutcome::Stored,
+            None if space.map_or(0, |space| space.len()) >= self.limits.max_peers_per_space => {
+                PutOutcome::SpaceFull
             }
+            None if peers.values().map(|space| space.len()).sum::<usize>()
+                >= self.limits.max_peers =>
+            {
+                PutOutcome::StoreFull
+            }
+            None => PutOutcome::Stored,
         }
     }
 
     pub fn random(&self, query: RandomQuery) -> Vec<Vec<u8>> {
-        // TODO: Max this limit
-        let limit = query.limit.0 as usize;
+        let limit = query.limit.0.min(self.limits.max_random_limit) as usize;
         let mut rng = rand::thread_rng();
-        let now = std::time::UNIX_EPOCH
-            .elapsed()
-            .expect("Bootstrap server time set before epoch")
-            .as_millis() as u64;
-        self.0
+        let now = now_ms();
+        self.peers
             .read()
             .get(query.space.as_ref())
             .map(|space| {
@@ -83,12 +294,175 @@ impl Store {
             .unwrap_or_default()
     }
 
-    pub fn clear(&self) {
-        self.0.write().clear()
+    pub fn clear(&self) -> rusqlite::Result<()> {
+        let mut lock = self.peers.write();
+        if let Some(db) = &self.db {
+            db.lock().execute("DELETE FROM Peer", [])?;
+        }
+        lock.clear();
+        Ok(())
+    }
+
+    pub fn status(&self) -> StoreStatus {
+        let spaces: BTreeMap<_, _> = self
+            .peers
+            .read()
+            .iter()
+            .map(|(space, agents)| (space.to_string(), agents.len()))
+            .collect();
+        let stats = self.stats.lock();
+        StoreStatus {
+            uptime_s: self.started_at.elapsed().as_secs(),
+            persistent: self.db.is_some(),
+            peers: spaces.values().sum(),
+            spaces,
+            puts: stats.puts.clone(),
+            puts_last_minute: stats.put_rate.last_minute(now_ms() / 1000),
+            prune: stats.prune.clone(),
+            limits: self.limits.as_ref().clone(),
+        }
     }
 
     #[cfg(test)]
     pub fn all(&self) -> HashMap<Arc<KitsuneSpace>, HashMap<Arc<KitsuneAgent>, AgentInfoSigned>> {
-        self.0.read().clone()
+        self.peers.read().clone()
+    }
+}
+
+fn insert(peers: &mut SpaceMap, info: AgentInfoSigned) {
+    peers
+        .entry(info.space.clone())
+        .or_default()
+        .insert(info.agent.clone(), info);
+}
+
+fn now_ms() -> u64 {
+    std::time::UNIX_EPOCH
+        .elapsed()
+        .expect("Bootstrap server time set before epoch")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    pub(crate) fn test_limits() -> StoreLimits {
+        StoreLimits {
+            max_peers_per_space: 100,
+            max_peers: 1000,
+            max_random_limit: 100,
+        }
+    }
+
+    pub(crate) async fn test_info(space: Arc<KitsuneSpace>, expires_in_ms: u64) -> AgentInfoSigned {
+        AgentInfoSigned::sign(
+            space,
+            Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+            u32::MAX / 4,
+            vec!["fake:".into()],
+            now_ms(),
+            now_ms() + expires_in_ms,
+            |_| async move { Ok(Arc::new(fixt!(KitsuneSignature, Unpredictable))) },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_enforces_limits() {
+        let store = Store::new(
+            vec![],
+            StoreLimits {
+                max_peers_per_space: 2,
+                max_peers: 3,
+                max_random_limit: 1,
+            },
+        );
+        let space_1 = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let space_2 = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+
+        let info = test_info(space_1.clone(), 60_000).await;
+        assert_eq!(store.put(info.clone()).unwrap(), PutOutcome::Stored);
+        assert_eq!(store.put(info).unwrap(), PutOutcome::Stale);
+        let info = test_info(space_1.clone(), 60_000).await;
+        assert_eq!(store.put(info).unwrap(), PutOutcome::Stored);
+        let info = test_info(space_1.clone(), 60_000).await;
+        assert_eq!(store.put(info).unwrap(), PutOutcome::SpaceFull);
+        let info = test_info(space_2.clone(), 60_000).await;
+        assert_eq!(store.put(info).unwrap(), PutOutcome::Stored);
+        let info = test_info(space_2.clone(), 60_000).await;
+        assert_eq!(store.put(info).unwrap(), PutOutcome::StoreFull);
+
+        let query = RandomQuery {
+            space: space_1.clone(),
+            limit: 10.into(),
+        };
+        assert_eq!(store.random(query).len(), 1);
+
+        let status = store.status();
+        assert_eq!(status.peers, 3);
+        assert_eq!(status.spaces.get(&space_1.to_string()), Some(&2));
+        assert_eq!(status.puts.stored, 3);
+        assert_eq!(status.puts.stale, 1);
+        assert_eq!(status.puts.rejected_space_full, 1);
+        assert_eq!(status.puts.rejected_store_full, 1);
+        assert_eq!(status.puts_last_minute, 6);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_persists_peers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.sqlite3");
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+
+        let store = Store::open(vec![], test_limits(), &path).unwrap();
+        let info = test_info(space.clone(), 60_000).await;
+        store.put(info.clone()).unwrap();
+        store.put(test_info(space.clone(), 100).await).unwrap();
+        drop(store);
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let store = Store::open(vec![], test_limits(), &path).unwrap();
+        let all = store.all();
+        assert_eq!(all.get(space.as_ref()).unwrap().len(), 1);
+        assert_eq!(
+            all.get(space.as_ref()).unwrap().get(info.agent.as_ref()),
+            Some(&info)
+        );
+
+        store.clear().unwrap();
+        drop(store);
+        let store = Store::open(vec![], test_limits(), &path).unwrap();
+        assert!(store.all().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prune_removes_expired_peers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.sqlite3");
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+
+        let store = Store::open(vec![], test_limits(), &path).unwrap();
+        store.put(test_info(space.clone(), 60_000).await).unwrap();
+        store.put(test_info(space.clone(), 100).await).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        store.prune();
+
+        let status = store.status();
+        assert_eq!(status.peers, 1);
+        assert_eq!(status.prune.runs, 1);
+        assert_eq!(status.prune.last_removed, 1);
+        assert_eq!(status.prune.last_error, None);
+        let count: i64 = store
+            .db
+            .as_ref()
+            .unwrap()
+            .lock()
+            .query_row("SELECT COUNT(*) FROM Peer", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
     }
 }
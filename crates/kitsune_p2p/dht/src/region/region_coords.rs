@@ -30,6 +30,22 @@ impl RegionCoords {
             && self.time.contains_quantum(topo, coords.time)
     }
 
+    /// Does the region fully contain this other region?
+    pub fn contains_region(&self, other: &Self) -> bool {
+        self.space.contains_segment(&other.space) && self.time.contains_segment(&other.time)
+    }
+
+    /// Do the two regions share any spacetime quanta?
+    pub fn overlaps(&self, other: &Self) -> bool {
+        (self.space.contains_segment(&other.space) || other.space.contains_segment(&self.space))
+            && (self.time.contains_segment(&other.time) || other.time.contains_segment(&self.time))
+    }
+
+    /// The number of spacetime quanta covered by this region
+    pub fn num_quanta(&self) -> u128 {
+        self.space.num_quanta() as u128 * self.time.num_quanta() as u128
+    }
+
     /// Split this region into 4 equal subregions, if possible.
     /// If one dimension is quantum, bisect the non-quantum dimension.
     /// If both dimensions are quantum, return None.
@@ -75,6 +91,11 @@ impl RegionBounds {
         self.arc_interval().contains(x) && self.time_range().contains(t)
     }
 
+    /// Does this region span this location, at any time?
+    pub fn contains_loc(&self, x: &Loc) -> bool {
+        self.arc_interval().contains(x)
+    }
+
     /// Just the primitive underlying numbers. For diagnostics.
     pub fn to_primitive(&self) -> ((u32, u32), (i64, i64)) {
         (
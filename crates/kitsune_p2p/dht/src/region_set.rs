@@ -14,7 +14,7 @@ pub use ltcs::*;
 
 use crate::{error::GossipResult, spacetime::*};
 
-use crate::region::{Region, RegionBounds, RegionCoords, RegionData, RegionDataConstraints};
+use crate::region::{Region, RegionCoords, RegionData, RegionDataConstraints};
 
 /// The generic definition of a set of Regions.
 /// The current representation is very specific to our current algorithm,
@@ -44,20 +44,28 @@ impl<D: RegionDataConstraints> RegionSet<D> {
 
     /// The RegionSet can be used to answer questions about more regions than
     /// just the ones specified: If a larger region is queried, and this set contains
-    /// a set of regions which over that larger region, then the larger region
+    /// a set of regions which cover that larger region, then the larger region
     /// can be dynamically constructed.
     ///
     /// This allows agents with differently computed RegionSets to still engage
     /// in gossip without needing to recompute regions.
-    pub fn query(&self, _bounds: &RegionBounds) -> ! {
-        unimplemented!("only implement after trying naive database-only approach")
+    ///
+    /// Returns None if the queried region cannot be constructed from this set.
+    pub fn query(&self, coords: &RegionCoords) -> Option<D> {
+        match self {
+            Self::Ltcs(set) => set.query(coords),
+        }
     }
 
     /// In order for this RegionSet to be queryable, new data needs to be
     /// integrated into it to avoid needing to recompute it from the database
     /// on each query.
-    pub fn update(&self, _c: SpacetimeQuantumCoords, _d: D) -> ! {
-        unimplemented!("only implement after trying naive database-only approach")
+    ///
+    /// Returns false if the data falls outside of every region in this set.
+    pub fn update(&mut self, topo: &Topology, c: SpacetimeQuantumCoords, d: D) -> bool {
+        match self {
+            Self::Ltcs(set) => set.update(topo, c, d),
+        }
     }
 
     /// Find a set of Regions which represents the intersection of the two
@@ -192,6 +200,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_query() {
+        let topo = Topology::unit_zero();
+        let arq = Arq::new(8, 0u32.into(), 4.into()).to_bounds(&topo);
+        let mut store = OpStore::new(topo.clone(), GossipParams::zero());
+        store.integrate_ops(op_grid(&topo, &arq, 10..20).into_iter());
+
+        let coords = RegionCoordSetLtcs::new(
+            TelescopingTimes::new(TimeQuantum::from(20)),
+            ArqBoundsSet::single(arq.clone()),
+        );
+        let rset = RegionSet::from(RegionSetLtcs::from_store(&store, coords));
+
+        // Every region in the set can be queried directly
+        for region in rset.regions() {
+            assert_eq!(rset.query(&region.coords), Some(region.data));
+        }
+
+        // A larger region made of two neighbouring regions in the set
+        let time = rset.regions().next().unwrap().coords.time;
+        let larger = RegionCoords::new(SpaceSegment::new(9, 0), time);
+        assert_eq!(rset.query(&larger), Some(store.query_region_data(&larger)));
+
+        // A region which cuts through regions of the set can't be answered
+        let smaller = rset
+            .regions()
+            .find_map(|r| r.coords.quadrisect())
+            .unwrap()
+            .remove(0);
+        assert_eq!(rset.query(&smaller), None);
+
+        // Neither can a region outside of the arq
+        let outside = RegionCoords::new(SpaceSegment::new(8, 100), time);
+        assert_eq!(rset.query(&outside), None);
+    }
+
+    #[test]
+    fn test_update() {
+        let topo = Topology::unit_zero();
+        let arq = Arq::new(8, 0u32.into(), 4.into()).to_bounds(&topo);
+        let mut store1 = OpStore::new(topo.clone(), GossipParams::zero());
+        store1.integrate_ops(op_grid(&topo, &arq, 10..20).into_iter());
+
+        // The grid has no ops before time 10, so this op doesn't collide with any
+        let extra_op = OpData::fake(Loc::from(256u32), Timestamp::from_micros(5), 4);
+        let mut store2 = store1.clone();
+        store2.integrate_ops([extra_op.clone()].into_iter());
+
+        let coords = RegionCoordSetLtcs::new(
+            TelescopingTimes::new(TimeQuantum::from(20)),
+            ArqBoundsSet::single(arq.clone()),
+        );
+        let mut rset1 = RegionSet::from(RegionSetLtcs::from_store(&store1, coords.clone()));
+        let rset2 = RegionSet::from(RegionSetLtcs::from_store(&store2, coords));
+
+        // Updating the first set with the extra op makes it match the second
+        assert!(rset1.update(&topo, extra_op.coords(&topo), extra_op.region_data()));
+        let RegionSet::Ltcs(rset1) = rset1;
+        let RegionSet::Ltcs(rset2) = rset2;
+        assert_eq!(rset1, rset2);
+
+        // An op outside of the arq is not absorbed by any region
+        let outside = OpData::fake(Loc::from(u32::MAX / 2), Timestamp::from_micros(15), 4);
+        let mut rset1 = RegionSet::from(rset1);
+        assert!(!rset1.update(&topo, outside.coords(&topo), outside.region_data()));
+    }
+
     #[test]
     fn test_rectify() {
         let topo = Topology::unit_zero();
@@ -207,6 +207,40 @@ impl<D: RegionDataConstraints> RegionSetLtcs<D> {
 
         Ok(regions)
     }
+
+    /// Compute the data for an arbitrary region from the regions in this set.
+    /// Returns None if the regions of this set do not exactly tile the queried
+    /// region, i.e. if the query is not (entirely) covered by this set,
+    /// or if it cuts through one of the regions of this set.
+    pub fn query(&self, coords: &RegionCoords) -> Option<D> {
+        let mut data = D::zero();
+        let mut covered = 0;
+        for region in self.regions() {
+            if coords.contains_region(&region.coords) {
+                data += region.data;
+                covered += region.coords.num_quanta();
+            } else if coords.overlaps(&region.coords) {
+                return None;
+            }
+        }
+        (covered == coords.num_quanta()).then_some(data)
+    }
+
+    /// Add the data for a new op at the given spacetime quantum into every
+    /// region of this set which contains it.
+    /// Returns false if no region contains the op.
+    pub fn update(&mut self, topo: &Topology, c: SpacetimeQuantumCoords, d: D) -> bool {
+        let indices: Vec<_> = self
+            .coords
+            .region_coords_flat()
+            .filter(|(_, coords)| coords.contains(topo, &c))
+            .map(|(i, _)| i)
+            .collect();
+        for &(ia, ix, it) in indices.iter() {
+            self.data[ia][ix][it] += d.clone();
+        }
+        !indices.is_empty()
+    }
 }
 
 #[cfg(feature = "test_utils")]
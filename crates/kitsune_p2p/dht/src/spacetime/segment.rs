@@ -170,6 +170,17 @@ impl<O: Offset> Segment<O> {
         }
     }
 
+    /// The segment contains the other segment.
+    /// Since segments are aligned to their own length, any two segments are
+    /// either disjoint or one contains the other.
+    pub fn contains_segment(&self, other: &Self) -> bool {
+        if other.power > self.power {
+            return false;
+        }
+        let shift = u32::from(self.power - other.power);
+        u64::from(*other.offset) >> shift == u64::from(*self.offset)
+    }
+
     /// Split a segment in half
     pub fn bisect(&self) -> Option<[Self; 2]> {
         if self.power == 0 {
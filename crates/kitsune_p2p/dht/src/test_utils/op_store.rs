@@ -36,8 +36,10 @@ impl<D: RegionDataConstraints, O: OpRegion<D>> AccessOpStore<O, D> for OpStore<O
         let region = region.to_bounds(self.topo());
         let (x0, x1) = region.x;
         let (t0, t1) = region.t;
+        // Ops are ordered by time, then location, so the upper bound must
+        // include every location at the final timestamp
         let op0 = O::bound(t0, x0);
-        let op1 = O::bound(t1, x0);
+        let op1 = O::bound(t1, u32::MAX.into());
         self.ops
             .range((Bound::Included(op0), Bound::Included(op1)))
             .filter(|o| x0 <= o.loc() && o.loc() <= x1)
@@ -103,6 +103,58 @@ fn test_multi() {
     assert_eq!(info.stats.op_data_rcvd, 234 + 456);
 }
 
+/// Test that a RegionSet which is updated in place as new ops are integrated
+/// can stand in for a freshly computed one during gossip
+#[test]
+fn test_region_set_update() {
+    let topo = Topology::unit_zero();
+    let gopa = GossipParams::new(1.into(), 0);
+    let ts = |t: u32| TimeQuantum::from(t).to_timestamp_bounds(&topo).0;
+
+    let alice_arq = Arq::new(8, (-128i32 as u32).into(), 4.into());
+    let bobbo_arq = Arq::new(8, 0u32.into(), 4.into());
+    let (mut alice, _) = TestNode::new_single(topo.clone(), gopa, alice_arq);
+    let (mut bobbo, _) = TestNode::new_single(topo.clone(), gopa, bobbo_arq);
+
+    alice.integrate_op(OpData::fake(0.into(), ts(10), 4321));
+    bobbo.integrate_op(OpData::fake(128.into(), ts(20), 1234));
+
+    let tq = TimeQuantum::from(30);
+    let common = gossip_direct_at(&mut alice, &mut bobbo, tq)
+        .unwrap()
+        .common_arqs;
+
+    // After gossip, both nodes agree on every common region
+    let regions_alice = alice.region_set(common.clone(), tq);
+    let regions_bobbo = bobbo.region_set(common.clone(), tq);
+    assert!(regions_alice.diff(regions_bobbo).unwrap().is_empty());
+
+    // Alice integrates a new op, and updates her existing region set rather
+    // than recomputing it
+    let op = OpData::fake(64.into(), ts(25), 99);
+    let mut regions_alice = alice.region_set(common.clone(), tq);
+    alice.integrate_op(op.clone());
+    assert!(regions_alice.update(&topo, op.coords(&topo), op.region_data()));
+    assert!(regions_alice
+        .clone()
+        .diff(alice.region_set(common.clone(), tq))
+        .unwrap()
+        .is_empty());
+
+    // The updated set pinpoints the new op as the only difference with bob
+    let diff = regions_alice
+        .diff(bobbo.region_set(common.clone(), tq))
+        .unwrap();
+    assert_eq!(diff.len(), 1);
+    assert!(diff[0].coords.contains(&topo, &op.coords(&topo)));
+
+    // A normal gossip round then brings bob up to date
+    let stats = gossip_direct_at(&mut alice, &mut bobbo, tq).unwrap().stats;
+    assert_eq!(stats.ops_sent, 1);
+    assert_eq!(stats.op_data_sent, 99);
+    assert_eq!(stats.ops_rcvd, 0);
+}
+
 /// Test that gossip still works when the two nodes have different arq powers
 #[test]
 fn test_mismatched_powers() {
@@ -937,7 +937,34 @@ async fn handle_fetch_op_data(
                 }
             }
         }
-        FetchOpDataEvtQuery::Regions(_coords) => unimplemented!(),
+        FetchOpDataEvtQuery::Regions(bounds) => {
+            // Entries carry no timestamp, so only the space bounds can be applied.
+            let mut seen = HashSet::new();
+            for info in &agent_info_list {
+                let agent = info.agent().clone();
+                let entries = kdirect
+                    .persist
+                    .query_entries(
+                        root.clone(),
+                        agent,
+                        full_time_window(),
+                        kitsune_p2p_types::dht_arc::DhtArcSet::Full,
+                    )
+                    .await?;
+                for entry in entries {
+                    let loc = entry.hash().as_loc().into();
+                    if !bounds.iter().any(|b| b.contains_loc(&loc)) {
+                        continue;
+                    }
+                    if seen.insert(entry.hash().clone()) {
+                        out.push((
+                            entry.hash().clone().to_kitsune_op_hash(),
+                            KitsuneOpData::new(entry.as_wire_data_ref().to_vec()),
+                        ));
+                    }
+                }
+            }
+        }
     }
 
     Ok(out)
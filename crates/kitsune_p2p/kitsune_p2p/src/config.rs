@@ -1,7 +1,12 @@
+pub use kitsune_p2p_types::config::KitsuneP2pConfigOverride;
 use kitsune_p2p_types::config::KitsuneP2pTuningParams;
+pub use kitsune_p2p_types::config::NetworkType;
+pub use kitsune_p2p_types::config::ProxyAcceptConfig;
+pub use kitsune_p2p_types::config::ProxyConfig;
 use kitsune_p2p_types::tx2::tx2_adapter::AdapterFactory;
 use kitsune_p2p_types::tx2::tx2_utils::*;
 use kitsune_p2p_types::*;
+use std::sync::Arc;
 use url2::Url2;
 
 // TODO - FIXME - holochain bootstrap should not be encoded in kitsune
@@ -70,6 +75,46 @@ fn cnv_bind_to(bind_to: &Option<url2::Url2>) -> TxUrl {
 }
 
 impl KitsuneP2pConfig {
+    /// The config for a single space, with the given overrides applied.
+    ///
+    /// Fails if the overrides set a proxy config other than the one used by
+    /// the transport, since all spaces share the transport.
+    pub fn with_override(&self, config_override: &KitsuneP2pConfigOverride) -> KitsuneResult<Self> {
+        if let Some(proxy_config) = &config_override.proxy_config {
+            if self.proxy_config() != Some(proxy_config) {
+                return Err(format!(
+                    "the proxy config can't be overridden per space, because all spaces share one transport: \
+                    the override sets {:?}, but the transport is configured with {:?}",
+                    proxy_config,
+                    self.proxy_config()
+                )
+                .into());
+            }
+        }
+        let mut config = self.clone();
+        if let Some(bootstrap_service) = &config_override.bootstrap_service {
+            config.bootstrap_service = Some(bootstrap_service.clone());
+        }
+        if let Some(network_type) = &config_override.network_type {
+            config.network_type = network_type.clone();
+        }
+        if !config_override.tuning_params.is_empty() {
+            config.tuning_params = Arc::new(
+                self.tuning_params
+                    .with_overrides(config_override.tuning_params.clone()),
+            );
+        }
+        Ok(config)
+    }
+
+    /// The proxy config of the transport, if it is proxied.
+    fn proxy_config(&self) -> Option<&ProxyConfig> {
+        match self.transport_pool.first() {
+            Some(TransportConfig::Proxy { proxy_config, .. }) => Some(proxy_config),
+            _ => None,
+        }
+    }
+
     /// `tx2` is currently designed to use exactly one proxy wrapped transport,
     /// so convert a bunch of the options from the previous transport
     /// paradigm into that pattern.
@@ -190,54 +235,3 @@ impl From<AdapterFactory> for AdapterFactoryMock {
         Self(adaptor_factory)
     }
 }
-
-/// Proxy configuration options
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum ProxyConfig {
-    /// We want to be hosted at a remote proxy location.
-    RemoteProxyClient {
-        /// The remote proxy url to be hosted at
-        proxy_url: Url2,
-    },
-
-    /// We want to be hosted at a remote proxy location.
-    /// We'd like to fetch a proxy list from a bootstrap server,
-    /// with an optional fallback to a specific proxy.
-    RemoteProxyClientFromBootstrap {
-        /// The bootstrap server from which to fetch the proxy_list
-        bootstrap_url: Url2,
-
-        /// The optional fallback specific proxy server
-        fallback_proxy_url: Option<Url2>,
-    },
-
-    /// We want to be a proxy server for others.
-    /// (We can also deny all proxy requests for something in-between.)
-    LocalProxyServer {
-        /// Accept proxy request options
-        /// Default: None = reject all proxy requests
-        proxy_accept_config: Option<ProxyAcceptConfig>,
-    },
-}
-
-/// Whether we are willing to proxy on behalf of others
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub enum ProxyAcceptConfig {
-    /// We will accept all requests to proxy for remotes
-    AcceptAll,
-
-    /// We will reject all requests to proxy for remotes
-    RejectAll,
-}
-
-/// Method for connecting to other peers and broadcasting our AgentInfo
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
-#[serde(rename_all = "snake_case")]
-pub enum NetworkType {
-    /// Via bootstrap server to the WAN
-    QuicBootstrap,
-    /// Via MDNS to the LAN
-    QuicMdns,
-}
@@ -583,6 +583,14 @@ impl ShardedGossipLocal {
     /// The number of bloom filters we want to send in a single gossip iteration.
     const UPPER_BLOOM_BOUND: usize = 10;
 
+    /// Historical rounds exchange region sets unless the
+    /// `sharded-gossip-bloom` strategy asks for op blooms instead.
+    /// Recent rounds always use blooms.
+    fn uses_regions(&self) -> bool {
+        self.gossip_type == GossipType::Historical
+            && !self.tuning_params.gossip_historical_blooms()
+    }
+
     /// Calculate the time range for a gossip round.
     fn calculate_time_range(&self) -> TimeWindow {
         const NOW: Duration = Duration::from_secs(0);
@@ -845,7 +853,7 @@ impl ShardedGossipLocal {
                 // TODO: come back to this later after implementing batching for
                 //      region gossip, for now I just don't care about the state,
                 //      and just want to handle the incoming ops.
-                if (self.gossip_type == GossipType::Historical || state.is_some())
+                if (self.uses_regions() || state.is_some())
                     && !ops.is_empty()
                 {
                     self.incoming_missing_ops(ops).await?;
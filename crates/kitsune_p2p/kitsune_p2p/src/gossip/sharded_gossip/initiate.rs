@@ -186,7 +186,8 @@ impl ShardedGossipLocal {
         Ok(gossip)
     }
 
-    /// Generate the bloom filters and generate a new state.
+    /// Generate the bloom filters or region set and generate a new state.
+    /// - Historical gossip sends a region set unless blooms are configured.
     /// - Agent bloom is only generated if this is a `Recent` gossip type.
     /// - Empty blooms are not created.
     /// - A new state is created for this round.
@@ -202,7 +203,7 @@ impl ShardedGossipLocal {
         let remote_arc_set: DhtArcSet = remote_arc_set.into();
         let common_arc_set = Arc::new(arc_set.intersection(&remote_arc_set));
 
-        let region_set = if let GossipType::Historical = self.gossip_type {
+        let region_set = if self.uses_regions() {
             let region_set = store::query_region_set(
                 self.host_api.clone(),
                 self.space.clone(),
@@ -218,20 +219,22 @@ impl ShardedGossipLocal {
         // Generate the new state.
         let mut state = self.new_state(remote_agent_list, common_arc_set, region_set)?;
 
-        // Generate the agent bloom.
-        if let GossipType::Recent = self.gossip_type {
-            let bloom = self.generate_agent_bloom(state.clone()).await?;
-            if let Some(bloom) = bloom {
-                let bloom = encode_bloom_filter(&bloom);
-                gossip.push(ShardedGossipWire::agents(bloom));
-            }
-            self.next_bloom_batch(state, gossip).await
-        } else {
+        if self.uses_regions() {
             // Everything has already been taken care of for Historical
             // gossip already. Just mark this true so that the state will not
             // be considered "finished" until all op data is received.
             state.has_pending_historical_op_data = true;
             Ok(state)
+        } else {
+            // Generate the agent bloom.
+            if let GossipType::Recent = self.gossip_type {
+                let bloom = self.generate_agent_bloom(state.clone()).await?;
+                if let Some(bloom) = bloom {
+                    let bloom = encode_bloom_filter(&bloom);
+                    gossip.push(ShardedGossipWire::agents(bloom));
+                }
+            }
+            self.next_bloom_batch(state, gossip).await
         }
     }
 
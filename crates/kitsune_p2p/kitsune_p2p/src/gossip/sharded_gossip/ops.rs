@@ -177,33 +177,32 @@ impl ShardedGossipLocal {
         &self,
         state: RoundState,
     ) -> KitsuneResult<Vec<ShardedGossipWire>> {
-        match self.gossip_type {
-            GossipType::Historical => self.process_next_region_batch(state).await,
-            GossipType::Recent => {
-                // Pop the next queued batch.
-                let next_batch = state
-                    .ops_batch_queue
-                    .0
-                    .share_mut(|queue, _| Ok(queue.pop_front()))?;
-
-                match next_batch {
-                    // The next batch is hashes, batch them into ops using the queue id.
-                    Some((queue_id, QueuedOps::Hashes(missing_hashes))) => {
-                        self.batch_missing_ops_from_bloom(state, missing_hashes, Some(queue_id))
-                            .await
-                    }
-                    // The next batch is a bloom so the hashes need to be fetched before
-                    // fetching the hashes.
-                    Some((queue_id, QueuedOps::Bloom(remote_bloom))) => {
-                        self.incoming_op_bloom(state, remote_bloom, Some(queue_id))
-                            .await
-                    }
-                    // Nothing is queued so this node is done.
-                    None => Ok(vec![ShardedGossipWire::missing_ops(
-                        Vec::with_capacity(0),
-                        MissingOpsStatus::AllComplete as u8,
-                    )]),
+        if self.uses_regions() {
+            self.process_next_region_batch(state).await
+        } else {
+            // Pop the next queued batch.
+            let next_batch = state
+                .ops_batch_queue
+                .0
+                .share_mut(|queue, _| Ok(queue.pop_front()))?;
+
+            match next_batch {
+                // The next batch is hashes, batch them into ops using the queue id.
+                Some((queue_id, QueuedOps::Hashes(missing_hashes))) => {
+                    self.batch_missing_ops_from_bloom(state, missing_hashes, Some(queue_id))
+                        .await
+                }
+                // The next batch is a bloom so the hashes need to be fetched before
+                // fetching the hashes.
+                Some((queue_id, QueuedOps::Bloom(remote_bloom))) => {
+                    self.incoming_op_bloom(state, remote_bloom, Some(queue_id))
+                        .await
                 }
+                // Nothing is queued so this node is done.
+                None => Ok(vec![ShardedGossipWire::missing_ops(
+                    Vec::with_capacity(0),
+                    MissingOpsStatus::AllComplete as u8,
+                )]),
             }
         }
     }
@@ -482,6 +482,63 @@ async fn no_data_still_finishes() {
         .unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+/// Historical gossip sends a region set by default and op blooms
+/// when the `sharded-gossip-bloom` strategy is configured.
+async fn historical_strategy_selects_regions_or_blooms() {
+    let mut u = arbitrary::Unstructured::new(&NOISE);
+    let bob_cert = Tx2Cert::arbitrary(&mut u).unwrap();
+    let agents = agents_with_infos(2).await;
+
+    for (strategy, expect_regions) in [("sharded-gossip", true), ("sharded-gossip-bloom", false)]
+    {
+        let mut tuning_params = tuning_params_struct::KitsuneP2pTuningParams::default();
+        tuning_params.gossip_strategy = strategy.to_string();
+
+        let mut alice = setup_standard_player(
+            ShardedGossipLocalState {
+                local_agents: maplit::hashset! { agents[0].0.clone() },
+                ..Default::default()
+            },
+            agents.clone(),
+        )
+        .await;
+        alice.tuning_params = Arc::new(tuning_params.clone());
+
+        let mut bob = setup_standard_player(
+            ShardedGossipLocalState {
+                local_agents: maplit::hashset! { agents[1].0.clone() },
+                ..Default::default()
+            },
+            agents.clone(),
+        )
+        .await;
+        bob.tuning_params = Arc::new(tuning_params);
+
+        // - Bob initiates and alice accepts.
+        let (_, _, bob_outgoing) = bob.try_initiate().await.unwrap().unwrap();
+        let alice_outgoing = alice
+            .process_incoming(bob_cert.clone(), bob_outgoing)
+            .await
+            .unwrap();
+
+        let num_regions = alice_outgoing
+            .iter()
+            .filter(|m| matches!(m, ShardedGossipWire::OpRegions(_)))
+            .count();
+        let num_blooms = alice_outgoing
+            .iter()
+            .filter(|m| matches!(m, ShardedGossipWire::OpBloom(_)))
+            .count();
+        if expect_regions {
+            assert_eq!((num_regions, num_blooms), (1, 0), "{}", strategy);
+        } else {
+            assert_eq!(num_regions, 0, "{}", strategy);
+            assert!(num_blooms > 0, "{}", strategy);
+        }
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 /// This test checks that when two players simultaneously
 /// initiate a round it is handled correctly.
@@ -90,6 +90,7 @@ pub(crate) struct KitsuneP2pActor {
         )>,
     >,
     config: Arc<KitsuneP2pConfig>,
+    space_overrides: HashMap<Arc<KitsuneSpace>, KitsuneP2pConfigOverride>,
     bandwidth_throttles: BandwidthThrottles,
     parallel_notify_permit: Arc<tokio::sync::Semaphore>,
 }
@@ -457,6 +458,7 @@ impl KitsuneP2pActor {
             host,
             spaces: HashMap::new(),
             config: Arc::new(config),
+            space_overrides: HashMap::new(),
             bandwidth_throttles,
             parallel_notify_permit,
         })
@@ -697,6 +699,31 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         Ok(async move { Ok(vec![this_addr?.into()]) }.boxed().into())
     }
 
+    fn handle_configure_space(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        config_override: KitsuneP2pConfigOverride,
+    ) -> KitsuneP2pHandlerResult<()> {
+        let current = self
+            .space_overrides
+            .get(&space)
+            .cloned()
+            .unwrap_or_default();
+        if current == config_override {
+            return unit_ok_fut();
+        }
+        if self.spaces.contains_key(&space) {
+            return Err("cannot change the config of a running space".into());
+        }
+        self.config.with_override(&config_override)?;
+        if config_override.is_empty() {
+            self.space_overrides.remove(&space);
+        } else {
+            self.space_overrides.insert(space, config_override);
+        }
+        unit_ok_fut()
+    }
+
     fn handle_join(
         &mut self,
         space: Arc<KitsuneSpace>,
@@ -707,7 +734,10 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         let space2 = space.clone();
         let ep_hnd = self.ep_hnd.clone();
         let host = self.host.clone();
-        let config = Arc::clone(&self.config);
+        let config = match self.space_overrides.get(&space) {
+            Some(config_override) => Arc::new(self.config.with_override(config_override)?),
+            None => Arc::clone(&self.config),
+        };
         let bandwidth_throttles = self.bandwidth_throttles.clone();
         let parallel_notify_permit = self.parallel_notify_permit.clone();
         let space_sender = match self.spaces.entry(space.clone()) {
@@ -600,6 +600,16 @@ impl KitsuneP2pHandler for Space {
         )
     }
 
+    fn handle_configure_space(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        _config_override: KitsuneP2pConfigOverride,
+    ) -> KitsuneP2pHandlerResult<()> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
+
     fn handle_join(
         &mut self,
         space: Arc<KitsuneSpace>,
@@ -1139,7 +1149,7 @@ impl Space {
             .gossip_strategy
             .split(',')
             .flat_map(|module| match module {
-                "sharded-gossip" => {
+                "sharded-gossip" | "sharded-gossip-bloom" => {
                     let mut gossips = vec![];
                     if config.tuning_params.danger_gossip_recent_threshold_secs > 0 {
                         gossips.push((
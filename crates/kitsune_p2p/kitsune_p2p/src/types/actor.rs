@@ -1,5 +1,6 @@
 //! Definitions related to the KitsuneP2p peer-to-peer / dht communications actor.
 
+use kitsune_p2p_types::config::KitsuneP2pConfigOverride;
 use kitsune_p2p_types::config::KitsuneP2pTuningParams;
 use kitsune_p2p_types::KitsuneTimeout;
 use std::sync::Arc;
@@ -84,6 +85,10 @@ ghost_actor::ghost_chan! {
         /// Get the calculated transport bindings.
         fn list_transport_bindings() -> Vec<Url2>;
 
+        /// Override the network config for a space. This must happen before
+        /// the space is first joined, and can't be changed while the space is running.
+        fn configure_space(space: KSpace, config_override: KitsuneP2pConfigOverride) -> ();
+
         /// Announce a space/agent pair on this network.
         fn join(space: KSpace, agent: KAgent, initial_arc: OptArc) -> ();
 
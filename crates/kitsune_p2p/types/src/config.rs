@@ -1,11 +1,119 @@
 //! Kitsune Config Tuning Params
 
+use std::collections::BTreeMap;
+use url2::Url2;
+
 /// How long kitsune should wait before timing out when joining the network.
 pub const JOIN_NETWORK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
 
 /// Fifteen minutes
 pub const RECENT_THRESHOLD_DEFAULT: std::time::Duration = std::time::Duration::from_secs(60 * 15);
 
+/// Proxy configuration options
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProxyConfig {
+    /// We want to be hosted at a remote proxy location.
+    RemoteProxyClient {
+        /// The remote proxy url to be hosted at
+        proxy_url: Url2,
+    },
+
+    /// We want to be hosted at a remote proxy location.
+    /// We'd like to fetch a proxy list from a bootstrap server,
+    /// with an optional fallback to a specific proxy.
+    RemoteProxyClientFromBootstrap {
+        /// The bootstrap server from which to fetch the proxy_list
+        bootstrap_url: Url2,
+
+        /// The optional fallback specific proxy server
+        fallback_proxy_url: Option<Url2>,
+    },
+
+    /// We want to be a proxy server for others.
+    /// (We can also deny all proxy requests for something in-between.)
+    LocalProxyServer {
+        /// Accept proxy request options
+        /// Default: None = reject all proxy requests
+        proxy_accept_config: Option<ProxyAcceptConfig>,
+    },
+}
+
+/// Whether we are willing to proxy on behalf of others
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyAcceptConfig {
+    /// We will accept all requests to proxy for remotes
+    AcceptAll,
+
+    /// We will reject all requests to proxy for remotes
+    RejectAll,
+}
+
+/// Method for connecting to other peers and broadcasting our AgentInfo
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum NetworkType {
+    /// Via bootstrap server to the WAN
+    QuicBootstrap,
+    /// Via MDNS to the LAN
+    QuicMdns,
+}
+
+/// Overrides of the network config for a single space.
+/// Anything left unset is taken from the config of the whole network.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct KitsuneP2pConfigOverride {
+    /// The bootstrap service used to discover peers in this space.
+    #[serde(default)]
+    pub bootstrap_service: Option<Url2>,
+
+    /// How peers in this space are discovered.
+    #[serde(default)]
+    pub network_type: Option<NetworkType>,
+
+    /// The proxy config of this space.
+    ///
+    /// Overriding the proxy config is not supported, since all spaces share
+    /// one transport. Any proxy config other than the transport's is
+    /// rejected, so a space meant for the LAN is never silently routed
+    /// through a remote proxy.
+    #[serde(default)]
+    pub proxy_config: Option<ProxyConfig>,
+
+    /// Tuning params applied on top of the network's tuning params,
+    /// given as strings in the same way as in the network config.
+    #[serde(default)]
+    pub tuning_params: BTreeMap<String, String>,
+}
+
+impl KitsuneP2pConfigOverride {
+    /// True if nothing is overridden.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for KitsuneP2pConfigOverride {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Urls aren't arbitrary, so only vary whether they are set.
+        let bootstrap_service = if u.arbitrary()? {
+            Some(url2::url2!("https://bootstrap.example.com"))
+        } else {
+            None
+        };
+        Ok(Self {
+            bootstrap_service,
+            network_type: u.arbitrary()?,
+            proxy_config: None,
+            tuning_params: u.arbitrary()?,
+        })
+    }
+}
+
 /// Wrapper for the actual KitsuneP2pTuningParams struct
 /// so the widely used type def can be an Arc<>
 pub mod tuning_params_struct {
@@ -61,8 +169,21 @@ pub mod tuning_params_struct {
                     D: serde::Deserializer<'de>,
                 {
                     let result = <HashMap<String, String>>::deserialize(deserializer)?;
-                    let mut out = KitsuneP2pTuningParams::default();
-                    for (k, v) in result.into_iter() {
+                    Ok(KitsuneP2pTuningParams::default().with_overrides(result))
+                }
+            }
+
+            impl KitsuneP2pTuningParams {
+                /// Apply tuning params given as strings, as they appear
+                /// in a config file, on top of these params.
+                /// As when deserializing, params that don't exist or values
+                /// that don't parse are skipped with a warning.
+                pub fn with_overrides(
+                    &self,
+                    overrides: impl IntoIterator<Item = (String, String)>,
+                ) -> Self {
+                    let mut out = self.clone();
+                    for (k, v) in overrides.into_iter() {
                         match k.as_str() {
                             $(
                                 stringify!($i) => match v.parse::<$t>() {
@@ -73,7 +194,7 @@ pub mod tuning_params_struct {
                             _ => tracing::warn!("INVALID TUNING PARAM: '{}'", k),
                         }
                     }
-                    Ok(out)
+                    out
                 }
             }
         };
@@ -81,6 +202,11 @@ pub mod tuning_params_struct {
 
     mk_tune! {
         /// Gossip strategy to use. [Default: "sharded-gossip"]
+        /// - "sharded-gossip": recent gossip uses op blooms, historical
+        ///   gossip compares quantized region set fingerprints.
+        /// - "sharded-gossip-bloom": as above, but historical gossip
+        ///   also uses op blooms over the whole historical window.
+        /// - "none": no gossip.
         gossip_strategy: String = "sharded-gossip".to_string(),
 
         /// Delay between gossip loop iteration. [Default: 1s]
@@ -231,6 +357,14 @@ pub mod tuning_params_struct {
             std::time::Duration::from_secs(self.danger_gossip_recent_threshold_secs)
         }
 
+        /// returns true if historical gossip should exchange op blooms
+        /// instead of region sets
+        pub fn gossip_historical_blooms(&self) -> bool {
+            self.gossip_strategy
+                .split(',')
+                .any(|s| s == "sharded-gossip-bloom")
+        }
+
         /// returns true if we should initialize a tls keylog
         /// based on the `SSLKEYLOGFILE` environment variable
         pub fn use_env_tls_keylog(&self) -> bool {
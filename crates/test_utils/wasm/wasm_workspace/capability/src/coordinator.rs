@@ -3,6 +3,9 @@ use hdk::prelude::*;
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 pub struct CapFor(CapSecret, AgentPubKey);
 
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RevokeFor(CapSecret, AgentPubKey, ActionHash);
+
 #[hdk_extern]
 fn init(_: ()) -> ExternResult<InitCallbackResult> {
     // grant unrestricted access to accept_cap_claim so other agents can send us claims
@@ -27,7 +30,9 @@ pub fn cap_secret(_: ()) -> ExternResult<CapSecret> {
 fn cap_grant_entry(secret: CapSecret) -> ExternResult<CapGrantEntry> {
     let mut functions: GrantedFunctions = BTreeSet::new();
     let this_zome = zome_info()?.name;
-    functions.insert((this_zome, "needs_cap_claim".into()));
+    functions.insert((this_zome.clone(), "needs_cap_claim".into()));
+    functions.insert((this_zome.clone(), "cap_info".into()));
+    functions.insert((this_zome, "revoke_then_cap_info".into()));
     Ok(CapGrantEntry {
         tag: "".into(),
         access: secret.into(),
@@ -51,6 +56,50 @@ pub fn delete_cap_grant(action_hash: ActionHash) -> ExternResult<ActionHash> {
     hdk::prelude::delete_cap_grant(action_hash)
 }
 
+#[hdk_extern]
+fn cap_grants(input: CapGrantsInput) -> ExternResult<Vec<CapGrantInfo>> {
+    capability_grants(input)
+}
+
+#[hdk_extern]
+fn cap_claims(input: CapClaimsInput) -> ExternResult<Vec<CapClaimInfo>> {
+    capability_claims(input)
+}
+
+#[hdk_extern]
+fn cap_info(_: ()) -> ExternResult<CapabilityInfo> {
+    capability_info()
+}
+
+#[hdk_extern]
+fn try_cap_info(cap_for: crate::CapFor) -> ExternResult<ZomeCallResponse> {
+    call_remote(
+        cap_for.1,
+        zome_info()?.name,
+        "cap_info".to_string().into(),
+        Some(cap_for.0),
+        &(),
+    )
+}
+
+/// Delete the grant which authorized this call before asking for it.
+#[hdk_extern]
+fn revoke_then_cap_info(action_hash: ActionHash) -> ExternResult<CapabilityInfo> {
+    hdk::prelude::delete_cap_grant(action_hash)?;
+    capability_info()
+}
+
+#[hdk_extern]
+fn try_revoke_then_cap_info(revoke_for: crate::RevokeFor) -> ExternResult<ZomeCallResponse> {
+    call_remote(
+        revoke_for.1,
+        zome_info()?.name,
+        "revoke_then_cap_info".to_string().into(),
+        Some(revoke_for.0),
+        &revoke_for.2,
+    )
+}
+
 #[hdk_extern]
 fn get_entry(action_hash: ActionHash) -> ExternResult<Option<Record>> {
     get(action_hash, GetOptions::latest())